@@ -1,22 +1,42 @@
+use std::fmt::{Display, Formatter};
 use std::hash::Hash;
 use std::ops::{Deref, DerefMut};
 use std::ptr::{addr_of, addr_of_mut};
+use std::sync::atomic::{AtomicU32, AtomicU8};
+use std::sync::atomic::Ordering::{Relaxed, SeqCst};
 
 use crate::page_model::{BlockID, BlockRef};
 use crate::page_model::leaf_page::LeafPage;
 use crate::page_model::node::Node;
-use crate::utils::smart_cell::{LatchType, SmartGuard};
+use crate::utils::slab::SlabArc;
+use crate::utils::smart_cell::{LatchType, SmartCell, SmartGuard};
+
+/// The block was changed since it was read from or written to its slot.
+pub const FRAME_DIRTY: u8 = 1;
+/// The block's leaves are not resident, its children refer to an obsolete placeholder.
+pub const FRAME_EVICTED: u8 = 2;
+/// The block was latched since the buffer pool's clock hand passed it.
+pub const FRAME_REFERENCED: u8 = 4;
+/// The block is one of the buffer pool's resident frames.
+pub const FRAME_LISTED: u8 = 8;
+/// The block was unlinked from the tree, it is never linked again.
+pub const FRAME_UNLINKED: u8 = 16;
 
 // #[repr(align(4096))]
-#[repr(C, packed)]
+#[repr(C)]
 pub struct Block<
     const FAN_OUT: usize,
     const NUM_RECORDS: usize,
     Key: Default + Ord + Copy + Hash,
     Payload: Default + Clone,
 > {
-    // pub block_id: BlockID,
     pub node_data: Node<FAN_OUT, NUM_RECORDS, Key, Payload>,
+    /// Identifies the block, e.g. its slot in the tree's block pool.
+    pub block_id: BlockID,
+    /// Frame flags, see `BufferPool`. Read and set without latching the block.
+    pub(crate) frame: AtomicU8,
+    /// Number of running operations that latched the block, see `PinScope`.
+    pub(crate) pins: AtomicU32,
 }
 
 impl<const FAN_OUT: usize,
@@ -27,8 +47,10 @@ impl<const FAN_OUT: usize,
 {
     fn default() -> Self {
         Block {
-            // block_id: 0,
             node_data: Node::Leaf(LeafPage::new()),
+            block_id: 0,
+            frame: AtomicU8::new(FRAME_DIRTY),
+            pins: AtomicU32::new(0),
         }
     }
 }
@@ -41,20 +63,53 @@ impl<const FAN_OUT: usize,
 {
     #[inline(always)]
     pub const fn block_id(&self) -> BlockID {
-        0
+        self.block_id
     }
 
     #[inline(always)]
-    pub fn into_cell(self, latch: LatchType) -> BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload> {
-        match latch {
-            LatchType::Exclusive => self.into_exclusive(),
-            LatchType::ReadersWriter => self.into_rw(),
-            LatchType::Optimistic => self.into_olc(),
-            LatchType::Hybrid => self.into_hybrid(),
-            LatchType::None => self.into_free(),
-            LatchType::LightWeightHybrid => self.into_lightweight_hybrid()
+    pub fn is_evicted(&self) -> bool {
+        self.frame.load(Relaxed) & FRAME_EVICTED != 0
+    }
+
+    /// Whether the block was unlinked from the tree, see `FRAME_UNLINKED`.
+    #[inline(always)]
+    pub fn is_unlinked(&self) -> bool {
+        self.frame.load(Relaxed) & FRAME_UNLINKED != 0
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_frame(&self, flags: u8) {
+        if self.frame.load(Relaxed) & flags != flags {
+            self.frame.fetch_or(flags, Relaxed);
         }
     }
+
+    #[inline(always)]
+    pub(crate) fn clear_frame(&self, flags: u8) -> u8 {
+        self.frame.fetch_and(!flags, Relaxed) & flags
+    }
+
+    /// Whether an operation that latched the block is still running, see `PinScope`.
+    #[inline(always)]
+    pub(crate) fn is_pinned(&self) -> bool {
+        self.pins.load(SeqCst) > 0
+    }
+
+    /// Wraps the block into a cell allocated on the heap, see `BlockManager::new_cell`.
+    #[inline(always)]
+    pub fn into_cell(self, latch: LatchType) -> BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload> {
+        SmartCell(SlabArc::new(self.into_flavor(latch)))
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Display,
+    Payload: Default + Clone + Display
+> Display for Block<FAN_OUT, NUM_RECORDS, Key, Payload> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{} {}", self.block_id, self.as_ref())
+    }
 }
 
 impl<const FAN_OUT: usize,
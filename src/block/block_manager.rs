@@ -2,20 +2,24 @@ use std::hash::Hash;
 use std::marker::PhantomData;
 use std::mem;
 use std::sync::Arc;
-// use crate::page_model::{AtomicBlockID, BlockID};
-use crate::block::block::Block;
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use crate::page_model::AtomicBlockID;
+use crate::block::block::{Block, FRAME_DIRTY};
+#[cfg(target_os = "linux")]
+use crate::block::block_pool::BlockPool;
 use crate::page_model::internal_page::InternalPage;
 use crate::page_model::leaf_page::LeafPage;
 use crate::page_model::node::Node;
-use crate::page_model::{BlockID, ObjectCount};
-use crate::utils::smart_cell::{SmartCell, SmartFlavor};
+use crate::page_model::{BlockID, BlockRef, ObjectCount};
+use crate::utils::slab::{Slab, SlabArc, SlabUsage};
+use crate::utils::smart_cell::{LatchType, SmartCell, SmartFlavor};
 // use crate::tree::settings::BlockSettings;
 
 const ENABLE_SMALL_BLOCK: bool = false;
 const MAX_ZEROS_PER_BLOCK: usize = 3964; // = data region in a block // outdated due to omitted block-id
 
 /// Default starting numerical value for a valid BlockID.
-// pub const START_BLOCK_ID: BlockID = BlockID::MIN;
+pub const START_BLOCK_ID: BlockID = BlockID::MIN;
 
 pub const _1KB: usize   = 1024;
 pub const _2KB: usize   = 2 * _1KB;
@@ -52,7 +56,13 @@ pub struct BlockManager<
     Key: Default + Ord + Copy + Hash,
     Payload: Default + Clone,
 > {
-    // block_id_counter: AtomicBlockID,
+    /// Shared by clones, hence IDs stay unique across all managers of a tree.
+    block_id_counter: Arc<AtomicBlockID>,
+    /// Holds the cells of all blocks, shared by clones like the ID counter.
+    slab: Arc<Slab>,
+    /// Hands out the IDs instead of the counter, if blocks are backed by a pool.
+    #[cfg(target_os = "linux")]
+    pub(crate) pool: Option<Arc<BlockPool>>,
     _marker: PhantomData<(Key, Payload)>
 }
 
@@ -63,7 +73,10 @@ impl<const FAN_OUT: usize,
 > Clone for BlockManager<FAN_OUT, NUM_RECORDS, Key, Payload> {
     fn clone(&self) -> Self {
         Self {
-            // block_id_counter: AtomicBlockID::new(START_BLOCK_ID),
+            block_id_counter: self.block_id_counter.clone(),
+            slab: self.slab.clone(),
+            #[cfg(target_os = "linux")]
+            pool: self.pool.clone(),
             _marker: PhantomData,
         }
     }
@@ -87,11 +100,16 @@ impl<const FAN_OUT: usize,
     Payload: Default + Clone
 > BlockManager<FAN_OUT, NUM_RECORDS, Key, Payload>
 {
-    // /// Generates and returns a new atomic (unique across callers) BlockID.
-    // #[inline(always)]
-    // pub(crate) fn next_block_id(&self) -> BlockID {
-    //     self.block_id_counter.fetch_add(1, Ordering::Relaxed)
-    // }
+    /// Generates and returns a new atomic (unique across callers) BlockID.
+    #[inline(always)]
+    pub(crate) fn next_block_id(&self) -> BlockID {
+        #[cfg(target_os = "linux")]
+        if let Some(pool) = &self.pool {
+            return pool.allocate();
+        }
+
+        self.block_id_counter.fetch_add(1, Ordering::Relaxed)
+    }
 
     #[inline(always)]
     pub const fn allocation_leaf(&self) -> usize {
@@ -103,20 +121,84 @@ impl<const FAN_OUT: usize,
         FAN_OUT - 1
     }
 
+    /// Number of IDs handed out so far, IDs of pooled blocks are reused once their slot is free.
+    #[inline(always)]
+    pub fn block_ids(&self) -> BlockID {
+        #[cfg(target_os = "linux")]
+        if let Some(pool) = &self.pool {
+            return pool.block_ids();
+        }
+
+        self.block_id_counter.load(Ordering::Relaxed) - START_BLOCK_ID
+    }
+
+    /// Moves the block into a cell allocated in the slab, unlike `Block::into_cell`.
+    #[inline(always)]
+    pub(crate) fn new_cell(&self,
+                           block: Block<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                           latch: LatchType) -> BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>
+    {
+        SmartCell(SlabArc::new_in(block.into_flavor(latch), &self.slab))
+    }
+
+    /// Memory held by the slab for the cells of all blocks, including retired ones.
+    #[inline(always)]
+    pub fn slab_usage(&self) -> SlabUsage {
+        self.slab.usage()
+    }
+
+    /// Cells allocated and not yet freed, without locking the slab, see `Slab::slots_in_use`.
+    #[inline(always)]
+    pub fn cells_in_use(&self) -> usize {
+        self.slab.slots_in_use()
+    }
+
+    /// Size of a single block's cell including its latch, reference count and padding.
+    #[inline(always)]
+    pub fn cell_bytes(&self) -> usize {
+        self.slab.slot_bytes()
+    }
+
     /// Main Constructor requiring supplied BlockSettings.
     #[inline(always)]
-    pub(crate) const fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
-            // block_id_counter: AtomicBlockID::new(START_BLOCK_ID),
+            block_id_counter: Arc::new(AtomicBlockID::new(START_BLOCK_ID)),
+            slab: Arc::new(Slab::new::<SmartFlavor<Block<FAN_OUT, NUM_RECORDS, Key, Payload>>>()),
+            #[cfg(target_os = "linux")]
+            pool: None,
             _marker: PhantomData,
         }
     }
 
+    /// Hands out the IDs of the pool's slots, see `BPlusTree::create_pooled`.
+    #[cfg(target_os = "linux")]
+    #[inline(always)]
+    pub(crate) fn with_pool(pool: Arc<BlockPool>) -> Self {
+        Self {
+            pool: Some(pool),
+            ..Self::new()
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn new_empty_leaf(&self) -> Block<FAN_OUT, NUM_RECORDS, Key, Payload> {
+        Block {
+            node_data: Node::Leaf(LeafPage::new()),
+            block_id: self.next_block_id(),
+            frame: AtomicU8::new(FRAME_DIRTY),
+            pins: AtomicU32::new(0),
+        }
+    }
+
+    /// Empty leaf taking over the ID of the leaf it replaces, e.g. a copy-on-write copy.
     #[inline(always)]
-    pub(crate) const fn new_empty_leaf(&self) -> Block<FAN_OUT, NUM_RECORDS, Key, Payload> {
+    pub(crate) fn new_leaf_as(&self, block_id: BlockID) -> Block<FAN_OUT, NUM_RECORDS, Key, Payload> {
         Block {
-            // block_id: self.next_block_id(),
-            node_data: Node::Leaf(LeafPage::new())
+            node_data: Node::Leaf(LeafPage::new()),
+            block_id,
+            frame: AtomicU8::new(FRAME_DIRTY),
+            pins: AtomicU32::new(0),
         }
     }
 
@@ -124,8 +206,10 @@ impl<const FAN_OUT: usize,
     #[inline(always)]
     pub(crate) fn new_empty_index_block(&self) -> Block<FAN_OUT, NUM_RECORDS, Key, Payload> {
         Block {
-            // block_id: self.next_block_id(),
-            node_data: Node::Index(InternalPage::new())
+            node_data: Node::Index(InternalPage::new()),
+            block_id: self.next_block_id(),
+            frame: AtomicU8::new(FRAME_DIRTY),
+            pins: AtomicU32::new(0),
         }
     }
 }
\ No newline at end of file
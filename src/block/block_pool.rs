@@ -0,0 +1,589 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::mem;
+use std::os::fd::AsRawFd;
+use std::path::Path;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicU32, AtomicU64};
+use std::sync::atomic::Ordering::Relaxed;
+use parking_lot::Mutex;
+use crate::page_model::BlockID;
+
+pub const POOL_MAGIC: [u8; 8] = *b"CCBPPOOL";
+pub const POOL_VERSION: u32 = 3;
+
+/// The first page holds both copies of the header followed by the encoded tree configuration.
+pub const POOL_HEADER_SIZE: usize = 4096;
+
+/// Each copy of the header takes as many bytes.
+pub const HEADER_COPY_LEN: usize = 128;
+
+/// Each slot starts with the sequence number of the checkpoint that wrote it.
+const SLOT_SEQ_LEN: usize = mem::size_of::<u64>();
+
+/// Slots are multiples of a cache line.
+const SLOT_ALIGNMENT: usize = 64;
+
+#[derive(Debug)]
+pub enum PoolError {
+    Io(io::Error),
+    Encoding(bincode::Error),
+    /// The file does not start with `POOL_MAGIC`.
+    NotAPool,
+    UnsupportedVersion(u32),
+    /// The pool was created for other node sizes, key or payload types.
+    LayoutMismatch,
+    /// The pool holds no complete checkpoint, e.g. due to a crash before the first one completed.
+    Unclean,
+    /// The encoded tree configuration exceeds the header page.
+    ConfigTooLarge(usize),
+    /// The slot does not hold the block its parent expects.
+    CorruptSlot(BlockID),
+    /// The slots of the ID lie beyond the file, e.g. since a corrupt slot referenced it.
+    UnmappedSlot(BlockID),
+    /// The tree was not created by `BPlusTree::create_pooled` or `BPlusTree::open_pooled`.
+    NotPooled,
+}
+
+impl Display for PoolError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PoolError::Io(error) => write!(f, "Block pool I/O failed: {}", error),
+            PoolError::Encoding(error) => write!(f, "Block pool encoding failed: {}", error),
+            PoolError::NotAPool => write!(f, "Not a block pool file"),
+            PoolError::UnsupportedVersion(version) =>
+                write!(f, "Unsupported block pool version {}, expected {}", version, POOL_VERSION),
+            PoolError::LayoutMismatch => write!(f, "Block pool was created for a different node layout"),
+            PoolError::Unclean => write!(f, "Block pool holds no complete checkpoint"),
+            PoolError::ConfigTooLarge(len) =>
+                write!(f, "Tree configuration of {} bytes exceeds the block pool header", len),
+            PoolError::CorruptSlot(block_id) => write!(f, "Block pool slot {} is corrupt", block_id),
+            PoolError::UnmappedSlot(block_id) => write!(f, "Block pool slot {} is not mapped", block_id),
+            PoolError::NotPooled => write!(f, "Tree without block pool")
+        }
+    }
+}
+
+impl Error for PoolError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PoolError::Io(error) => Some(error),
+            PoolError::Encoding(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for PoolError {
+    fn from(error: io::Error) -> Self {
+        PoolError::Io(error)
+    }
+}
+
+impl From<bincode::Error> for PoolError {
+    fn from(error: bincode::Error) -> Self {
+        PoolError::Encoding(error)
+    }
+}
+
+/// Plain data copied bitwise into pool slots, i.e. without pointers, padding or invalid bit patterns.
+/// Trees owning heap data are persisted by `BPlusTree::save_to` instead.
+///
+/// # Safety
+/// Any `size_of::<Self>()` bytes must read back as a valid value, also in another process.
+pub unsafe trait PoolData: Copy + 'static {
+    /// Names the type in the pool's layout fingerprint, stable across compilers.
+    fn describe() -> String;
+}
+
+macro_rules! pool_data {
+    ($($data: ty),*) => {
+        $(unsafe impl PoolData for $data {
+            #[inline(always)]
+            fn describe() -> String {
+                stringify!($data).to_string()
+            }
+        })*
+    };
+}
+
+pool_data!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+unsafe impl<Data: PoolData, const N: usize> PoolData for [Data; N] {
+    #[inline(always)]
+    fn describe() -> String {
+        format!("[{}; {}]", Data::describe(), N)
+    }
+}
+
+/// Fixed part of the header page, kept twice. A checkpoint overwrites the copy not holding
+/// the previous one, hence the complete copy of the higher sequence number is valid.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub(crate) struct PoolHeader {
+    magic: [u8; 8],
+    version: u32,
+    /// Checksum of the copy with this field zeroed, a copy torn by a crash fails it.
+    crc: u32,
+    /// Sequence number of the checkpoint, zero until the first one completed.
+    seq: u64,
+    slot_size: u64,
+    /// Fingerprint of node sizes, key and payload types.
+    layout: u64,
+    /// IDs below were handed out.
+    pub(crate) next_block_id: u64,
+    pub(crate) root: u64,
+    pub(crate) height: u64,
+    config_len: u64,
+}
+
+const CONFIG_OFFSET: usize = 2 * HEADER_COPY_LEN;
+
+impl PoolHeader {
+    #[inline(always)]
+    fn checksum(&self) -> u32 {
+        let mut header
+            = *self;
+
+        header.crc = 0;
+        crc32fast::hash(unsafe {
+            slice::from_raw_parts(&header as *const Self as *const u8, mem::size_of::<Self>())
+        })
+    }
+}
+
+/// Mapped region of the pool's file, the header page followed by `capacity` slots.
+struct Mapping {
+    ptr: *mut u8,
+    len: usize,
+}
+
+/// Memory mapped file of fixed-size block slots, each addressed by its `BlockID`.
+/// Blocks get their IDs from the pool and are written to their slots referencing their children by `BlockID`,
+/// hence reopening the file yields the same blocks with the same IDs. `BPlusTree::checkpoint_pool` writes
+/// all resident blocks, a `BufferPool` writes dirty ones on eviction and faults them in by their IDs.
+/// In memory, children stay reference counted cells, since these hold the latches, i.e. versions,
+/// reader-writer locks and queues, and are reclaimed by epochs once unlinked.
+///
+/// Writes survive a crash: each ID owns two slots stamped with the sequence number of the checkpoint
+/// that wrote them. A block is written to the slot of the checkpoint in progress, or else to the slot
+/// of the older version, and read from the one of the newer version. The last complete checkpoint's
+/// versions are never overwritten, a checkpoint completes by syncing all slots, then writing
+/// the header copy not holding the previous one. Opening a pool discards slots written since,
+/// hence a crash loses the mutations since the last complete checkpoint only.
+pub struct BlockPool {
+    file: File,
+    mapping: Mutex<Mapping>,
+    slot_size: usize,
+    layout: u64,
+    next_block_id: AtomicU32,
+    /// IDs no block held at the last checkpoint or open.
+    free: Mutex<Vec<BlockID>>,
+    /// Header of the last complete checkpoint.
+    header: Mutex<PoolHeader>,
+    /// Sequence number stamped into written slots, i.e. the one of the checkpoint in progress.
+    pending: AtomicU64,
+}
+
+unsafe impl Send for BlockPool {}
+unsafe impl Sync for BlockPool {}
+
+impl BlockPool {
+    /// Rounds a node image and its sequence number up to the slot size.
+    #[inline(always)]
+    pub const fn slot_size_of(image_size: usize) -> usize {
+        (SLOT_SEQ_LEN + image_size).div_ceil(SLOT_ALIGNMENT) * SLOT_ALIGNMENT
+    }
+
+    /// Creates the pool's file with room for as many slots as fit into `pool_size` bytes,
+    /// it grows once they are handed out. Opening the pool fails until its first checkpoint completed.
+    /// Existing files are never overwritten, i.e. fail with `io::ErrorKind::AlreadyExists`.
+    pub fn create(path: impl AsRef<Path>, pool_size: usize, slot_size: usize, layout: u64) -> Result<Self, PoolError> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(path)?;
+
+        let capacity
+            = (pool_size.saturating_sub(POOL_HEADER_SIZE) / (2 * slot_size)).max(1);
+
+        let len
+            = POOL_HEADER_SIZE + 2 * capacity * slot_size;
+
+        file.set_len(len as _)?;
+
+        let header = PoolHeader {
+            magic: POOL_MAGIC,
+            version: POOL_VERSION,
+            crc: 0,
+            seq: 0,
+            slot_size: slot_size as _,
+            layout,
+            next_block_id: BlockID::MIN as _,
+            root: 0,
+            height: 0,
+            config_len: 0,
+        };
+
+        let pool = Self {
+            mapping: Mutex::new(map(&file, len)?),
+            file,
+            slot_size,
+            layout,
+            next_block_id: AtomicU32::new(BlockID::MIN),
+            free: Mutex::new(vec![]),
+            header: Mutex::new(header),
+            pending: AtomicU64::new(1),
+        };
+
+        pool.write_header_copy(0, &header);
+        pool.write_header_copy(1, &header);
+        pool.flush()?;
+        Ok(pool)
+    }
+
+    /// Maps an existing pool, which must have been checkpointed completely with the same layout.
+    /// Slots written after its last complete checkpoint are discarded, which visits all slots.
+    pub fn open(path: impl AsRef<Path>, slot_size: usize, layout: u64) -> Result<Self, PoolError> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+
+        let len
+            = file.metadata()?.len() as usize;
+
+        if len < POOL_HEADER_SIZE {
+            return Err(PoolError::NotAPool);
+        }
+
+        let mapping
+            = map(&file, len)?;
+
+        let copies = [0, HEADER_COPY_LEN]
+            .map(|offset| unsafe { ptr::read_unaligned(mapping.ptr.add(offset) as *const PoolHeader) });
+
+        let pool = Self {
+            mapping: Mutex::new(mapping),
+            file,
+            slot_size,
+            layout,
+            next_block_id: AtomicU32::new(BlockID::MIN),
+            free: Mutex::new(vec![]),
+            header: Mutex::new(copies[0]),
+            pending: AtomicU64::new(1),
+        };
+
+        if copies.iter().all(|copy| copy.magic != POOL_MAGIC) {
+            return Err(PoolError::NotAPool);
+        }
+
+        if let Some(copy) = copies.iter().find(|copy| copy.magic == POOL_MAGIC && copy.version != POOL_VERSION) {
+            return Err(PoolError::UnsupportedVersion(copy.version));
+        }
+
+        let header = copies
+            .into_iter()
+            .filter(|copy| copy.magic == POOL_MAGIC && copy.crc == copy.checksum())
+            .max_by_key(|copy| copy.seq)
+            .ok_or(PoolError::Unclean)?;
+
+        if header.slot_size != slot_size as u64 || header.layout != layout {
+            return Err(PoolError::LayoutMismatch);
+        }
+
+        if header.seq == 0 {
+            return Err(PoolError::Unclean);
+        }
+
+        pool.discard_slots_after(header.seq);
+        pool.next_block_id.store(header.next_block_id as _, Relaxed);
+        pool.pending.store(header.seq + 1, Relaxed);
+        *pool.header.lock() = header;
+        Ok(pool)
+    }
+
+    #[inline(always)]
+    pub const fn slot_size(&self) -> usize {
+        self.slot_size
+    }
+
+    #[inline(always)]
+    pub const fn layout(&self) -> u64 {
+        self.layout
+    }
+
+    /// Number of IDs handed out so far, i.e. the slots in use or free.
+    #[inline(always)]
+    pub fn block_ids(&self) -> BlockID {
+        self.next_block_id.load(Relaxed)
+    }
+
+    /// Number of IDs whose slots the file currently holds.
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        (self.mapping.lock().len - POOL_HEADER_SIZE) / (2 * self.slot_size)
+    }
+
+    /// Sequence number of the last complete checkpoint, the pool reopens to its tree.
+    #[inline(always)]
+    pub fn checkpoints(&self) -> u64 {
+        self.header.lock().seq
+    }
+
+    /// Hands out a free slot's ID, or the next unused one.
+    #[inline]
+    pub(crate) fn allocate(&self) -> BlockID {
+        match self.free.lock().pop() {
+            Some(block_id) => block_id,
+            None => self.next_block_id.fetch_add(1, Relaxed)
+        }
+    }
+
+    /// Replaces the free IDs, i.e. the ones below `block_ids` no block holds.
+    #[inline(always)]
+    pub(crate) fn set_free(&self, mut free: Vec<BlockID>) {
+        free.sort_unstable_by(|a, b| b.cmp(a)); // lowest IDs are handed out first
+        *self.free.lock() = free;
+    }
+
+    /// Header of the last complete checkpoint.
+    #[inline(always)]
+    pub(crate) fn header(&self) -> PoolHeader {
+        *self.header.lock()
+    }
+
+    /// Writes the header with its checksum to the first or second copy.
+    #[inline(always)]
+    fn write_header_copy(&self, copy: usize, header: &PoolHeader) {
+        let mut header
+            = *header;
+
+        header.crc = header.checksum();
+        unsafe { ptr::write_unaligned(self.mapping.lock().ptr.add(copy * HEADER_COPY_LEN) as *mut PoolHeader, header) }
+    }
+
+    /// Encoded tree configuration stored behind the header.
+    pub(crate) fn config(&self) -> Vec<u8> {
+        let len = self.header()
+            .config_len
+            .min((POOL_HEADER_SIZE - CONFIG_OFFSET) as u64) as usize;
+
+        let mapping
+            = self.mapping.lock();
+
+        unsafe { slice::from_raw_parts(mapping.ptr.add(CONFIG_OFFSET), len) }.to_vec()
+    }
+
+    /// Stores the configuration, which becomes durable with the first checkpoint.
+    pub(crate) fn set_config(&self, config: &[u8]) -> Result<(), PoolError> {
+        if config.len() > POOL_HEADER_SIZE - CONFIG_OFFSET {
+            return Err(PoolError::ConfigTooLarge(config.len()));
+        }
+
+        self.header.lock().config_len = config.len() as _;
+
+        let mapping
+            = self.mapping.lock();
+
+        unsafe { ptr::copy_nonoverlapping(config.as_ptr(), mapping.ptr.add(CONFIG_OFFSET), config.len()) };
+        Ok(())
+    }
+
+    /// Syncs all slots, then writes the header of the new root to the copy not holding
+    /// the last checkpoint and syncs it. Until then, the pool reopens to the last checkpoint.
+    pub(crate) fn end_checkpoint(&self, root: BlockID, height: u64) -> Result<(), PoolError> {
+        self.flush()?;
+
+        let mut header
+            = self.header();
+
+        header.seq = self.pending.load(Relaxed);
+        header.root = root as _;
+        header.height = height;
+        header.next_block_id = self.block_ids() as _;
+
+        self.write_header_copy(header.seq as usize % 2, &header);
+        self.flush_range(0, POOL_HEADER_SIZE)?;
+
+        *self.header.lock() = header;
+        self.pending.store(header.seq + 1, Relaxed);
+        Ok(())
+    }
+
+    /// Clears the sequence numbers of slots written by a checkpoint that did not complete,
+    /// hence their versions are neither read nor taken for the checkpoint in progress.
+    fn discard_slots_after(&self, seq: u64) {
+        let mapping
+            = self.mapping.lock();
+
+        let slots
+            = (mapping.len - POOL_HEADER_SIZE) / self.slot_size;
+
+        (0..slots)
+            .map(|slot| POOL_HEADER_SIZE + slot * self.slot_size)
+            .for_each(|offset| unsafe {
+                let slot
+                    = mapping.ptr.add(offset) as *mut u64;
+
+                if ptr::read_unaligned(slot) > seq {
+                    ptr::write_unaligned(slot, 0);
+                }
+            });
+    }
+
+    /// Grows the file and its mapping, until the slot of every ID handed out fits.
+    pub(crate) fn reserve(&self) -> Result<(), PoolError> {
+        let mut mapping
+            = self.mapping.lock();
+
+        let needed
+            = POOL_HEADER_SIZE + 2 * self.block_ids() as usize * self.slot_size;
+
+        if needed <= mapping.len {
+            return Ok(());
+        }
+
+        let len
+            = needed.max(POOL_HEADER_SIZE + 2 * (mapping.len - POOL_HEADER_SIZE));
+
+        self.file.set_len(len as _)?;
+
+        let ptr = unsafe {
+            libc::mremap(mapping.ptr as _, mapping.len, len, libc::MREMAP_MAYMOVE)
+        };
+
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        *mapping = Mapping { ptr: ptr as _, len };
+        Ok(())
+    }
+
+    /// File offset of the slot holding the block's newest version.
+    pub fn slot_offset(&self, block_id: BlockID) -> Result<usize, PoolError> {
+        let mapping
+            = self.mapping.lock();
+
+        self.newest_slot(&mapping, block_id)
+    }
+
+    /// Offsets of both slots of the ID.
+    #[inline(always)]
+    fn slots(&self, mapping: &Mapping, block_id: BlockID) -> Result<[usize; 2], PoolError> {
+        let offset
+            = POOL_HEADER_SIZE + 2 * block_id as usize * self.slot_size;
+
+        match offset + 2 * self.slot_size <= mapping.len {
+            true => Ok([offset, offset + self.slot_size]),
+            false => Err(PoolError::UnmappedSlot(block_id))
+        }
+    }
+
+    #[inline(always)]
+    fn seq_at(mapping: &Mapping, offset: usize) -> u64 {
+        unsafe { ptr::read_unaligned(mapping.ptr.add(offset) as *const u64) }
+    }
+
+    #[inline(always)]
+    fn newest_slot(&self, mapping: &Mapping, block_id: BlockID) -> Result<usize, PoolError> {
+        let [first, second]
+            = self.slots(mapping, block_id)?;
+
+        match Self::seq_at(mapping, second) > Self::seq_at(mapping, first) {
+            true => Ok(second),
+            false => Ok(first)
+        }
+    }
+
+    /// Hands the image of the block's newest version to `access`, which must not exceed
+    /// the slot's image, i.e. `slot_size` bytes less its sequence number.
+    /// Slots are accessed one at a time, since the mapping may move while growing.
+    #[inline(always)]
+    pub(crate) fn with_slot<R>(&self, block_id: BlockID, access: impl FnOnce(*const u8) -> R) -> Result<R, PoolError> {
+        let mapping
+            = self.mapping.lock();
+
+        let offset
+            = self.newest_slot(&mapping, block_id)?;
+
+        Ok(access(unsafe { mapping.ptr.add(offset + SLOT_SEQ_LEN) }))
+    }
+
+    /// Hands the image of the slot written by the checkpoint in progress to `access`,
+    /// i.e. the one written before by this checkpoint or else the one of the older version,
+    /// and stamps it with the checkpoint's sequence number.
+    #[inline(always)]
+    pub(crate) fn with_slot_mut<R>(&self, block_id: BlockID, access: impl FnOnce(*mut u8) -> R) -> Result<R, PoolError> {
+        let mapping
+            = self.mapping.lock();
+
+        let [first, second]
+            = self.slots(&mapping, block_id)?;
+
+        let pending
+            = self.pending.load(Relaxed);
+
+        let (first_seq, second_seq)
+            = (Self::seq_at(&mapping, first), Self::seq_at(&mapping, second));
+
+        let offset = match first_seq == pending || (second_seq != pending && first_seq < second_seq) {
+            true => first,
+            false => second
+        };
+
+        let accessed
+            = access(unsafe { mapping.ptr.add(offset + SLOT_SEQ_LEN) });
+
+        unsafe { ptr::write_unaligned(mapping.ptr.add(offset) as *mut u64, pending) };
+        Ok(accessed)
+    }
+
+    #[inline(always)]
+    fn flush(&self) -> Result<(), PoolError> {
+        let len
+            = self.mapping.lock().len;
+
+        self.flush_range(0, len)
+    }
+
+    fn flush_range(&self, offset: usize, len: usize) -> Result<(), PoolError> {
+        let mapping
+            = self.mapping.lock();
+
+        match unsafe { libc::msync(mapping.ptr.add(offset) as _, len, libc::MS_SYNC) } {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error().into())
+        }
+    }
+}
+
+impl Drop for BlockPool {
+    fn drop(&mut self) {
+        let mapping
+            = self.mapping.get_mut();
+
+        unsafe { libc::munmap(mapping.ptr as _, mapping.len) };
+    }
+}
+
+fn map(file: &File, len: usize) -> io::Result<Mapping> {
+    let ptr = unsafe {
+        libc::mmap(ptr::null_mut(),
+                   len,
+                   libc::PROT_READ | libc::PROT_WRITE,
+                   libc::MAP_SHARED,
+                   file.as_raw_fd(),
+                   0)
+    };
+
+    match ptr == libc::MAP_FAILED {
+        true => Err(io::Error::last_os_error()),
+        false => Ok(Mapping { ptr: ptr as _, len })
+    }
+}
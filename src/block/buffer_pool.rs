@@ -0,0 +1,306 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::mem;
+use std::sync::atomic::{AtomicUsize, fence};
+use std::sync::atomic::Ordering::{Relaxed, SeqCst};
+use parking_lot::Mutex;
+use crate::block::block::{Block, FRAME_EVICTED, FRAME_LISTED, FRAME_REFERENCED};
+use crate::block::block_pool::{BlockPool, PoolError};
+use crate::page_model::{BlockID, BlockRef};
+use crate::utils::slab::SlabArc;
+use crate::utils::smart_cell::{LatchState, LatchType};
+
+/// Reads a leaf from its slot, or an index block as evicted frame whose children refer to the given placeholder.
+pub(crate) type ReadChild<const FAN_OUT: usize, const NUM_RECORDS: usize, Key, Payload>
+    = fn(&BlockPool, BlockID, &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>) -> Result<Block<FAN_OUT, NUM_RECORDS, Key, Payload>, PoolError>;
+
+/// Reads the IDs of an index block's children from its slot.
+pub(crate) type ReadChildIds = fn(&BlockPool, BlockID) -> Result<Vec<BlockID>, PoolError>;
+
+/// Writes a block to its slot.
+pub(crate) type WriteBlock<const FAN_OUT: usize, const NUM_RECORDS: usize, Key, Payload>
+    = fn(&BlockPool, &Block<FAN_OUT, NUM_RECORDS, Key, Payload>) -> Result<(), PoolError>;
+
+/// Pins of the current thread's running operation and the depth of its nested `PinScope`s.
+#[derive(Default)]
+struct Pins {
+    depth: usize,
+    blocks: Vec<Box<dyn Any>>,
+}
+
+thread_local! {
+    static PINS: RefCell<Pins> = RefCell::new(Pins::default());
+}
+
+/// Pin on a block, released once dropped.
+struct Pin<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash,
+    Payload: Default + Clone
+>(BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>);
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash,
+    Payload: Default + Clone
+> Drop for Pin<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.0.unsafe_borrow().pins.fetch_sub(1, SeqCst);
+    }
+}
+
+/// Keeps the blocks latched by the current thread's operation pinned, until the operation completes.
+/// Frames with pinned blocks are not evicted, hence guards and children read from a frame stay valid.
+/// Scopes nest, only the outermost one releases the pins.
+pub(crate) struct PinScope {
+    _not_send: PhantomData<*const ()>,
+}
+
+impl PinScope {
+    #[inline(always)]
+    pub(crate) fn enter() -> Self {
+        PINS.with(|pins| pins.borrow_mut().depth += 1);
+
+        Self { _not_send: PhantomData }
+    }
+
+    /// Pins the block until the outermost scope ends. Pinned before it is latched, such that
+    /// an eviction latching the frame afterwards sees the pin, see `BPlusTree::evict_frame`.
+    #[inline(always)]
+    pub(crate) fn pin<const FAN_OUT: usize,
+        const NUM_RECORDS: usize,
+        Key: Default + Ord + Copy + Hash + 'static,
+        Payload: Default + Clone + 'static
+    >(block: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>)
+    {
+        PINS.with(|pins| {
+            let mut pins
+                = pins.borrow_mut();
+
+            debug_assert!(pins.depth > 0, "Latched a paged block outside of a pin scope");
+
+            if pins.depth > 0 {
+                block.unsafe_borrow().pins.fetch_add(1, SeqCst);
+                pins.blocks.push(Box::new(Pin(block.clone())));
+            }
+        });
+
+        fence(SeqCst);
+    }
+}
+
+impl Drop for PinScope {
+    #[inline(always)]
+    fn drop(&mut self) {
+        let released = PINS.try_with(|pins| {
+            let mut pins
+                = pins.borrow_mut();
+
+            pins.depth -= 1;
+
+            match pins.depth {
+                0 => mem::take(&mut pins.blocks),
+                _ => Vec::new()
+            }
+        });
+
+        mem::drop(released);
+    }
+}
+
+/// Frames in the order the clock hand passes them.
+struct Clock<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash,
+    Payload: Default + Clone
+> {
+    frames: Vec<BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>>,
+    hand: usize,
+}
+
+/// Caches the blocks of a pooled tree in a fixed number of frames, the rest stays in the `BlockPool`.
+/// A frame is an index block below the root together with its children. Latching an evicted frame's
+/// block faults its children in from their slots, children that are index blocks are read as evicted
+/// frames themselves. The frames exceeding the capacity are evicted by the CLOCK algorithm once
+/// an operation completed, see `BPlusTree::release_frames`. Dirty blocks are written back to their
+/// slots on eviction.
+///
+/// Operations pin the blocks they latch until they complete, see `PinScope`, frames with pinned
+/// blocks are not evicted. A frame is evicted once its children are leaves or evicted frames, hence
+/// levels are evicted bottom-up. Evicted children are marked obsolete, hence optimistic readers restart
+/// as after a merge. A frame whose slots are corrupt stays evicted and fails the operations reaching it,
+/// see `CRUDOperationResult::Error`.
+///
+/// Only the root and its children stay resident regardless of the capacity. Hence, a tree needs memory
+/// for `capacity` frames, each with up to `FAN_OUT` children, plus the root's.
+pub struct BufferPool<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash,
+    Payload: Default + Clone
+> {
+    capacity: usize,
+    clock: Mutex<Clock<FAN_OUT, NUM_RECORDS, Key, Payload>>,
+    /// Obsolete leaf all children of evicted frames refer to, flagged as evicted itself.
+    pub(crate) evicted: BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>,
+    pub(crate) read_child: ReadChild<FAN_OUT, NUM_RECORDS, Key, Payload>,
+    pub(crate) read_child_ids: ReadChildIds,
+    pub(crate) write_block: WriteBlock<FAN_OUT, NUM_RECORDS, Key, Payload>,
+    faults: AtomicUsize,
+    failures: AtomicUsize,
+    evictions: AtomicUsize,
+    write_backs: AtomicUsize,
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + 'static,
+    Payload: Default + Clone + 'static
+> BufferPool<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    pub(crate) fn new(capacity: usize,
+                      latch_type: LatchType,
+                      read_child: ReadChild<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                      read_child_ids: ReadChildIds,
+                      write_block: WriteBlock<FAN_OUT, NUM_RECORDS, Key, Payload>) -> Self
+    {
+        let evicted
+            = Block::default().into_cell(latch_type);
+
+        let mut guard
+            = evicted.borrow_mut();
+
+        guard.mark_obsolete();
+        mem::drop(guard);
+
+        evicted.unsafe_borrow().set_frame(FRAME_EVICTED);
+
+        Self {
+            capacity: capacity.max(1),
+            clock: Mutex::new(Clock { frames: vec![], hand: 0 }),
+            evicted,
+            read_child,
+            read_child_ids,
+            write_block,
+            faults: AtomicUsize::new(0),
+            failures: AtomicUsize::new(0),
+            evictions: AtomicUsize::new(0),
+            write_backs: AtomicUsize::new(0),
+        }
+    }
+
+    /// Number of frames kept resident once operations complete.
+    #[inline(always)]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of frames currently resident, including frames unlinked since the clock hand passed them.
+    #[inline(always)]
+    pub fn resident(&self) -> usize {
+        self.clock.lock().frames.len()
+    }
+
+    #[inline(always)]
+    pub fn faults(&self) -> usize {
+        self.faults.load(Relaxed)
+    }
+
+    /// Number of frames not faulted in, since a slot was corrupt. Each failed an operation.
+    #[inline(always)]
+    pub fn failures(&self) -> usize {
+        self.failures.load(Relaxed)
+    }
+
+    #[inline(always)]
+    pub fn evictions(&self) -> usize {
+        self.evictions.load(Relaxed)
+    }
+
+    /// Number of leaves and index blocks written to their slots on eviction.
+    #[inline(always)]
+    pub fn write_backs(&self) -> usize {
+        self.write_backs.load(Relaxed)
+    }
+
+    #[inline(always)]
+    pub(crate) fn record_fault(&self) {
+        self.faults.fetch_add(1, Relaxed);
+    }
+
+    #[inline(always)]
+    pub(crate) fn record_failure(&self) {
+        self.failures.fetch_add(1, Relaxed);
+    }
+
+    #[inline(always)]
+    pub(crate) fn record_eviction(&self, write_backs: usize) {
+        self.evictions.fetch_add(1, Relaxed);
+        self.write_backs.fetch_add(write_backs, Relaxed);
+    }
+
+    /// Gives the frame a second chance and lists it, unless it is listed already.
+    #[inline(always)]
+    pub(crate) fn touch(&self, frame: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>) {
+        let block
+            = frame.unsafe_borrow();
+
+        block.set_frame(FRAME_REFERENCED);
+
+        if block.frame.load(Relaxed) & FRAME_LISTED == 0
+            && block.frame.fetch_or(FRAME_LISTED, Relaxed) & FRAME_LISTED == 0
+        {
+            self.clock.lock().frames.push(frame.clone());
+        }
+    }
+
+    /// Advances the clock hand to the next frame not referenced since the hand passed it and unlists it.
+    /// Frames unlinked from the tree, marked obsolete or equal to `root` are unlisted on the way.
+    pub(crate) fn next_victim(&self, root: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>)
+                              -> Option<BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>>
+    {
+        let mut clock
+            = self.clock.lock();
+
+        for _ in 0..2 * clock.frames.len() {
+            if clock.frames.is_empty() {
+                break;
+            }
+
+            if clock.hand >= clock.frames.len() {
+                clock.hand = 0;
+            }
+
+            let hand
+                = clock.hand;
+
+            let frame
+                = &clock.frames[hand];
+
+            // unlinked frames may still be referenced by readers or retired, but never relinked
+            let discard = SlabArc::strong_count(&frame.0) == 1
+                || frame.unsafe_borrow().is_unlinked()
+                || SlabArc::ptr_eq(&frame.0, &root.0)
+                || frame.latch_state().0 == LatchState::Obsolete;
+
+            if !discard && frame.unsafe_borrow().clear_frame(FRAME_REFERENCED) != 0 {
+                clock.hand += 1;
+                continue;
+            }
+
+            let frame
+                = clock.frames.swap_remove(hand);
+
+            frame.unsafe_borrow().clear_frame(FRAME_LISTED);
+
+            if !discard {
+                return Some(frame);
+            }
+        }
+
+        None
+    }
+}
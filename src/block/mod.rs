@@ -1,2 +1,7 @@
 pub mod block_manager;
+#[allow(clippy::module_inception)]
 pub mod block;
+#[cfg(target_os = "linux")]
+pub mod block_pool;
+#[cfg(target_os = "linux")]
+pub mod buffer_pool;
@@ -0,0 +1,346 @@
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::iter::Peekable;
+use std::{mem, thread, vec};
+use crate::crud_model::crud_api::NodeVisits;
+use crate::crud_model::crud_operation_result::CRUDOperationResult;
+use crate::locking::strategy_override;
+use crate::page_model::node::Node;
+use crate::record_model::record_point::RecordPoint;
+use crate::tree::bplus_tree::{BPlusTree, INIT_TREE_HEIGHT};
+use crate::utils::epoch;
+use crate::utils::metrics::OperationKind;
+
+type Records<Key, Payload> = Peekable<vec::IntoIter<RecordPoint<Key, Payload>>>;
+
+/// Outcome of `BPlusTree::insert_batch`.
+#[derive(Clone, Debug, Default)]
+pub struct BatchResult<Key> {
+    pub inserted: usize,
+    /// Keys stored already or repeated within the batch, of which only the first record was inserted.
+    pub duplicates: Vec<Key>,
+    /// Keys not inserted, since the tree reached its memory limit, see `BPlusTree::with_memory_limit`.
+    pub rejected: Vec<Key>,
+    /// Keys inserted, but whose log record could not be appended or committed,
+    /// see `CRUDOperationResult::NotDurable`.
+    pub not_durable: Vec<Key>,
+    /// Keys not inserted, since their leaf could not be read from the block pool, see `BufferPool`.
+    pub failed: Vec<Key>,
+    pub node_visits: NodeVisits,
+    /// Leaves written, i.e. traversals taken.
+    pub leaf_visits: usize,
+}
+
+impl<Key> BatchResult<Key> {
+    fn merge(mut self, other: Self) -> Self {
+        self.inserted += other.inserted;
+        self.duplicates.extend(other.duplicates);
+        self.rejected.extend(other.rejected);
+        self.not_durable.extend(other.not_durable);
+        self.failed.extend(other.failed);
+        self.node_visits += other.node_visits;
+        self.leaf_visits += other.leaf_visits;
+        self
+    }
+}
+
+impl<Key> Display for BatchResult<Key> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BatchResult(inserted={};duplicates={};rejected={};not_durable={};failed={};node_visits={};leaf_visits={})",
+               self.inserted,
+               self.duplicates.len(),
+               self.rejected.len(),
+               self.not_durable.len(),
+               self.failed.len(),
+               self.node_visits,
+               self.leaf_visits)
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + Send + Display + 'static,
+    Payload: Default + Clone + Sync + Send + Display + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Inserts an unsorted batch of records from up to `threads` workers. The batch is sorted and split
+    /// at the root's separators into one run per worker, hence workers rarely meet in a subtree.
+    /// Each traversal fills the leaf with all following records up to its fence until it overflows,
+    /// instead of traversing once per record. Records are latched, logged and limited like single inserts,
+    /// of records with equal keys the first one is inserted. Mono writer trees insert from a single worker.
+    pub fn insert_batch(&self,
+                        records: impl IntoIterator<Item=(Key, Payload)>,
+                        threads: usize) -> BatchResult<Key>
+    {
+        let mut records = records
+            .into_iter()
+            .map(|(key, payload)| RecordPoint::new(key, payload))
+            .collect::<Vec<_>>();
+
+        records.sort_by_key(RecordPoint::key);
+
+        let mut result
+            = BatchResult::default();
+
+        records.dedup_by(|next, previous| match next.key == previous.key {
+            true => {
+                result.duplicates.push(next.key);
+                true
+            }
+            false => false
+        });
+
+        let threads = match self.locking_strategy.is_mono_writer() {
+            true => 1,
+            false => threads.max(1)
+        };
+
+        let mut runs
+            = self.split_batch(records, threads);
+
+        if runs.len() == 1 {
+            return result.merge(self.insert_run(runs.pop().unwrap()));
+        }
+
+        // workers latch like the calling thread, see `dispatch_with`
+        let strategy_override
+            = strategy_override::current();
+
+        thread::scope(|scope| runs
+            .into_iter()
+            .map(|run| scope.spawn(move || {
+                let _override
+                    = strategy_override::scoped(strategy_override);
+
+                self.insert_run(run)
+            }))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .fold(result, BatchResult::merge))
+    }
+
+    /// Splits ascending records into at most `threads` runs of similar size. Runs end at separators
+    /// of the root, which is read like by a point query. Separators changed meanwhile only unbalance runs,
+    /// a root without separators is split evenly.
+    fn split_batch(&self,
+                   records: Vec<RecordPoint<Key, Payload>>,
+                   threads: usize) -> Vec<Vec<RecordPoint<Key, Payload>>>
+    {
+        let len
+            = records.len();
+
+        let separators = {
+            let _epoch
+                = self.locking_strategy.is_optimistic().then(epoch::pin);
+
+            #[cfg(target_os = "linux")]
+            let _pins
+                = self.pin_scope();
+
+            let root
+                = self.root.block();
+
+            // an unreadable root fails the runs' inserts instead
+            match self.lock_reader(&root, INIT_TREE_HEIGHT) {
+                Ok(guard) => {
+                    let separators = match unsafe { guard.deref_unsafe() }.map(|node| node.as_ref()) {
+                        Some(Node::Index(index_page)) => index_page.keys().to_vec(),
+                        _ => Vec::new()
+                    };
+
+                    match guard.is_valid() {
+                        true => separators,
+                        false => Vec::new()
+                    }
+                }
+                Err(..) => Vec::new()
+            }
+        };
+
+        let ends = match separators.is_empty() {
+            true => (1..threads)
+                .map(|run| run * len / threads)
+                .collect::<Vec<_>>(),
+            false => separators
+                .iter()
+                .map(|separator| records.partition_point(|record| (self.inc_key)(record.key) <= *separator))
+                .collect()
+        };
+
+        let target
+            = len.div_ceil(threads).max(1);
+
+        let mut sizes
+            = Vec::with_capacity(threads);
+
+        let mut start = 0;
+        for end in ends {
+            if end - start >= target && sizes.len() + 1 < threads {
+                sizes.push(end - start);
+                start = end;
+            }
+        }
+
+        sizes.push(len - start);
+
+        let mut records
+            = records.into_iter();
+
+        sizes.into_iter()
+            .filter(|size| *size > 0)
+            .map(|size| records.by_ref().take(size).collect())
+            .collect()
+    }
+
+    /// Inserts ascending records leaf by leaf, the remaining records are rejected once the limit is reached.
+    fn insert_run(&self, records: Vec<RecordPoint<Key, Payload>>) -> BatchResult<Key> {
+        let mut result
+            = BatchResult::default();
+
+        let mut records
+            = records.into_iter().peekable();
+
+        while let Some(next) = records.peek() {
+            if self.admit_insert(self.memory.payload_bytes(next.payload_ref())).is_err() {
+                result.rejected.extend(records.map(|record| record.key));
+                break;
+            }
+
+            self.insert_leaf_run(&mut records, &mut result);
+        }
+
+        result
+    }
+
+    /// Traverses to the leaf of the next record once and fills it, see `fill_leaf`.
+    /// Latches, logs and commits like `dispatch` does for a single insert.
+    fn insert_leaf_run(&self, records: &mut Records<Key, Payload>, result: &mut BatchResult<Key>) {
+        let key = records
+            .peek()
+            .unwrap()
+            .key;
+
+        let _epoch
+            = self.locking_strategy.is_optimistic().then(epoch::pin);
+
+        #[cfg(target_os = "linux")]
+        let _pins
+            = self.pin_scope();
+
+        let _metrics
+            = self.metrics.enter(OperationKind::Insert);
+
+        let applied = if self.locking_strategy.is_copy_on_write() {
+            self.apply_cow_with(
+                key,
+                |leaf, fence_upper| {
+                    let inserted
+                        = self.fill_leaf(leaf, fence_upper, records, result);
+
+                    let changed
+                        = !inserted.is_empty();
+
+                    ((inserted, vec![]), changed)
+                },
+                |leaf, inserted| self.logged_batch(leaf, inserted))
+        } else {
+            let fenced = match self.locking_strategy.is_optimistic() {
+                true => self.traversal_write_olc_fenced(key),
+                false => self.traversal_write_fenced(key)
+            };
+
+            fenced.map(|(node_visits, guard, fence_upper)| {
+                let leaf
+                    = guard.deref_mut().unwrap();
+
+                let inserted
+                    = self.fill_leaf(leaf, fence_upper, records, result);
+
+                (node_visits, self.logged_batch(leaf, (inserted, vec![])))
+            })
+        };
+
+        mem::drop(_epoch);
+        #[cfg(target_os = "linux")]
+        mem::drop(_pins);
+
+        // the leaf's frame can't be faulted in, hence the next record fails like a single insert
+        let (node_visits, (inserted, not_logged)) = match applied {
+            Ok(applied) => applied,
+            Err(..) => {
+                result.failed.extend(records.next().map(|record| record.key));
+                (NodeVisits::MIN, (vec![], vec![]))
+            }
+        };
+
+        result.node_visits += node_visits;
+        result.leaf_visits += 1;
+        result.inserted += inserted.len();
+
+        if let Some(key) = inserted.last() {
+            match self.commit_log(CRUDOperationResult::Inserted(*key)) {
+                CRUDOperationResult::NotDurable(..) => result.not_durable.extend(inserted),
+                _ => result.not_durable.extend(not_logged)
+            }
+        }
+
+        // failures keep the frames resident, they are evicted by a later operation
+        #[cfg(target_os = "linux")]
+        if self.buffer.is_some() {
+            let _ = self.release_frames();
+        }
+    }
+
+    /// Inserts the next record into the leaf, followed by all records up to the leaf's upper fence
+    /// until the leaf overflows or the limit is reached. The overflow is corrected by the next traversal
+    /// like after a single insert. Returns the keys inserted.
+    fn fill_leaf(&self,
+                 leaf: &mut Node<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                 fence_upper: Key,
+                 records: &mut Records<Key, Payload>,
+                 result: &mut BatchResult<Key>) -> Vec<Key>
+    {
+        let mut inserted
+            = Vec::new();
+
+        let mut next
+            = records.next();
+
+        while let Some(record) = next {
+            let (key, stored)
+                = (record.key, self.memory.payload_bytes(record.payload_ref()));
+
+            if leaf.push_record_point(key, record.payload) {
+                self.memory.account(stored, &CRUDOperationResult::<Key, Payload>::Inserted(key));
+                inserted.push(key);
+            } else {
+                result.duplicates.push(key);
+            }
+
+            next = match self.has_overflow(leaf) {
+                true => None,
+                false => records.next_if(|record| record.key <= fence_upper &&
+                    !self.exceeds_limit(self.memory.payload_bytes(record.payload_ref())))
+            };
+        }
+
+        inserted
+    }
+
+    /// Logs the records inserted into the leaf, see `logged`. Returns the keys inserted along with those not logged.
+    #[inline(always)]
+    fn logged_batch(&self,
+                    leaf: &Node<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                    (inserted, mut not_logged): (Vec<Key>, Vec<Key>)) -> (Vec<Key>, Vec<Key>)
+    {
+        if self.log.is_some() {
+            not_logged.extend(inserted
+                .iter()
+                .filter(|key| matches!(self.logged(leaf, CRUDOperationResult::Inserted(**key)),
+                    CRUDOperationResult::NotDurable(..))));
+        }
+
+        (inserted, not_logged)
+    }
+}
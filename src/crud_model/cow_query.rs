@@ -0,0 +1,472 @@
+use std::fmt::Display;
+use std::hash::Hash;
+use std::mem;
+use itertools::Itertools;
+use crate::block::block::{Block, BlockGuard};
+use crate::crud_model::crud_api::NodeVisits;
+use crate::crud_model::crud_operation_result::CRUDOperationResult;
+use crate::page_model::{Attempts, BlockID, BlockRef};
+use crate::page_model::node::Node;
+use crate::tree::bplus_tree::{BPlusTree, INIT_TREE_HEIGHT, LockLevel, MAX_TREE_HEIGHT};
+use crate::tree::export::NodeKind;
+use crate::utils::epoch;
+use crate::utils::interval::Interval;
+use crate::utils::smart_cell::sched_yield;
+
+/// Guard on the pinned parent of the target leaf, the leaf's position within it and the leaf's upper fence,
+/// or the restart level and attempt. Without position, the guard write latches a leaf root.
+type CowTarget<'a, const FAN_OUT: usize, const NUM_RECORDS: usize, Key, Payload>
+    = Result<(BlockGuard<'a, FAN_OUT, NUM_RECORDS, Key, Payload>, Option<usize>, Key), (LockLevel, Attempts)>;
+
+/// Copy-on-write (RCU) access paths.
+/// Leaves are never modified in place: writers pin the parent, copy the leaf, apply their change
+/// and publish the copy into the parent's slot. Hence, readers only validate internal pages
+/// and read leaves without any latch or restart. A leaf root is the only exception,
+/// since it is updated in place and read under a pin.
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + Display + 'static,
+    Payload: Default + Clone + Sync + Display + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Descends to the leaf covering the key and hands the leaf with its fence to the reader.
+    /// Children are copied out of their parent before it validates, hence a leaf replaced afterwards
+    /// is still read from the copy. The epoch is pinned, such that the copy is not released meanwhile.
+    #[inline]
+    pub(crate) fn read_leaf_cow<R>(&self,
+                                   key: Key,
+                                   read: impl Fn(&Block<FAN_OUT, NUM_RECORDS, Key, Payload>, &Interval<Key>) -> R)
+                                   -> Result<(NodeVisits, R), BlockID>
+    {
+        // checkpoints and multi-gets read outside of `dispatch`
+        let _epoch
+            = epoch::pin();
+
+        let mut attempt = 0;
+        let mut node_visits = 0;
+        let mut curr_level = INIT_TREE_HEIGHT;
+
+        let search_key
+            = (self.inc_key)(key);
+
+        'restart: loop {
+            if attempt > 0 {
+                self.record_restart(curr_level);
+                sched_yield(attempt);
+            }
+
+            attempt += 1;
+            curr_level = INIT_TREE_HEIGHT;
+
+            let mut fence
+                = Interval::new(self.min_key, self.max_key);
+
+            let mut current_guard
+                = self.lock_reader(&self.root.block, INIT_TREE_HEIGHT)?;
+
+            loop {
+                node_visits += 1;
+
+                let current
+                    = unsafe { current_guard.deref_unsafe() };
+
+                let (read_ok, current_reader_version)
+                    = current_guard.is_read_not_obsolete_result();
+
+                if current.is_none() || !read_ok {
+                    continue 'restart;
+                }
+
+                match current.unwrap().as_ref() {
+                    Node::Leaf(..) => {
+                        if !current_guard.upgrade_pin_lock() {
+                            continue 'restart;
+                        }
+
+                        let result
+                            = read(unsafe { current_guard.deref_unsafe() }.unwrap(), &fence);
+
+                        break 'restart Ok((node_visits, result))
+                    }
+                    Node::Index(index_page) => unsafe {
+                        let keys
+                            = index_page.keys();
+
+                        let pos = match keys.binary_search(&search_key) {
+                            Ok(pos) | Err(pos) => pos
+                        };
+
+                        let next_fence = Interval::new(
+                            pos.checked_sub(1)
+                                .and_then(|lower| keys.get(lower).cloned())
+                                .unwrap_or(fence.lower()),
+                            keys.get(pos)
+                                .map(|upper| (self.dec_key)(*upper))
+                                .unwrap_or(fence.upper()));
+
+                        // copied first, the slot may be republished once validated
+                        let next_node
+                            = index_page.get_child_result(pos);
+
+                        let (read_ok, read_version)
+                            = current_guard.is_read_not_obsolete_result();
+
+                        if !read_ok || read_version != current_reader_version {
+                            continue 'restart;
+                        }
+
+                        fence = next_fence;
+
+                        let next_block
+                            = next_node.assume_init_ref();
+
+                        let next_leaf
+                            = next_block.unsafe_borrow();
+
+                        if next_leaf.is_leaf() {
+                            // the parent's frame was evicted after validating it, see `BufferPool`
+                            if next_leaf.is_evicted() {
+                                continue 'restart;
+                            }
+
+                            break 'restart Ok((node_visits + 1, read(next_leaf, &fence)))
+                        }
+
+                        curr_level += 1;
+                        current_guard = self.lock_reader(next_block, curr_level)?;
+                    }
+                }
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn range_query_cow(&self, key_interval: Interval<Key>)
+                                  -> Result<(NodeVisits, CRUDOperationResult<Key, Payload>), BlockID>
+    {
+        let mut node_visits
+            = 0;
+
+        let mut all_results
+            = vec![];
+
+        let mut lower
+            = key_interval.lower();
+
+        loop {
+            let (visits, (local_results, fence_upper)) = self.read_leaf_cow(
+                lower,
+                |leaf, fence| (leaf
+                    .as_records()
+                    .iter()
+                    .skip_while(|record| record.key() < lower)
+                    .take_while(|record| record.key() <= key_interval.upper())
+                    .cloned()
+                    .collect_vec(),
+                    fence.upper()))?;
+
+            node_visits += visits;
+            all_results.extend(local_results);
+
+            if fence_upper >= key_interval.upper() || fence_upper == self.max_key {
+                break;
+            }
+
+            lower = (self.inc_key)(fence_upper);
+        }
+
+        Ok((node_visits, CRUDOperationResult::MatchedRecords(all_results)))
+    }
+
+    /// Applies a mutating operation on a private copy of the target leaf, publishes the copy and logs the result.
+    /// Operations yielding `CRUDOperationResult::Error` did not change anything and publish nothing.
+    #[inline]
+    pub(crate) fn apply_cow(&self,
+                            key: Key,
+                            operation: impl Fn(&mut Block<FAN_OUT, NUM_RECORDS, Key, Payload>) -> CRUDOperationResult<Key, Payload>)
+                            -> Result<(NodeVisits, CRUDOperationResult<Key, Payload>), BlockID>
+    {
+        self.apply_cow_with(
+            key,
+            |leaf, _| {
+                let result
+                    = operation(leaf);
+
+                let changed
+                    = !matches!(result, CRUDOperationResult::Error);
+
+                (result, changed)
+            },
+            |leaf, result| self.logged(leaf, result))
+    }
+
+    /// Like `apply_cow`, but the operation also receives the leaf's upper fence, see `traversal_write_fenced`,
+    /// and tells whether it changed the leaf. `log` logs the result once the copy is published.
+    #[inline]
+    pub(crate) fn apply_cow_with<R>(&self,
+                                    key: Key,
+                                    operation: impl FnOnce(&mut Block<FAN_OUT, NUM_RECORDS, Key, Payload>, Key) -> (R, bool),
+                                    log: impl FnOnce(&Node<FAN_OUT, NUM_RECORDS, Key, Payload>, R) -> R)
+                                    -> Result<(NodeVisits, R), BlockID>
+    {
+        let (node_visits, guard, leaf_pos, fence_upper)
+            = self.traversal_write_cow(key)?;
+
+        let leaf_pos = match leaf_pos {
+            None => {
+                let leaf
+                    = guard.deref_mut().unwrap();
+
+                let (result, ..)
+                    = operation(leaf, fence_upper);
+
+                return Ok((node_visits, log(leaf, result)))
+            }
+            Some(leaf_pos) => leaf_pos
+        };
+
+        let index_page = match unsafe { guard.deref_unsafe() }.unwrap().as_ref() {
+            Node::Index(index_page) => index_page,
+            Node::Leaf(..) => unreachable!("Pinned parent of a copy-on-write leaf must be an index page")
+        };
+
+        let leaf
+            = unsafe { index_page.get_child_unsafe(leaf_pos) }.unsafe_borrow();
+
+        // the copy replaces the leaf in place, hence it keeps the leaf's ID
+        let mut leaf_copy
+            = self.block_manager.new_leaf_as(leaf.block_id());
+
+        leaf_copy
+            .records_mut()
+            .extend_from_slice(leaf.as_records());
+
+        let (result, changed)
+            = operation(&mut leaf_copy, fence_upper);
+
+        if !changed {
+            return Ok((node_visits, result));
+        }
+
+        let old_leaf = unsafe {
+            index_page.publish_child(
+                leaf_pos,
+                self.block_manager.new_cell(leaf_copy, self.locking_strategy.latch_type()))
+        };
+
+        // logged once published under the pinned parent, hence a checkpoint reading
+        // the leaf after the record's position sees the record applied
+        let result = log(
+            unsafe { index_page.get_child_unsafe(leaf_pos) }.unsafe_borrow(),
+            result);
+
+        self.mark_obsolete_cow(&old_leaf);
+        self.retire_block(old_leaf);
+
+        Ok((node_visits, result))
+    }
+
+    /// Unlinked leaves are latched by nobody else, since every structure modification on them
+    /// requires the pinned parent. Marking them obsolete lets pending writers restart.
+    #[inline(always)]
+    fn mark_obsolete_cow(&self, old_leaf: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>) {
+        let mut attempt = 0;
+        loop {
+            let mut leaf_guard
+                = old_leaf.borrow_mut();
+
+            if leaf_guard.is_write_lock() {
+                leaf_guard.mark_obsolete();
+                self.observe(|observer| observer.on_obsolete(NodeKind::Leaf, leaf_guard.deref().unwrap().block_id()));
+                break;
+            }
+
+            mem::drop(leaf_guard);
+
+            attempt += 1;
+            sched_yield(attempt);
+        }
+    }
+
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn traversal_write_cow(&self, key: Key)
+                           -> Result<(NodeVisits, BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>, Option<usize>, Key), BlockID>
+    {
+        let mut attempt = 0;
+        let mut lock_level = MAX_TREE_HEIGHT;
+        let mut node_visits = 0usize;
+
+        loop {
+            match self.traversal_write_cow_internal(lock_level, attempt, key)? {
+                (visits, Err((n_lock_level, n_attempt))) => {
+                    attempt = n_attempt;
+                    lock_level = n_lock_level;
+                    node_visits += visits;
+
+                    self.record_restart(n_lock_level + 1);
+                    sched_yield(attempt);
+                }
+                (visits, Ok((guard, leaf_pos, fence_upper))) => break Ok((node_visits + visits, guard, leaf_pos, fence_upper)),
+            }
+        }
+    }
+
+    /// Descends like `traversal_write_olc_internal`, but stops at the parent of the target leaf
+    /// and pins it. If the root is a leaf, the root is write latched and no position returned.
+    #[inline]
+    fn traversal_write_cow_internal(&self, lock_level: LockLevel, attempt: Attempts, key: Key)
+        -> Result<(NodeVisits, CowTarget<'_, FAN_OUT, NUM_RECORDS, Key, Payload>), BlockID>
+    {
+        let mut curr_level = INIT_TREE_HEIGHT;
+
+        let (mut node_visits,
+            mut current_guard,
+            height,
+            lock_level,
+            attempt
+        ) = self.retrieve_root_olc(lock_level, attempt)?;
+
+        let mut fence
+            = Interval::new(self.min_key, self.max_key);
+
+        let key = (self.inc_key)(key);
+
+        loop {
+            let current_guard_result
+                = current_guard.deref();
+
+            if current_guard_result.is_none() {
+                mem::drop(current_guard);
+
+                return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
+            }
+
+            match current_guard_result.unwrap().as_ref() {
+                Node::Index(index_page) => unsafe {
+                    node_visits += 1;
+
+                    let (child_pos, next_node)
+                        = match index_page.keys().binary_search(&key)
+                    {
+                        Ok(pos) | Err(pos) => (pos, index_page.get_child_result(pos)),
+                    };
+
+                    if !current_guard.is_valid() {
+                        mem::drop(current_guard);
+
+                        return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
+                    }
+
+                    let next_block
+                        = next_node.assume_init_ref().unsafe_borrow();
+
+                    if next_block.is_leaf() && !self.has_overflow(next_block) && !self.has_underflow(next_block) {
+                        // validated by pinning, the parent's keys stay while pinned
+                        let fence_upper = self.fence_upper_inclusive(match child_pos < index_page.len() {
+                            true => index_page.get_key(child_pos),
+                            false => fence.upper
+                        });
+
+                        if !current_guard.upgrade_pin_lock() {
+                            mem::drop(current_guard);
+
+                            return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
+                        }
+
+                        let leaf = match current_guard.deref_unsafe().unwrap().as_ref() {
+                            Node::Index(index_page) => index_page
+                                .get_child_unsafe(child_pos)
+                                .unsafe_borrow(),
+                            Node::Leaf(..) => unreachable!()
+                        };
+
+                        if self.has_overflow(leaf) || self.has_underflow(leaf) { // changed before pinned
+                            mem::drop(current_guard);
+
+                            return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
+                        }
+
+                        return Ok((node_visits + 1, Ok((current_guard, Some(child_pos), fence_upper))));
+                    }
+
+                    curr_level += 1;
+
+                    let mut next_guard = self.apply_for_ref(
+                        curr_level,
+                        lock_level,
+                        attempt,
+                        height,
+                        next_node.assume_init_ref())?;
+
+                    let next_guard_result
+                        = next_guard.deref_unsafe();
+
+                    // read before the parent is validated, see `traversal_write_olc_internal`
+                    let parent_len
+                        = index_page.len();
+
+                    let next_fence = Interval::new(
+                        match child_pos > 0 {
+                            true => index_page.get_key(child_pos - 1),
+                            false => fence.lower
+                        },
+                        match child_pos < parent_len {
+                            true => index_page.get_key(child_pos),
+                            false => fence.upper
+                        });
+
+                    if next_guard_result.is_none() || !current_guard.is_valid() {
+                        mem::drop(next_guard);
+                        mem::drop(current_guard);
+
+                        return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
+                    }
+
+                    let has_overflow_next
+                        = self.has_overflow(next_guard_result.unwrap());
+
+                    let has_underflow_next = self.has_underflow_at(
+                        next_guard_result.unwrap(), fence.upper, child_pos, parent_len);
+
+                    if has_overflow_next || has_underflow_next {
+                        if !current_guard.upgrade_write_lock() || !next_guard.upgrade_write_lock() {
+                            mem::drop(next_guard);
+                            mem::drop(current_guard);
+
+                            return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
+                        }
+
+                        if has_overflow_next {
+                            self.do_overflow_correction(
+                                &fence,
+                                &mut current_guard,
+                                child_pos,
+                                next_guard)
+                        } else if self.do_underflow_correction(
+                            &fence,
+                            curr_level,
+                            attempt,
+                            lock_level,
+                            &mut current_guard,
+                            child_pos,
+                            next_guard)?.is_err()
+                        {
+                            return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
+                        }
+
+                        curr_level -= 1;
+                    } else {
+                        fence = next_fence;
+                        current_guard = next_guard;
+                    }
+                }
+                Node::Leaf(..) => return Ok(if current_guard.upgrade_write_lock() {
+                    (node_visits, Ok((current_guard, None, self.fence_upper_inclusive(fence.upper))))
+                } else {
+                    (node_visits, Err((curr_level - 1, attempt + 1)))
+                }),
+            }
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use std::hash::Hash;
 use crate::crud_model::crud_operation::CRUDOperation;
 use crate::crud_model::crud_operation_result::CRUDOperationResult;
+use crate::locking::strategy_override::{self, StrategyOverride};
+use crate::utils::profile::{self, OperationProfile};
 
 pub type NodeVisits = usize;
 pub trait CRUDDispatcher<
@@ -10,4 +12,46 @@ pub trait CRUDDispatcher<
     fn dispatch(&self,
                 operation: CRUDOperation<Key, Payload>
     ) -> (NodeVisits, CRUDOperationResult<Key, Payload>);
+
+    /// Whether operations may be dispatched with the override. Dispatchers support inheriting
+    /// their strategy only, unless they override this. `StrategyOverride::OptimisticRead` is only
+    /// honoured by trees latched by `LightweightHybridLock` or `HybridLocking`,
+    /// see `StrategyOverride::is_supported_by`.
+    #[inline]
+    fn supports(&self, strategy_override: StrategyOverride) -> bool {
+        strategy_override == StrategyOverride::Inherit
+    }
+
+    /// Dispatches the operation with the tree's locking strategy overridden,
+    /// e.g. for request classes known to be contentious ahead of time.
+    /// Overrides the tree does not support are rejected as `UnsupportedOverride`, see `supports`.
+    #[inline]
+    fn dispatch_with(&self,
+                     operation: CRUDOperation<Key, Payload>,
+                     strategy_override: StrategyOverride
+    ) -> (NodeVisits, CRUDOperationResult<Key, Payload>) {
+        if !self.supports(strategy_override) {
+            return (NodeVisits::MIN, CRUDOperationResult::UnsupportedOverride(strategy_override));
+        }
+
+        let _override
+            = strategy_override::scoped(strategy_override);
+
+        self.dispatch(operation)
+    }
+
+    /// Dispatches the operation and records its latches, restarts, structure modifications
+    /// and elapsed time, e.g. to trace individual slow requests.
+    #[inline]
+    fn dispatch_profiled(&self,
+                         operation: CRUDOperation<Key, Payload>
+    ) -> (NodeVisits, CRUDOperationResult<Key, Payload>, OperationProfile) {
+        let profile
+            = profile::start((&operation).into());
+
+        let (node_visits, result)
+            = self.dispatch(operation);
+
+        (node_visits, result, profile.finish())
+    }
 }
\ No newline at end of file
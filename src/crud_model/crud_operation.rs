@@ -63,10 +63,7 @@ impl<Key: Ord + Hash + Copy, Payload: Clone> CRUDOperation<Key, Payload> {
     /// Returns false, otherwise.
     #[inline(always)]
     pub const fn is_read(&self) -> bool {
-        match self {
-            Insert(..) | Delete(..) | Update(..) => false,
-            _ => true,
-        }
+        !matches!(self, Insert(..) | Delete(..) | Update(..))
     }
 
     /// Returns true, only if the Transaction requires write access when executing.
@@ -1,7 +1,11 @@
 use std::fmt::{Display, Formatter};
 use std::hash::Hash;
+use std::sync::Arc;
 use crate::record_model::record_point::RecordPoint;
-use crate::crud_model::crud_operation_result::CRUDOperationResult::{Deleted, Inserted, MatchedRecord, MatchedRecords, Updated};
+use crate::tree::memory::MemoryUsage;
+use crate::tree::wal::WalError;
+use crate::locking::strategy_override::StrategyOverride;
+use crate::crud_model::crud_operation_result::CRUDOperationResult::{Deleted, Inserted, MatchedRecord, MatchedRecords, MemoryLimitExceeded, NotDurable, UnsupportedOverride, Updated};
 
 /// Defines possible Transaction execution result.
 /// *Error*, indicates execution error.
@@ -11,6 +15,12 @@ use crate::crud_model::crud_operation_result::CRUDOperationResult::{Deleted, Ins
 /// a potential match is held.
 /// *MatchedRecords*, indicates that the Transaction executed was successful and the result of
 /// matches is held.
+/// *MemoryLimitExceeded*, indicates that an insert was rejected, since it exceeded the tree's
+/// memory limit, see `BPlusTree::with_memory_limit`. The tree's usage at that time is held.
+/// *UnsupportedOverride*, indicates that the Transaction was not executed, since the tree's locking
+/// strategy does not support the override it was dispatched with, see `CRUDDispatcher::dispatch_with`.
+/// *NotDurable*, indicates that the held result was applied, but its log record could not be
+/// appended or committed, see `BPlusTree::open_durable`.
 #[derive(Clone, Default)]
 pub enum CRUDOperationResult<Key: Ord + Hash + Copy + Default, Payload: Clone + Default> {
     MatchedRecords(Vec<RecordPoint<Key, Payload>>),
@@ -18,6 +28,9 @@ pub enum CRUDOperationResult<Key: Ord + Hash + Copy + Default, Payload: Clone +
     Inserted(Key),
     Updated(Key, Payload),
     Deleted(Key, Payload),
+    MemoryLimitExceeded(MemoryUsage),
+    UnsupportedOverride(StrategyOverride),
+    NotDurable(Box<CRUDOperationResult<Key, Payload>>, Arc<WalError>),
 
     #[default]
     Error, // flatten no good
@@ -35,10 +48,10 @@ impl<Key: Display + Ord + Hash + Copy + Default, Payload: Display + Clone + Defa
                     .map(|found| found.to_string())
                     .unwrap_or("None".to_string())),
             MatchedRecords(records) => {
-                write!(f, "MatchedRecords[len={}\n", records.len());
-                records.iter().for_each(|record| {
-                    write!(f, "{}\n", record);
-                });
+                writeln!(f, "MatchedRecords[len={}", records.len())?;
+                for record in records {
+                    writeln!(f, "{}", record)?;
+                }
                 write!(f, "]")
             }
             Inserted(key) =>
@@ -52,21 +65,31 @@ impl<Key: Display + Ord + Hash + Copy + Default, Payload: Display + Clone + Defa
                 write!(f, "Deleted(key: {}, version: {})",
                        key,
                        payload),
+            MemoryLimitExceeded(usage) =>
+                write!(f, "MemoryLimitExceeded({})",
+                       usage),
+            UnsupportedOverride(strategy_override) =>
+                write!(f, "UnsupportedOverride({})",
+                       strategy_override),
+            NotDurable(result, error) =>
+                write!(f, "NotDurable({}, error: {})",
+                       result,
+                       error),
 
         }
     }
 }
 
 /// Sugar implementation, wrapping collection of records to a TransactionResult.
-impl<Key: Ord + Hash + Copy + Default, Payload: Clone + Default> Into<CRUDOperationResult<Key, Payload>> for Vec<RecordPoint<Key, Payload>> {
-    fn into(self) -> CRUDOperationResult<Key, Payload> {
-        MatchedRecords(self)
+impl<Key: Ord + Hash + Copy + Default, Payload: Clone + Default> From<Vec<RecordPoint<Key, Payload>>> for CRUDOperationResult<Key, Payload> {
+    fn from(records: Vec<RecordPoint<Key, Payload>>) -> Self {
+        MatchedRecords(records)
     }
 }
 
 /// Sugar implementation, wrapping a potential record to a TransactionResult.
-impl<Key: Ord + Hash + Copy + Default, Payload: Clone + Default> Into<CRUDOperationResult<Key, Payload>> for Option<RecordPoint<Key, Payload>> {
-    fn into(self) -> CRUDOperationResult<Key, Payload> {
-        MatchedRecord(self)
+impl<Key: Ord + Hash + Copy + Default, Payload: Clone + Default> From<Option<RecordPoint<Key, Payload>>> for CRUDOperationResult<Key, Payload> {
+    fn from(records: Option<RecordPoint<Key, Payload>>) -> Self {
+        MatchedRecord(records)
     }
 }
\ No newline at end of file
@@ -4,8 +4,11 @@ use std::mem;
 use crate::crud_model::crud_api::{CRUDDispatcher, NodeVisits};
 use crate::crud_model::crud_operation::CRUDOperation;
 use crate::crud_model::crud_operation_result::CRUDOperationResult;
+use crate::page_model::BlockID;
+use crate::locking::strategy_override::StrategyOverride;
 use crate::record_model::record_point::RecordPoint;
 use crate::tree::bplus_tree::BPlusTree;
+use crate::utils::epoch;
 
 impl<const FAN_OUT: usize,
     const NUM_RECORDS: usize,
@@ -16,95 +19,255 @@ impl<const FAN_OUT: usize,
     #[inline]
     fn dispatch(&self, crud_operation: CRUDOperation<Key, Payload>)
                 -> (NodeVisits, CRUDOperationResult<Key, Payload>) {
+        let stored = match &crud_operation {
+            CRUDOperation::Insert(_, payload) | CRUDOperation::Update(_, payload) =>
+                self.memory.payload_bytes(payload),
+            _ => 0
+        };
+
+        if let CRUDOperation::Insert(..) = crud_operation {
+            if let Err(usage) = self.admit_insert(stored) {
+                return (NodeVisits::MIN, CRUDOperationResult::MemoryLimitExceeded(usage));
+            }
+        }
+
+        let _epoch
+            = self.locking_strategy.is_optimistic().then(epoch::pin);
+
+        #[cfg(target_os = "linux")]
+        let _pins
+            = self.pin_scope();
+
+        let _metrics
+            = self.metrics.enter((&crud_operation).into());
+
+        let (node_visits, result) = self
+            .dispatch_operation(crud_operation)
+            .unwrap_or((NodeVisits::MIN, CRUDOperationResult::Error));
+
+        mem::drop(_epoch);
+        #[cfg(target_os = "linux")]
+        mem::drop(_pins);
+        self.memory.account(stored, &result);
+
+        let result
+            = self.commit_log(result);
+
+        // failures keep the frames resident, they are evicted by a later operation
+        #[cfg(target_os = "linux")]
+        if self.buffer.is_some() {
+            let _ = self.release_frames();
+        }
+
+        (node_visits, result)
+    }
+
+    #[inline(always)]
+    fn supports(&self, strategy_override: StrategyOverride) -> bool {
+        strategy_override.is_supported_by(&self.locking_strategy)
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + Display,
+    Payload: Default + Clone + Sync + Display
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Applies the operation without admission, metrics, logging or frame release, see `dispatch`.
+    /// Restarts and operations answered by others recurse here, i.e. are accounted once.
+    /// Fails with the ID of a frame that could not be faulted in, see `BufferPool`.
+    fn dispatch_operation(&self, crud_operation: CRUDOperation<Key, Payload>)
+                          -> Result<(NodeVisits, CRUDOperationResult<Key, Payload>), BlockID> {
         let olc
             = self.locking_strategy.is_optimistic();
 
-        match crud_operation {
+        let cow
+            = self.locking_strategy.is_copy_on_write();
+
+        Ok(match crud_operation {
+            CRUDOperation::Delete(key) if cow => self.apply_cow(key, |leaf| leaf
+                .delete_key(key)
+                .map(|payload| CRUDOperationResult::Deleted(key, payload))
+                .unwrap_or_default())?,
+            CRUDOperation::Insert(key, payload) if cow => self.apply_cow(key, |leaf|
+                if leaf.push_record_point(key, payload.clone()) {
+                    CRUDOperationResult::Inserted(key)
+                }
+                else {
+                    CRUDOperationResult::Error
+                })?,
+            CRUDOperation::Update(key, payload) if cow => self.apply_cow(key, |leaf| leaf
+                .update_record_point(key, payload.clone())
+                .map(|old| CRUDOperationResult::Updated(key, old))
+                .unwrap_or_default())?,
+            CRUDOperation::PopMin if cow => self.apply_cow(self.min_key, |leaf|
+                if !leaf.as_records().is_empty() {
+                    let r
+                        = leaf.records_mut().remove(0);
+
+                    CRUDOperationResult::Deleted(r.key, r.payload)
+                }
+                else {
+                    CRUDOperationResult::Error
+                })?,
+            CRUDOperation::PopMax if cow => self.apply_cow(self.max_key, |leaf|
+                if !leaf.as_records().is_empty() {
+                    let r
+                        = leaf.records_mut().pop();
+
+                    CRUDOperationResult::Deleted(r.key, r.payload)
+                }
+                else {
+                    CRUDOperationResult::Error
+                })?,
+            CRUDOperation::Point(key) if cow => {
+                let (node_visits, record) = self.read_leaf_cow(key, |leaf, _| leaf
+                    .as_records()
+                    .binary_search_by_key(&key, |record| record.key)
+                    .ok()
+                    .map(|pos| unsafe { leaf.as_records().get_unchecked(pos) }.clone()))?;
+
+                (node_visits, record.into())
+            }
+            CRUDOperation::Range(key_interval) if cow =>
+                self.range_query_cow(key_interval)?,
+            CRUDOperation::PeekMin if cow => {
+                let (node_visits, record) = self.read_leaf_cow(self.min_key, |leaf, _| leaf
+                    .as_records()
+                    .first()
+                    .cloned())?;
+
+                (node_visits, record.into())
+            }
+            CRUDOperation::PeekMax if cow => {
+                let (node_visits, record) = self.read_leaf_cow(self.max_key, |leaf, _| leaf
+                    .as_records()
+                    .last()
+                    .cloned())?;
+
+                (node_visits, record.into())
+            }
             CRUDOperation::Delete(key) if olc => {
                 let (node_visits, guard) = self
-                    .traversal_write_olc(key);
+                    .traversal_write_olc(key)?;
 
-                (node_visits,
-                 guard.deref_mut()
-                     .unwrap()
-                     .delete_key(key)
-                     .map(|payload| CRUDOperationResult::Deleted(key, payload))
-                     .unwrap_or_default())
+                let leaf
+                    = guard.deref_mut().unwrap();
+
+                let result = leaf
+                    .delete_key(key)
+                    .map(|payload| CRUDOperationResult::Deleted(key, payload))
+                    .unwrap_or_default();
+
+                (node_visits, self.logged(leaf, result))
             }
             CRUDOperation::Delete(key) => {
                 let (node_visits, guard) = self
-                    .traversal_write(key);
+                    .traversal_write(key)?;
 
-                (node_visits,
-                 guard.deref_mut()
-                     .unwrap()
-                     .delete_key(key)
-                     .map(|payload| CRUDOperationResult::Deleted(key, payload))
-                     .unwrap_or_default())
+                let leaf
+                    = guard.deref_mut().unwrap();
+
+                let result = leaf
+                    .delete_key(key)
+                    .map(|payload| CRUDOperationResult::Deleted(key, payload))
+                    .unwrap_or_default();
+
+                (node_visits, self.logged(leaf, result))
             }
             CRUDOperation::Insert(key, payload) if olc => {
-                let (node_visits, guard) = self
-                    .traversal_write_olc(key);
+                let appended = self.append(key, |leaf| {
+                    let result = match leaf.push_record_point(key, payload.clone()) {
+                        true => CRUDOperationResult::Inserted(key),
+                        false => CRUDOperationResult::Error
+                    };
 
-                (node_visits, guard.deref_mut()
-                    .unwrap()
-                    .push_record_point(key, payload)
-                    .then(|| CRUDOperationResult::Inserted(key))
-                    .unwrap_or_default())
+                    self.logged(leaf, result)
+                });
+
+                if let Some(result) = appended {
+                    (1, result)
+                } else {
+                    let (node_visits, guard) = self
+                        .traversal_write_olc(key)?;
+
+                    let leaf
+                        = guard.deref_mut().unwrap();
+
+                    let result = match leaf.push_record_point(key, payload) {
+                        true => CRUDOperationResult::Inserted(key),
+                        false => CRUDOperationResult::Error
+                    };
+
+                    (node_visits, self.logged(leaf, result))
+                }
             }
             CRUDOperation::Insert(key, payload) => {
                 let (node_visits, guard) = self
-                    .traversal_write(key);
+                    .traversal_write(key)?;
 
-                (node_visits, guard.deref_mut()
-                    .unwrap()
-                    .push_record_point(key, payload)
-                    .then(|| CRUDOperationResult::Inserted(key))
-                    .unwrap_or_default())
+                let leaf
+                    = guard.deref_mut().unwrap();
+
+                let result = match leaf.push_record_point(key, payload) {
+                    true => CRUDOperationResult::Inserted(key),
+                    false => CRUDOperationResult::Error
+                };
+
+                (node_visits, self.logged(leaf, result))
             }
             CRUDOperation::Update(key, payload) if olc => {
                 let (node_visits, guard) = self
-                    .traversal_write_olc(key);
+                    .traversal_write_olc(key)?;
 
-                (node_visits, guard
-                    .deref_mut()
-                    .unwrap()
+                let leaf
+                    = guard.deref_mut().unwrap();
+
+                let result = leaf
                     .update_record_point(key, payload)
                     .map(|old| CRUDOperationResult::Updated(key, old))
-                    .unwrap_or_default())
+                    .unwrap_or_default();
+
+                (node_visits, self.logged(leaf, result))
             }
             CRUDOperation::Update(key, payload) => {
                 let (node_visits, guard) = self
-                    .traversal_write(key);
+                    .traversal_write(key)?;
 
-                (node_visits, guard.deref_mut()
-                    .unwrap()
+                let leaf
+                    = guard.deref_mut().unwrap();
+
+                let result = leaf
                     .update_record_point(key, payload)
                     .map(|old| CRUDOperationResult::Updated(key, old))
-                    .unwrap_or_default())
+                    .unwrap_or_default();
+
+                (node_visits, self.logged(leaf, result))
             }
-            CRUDOperation::Point(key) if olc => match self.dispatch(
-                CRUDOperation::Range((key..=key).into()))
+            CRUDOperation::Point(key) if olc => match self.dispatch_operation(
+                CRUDOperation::Range((key..=key).into()))?
             {
                 (node_visits,
                     CRUDOperationResult::MatchedRecords(mut records))
                 if records.len() <= 1 => (node_visits, records.pop().into()),
                 (node_visits, ..) => (node_visits, CRUDOperationResult::Error)
             },
-            CRUDOperation::Point(key) => match self.traversal_read(key) {
-                (node_visits, leaf_guard) => {
-                    let leaf_page =  leaf_guard
-                        .deref()
-                        .unwrap()
-                        .as_ref();
+            CRUDOperation::Point(key) => {
+                let (node_visits, leaf_guard)
+                    = self.traversal_read(key)?;
 
-                    (node_visits, leaf_page
-                        .as_records()
-                        .binary_search_by_key(&key, |record| record.key)
-                        .ok()
-                        .map(|pos| unsafe { leaf_page.as_records().get_unchecked(pos) }.clone())
-                        .into())
-                }
+                let leaf_page =  leaf_guard
+                    .deref()
+                    .unwrap()
+                    .as_ref();
+
+                (node_visits, leaf_page
+                    .as_records()
+                    .binary_search_by_key(&key, |record| record.key)
+                    .ok()
+                    .map(|pos| unsafe { leaf_page.as_records().get_unchecked(pos) }.clone())
+                    .into())
             },
             CRUDOperation::Range(key_interval) if olc => {
                 let mut path
@@ -112,13 +275,13 @@ impl<const FAN_OUT: usize,
 
                 let node_visits = self.next_leaf_page(path.as_mut(),
                                                       0,
-                                                      key_interval.lower);
+                                                      key_interval.lower)?;
 
-                self.range_query_olc(path.as_mut(), key_interval, node_visits)
+                self.range_query_olc(path.as_mut(), key_interval, node_visits)?
             }
             CRUDOperation::Range(interval) => {
                 let (node_visits, guards)
-                    =  self.traversal_read_range(&interval);
+                    =  self.traversal_read_range(&interval)?;
 
                 (node_visits,
                  guards.into_iter()
@@ -135,8 +298,11 @@ impl<const FAN_OUT: usize,
                     .collect::<Vec<_>>()
                     .into())
             },
-            CRUDOperation::PeekMin if olc => match self.traversal_read_olc(self.min_key) {
-                (node_visits, leaf_guard) => unsafe {
+            CRUDOperation::PeekMin if olc => {
+                let (node_visits, leaf_guard)
+                    = self.traversal_read_olc(self.min_key)?;
+
+                unsafe {
                     let leaf_page = leaf_guard
                         .deref_unsafe()
                         .unwrap()
@@ -152,28 +318,33 @@ impl<const FAN_OUT: usize,
                     }
                     else {
                         mem::drop(leaf_guard);
-                        self.dispatch(CRUDOperation::PeekMin)
+                        self.record_restart(self.root.height());
+                        self.dispatch_operation(CRUDOperation::PeekMin)?
                     }
                 }
             }
-            CRUDOperation::PeekMin => match self.traversal_read(self.min_key) {
-                (node_visits, leaf_guard) => {
-                    let leaf_page = leaf_guard
-                        .deref()
-                        .unwrap()
-                        .as_ref();
+            CRUDOperation::PeekMin => {
+                let (node_visits, leaf_guard)
+                    = self.traversal_read(self.min_key)?;
 
-                    let result = leaf_page
-                        .as_records()
-                        .first()
-                        .cloned()
-                        .into();
-                    
-                    (node_visits, result)
-                }
+                let leaf_page = leaf_guard
+                    .deref()
+                    .unwrap()
+                    .as_ref();
+
+                let result = leaf_page
+                    .as_records()
+                    .first()
+                    .cloned()
+                    .into();
+                
+                (node_visits, result)
             }
-            CRUDOperation::PeekMax if olc => match self.traversal_read_olc(self.max_key) {
-                (node_visits, leaf_guard) => unsafe {
+            CRUDOperation::PeekMax if olc => {
+                let (node_visits, leaf_guard)
+                    = self.traversal_read_olc(self.max_key)?;
+
+                unsafe {
                     let leaf_page = leaf_guard
                         .deref_unsafe()
                         .unwrap()
@@ -189,12 +360,16 @@ impl<const FAN_OUT: usize,
                     }
                     else {
                         mem::drop(leaf_guard);
-                        self.dispatch(CRUDOperation::PeekMax)
+                        self.record_restart(self.root.height());
+                        self.dispatch_operation(CRUDOperation::PeekMax)?
                     }
                 }
             },
-            CRUDOperation::Pred(key) => match self.traversal_read_pred_olc(key) {
-                (node_visits, leaf_guard, pred) => unsafe {
+            CRUDOperation::Pred(key) => {
+                let (node_visits, leaf_guard, pred)
+                    = self.traversal_read_pred_olc(key)?;
+
+                unsafe {
                     let leaf_page = leaf_guard
                         .deref_unsafe()
                         .unwrap()
@@ -213,7 +388,8 @@ impl<const FAN_OUT: usize,
                         }
                         else {
                             mem::drop(leaf_guard);
-                            self.dispatch(CRUDOperation::Pred(key))
+                            self.record_restart(self.root.height());
+                            self.dispatch_operation(CRUDOperation::Pred(key))?
                         }
                     }
                     else if pos > 0 {
@@ -223,7 +399,8 @@ impl<const FAN_OUT: usize,
                         }
                         else {
                             mem::drop(leaf_guard);
-                            self.dispatch(CRUDOperation::Pred(key))
+                            self.record_restart(self.root.height());
+                            self.dispatch_operation(CRUDOperation::Pred(key))?
                         }
                     }
                     else {
@@ -232,97 +409,102 @@ impl<const FAN_OUT: usize,
                     }
                 }
             },
-            CRUDOperation::PeekMax => match self.traversal_read(self.max_key) {
-                (node_visits, leaf_guard) => {
-                    let leaf_page = leaf_guard
-                        .deref()
-                        .unwrap()
-                        .as_ref();
+            CRUDOperation::PeekMax => {
+                let (node_visits, leaf_guard)
+                    = self.traversal_read(self.max_key)?;
 
-                    let result = leaf_page
-                        .as_records()
-                        .last()
-                        .cloned()
-                        .into();
-                    
-                    (node_visits, result)
-                }
+                let leaf_page = leaf_guard
+                    .deref()
+                    .unwrap()
+                    .as_ref();
+
+                let result = leaf_page
+                    .as_records()
+                    .last()
+                    .cloned()
+                    .into();
+                
+                (node_visits, result)
             }
-            CRUDOperation::PopMin if olc => match self.traversal_write_olc(self.min_key) {
-                (node_visits, leaf_guard) => {
-                    let leaf_page = leaf_guard
-                        .deref()
-                        .unwrap()
-                        .as_ref();
+            CRUDOperation::PopMin if olc => {
+                let (node_visits, leaf_guard)
+                    = self.traversal_write_olc(self.min_key)?;
 
-                    if !leaf_page.as_records().is_empty() {
-                        let r
-                            = leaf_page.records_mut().remove(0);
+                let leaf_page = leaf_guard
+                    .deref()
+                    .unwrap()
+                    .as_ref();
 
-                        (node_visits, CRUDOperationResult::Deleted(r.key(), r.payload.clone()))
-                    }
-                    else {
-                        (node_visits, CRUDOperationResult::Error)
-                    }
+                if !leaf_page.as_records().is_empty() {
+                    let r
+                        = leaf_page.records_mut().remove(0);
+
+                    (node_visits, self.logged(leaf_page, CRUDOperationResult::Deleted(r.key(), r.payload.clone())))
+                }
+                else {
+                    (node_visits, CRUDOperationResult::Error)
                 }
             }
-            CRUDOperation::PopMin => match self.traversal_write(self.min_key) {
-                (node_visits, leaf_guard) => {
-                    let leaf_page =  leaf_guard
-                        .deref()
-                        .unwrap()
-                        .as_ref();
+            CRUDOperation::PopMin => {
+                let (node_visits, leaf_guard)
+                    = self.traversal_write(self.min_key)?;
 
-                    if !leaf_page.as_records().is_empty() {
-                        let r 
-                            = leaf_page.records_mut().remove(0);
-                        
-                        (node_visits, CRUDOperationResult::Deleted(r.key, r.payload))
-                    }
-                    else {
-                        (node_visits, CRUDOperationResult::Error)
-                    }
+                let leaf_page =  leaf_guard
+                    .deref()
+                    .unwrap()
+                    .as_ref();
+
+                if !leaf_page.as_records().is_empty() {
+                    let r 
+                        = leaf_page.records_mut().remove(0);
+                    
+                    (node_visits, self.logged(leaf_page, CRUDOperationResult::Deleted(r.key, r.payload)))
+                }
+                else {
+                    (node_visits, CRUDOperationResult::Error)
                 }
             }
-            CRUDOperation::PopMax if olc => match self.traversal_write_olc(self.max_key) {
-                (node_visits, leaf_guard) => {
-                    let leaf_page =  leaf_guard
-                        .deref()
-                        .unwrap()
-                        .as_ref();
+            CRUDOperation::PopMax if olc => {
+                let (node_visits, leaf_guard)
+                    = self.traversal_write_olc(self.max_key)?;
 
-                    let len = leaf_page.len();
-                    if len > 0 {
-                        let r
-                            = leaf_page.records_mut().pop();
+                let leaf_page =  leaf_guard
+                    .deref()
+                    .unwrap()
+                    .as_ref();
 
-                        (node_visits, CRUDOperationResult::Deleted(r.key(), r.payload.clone()))
-                    }
-                    else {
-                        (node_visits, CRUDOperationResult::Error)
-                    }
+                let len = leaf_page.len();
+                if len > 0 {
+                    let r
+                        = leaf_page.records_mut().pop();
+
+                    (node_visits, self.logged(leaf_page, CRUDOperationResult::Deleted(r.key(), r.payload.clone())))
+                }
+                else {
+                    (node_visits, CRUDOperationResult::Error)
                 }
             }
-            CRUDOperation::PopMax => match self.traversal_write(self.max_key) {
-                (node_visits, leaf_guard) => {
-                    let leaf_page =  leaf_guard
-                        .deref()
-                        .unwrap()
-                        .as_ref();
+            CRUDOperation::PopMax => {
+                let (node_visits, leaf_guard)
+                    = self.traversal_write(self.max_key)?;
 
-                    let len = leaf_page.len();
-                    if len > 0 {
-                        let r
-                            = leaf_page.records_mut().pop();
-                        
-                        (node_visits, CRUDOperationResult::Deleted(r.key(), r.payload.clone()))
-                    }
-                    else {
-                        (node_visits, CRUDOperationResult::Error)
-                    }
+                let leaf_page =  leaf_guard
+                    .deref()
+                    .unwrap()
+                    .as_ref();
+
+                let len = leaf_page.len();
+                if len > 0 {
+                    let r
+                        = leaf_page.records_mut().pop();
+                    
+                    (node_visits, self.logged(leaf_page, CRUDOperationResult::Deleted(r.key(), r.payload.clone())))
+                }
+                else {
+                    (node_visits, CRUDOperationResult::Error)
                 }
             }
             CRUDOperation::Empty => (NodeVisits::MIN, CRUDOperationResult::Error),
-        }
+        })
     }
-}
\ No newline at end of file
+}
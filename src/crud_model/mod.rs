@@ -1,6 +1,9 @@
 pub mod crud_operation;
 pub mod crud_operation_result;
+pub mod batch;
+pub mod multi_get;
 pub mod dispatch;
 pub mod query;
 pub mod olc_query;
+pub mod cow_query;
 pub mod crud_api;
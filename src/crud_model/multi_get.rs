@@ -0,0 +1,166 @@
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::mem;
+use crate::crud_model::crud_api::NodeVisits;
+use crate::page_model::node::Node;
+use crate::record_model::record_point::RecordPoint;
+use crate::tree::bplus_tree::BPlusTree;
+use crate::utils::epoch;
+use crate::utils::metrics::OperationKind;
+
+/// Outcome of `BPlusTree::get_many`.
+#[derive(Clone, Default)]
+pub struct MultiGetResult<Key: Default + Ord + Copy + Hash, Payload: Default + Clone> {
+    /// Record of each key in the order the keys were passed, `None` if the key is not stored.
+    pub records: Vec<Option<RecordPoint<Key, Payload>>>,
+    /// Keys whose leaf could not be read from the block pool, their records are `None`, see `BufferPool`.
+    pub failed: Vec<Key>,
+    pub node_visits: NodeVisits,
+    /// Leaves read, i.e. traversals taken.
+    pub leaf_visits: usize,
+}
+
+impl<Key: Default + Ord + Copy + Hash, Payload: Default + Clone> MultiGetResult<Key, Payload> {
+    /// Number of keys found.
+    pub fn matched(&self) -> usize {
+        self.records
+            .iter()
+            .filter(|record| record.is_some())
+            .count()
+    }
+}
+
+impl<Key: Default + Ord + Copy + Hash, Payload: Default + Clone> Display for MultiGetResult<Key, Payload> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MultiGetResult(keys={};matched={};failed={};node_visits={};leaf_visits={})",
+               self.records.len(),
+               self.matched(),
+               self.failed.len(),
+               self.node_visits,
+               self.leaf_visits)
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + Display + 'static,
+    Payload: Default + Clone + Sync + Display + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Looks up many keys at once, see `get_many_with`.
+    pub fn get_many(&self, keys: impl IntoIterator<Item=Key>) -> MultiGetResult<Key, Payload> {
+        self.get_many_with(keys, 0)
+    }
+
+    /// Looks up many keys at once and returns their records in the order of `keys`.
+    /// The keys are sorted, hence all keys covered by the same leaf are resolved by a single traversal,
+    /// which is latched and validated like a point query. With `interleave` greater zero,
+    /// each traversal prefetches the nodes of up to `interleave` following traversals per level,
+    /// hence their memory is loaded while the current leaf is read. Each leaf is read atomically,
+    /// but keys of different leaves may observe different states of the tree.
+    pub fn get_many_with(&self,
+                         keys: impl IntoIterator<Item=Key>,
+                         interleave: usize) -> MultiGetResult<Key, Payload>
+    {
+        let mut sorted = keys
+            .into_iter()
+            .enumerate()
+            .map(|(pos, key)| (key, pos))
+            .collect::<Vec<_>>();
+
+        sorted.sort_unstable();
+
+        let mut result = MultiGetResult {
+            records: vec![None; sorted.len()],
+            ..MultiGetResult::default()
+        };
+
+        let mut next = 0;
+        while next < sorted.len() {
+            let (node_visits, records) = match self.get_leaf_run(&sorted[next..], interleave) {
+                Some(run) => run,
+                None => {
+                    result.failed.push(sorted[next].0);
+                    next += 1;
+                    continue;
+                }
+            };
+
+            result.node_visits += node_visits;
+            result.leaf_visits += 1;
+
+            for (record, (_, pos)) in records.into_iter().zip(&sorted[next..]) {
+                result.records[*pos] = record;
+                next += 1;
+            }
+        }
+
+        result
+    }
+
+    /// Traverses to the leaf of the first key once and resolves it, followed by all keys up to
+    /// the leaf's upper fence. Returns one record per resolved key, none if the leaf can't be read.
+    #[allow(clippy::type_complexity)]
+    fn get_leaf_run(&self,
+                    keys: &[(Key, usize)],
+                    interleave: usize) -> Option<(NodeVisits, Vec<Option<RecordPoint<Key, Payload>>>)>
+    {
+        let _epoch
+            = self.locking_strategy.is_optimistic().then(epoch::pin);
+
+        #[cfg(target_os = "linux")]
+        let _pins
+            = self.pin_scope();
+
+        let _metrics
+            = self.metrics.enter(OperationKind::Point);
+
+        let (key, prefetch_until)
+            = (keys[0].0, keys[keys.len() - 1].0);
+
+        let read = self.read_leaf_with(
+            key,
+            interleave,
+            prefetch_until,
+            |leaf, fence| Self::resolve_keys(leaf, fence.upper(), keys));
+
+        mem::drop(_epoch);
+        #[cfg(target_os = "linux")]
+        mem::drop(_pins);
+
+        #[cfg(target_os = "linux")]
+        if self.buffer.is_some() {
+            let _ = self.release_frames();
+        }
+
+        read.ok()
+    }
+
+    /// Resolves the first key and all following keys up to `fence_upper` in the leaf.
+    /// Keys are ascending, hence each one is searched right of the record found for the previous one.
+    fn resolve_keys(leaf: &Node<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                    fence_upper: Key,
+                    keys: &[(Key, usize)]) -> Vec<Option<RecordPoint<Key, Payload>>>
+    {
+        let mut records
+            = leaf.as_records();
+
+        keys.iter()
+            .enumerate()
+            .take_while(|(pos, (key, _))| *pos == 0 || *key <= fence_upper)
+            .map(|(_, (key, _))| match records.binary_search_by_key(key, |record| record.key) {
+                Ok(pos) => {
+                    let record
+                        = records[pos].clone();
+
+                    records = &records[pos..];
+                    Some(record)
+                }
+                Err(pos) => {
+                    records = &records[pos..];
+                    None
+                }
+            })
+            .collect()
+    }
+}
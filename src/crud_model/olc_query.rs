@@ -2,7 +2,8 @@ use std::collections::VecDeque;
 use std::fmt::Display;
 use std::hash::Hash;
 use std::mem;
-use crate::page_model::{Attempts, Height, Level};
+use std::mem::ManuallyDrop;
+use crate::page_model::{Attempts, BlockID, Height, Level};
 use crate::block::block::BlockGuard;
 use crate::crud_model::crud_api::{CRUDDispatcher, NodeVisits};
 use crate::page_model::node::Node;
@@ -10,6 +11,7 @@ use crate::record_model::record_point::RecordPoint;
 use crate::record_model::unsafe_clone::UnsafeClone;
 use crate::crud_model::crud_operation::CRUDOperation;
 use crate::crud_model::crud_operation_result::CRUDOperationResult;
+use crate::crud_model::query::WriteTarget;
 use crate::tree::bplus_tree::{BPlusTree, INIT_TREE_HEIGHT, LockLevel, MAX_TREE_HEIGHT};
 use crate::utils::interval::Interval;
 use crate::utils::smart_cell::sched_yield;
@@ -21,21 +23,23 @@ impl<const FAN_OUT: usize,
 > BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
 {
     #[inline]
-    fn retrieve_root_olc(&self, mut lock_level: Level, mut attempt: Attempts)
-    -> (NodeVisits, BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>, Height, LockLevel, Attempts)
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn retrieve_root_olc(&self, mut lock_level: Level, mut attempt: Attempts)
+    -> Result<(NodeVisits, BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>, Height, LockLevel, Attempts), BlockID>
     {
         let mut node_visits = 0usize;
         loop {
-            match self.retrieve_root_internal(lock_level, attempt) {
+            match self.retrieve_root_internal(lock_level, attempt)? {
                 Err((n_lock_level, n_attempt)) => {
                     lock_level = n_lock_level;
                     attempt = n_attempt;
                     node_visits += 1;
 
+                    self.record_restart(INIT_TREE_HEIGHT);
                     sched_yield(attempt);
                 }
                 Ok((guard, height)) =>
-                    break (node_visits + 1, guard, height, lock_level, attempt)
+                    break Ok((node_visits + 1, guard, height, lock_level, attempt))
             }
         }
     }
@@ -45,7 +49,7 @@ impl<const FAN_OUT: usize,
                                   path: &mut Vec<(Interval<Key>, BlockGuard<'static, FAN_OUT, NUM_RECORDS, Key, Payload>)>,
                                   org_key_interval: Interval<Key>,
                                   mut node_visits: NodeVisits
-    ) -> (NodeVisits, CRUDOperationResult<Key, Payload>)
+    ) -> Result<(NodeVisits, CRUDOperationResult<Key, Payload>), BlockID>
     {
         let mut key_interval
             = org_key_interval.clone();
@@ -60,7 +64,7 @@ impl<const FAN_OUT: usize,
             history_path.push_back(path.to_vec());
 
             let (visits, local_results) =
-                self.range_query_leaf_results(path, &key_interval);
+                self.range_query_leaf_results(path, &key_interval)?;
 
             node_visits += visits;
 
@@ -70,6 +74,8 @@ impl<const FAN_OUT: usize,
 
                 match prev_path.get(prev_path.len() - 2) {
                     Some((.., parent_leaf)) if !parent_leaf.is_valid() => {
+                        self.record_restart((prev_path.len() - 1) as _);
+
                         mem::drop(prev_path);
                         mem::drop(history_path);
                         mem::drop(local_results);
@@ -79,7 +85,7 @@ impl<const FAN_OUT: usize,
                         let (visits, retry)
                             = self.dispatch(CRUDOperation::Range(org_key_interval));
 
-                        return (visits + node_visits, retry)
+                        return Ok((visits + node_visits, retry))
                     }
                     _ => {}
                 };
@@ -100,20 +106,20 @@ impl<const FAN_OUT: usize,
                 node_visits += self.next_leaf_page(
                     path,
                     path.len() - 2,
-                    key_interval.lower());
+                    key_interval.lower())?;
             } else {
                 break;
             }
         }
 
-        (node_visits, CRUDOperationResult::MatchedRecords(all_results))
+        Ok((node_visits, CRUDOperationResult::MatchedRecords(all_results)))
     }
 
     #[inline]
     pub(crate) fn next_leaf_page(&self,
                                  path: &mut Vec<(Interval<Key>, BlockGuard<'static, FAN_OUT, NUM_RECORDS, Key, Payload>)>,
                                  mut parent_index: usize,
-                                 next_key: Key) -> NodeVisits
+                                 next_key: Key) -> Result<NodeVisits, BlockID>
     {
         let mut attempts = 0;
         let mut node_visits = 0;
@@ -131,9 +137,10 @@ impl<const FAN_OUT: usize,
                     &self.root.block,
                     0,
                     attempts,
-                    0);
+                    0)?;
 
                 if !root_read.is_read_not_obsolete() {
+                    self.record_restart(INIT_TREE_HEIGHT);
                     attempts += 1;
                     continue
                 }
@@ -168,7 +175,10 @@ impl<const FAN_OUT: usize,
             let (read, current_reader_version)
                 = curr_parent.is_read_not_obsolete_result();
 
-            if curr_deref.is_none() || !read {
+            // an evicted parent is faulted in by latching it again, see `BufferPool`,
+            // a changed one may have split since its interval was taken from the path
+            if curr_deref.is_none() || !read || curr_deref.unwrap().is_evicted() || !curr_parent.is_valid() {
+                self.record_restart((parent_index + 1) as _);
                 path.truncate(parent_index);
                 attempts += 1;
                 parent_index -= 1;
@@ -202,6 +212,28 @@ impl<const FAN_OUT: usize,
                         = curr_parent.is_read_not_obsolete_result();
 
                     if !read || read_version != current_reader_version {
+                        self.record_restart((parent_index + 1) as _);
+                        path.truncate(parent_index);
+                        parent_index -= 1;
+                        attempts += 1;
+                        continue;
+                    }
+
+                    let next_guard = self.lock_reader_olc(
+                        next_page.assume_init_ref(),
+                        (parent_index + 1) as _,
+                        0,
+                        self.height())?;
+
+                    // a split completed before the child is latched moves keys into a sibling,
+                    // which the child's version does not tell, hence the parent is validated again
+                    let (read, child_read_version)
+                        = curr_parent.is_read_not_obsolete_result();
+
+                    if !read || child_read_version != read_version {
+                        mem::drop(next_guard);
+
+                        self.record_restart((parent_index + 1) as _);
                         path.truncate(parent_index);
                         parent_index -= 1;
                         attempts += 1;
@@ -212,15 +244,11 @@ impl<const FAN_OUT: usize,
 
                     attempts = 0;
                     parent_index += 1;
-                    path.insert(parent_index, (curr_interval, self.lock_reader_olc(
-                        next_page.assume_init_ref(),
-                        parent_index as _,
-                        attempts,
-                        self.height())));
+                    path.insert(parent_index, (curr_interval, next_guard));
                 }
                 Node::Leaf(..) => {
                     path.truncate(parent_index + 1);
-                    return node_visits;
+                    return Ok(node_visits);
                 }
             }
         }
@@ -230,7 +258,7 @@ impl<const FAN_OUT: usize,
     fn range_query_leaf_results(&self,
                                 path: &mut Vec<(Interval<Key>, BlockGuard<'static, FAN_OUT, NUM_RECORDS, Key, Payload>)>,
                                 key_interval: &Interval<Key>)
-                                -> (NodeVisits, Vec<RecordPoint<Key, Payload>>)
+                                -> Result<(NodeVisits, Vec<RecordPoint<Key, Payload>>), BlockID>
     {
         let mut node_visits
             = 0;
@@ -247,28 +275,31 @@ impl<const FAN_OUT: usize,
                         = leaf.is_read_not_obsolete_result();
 
                     if read {
-                        let mut potential_results = leaf_page
+                        // copies alias the payloads' heap memory, hence are never dropped
+                        let potential_results = leaf_page
                             .as_records()
                             .iter()
                             .skip_while(|record| record.key().lt(&key_interval.lower()))
                             .take_while(|record| record.key().le(&key_interval.upper()))
-                            .map(|record| record.unsafe_clone())
+                            .map(|record| ManuallyDrop::new(record.unsafe_clone()))
                             .collect::<Vec<_>>();
 
                         let (read, n_current_read_version)
                             = leaf.is_read_not_obsolete_result();
 
-                        if read && n_current_read_version == current_read_version { // avoid write in-between
-                            return (node_visits + 1, potential_results)
-                        } else {
-                            potential_results.set_len(0);
+                        // the version latched under the parent, a split since moved keys into a sibling
+                        if read && n_current_read_version == current_read_version && leaf.is_valid() { // avoid write in-between
+                            return Ok((node_visits + 1, potential_results
+                                .iter()
+                                .map(|record| RecordPoint::clone(record))
+                                .collect()))
                         }
                     }
 
                     node_visits += 1 + self.next_leaf_page(
                         path,
                         path.len() - 2,
-                        key_interval.lower());
+                        key_interval.lower())?;
                 }
                 _ => unreachable!("Found Index but expected leaf = {}", leaf_unchecked)
             }
@@ -276,10 +307,11 @@ impl<const FAN_OUT: usize,
     }
 
     #[inline]
+    #[allow(clippy::type_complexity)]
     fn traversal_read_pred_olc_internal(&self, key: Key) 
-        -> (NodeVisits, Option<(BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>, Key)>) {
+        -> Result<(NodeVisits, Option<(BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>, Key)>), BlockID> {
         let mut current_guard
-            = self.lock_reader(&self.root.block);
+            = self.lock_reader(&self.root.block, INIT_TREE_HEIGHT)?;
 
         // let key = (self.inc_key)(key);
         let mut node_visits = 0;
@@ -294,7 +326,7 @@ impl<const FAN_OUT: usize,
             if current.is_none() || !read {
                 mem::drop(current_guard);
 
-                return (node_visits + 1, None);
+                return Ok((node_visits + 1, None));
             }
 
             match current.unwrap().as_ref() {
@@ -306,33 +338,41 @@ impl<const FAN_OUT: usize,
                         Err(pos) => (pos, index_page.get_child_result(pos))
                     };
 
+                    if pos > 0 {
+                        let check = *index_page.keys().get_unchecked(pos - 1);
+                        if check <= key {
+                            pred = check;
+                        }
+                    }
+
                     let (read, read_version)
                         = current_guard.is_read_not_obsolete_result();
 
                     if !read || read_version != current_reader_version {
-                        return (node_visits, None);
+                        return Ok((node_visits, None));
                     }
 
-                    if pos > 0 {
-                        let check = *index_page.keys().get_unchecked(pos - 1);
-                        if check <= key {
-                            pred = check;
-                        }
+                    let next_guard
+                        = self.lock_reader(next_node.assume_init_ref(), (node_visits + 1) as _)?;
+
+                    // validated again, see `next_leaf_page`
+                    if !current_guard.is_valid() {
+                        return Ok((node_visits, None));
                     }
-                    
-                    current_guard
-                        = self.lock_reader(next_node.assume_init_ref());
+
+                    current_guard = next_guard;
                 }
-                _ => break (node_visits + 1, Some((current_guard, pred))),
+                _ => break Ok((node_visits + 1, Some((current_guard, pred)))),
             }
         }
     }
 
     
     #[inline]
-    fn traversal_read_olc_internal(&self, key: Key) -> (NodeVisits, Option<BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>>) {
+    #[allow(clippy::type_complexity)]
+    fn traversal_read_olc_internal(&self, key: Key) -> Result<(NodeVisits, Option<BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>>), BlockID> {
         let mut current_guard
-            = self.lock_reader(&self.root.block);
+            = self.lock_reader(&self.root.block, INIT_TREE_HEIGHT)?;
 
         let key = (self.inc_key)(key);
         let mut node_visits = 0;
@@ -346,7 +386,7 @@ impl<const FAN_OUT: usize,
             if current.is_none() || !read {
                 mem::drop(current_guard);
 
-                return (node_visits + 1, None);
+                return Ok((node_visits + 1, None));
             }
 
             match current.unwrap().as_ref() {
@@ -362,30 +402,40 @@ impl<const FAN_OUT: usize,
                         = current_guard.is_read_not_obsolete_result();
 
                     if !read || read_version != current_reader_version {
-                        return (node_visits, None);
+                        return Ok((node_visits, None));
                     }
 
-                    current_guard
-                        = self.lock_reader(next_node.assume_init_ref());
+                    let next_guard
+                        = self.lock_reader(next_node.assume_init_ref(), (node_visits + 1) as _)?;
+
+                    // validated again, see `next_leaf_page`
+                    if !current_guard.is_valid() {
+                        return Ok((node_visits, None));
+                    }
+
+                    current_guard = next_guard;
                 }
-                _ => break (node_visits + 1, Some(current_guard)),
+                _ => break Ok((node_visits + 1, Some(current_guard))),
             }
         }
     }
 
     #[inline]
+    #[allow(clippy::type_complexity)]
     pub(crate) fn traversal_read_pred_olc(&self, key: Key)
-        -> (NodeVisits, BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>, Key) {
+        -> Result<(NodeVisits, BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>, Key), BlockID> {
         let mut attempt = 0;
         let mut node_visits = 0;
 
         loop {
-            match self.traversal_read_pred_olc_internal(key) {
+            match self.traversal_read_pred_olc_internal(key)? {
                 (nv, Some((guard, pred))) if guard.is_valid() =>
-                    break (node_visits + nv, guard, pred),
+                    break Ok((node_visits + nv, guard, pred)),
                 (nv, ..) => {
                     node_visits += nv;
                     attempt += 1;
+
+                    self.record_restart(nv as _);
                     sched_yield(attempt)
                 }
             }
@@ -393,17 +443,19 @@ impl<const FAN_OUT: usize,
     }
     
     #[inline]
-    pub(crate) fn traversal_read_olc(&self, key: Key) -> (NodeVisits, BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>) {
+    pub(crate) fn traversal_read_olc(&self, key: Key) -> Result<(NodeVisits, BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>), BlockID> {
         let mut attempt = 0;
         let mut node_visits = 0;
 
         loop {
-            match self.traversal_read_olc_internal(key) {
+            match self.traversal_read_olc_internal(key)? {
                 (nv, Some(guard)) if guard.is_valid() => 
-                    break (node_visits + nv, guard),
+                    break Ok((node_visits + nv, guard)),
                 (nv, ..) => {
                     node_visits += nv;
                     attempt += 1;
+
+                    self.record_restart(nv as _);
                     sched_yield(attempt)
                 }
             }
@@ -411,28 +463,43 @@ impl<const FAN_OUT: usize,
     }
 
     #[inline]
-    pub(crate) fn traversal_write_olc(&self, key: Key) -> (NodeVisits, BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>) {
+    pub(crate) fn traversal_write_olc(&self, key: Key) -> Result<(NodeVisits, BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>), BlockID> {
+        let (node_visits, guard, ..)
+            = self.traversal_write_olc_fenced(key)?;
+
+        Ok((node_visits, guard))
+    }
+
+    /// Like `traversal_write_olc`, but also returns the leaf's upper fence, see `traversal_write_fenced`.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn traversal_write_olc_fenced(&self, key: Key) -> Result<(NodeVisits, BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>, Key), BlockID> {
         let mut attempt = 0;
         let mut lock_level = MAX_TREE_HEIGHT;
         let mut node_visits = 0usize;
 
         loop {
-            match self.traversal_write_olc_internal(lock_level, attempt, key) {
+            match self.traversal_write_olc_internal(lock_level, attempt, key)? {
                 (visits, Err((n_lock_level, n_attempt))) => {
                     attempt = n_attempt;
                     lock_level = n_lock_level;
                     node_visits += visits;
 
+                    self.record_restart(n_lock_level + 1);
                     sched_yield(attempt);
                 }
-                (visits, Ok(guard)) => break (node_visits + visits, guard),
+                (visits, Ok((guard, fence_upper))) => {
+                    self.remember_rightmost(&guard, fence_upper);
+
+                    break Ok((node_visits + visits, guard, fence_upper))
+                }
             }
         }
     }
 
     #[inline]
     fn traversal_write_olc_internal(&self, lock_level: LockLevel, attempt: Attempts, key: Key)
-    -> (NodeVisits, Result<BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>, (LockLevel, Attempts)>)
+    -> Result<(NodeVisits, WriteTarget<'_, FAN_OUT, NUM_RECORDS, Key, Payload>), BlockID>
     {
         let mut curr_level = INIT_TREE_HEIGHT;
 
@@ -441,7 +508,7 @@ impl<const FAN_OUT: usize,
             height,
             lock_level,
             attempt
-        ) = self.retrieve_root_olc(lock_level, attempt);
+        ) = self.retrieve_root_olc(lock_level, attempt)?;
 
         let mut fence
             = Interval::new(self.min_key, self.max_key);
@@ -455,7 +522,7 @@ impl<const FAN_OUT: usize,
             if current_guard_result.is_none() {
                 mem::drop(current_guard);
 
-                return (node_visits, Err((curr_level - 1, attempt + 1)));
+                return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
             }
 
             match current_guard_result.unwrap().as_ref() {
@@ -471,7 +538,7 @@ impl<const FAN_OUT: usize,
                     if !current_guard.is_valid() {
                         mem::drop(current_guard);
 
-                        return (node_visits, Err((curr_level - 1, attempt + 1)));
+                        return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
                     }
 
                     curr_level += 1;
@@ -481,30 +548,45 @@ impl<const FAN_OUT: usize,
                         lock_level,
                         attempt,
                         height,
-                        next_node.assume_init_ref());
+                        next_node.assume_init_ref())?;
 
                     let next_guard_result
                         = next_guard.deref_unsafe();
 
+                    // the child's fence is read before the parent is validated, a split in between
+                    // would hand a fence of the parent's last child to an inner one
+                    let parent_len
+                        = index_page.len();
+
+                    let next_fence = Interval::new(
+                        match child_pos > 0 {
+                            true => index_page.get_key(child_pos - 1),
+                            false => fence.lower
+                        },
+                        match child_pos < parent_len {
+                            true => index_page.get_key(child_pos),
+                            false => fence.upper
+                        });
+
                     if next_guard_result.is_none() || !current_guard.is_valid() {
                         mem::drop(next_guard);
                         mem::drop(current_guard);
 
-                        return (node_visits, Err((curr_level - 1, attempt + 1)));
+                        return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
                     }
 
                     let has_overflow_next
                         = self.has_overflow(next_guard_result.unwrap());
                     
-                    let has_underflow_next
-                        = self.has_underflow(next_guard_result.unwrap());
+                    let has_underflow_next = self.has_underflow_at(
+                        next_guard_result.unwrap(), fence.upper, child_pos, parent_len);
                     
                     if has_overflow_next || has_underflow_next {
                         if !current_guard.upgrade_write_lock() || !next_guard.upgrade_write_lock() {
                             mem::drop(next_guard);
                             mem::drop(current_guard);
 
-                            return (node_visits, Err((curr_level - 1, attempt + 1)));
+                            return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
                         }
 
                         debug_assert!(current_guard.upgrade_write_lock() &&
@@ -512,42 +594,35 @@ impl<const FAN_OUT: usize,
 
                         if has_overflow_next {
                             self.do_overflow_correction(
+                                &fence,
                                 &mut current_guard,
                                 child_pos,
                                 next_guard)
                         }
                         else {
-                            match self.do_underflow_correction(
+                            if self.do_underflow_correction(
                                 &fence,
                                 curr_level,
                                 attempt,
                                 lock_level,
                                 &mut current_guard,
                                 child_pos,
-                                next_guard)
+                                next_guard)?.is_err()
                             {
-                                Err(_) => return (node_visits, Err((curr_level - 1, attempt + 1))),
-                                _ => { }
+                                return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
                             }
                         }
                     }
                     else {
-                        if child_pos < index_page.len() {
-                            fence.upper = index_page.get_key(child_pos);
-                        }
-
-                        if child_pos > 0 {
-                            fence.lower = index_page.get_key(child_pos - 1)
-                        }
-                        
+                        fence = next_fence;
                         current_guard = next_guard;
                     }
                 }
-                _ => return if current_guard.upgrade_write_lock() {
-                    (node_visits, Ok(current_guard))
+                _ => return Ok(if current_guard.upgrade_write_lock() {
+                    (node_visits, Ok((current_guard, self.fence_upper_inclusive(fence.upper))))
                 } else {
                     (node_visits, Err((curr_level - 1, attempt + 1)))
-                },
+                }),
             }
         }
     }
@@ -4,12 +4,21 @@ use std::fmt::Display;
 use std::{mem, ptr};
 use itertools::Itertools;
 use crate::locking::locking_strategy::LockingStrategy;
-use crate::page_model::{Attempts, BlockRef, Height, Level};
+use crate::page_model::{Attempts, BlockID, BlockRef, Height, Level};
 use crate::block::block::{Block, BlockGuard};
 use crate::crud_model::crud_api::NodeVisits;
-use crate::page_model::node::{Node, NodeUnsafeDegree};
+use crate::page_model::node::Node;
 use crate::tree::bplus_tree::{BPlusTree, INIT_TREE_HEIGHT, LockLevel, MAX_TREE_HEIGHT};
+use crate::tree::export::NodeKind;
+use crate::tree::fill_policy::Rebalance;
 use crate::utils::interval::Interval;
+use crate::utils::epoch;
+use crate::utils::metrics::{self, Counter};
+use crate::utils::smart_cell::sched_yield;
+
+/// Guard write latching the target leaf and the leaf's upper fence, or the restart level and attempt.
+pub(crate) type WriteTarget<'a, const FAN_OUT: usize, const NUM_RECORDS: usize, Key, Payload>
+    = Result<(BlockGuard<'a, FAN_OUT, NUM_RECORDS, Key, Payload>, Key), (LockLevel, Attempts)>;
 
 impl<const FAN_OUT: usize,
     const NUM_RECORDS: usize,
@@ -25,48 +34,109 @@ impl<const FAN_OUT: usize,
         }
     }
 
+    /// IDs of two siblings in key order, given their positions in the parent.
+    #[inline(always)]
+    fn sibling_ids(from_pos: usize,
+                   from: &Block<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                   merge_pos: usize,
+                   merge: &Block<FAN_OUT, NUM_RECORDS, Key, Payload>) -> [BlockID; 2]
+    {
+        if from_pos < merge_pos {
+            [from.block_id(), merge.block_id()]
+        } else {
+            [merge.block_id(), from.block_id()]
+        }
+    }
+
+    /// Whether the parent still holds `len` entries and the child at `child_pos`. Upgrading shared
+    /// latches of `ORWC` releases them in between, a sibling split or merge meanwhile moves the child.
+    #[inline(always)]
+    fn holds_child_at(parent: &Node<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                      len: usize,
+                      child_pos: usize,
+                      child: &Node<FAN_OUT, NUM_RECORDS, Key, Payload>) -> bool
+    {
+        parent.len() == len && parent
+            .children()
+            .get(child_pos)
+            .is_some_and(|held| ptr::addr_eq(held.unsafe_borrow().as_ref() as *const _, child as *const _))
+    }
+
     pub(crate) fn has_underflow(&self, node: &Node<FAN_OUT, NUM_RECORDS, Key, Payload>) -> bool {
-        match node.is_leaf() {
-            true => node.is_underflow(self.block_manager.allocation_leaf() - 1),
-            false => node.is_underflow(self.block_manager.allocation_directory())
+        node.len() < self.min_len(node.is_leaf())
+    }
+
+    /// Like `has_underflow` for the child at `child_pos` of a parent ending at `fence_upper`,
+    /// but nodes appended to underflow only below `append_min_len`, see `is_append_target`.
+    #[inline(always)]
+    pub(crate) fn has_underflow_at(&self,
+                                   node: &Node<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                                   fence_upper: Key,
+                                   child_pos: usize,
+                                   parent_len: usize) -> bool
+    {
+        match self.is_append_target(fence_upper, child_pos, parent_len) {
+            true => node.len() < Self::append_min_len(node.is_leaf()),
+            false => self.has_underflow(node)
         }
     }
 
-    fn unsafe_degree_of(&self, node: &Node<FAN_OUT, NUM_RECORDS, Key, Payload>) -> NodeUnsafeDegree {
-        match node.is_leaf() {
-            true => node.unsafe_degree(self.block_manager.allocation_leaf()),
-            false => node.unsafe_degree(self.block_manager.allocation_directory()),
+    /// Position at which the overflowing node is split, i.e. its first record moving right or its key moving up.
+    /// Nodes split by the fill policy's ratio, unless they are appended to, see `is_append_target`.
+    /// The right node then keeps `append_min_len` entries only, which ascending inserts refill.
+    fn split_pos(&self, node: &Node<FAN_OUT, NUM_RECORDS, Key, Payload>, append_target: bool) -> usize {
+        let len
+            = node.len();
+
+        if append_target {
+            return match node.is_leaf() {
+                true => len - Self::append_min_len(true),
+                false => len.saturating_sub(Self::append_min_len(false) + 1).max(len / 2)
+            };
         }
+
+        let min_len
+            = self.min_len(node.is_leaf());
+
+        let right_min = match node.is_leaf() {
+            true => min_len,
+            false => min_len + 1
+        };
+
+        self.fill_policy.split_pos(len, min_len, right_min)
     }
 
     #[inline]
+    #[allow(clippy::type_complexity)]
     pub(crate) fn retrieve_root(&self, mut lock_level: Level, mut attempt: Attempts)
-                                -> (NodeVisits, BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>, Height, LockLevel, Attempts)
+                                -> Result<(NodeVisits, BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>, Height, LockLevel, Attempts), BlockID>
     {
         let mut node_visits = 0;
         loop {
-            match self.retrieve_root_internal(lock_level, attempt) {
+            match self.retrieve_root_internal(lock_level, attempt)? {
                 Err((n_lock_level, n_attempt)) => {
                     lock_level = n_lock_level;
                     attempt = n_attempt;
                     node_visits += 1;
+
+                    self.record_restart(INIT_TREE_HEIGHT);
                 }
                 Ok((guard, height)) =>
-                    break (node_visits + 1, guard, height, lock_level, attempt)
+                    break Ok((node_visits + 1, guard, height, lock_level, attempt))
             }
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn merge(&self,
              block_guard: &mut BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>,
              from_guard: BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>,
              merge_guard: Option<(usize, BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>)>,
              child_pos: usize,
-             child_key: Key,
              curr_level: Level,
              lock_level: LockLevel,
              attempt: Attempts,
-    ) -> Result<(), ()> {
+    ) -> Result<Result<(), ()>, BlockID> {
         // unsafe {
         //     if ptr::read_unaligned(&child_key as *const _ as *const u64) == 246 {
         //         println!("FROM ROOT");
@@ -80,7 +150,7 @@ impl<const FAN_OUT: usize,
             = self.root.height();
 
         if !block_guard.upgrade_write_lock() {
-            return Err(());
+            return Ok(Err(()));
         }
 
         let block_deref
@@ -96,7 +166,7 @@ impl<const FAN_OUT: usize,
                 _ => true
             })
             .map(|(.., c)| self.apply_for_ref(curr_level, lock_level, attempt, height, c))
-            .collect_vec();
+            .collect::<Result<Vec<_>, _>>()?;
         
         if children_latched.is_empty() {
             children_latched.push(from_guard);
@@ -114,7 +184,7 @@ impl<const FAN_OUT: usize,
             !guard.upgrade_write_lock())
         {
             mem::drop(children_latched);
-            return Err(());
+            return Ok(Err(()));
         }
         // println!("FROM ROOT");
         // Self::log_console(self.root.block.unsafe_borrow(), 0);
@@ -124,6 +194,15 @@ impl<const FAN_OUT: usize,
         let is_leaf_children = unsafe { children_latched.get_unchecked(0).deref_unsafe().unwrap() }
             .is_leaf();
 
+        // collected for observers only, the merged children are gone afterwards
+        let merged_ids = match self.observer.is_some() {
+            true => children_latched
+                .iter()
+                .map(|guard| guard.deref().unwrap().block_id())
+                .collect_vec(),
+            false => vec![]
+        };
+
         let block = match is_leaf_children {
             true => {
                 let n_leaf
@@ -143,6 +222,9 @@ impl<const FAN_OUT: usize,
                 let n_internal
                     = self.block_manager.new_empty_index_block();
 
+                let separators
+                    = block_deref.keys();
+
                 children_latched
                     .into_iter()
                     .enumerate()
@@ -153,8 +235,8 @@ impl<const FAN_OUT: usize,
 
                         n_internal.children_mut().extend_from_slice(children);
 
-                        if index == child_pos + 1 {
-                            n_internal.keys_mut().push(child_key)
+                        if index > 0 {
+                            n_internal.keys_mut().push(separators[index - 1])
                         }
                         n_internal.keys_mut().extend_from_slice(keys);
                     });
@@ -166,21 +248,44 @@ impl<const FAN_OUT: usize,
         if ptr::addr_eq(self.root.block.unsafe_borrow() as *const _,
                         BlockGuard::deref(block_guard).unwrap() as *const _)
         {
+            metrics::record(Counter::Merge);
+            metrics::record(Counter::RootChange);
+            self.observe(|observer| {
+                let node_kind
+                    = if is_leaf_children { NodeKind::Leaf } else { NodeKind::Index };
+
+                observer.on_merge(node_kind, &merged_ids, block.block_id());
+                observer.on_root_change(block.block_id(), height, height - 1);
+            });
+
             self.root.get_mut().height -= 1;
         } else {
             unreachable!("Merge non-root into lower height")
         }
 
-        *block_guard.deref_mut().unwrap() = block;
+        let old_block
+            = mem::replace(block_guard.deref_mut().unwrap(), block);
+
+        if let Node::Index(index_page) = old_block.as_ref() {
+            index_page.children()
+                .iter()
+                .for_each(|child| self.retire_block(child.clone()));
+        }
+
+        // optimistic readers may still read records or children of the old root
+        if self.locking_strategy.is_optimistic() {
+            self.retire_block(self.block_manager.new_cell(old_block, self.locking_strategy.latch_type()))
+        }
 
         // println!("AFTER MERGE BLOCKGUARD");
         // Self::log_console(BlockGuard::deref(block_guard).unwrap(), 0);
-        Ok(())
+        Ok(Ok(()))
     }
 
     #[inline]
+    #[allow(clippy::type_complexity)]
     pub(crate) fn retrieve_root_internal(&self, lock_level: LockLevel, attempt: Attempts)
-                                         -> Result<(BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>, Height), (LockLevel, Attempts)>
+                                         -> Result<Result<(BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>, Height), (LockLevel, Attempts)>, BlockID>
     {
         let root
             = self.root.get();
@@ -190,12 +295,12 @@ impl<const FAN_OUT: usize,
                                lock_level,
                                attempt,
                                root.height(),
-                               &root.block);
+                               &root.block)?;
 
         if !root_guard.is_valid() {
             mem::drop(root_guard);
 
-            return Err((lock_level, attempt + 1));
+            return Ok(Err((lock_level, attempt + 1)));
         }
 
         let has_overflow_root
@@ -209,7 +314,7 @@ impl<const FAN_OUT: usize,
         if force_restart && has_overflow_root && !root_guard.upgrade_write_lock() { // !root_guard.is_valid() ||
             mem::drop(root_guard);
 
-            return Err((lock_level, attempt + 1));
+            return Ok(Err((lock_level, attempt + 1)));
         }
 
         if has_overflow_root && self.locking_strategy.is_orwc() &&
@@ -218,28 +323,34 @@ impl<const FAN_OUT: usize,
             // if !root_guard.deref().unwrap().is_leaf() {
             //     root_guard.downgrade(); // allow possible concurrency, instead of definitive lock
             // }
-            return Ok((root_guard, root.height()));
+            return Ok(Ok((root_guard, root.height())));
         }
 
         if !has_overflow_root {
-            return Ok((root_guard, root.height()));
+            return Ok(Ok((root_guard, root.height())));
         }
 
         let root_ref
             = root_guard.deref_mut().unwrap();
 
+        let root_id
+            = root_ref.block_id();
+
         let latch_type
             = self.locking_strategy.latch_type();
 
         let n_height
             = root.height() + 1;
 
+        let split_pos
+            = self.split_pos(root_ref, self.append.is_some());
+
         match root_ref.as_mut() {
             Node::Index(index_page) => unsafe {
                 let keys = index_page.keys();
                 let children = index_page.children();
 
-                let keys_mid = keys.len() / 2;
+                let keys_mid = split_pos;
                 let k3 = *keys.get_unchecked(keys_mid);
 
                 let index_block
@@ -267,14 +378,18 @@ impl<const FAN_OUT: usize,
                     .keys_mut()
                     .extend_from_slice(keys.get_unchecked(..keys_mid));
 
+                let split_ids
+                    = (new_root_left.block_id(), new_node_right.block_id());
+
                 index_block.children_mut().extend([
-                    new_root_left.into_cell(latch_type),
-                    new_node_right.into_cell(latch_type)
+                    self.block_manager.new_cell(new_root_left, latch_type),
+                    self.block_manager.new_cell(new_node_right, latch_type)
                 ]);
 
                 index_block.keys_mut()
                     .push(k3);
 
+                self.observe(|observer| observer.on_split(NodeKind::Index, root_id, split_ids, k3));
                 self.set_new_root(
                     index_block,
                     n_height);
@@ -284,7 +399,7 @@ impl<const FAN_OUT: usize,
                     = records.as_records();
 
                 let records_mid
-                    = records.len() / 2;
+                    = split_pos;
 
                 let k3 = records
                     .get_unchecked(records_mid)
@@ -307,23 +422,28 @@ impl<const FAN_OUT: usize,
                     .records_mut()
                     .extend_from_slice(records.get_unchecked(..records_mid));
 
+                let split_ids
+                    = (new_node_left.block_id(), new_node_right.block_id());
+
                 new_root.children_mut().extend([
-                    new_node_left.into_cell(latch_type),
-                    new_node_right.into_cell(latch_type)
+                    self.block_manager.new_cell(new_node_left, latch_type),
+                    self.block_manager.new_cell(new_node_right, latch_type)
                 ]);
 
                 new_root.keys_mut()
                     .push(k3);
 
+                self.observe(|observer| observer.on_split(NodeKind::Leaf, root_id, split_ids, k3));
                 self.set_new_root(
                     new_root,
                     n_height);
             }
         }
 
-        Ok((root_guard, n_height))
+        Ok(Ok((root_guard, n_height)))
     }
 
+    #[allow(dead_code)]
     pub(crate) fn log_console(mufasa: &Block<FAN_OUT, NUM_RECORDS, Key, Payload>, rec: usize) {
         if let Node::Index(internal_page) = mufasa.as_ref() {
             println!("{}Keys: [{}]", "\t".repeat(rec), internal_page
@@ -350,6 +470,7 @@ impl<const FAN_OUT: usize,
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn do_underflow_correction(
         &self,
         fence: &Interval<Key>,
@@ -359,13 +480,22 @@ impl<const FAN_OUT: usize,
         parent_guard: &mut BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>,
         mut child_pos: usize,
         from_guard: BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>)
-        -> Result<(), ()>
+        -> Result<Result<(), ()>, BlockID>
     {
+        // only the root is merged into by its children, lowering the tree's height
+        let is_root = ptr::addr_eq(self.root.block.unsafe_borrow() as *const _,
+                                   BlockGuard::deref(parent_guard).unwrap() as *const _);
+
         let mufasa = parent_guard
             .deref_mut()
             .unwrap();
 
-        let child_key = unsafe { *mufasa.keys().get_unchecked(child_pos) };
+        // the last child is bound by the parent's fence
+        let child_key = mufasa
+            .keys()
+            .get(child_pos)
+            .cloned()
+            .unwrap_or(fence.upper);
 
         let from_deref
             = from_guard.deref_mut().unwrap();
@@ -391,17 +521,15 @@ impl<const FAN_OUT: usize,
         ) = match all_candidates
             .binary_search_by_key(&child_key, |(.., key)| *key)
         {
-            _ if all_candidates.len() == 1 && !is_leaf => { // copy all to mufasa
-                mem::drop(all_candidates);
-                return self.merge(parent_guard, from_guard, None, child_pos, child_key, curr_level, lock_level, attempts);
-            }
             Ok(index) | Err(index) => if index < all_candidates.len() {
                 all_candidates.remove(index)
             } else if !all_candidates.is_empty() && index == all_candidates.len() {
                 all_candidates.pop().unwrap()
-            } else {
+            } else if is_root {
                 mem::drop(all_candidates);
-                return self.merge(parent_guard, from_guard, None, child_pos, child_key, curr_level, lock_level, attempts);
+                return self.merge(parent_guard, from_guard, None, child_pos, curr_level, lock_level, attempts);
+            } else { // single child left, corrected by the grandparent once restarted
+                return Ok(Err(()));
             }
             // Err(..) if !all_candidates.is_empty() && !is_leaf => all_candidates.pop().unwrap(),
             // r => {
@@ -417,28 +545,44 @@ impl<const FAN_OUT: usize,
         all_candidates.clear();
 
         let merge_guard
-            = _merge_block.borrow_mut();
+            = self.latch_resident(&_merge_block, curr_level, true, || _merge_block.borrow_mut())?;
 
         if !merge_guard.is_valid() { // OLC
-            return Err(());
+            return Ok(Err(()));
         }
 
         let merge_deref
             = merge_guard.deref().unwrap();
 
+        let borrow
+            = self.fill_policy.rebalance == Rebalance::Borrow;
+
         if is_leaf {
+            let joined_len
+                = from_deref.len() + merge_deref.len();
+
+            // borrowing siblings are redistributed even if they fit, unless the left one underflows then
+            let redistribute
+                = borrow && joined_len / 2 >= self.min_len(true);
+
             let fit
-                = from_deref.len() + merge_deref.len() < NUM_RECORDS;
+                = joined_len < NUM_RECORDS && !redistribute;
 
-            if mufasa.len() == 1 && fit {
-                return self.merge(parent_guard, from_guard, Some((merge_index, merge_guard)), child_pos, child_key, curr_level, lock_level, attempts);
+            if is_root && mufasa.len() == 1 && fit {
+                return self.merge(parent_guard, from_guard, Some((merge_index, merge_guard)), child_pos, curr_level, lock_level, attempts);
             }
             if fit { // merge into one new leaf; checked
+                metrics::record(Counter::Merge);
                 // println!("Before Leaf Merge");
                 // Self::log_console(mufasa, 0);
-                let new_leaf = self.block_manager
-                    .new_empty_leaf()
-                    .into_cell(self.locking_strategy.latch_type());
+                let new_leaf = self.block_manager.new_cell(
+                    self.block_manager.new_empty_leaf(),
+                    self.locking_strategy.latch_type());
+
+                self.observe(|observer| observer.on_merge(
+                    NodeKind::Leaf,
+                    &Self::sibling_ids(child_pos, from_deref, merge_index, merge_deref),
+                    new_leaf.unsafe_borrow().block_id()));
 
                 new_leaf
                     .unsafe_borrow_mut()
@@ -465,9 +609,8 @@ impl<const FAN_OUT: usize,
 
                 // Self::log_console(mufasa, 0);
                 mufasa_keys_mut.remove(child_pos);
-                mufasa_children_mut.remove(merge_index);
-
-                *mufasa_children_mut.get_unchecked_mut(child_pos) = new_leaf;
+                self.retire_block(mufasa_children_mut.remove(merge_index));
+                self.retire_block(mem::replace(mufasa_children_mut.get_unchecked_mut(child_pos), new_leaf));
 
                 mem::drop(mufasa_keys_mut);
                 mem::drop(mufasa_children_mut);
@@ -477,14 +620,25 @@ impl<const FAN_OUT: usize,
                 // println!("After Leaf Merge");
                 // Self::log_console(mufasa, 0);
             } else { // Leaf: key-split
+                metrics::record(Counter::Redistribution);
                 // Self::log_console(mufasa, 0);
-                let new_leaf_left = self.block_manager
-                    .new_empty_leaf()
-                    .into_cell(self.locking_strategy.latch_type());
+                let new_leaf_left = self.block_manager.new_cell(
+                    self.block_manager.new_empty_leaf(),
+                    self.locking_strategy.latch_type());
+
+                let new_leaf_right = self.block_manager.new_cell(
+                    self.block_manager.new_empty_leaf(),
+                    self.locking_strategy.latch_type());
 
-                let new_leaf_right = self.block_manager
-                    .new_empty_leaf()
-                    .into_cell(self.locking_strategy.latch_type());
+                self.observe(|observer| {
+                    let [left, right]
+                        = Self::sibling_ids(child_pos, from_deref, merge_index, merge_deref);
+
+                    observer.on_redistribute(
+                        NodeKind::Leaf,
+                        (left, right),
+                        (new_leaf_left.unsafe_borrow().block_id(), new_leaf_right.unsafe_borrow().block_id()))
+                });
 
                 let joined = from_deref
                     .as_records()
@@ -506,6 +660,9 @@ impl<const FAN_OUT: usize,
                 let right_key
                     = merge_key.max(child_key);
 
+                // the new separator stays within the fence of both leaves
+                debug_assert!(left_key < right_key);
+
                 new_leaf_left.unsafe_borrow_mut()
                     .records_mut()
                     .extend_from_slice(first);
@@ -528,18 +685,41 @@ impl<const FAN_OUT: usize,
 
                 *mufasa_keys_mut.get_unchecked_mut(child_pos) = left_key;
                 // *mufasa_keys_mut.get_unchecked_mut(merge_index) = right_key;
-                *mufasa_children_mut.get_unchecked_mut(child_pos) = new_leaf_left;
-                *mufasa_children_mut.get_unchecked_mut(merge_index) = new_leaf_right;
+                self.retire_block(mem::replace(mufasa_children_mut.get_unchecked_mut(child_pos), new_leaf_left));
+                self.retire_block(mem::replace(mufasa_children_mut.get_unchecked_mut(merge_index), new_leaf_right));
 
                 // Self::log_console(mufasa, 0);
             }
         } else { // is Internal page: Combine
-            if from_deref.len() + merge_deref.len() < FAN_OUT - 1 {
+            let joined_len
+                = from_deref.len() + merge_deref.len();
+
+            // the separator moves down and the split key up, hence the right one keeps a key less
+            let left_len
+                = joined_len.div_ceil(2);
+
+            let redistribute = borrow &&
+                left_len >= self.min_len(false) &&
+                joined_len - left_len >= self.min_len(false);
+
+            let fit
+                = joined_len < FAN_OUT - 1 && !redistribute;
+
+            if is_root && mufasa.len() == 1 && fit {
+                return self.merge(parent_guard, from_guard, Some((merge_index, merge_guard)), child_pos, curr_level, lock_level, attempts);
+            }
+            if fit {
+                metrics::record(Counter::Merge);
                 // Self::log_console(mufasa, 0);
 
-                let new_index = self.block_manager
-                    .new_empty_index_block()
-                    .into_cell(self.locking_strategy.latch_type());
+                let new_index = self.block_manager.new_cell(
+                    self.block_manager.new_empty_index_block(),
+                    self.locking_strategy.latch_type());
+
+                self.observe(|observer| observer.on_merge(
+                    NodeKind::Index,
+                    &Self::sibling_ids(child_pos, from_deref, merge_index, merge_deref),
+                    new_index.unsafe_borrow().block_id()));
 
                 let keys_mut = new_index
                     .unsafe_borrow_mut()
@@ -575,13 +755,13 @@ impl<const FAN_OUT: usize,
                 let mufasa_keys_mut
                     = mufasa.keys_mut();
 
-                mufasa_children_mut.remove(merge_index);
+                self.retire_block(mufasa_children_mut.remove(merge_index));
                 mufasa_keys_mut.remove(child_pos);
 
                 mem::drop(keys_mut);
                 mem::drop(children_mut);
 
-                *mufasa_children_mut.get_unchecked_mut(child_pos) = new_index;
+                self.retire_block(mem::replace(mufasa_children_mut.get_unchecked_mut(child_pos), new_index));
 
                 mem::drop(mufasa_keys_mut);
                 mem::drop(mufasa_children_mut);
@@ -589,22 +769,35 @@ impl<const FAN_OUT: usize,
                 // Self::log_console(mufasa, 0);
                 // let s = "adasda".to_string();
             } else { // key-split: Internal Page
+                metrics::record(Counter::Redistribution);
                 // println!("aaaaa");
-                let new_internal_left = self.block_manager
-                    .new_empty_index_block()
-                    .into_cell(self.locking_strategy.latch_type());
+                let new_internal_left = self.block_manager.new_cell(
+                    self.block_manager.new_empty_index_block(),
+                    self.locking_strategy.latch_type());
+
+                let new_internal_right = self.block_manager.new_cell(
+                    self.block_manager.new_empty_index_block(),
+                    self.locking_strategy.latch_type());
+
+                self.observe(|observer| {
+                    let [left, right]
+                        = Self::sibling_ids(child_pos, from_deref, merge_index, merge_deref);
 
-                let new_internal_right = self.block_manager
-                    .new_empty_index_block()
-                    .into_cell(self.locking_strategy.latch_type());
+                    observer.on_redistribute(
+                        NodeKind::Index,
+                        (left, right),
+                        (new_internal_left.unsafe_borrow().block_id(), new_internal_right.unsafe_borrow().block_id()))
+                });
 
+                // the separator of both moves down between their keys
                 let (child_pos, merge_index, joined_keys, joined_children) = if child_pos < merge_index
                 {
                     (child_pos, merge_index,
                      from_deref
                          .keys()
                          .iter()
-                         .merge(merge_deref.keys())
+                         .chain([&child_key])
+                         .chain(merge_deref.keys())
                          .cloned()
                          .collect_vec(),
                      from_deref
@@ -618,7 +811,8 @@ impl<const FAN_OUT: usize,
                      merge_deref
                          .keys()
                          .iter()
-                         .merge(from_deref.keys())
+                         .chain([&merge_key])
+                         .chain(from_deref.keys())
                          .cloned()
                          .collect_vec(),
                      merge_deref
@@ -638,6 +832,9 @@ impl<const FAN_OUT: usize,
                 let split_key
                     = unsafe { *keys_right.get_unchecked(0) };
 
+                // the new separator stays within the fence of both nodes
+                debug_assert!(split_key < merge_key.max(child_key));
+
                 let keys_right
                     = unsafe { keys_right.get_unchecked(1..) };
 
@@ -665,26 +862,38 @@ impl<const FAN_OUT: usize,
                     .keys_mut()
                     .extend_from_slice(keys_right);
 
-                *mufasa.keys_mut().get_unchecked_mut(merge_index) = split_key;
-                *mufasa.children_mut().get_unchecked_mut(child_pos) = new_internal_left;
-                *mufasa.children_mut().get_unchecked_mut(merge_index) = new_internal_right;
+                *mufasa.keys_mut().get_unchecked_mut(child_pos) = split_key;
+                self.retire_block(mem::replace(mufasa.children_mut().get_unchecked_mut(child_pos), new_internal_left));
+                self.retire_block(mem::replace(mufasa.children_mut().get_unchecked_mut(merge_index), new_internal_right));
             }
         }
         // Self::log_console(mufasa, 0);
-        Ok(())
+        Ok(Ok(()))
     }
 
+    /// Splits the child at `child_pos` of the parent, whose fence is `fence`.
     pub(crate) fn do_overflow_correction(
         &self,
+        fence: &Interval<Key>,
         parent_guard: &mut BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>,
         child_pos: usize,
         mut from_guard: BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>)
     {
+        metrics::record(Counter::Split);
         from_guard.mark_obsolete();
 
         let latch_type
             = self.locking_strategy.latch_type();
 
+        let from_id
+            = from_guard.deref().unwrap().block_id();
+
+        let append_target
+            = self.is_append_target(fence.upper, child_pos, parent_guard.deref().unwrap().len());
+
+        let split_pos
+            = self.split_pos(from_guard.deref().unwrap(), append_target);
+
         match from_guard.deref_mut().unwrap().as_mut() {
             Node::Index(index_page) => unsafe {
                 let keys
@@ -693,7 +902,7 @@ impl<const FAN_OUT: usize,
                 let children
                     = index_page.children();
 
-                let keys_mid = keys.len() / 2;
+                let keys_mid = split_pos;
                 let k3 = *keys
                     .get_unchecked(keys_mid);
 
@@ -719,28 +928,36 @@ impl<const FAN_OUT: usize,
                     .keys_mut()
                     .extend_from_slice(keys.get_unchecked(..keys_mid));
 
+                let split_ids
+                    = (new_node_from.block_id(), new_node_right.block_id());
+
                 let parent_mut = parent_guard
                     .deref_mut()
                     .unwrap();
 
-                let mut parent_children
+                let parent_children
                     = parent_mut.children_mut();
 
                 parent_children
-                    .insert(child_pos + 1, new_node_right.into_cell(latch_type));
+                    .insert(child_pos + 1, self.block_manager.new_cell(new_node_right, latch_type));
 
-                mem::drop(mem::replace(parent_children.get_unchecked_mut(child_pos),
-                                       new_node_from.into_cell(latch_type)));
+                self.retire_block(mem::replace(parent_children.get_unchecked_mut(child_pos),
+                                               self.block_manager.new_cell(new_node_from, latch_type)));
 
                 parent_mut
                     .keys_mut()
                     .insert(child_pos, k3);
+
+                self.observe(|observer| {
+                    observer.on_obsolete(NodeKind::Index, from_id);
+                    observer.on_split(NodeKind::Index, from_id, split_ids, k3);
+                });
             }
             Node::Leaf(records) => unsafe {
                 let records
                     = records.as_records();
 
-                let records_mid = records.len() / 2;
+                let records_mid = split_pos;
                 let k3 = records
                     .get_unchecked(records_mid)
                     .key;
@@ -759,6 +976,9 @@ impl<const FAN_OUT: usize,
                     .records_mut()
                     .extend_from_slice(records.get_unchecked(..records_mid));
 
+                let split_ids
+                    = (new_node_from.block_id(), new_node.block_id());
+
                 let parent_mut = parent_guard
                     .deref_mut()
                     .unwrap();
@@ -767,21 +987,26 @@ impl<const FAN_OUT: usize,
                     = parent_mut.children_mut();
 
                 parent_children
-                    .insert(child_pos + 1, new_node.into_cell(latch_type));
+                    .insert(child_pos + 1, self.block_manager.new_cell(new_node, latch_type));
 
-                mem::drop(mem::replace(parent_children.get_unchecked_mut(child_pos),
-                                       new_node_from.into_cell(latch_type)));
+                self.retire_block(mem::replace(parent_children.get_unchecked_mut(child_pos),
+                                               self.block_manager.new_cell(new_node_from, latch_type)));
 
                 parent_mut
                     .keys_mut()
                     .insert(child_pos, k3);
+
+                self.observe(|observer| {
+                    observer.on_obsolete(NodeKind::Leaf, from_id);
+                    observer.on_split(NodeKind::Leaf, from_id, split_ids, k3);
+                });
             }
         }
     }
 
     #[inline]
     fn traversal_write_internal(&self, lock_level: LockLevel, attempt: Attempts, key: Key)
-                                -> (NodeVisits, Result<BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>, (LockLevel, Attempts)>)
+                                -> Result<(NodeVisits, WriteTarget<'_, FAN_OUT, NUM_RECORDS, Key, Payload>), BlockID>
     {
         let mut curr_level = INIT_TREE_HEIGHT;
 
@@ -790,15 +1015,15 @@ impl<const FAN_OUT: usize,
             height,
             lock_level,
             attempt
-        ) = self.retrieve_root(lock_level, attempt);
-
-        let mut _curr_block
-            = self.root.block();
+        ) = self.retrieve_root(lock_level, attempt)?;
 
         // if unsafe { ptr::read_unaligned(&key as *const _ as *const u64) } == 23 {
         //     Self::log_console(current_guard.deref().unwrap(), 0);
         //     let s = "daasd".to_string();
         // }
+        let mut _curr_block
+            = None;
+
         let mut fence
             = Interval::new(self.min_key, self.max_key);
 
@@ -818,9 +1043,13 @@ impl<const FAN_OUT: usize,
                     let (child_pos, next_node)
                         = match index_page.keys().binary_search(&key)
                     {
-                        Ok(pos) | Err(pos) => (pos, index_page.get_child_unsafe_cloned(pos)),
+                        Ok(pos) | Err(pos) => (pos, index_page.get_child_unsafe(pos)),
                     };
 
+                    // outlives the child's guard, which corrections release after unlinking the child
+                    let next_held
+                        = self.hold_child(next_node);
+
                     curr_level += 1;
 
                     let mut next_guard = self.apply_for_ref(
@@ -828,13 +1057,16 @@ impl<const FAN_OUT: usize,
                         lock_level,
                         attempt,
                         height,
-                        &next_node);
+                        next_node)?;
+
+                    let parent_len
+                        = index_page.len();
 
                     let has_overflow_next
                         = self.has_overflow(next_guard.deref().unwrap());
 
-                    let has_underflow_next
-                        = self.has_underflow(next_guard.deref().unwrap());
+                    let has_underflow_next = self.has_underflow_at(
+                        next_guard.deref().unwrap(), fence.upper, child_pos, parent_len);
 
                     if has_overflow_next || has_underflow_next {
                         let current_exclusive
@@ -846,15 +1078,19 @@ impl<const FAN_OUT: usize,
                             mem::drop(next_guard);
                             mem::drop(current_guard);
 
-                            return (node_visits, Err((curr_level - 1, attempt + 1)));
+                            return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
                         } else if self.locking_strategy.is_orwc() &&
                             !current_exclusive &&
                             (self.has_overflow(current_guard.deref().unwrap()) ||
-                             self.has_underflow(current_guard.deref().unwrap()))
+                             self.has_underflow(current_guard.deref().unwrap()) ||
+                             !Self::holds_child_at(current_guard.deref().unwrap(),
+                                                   parent_len,
+                                                   child_pos,
+                                                   next_guard.deref().unwrap()))
                         { // this maps the obsolete check within an is_valid/deref auto call
                             mem::drop(next_guard);
                             mem::drop(current_guard);
-                            return (node_visits, Err((curr_level - 1, attempt + 1)));
+                            return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
                         }
 
                         debug_assert!(self.locking_strategy.additional_lock_required() &&
@@ -863,21 +1099,21 @@ impl<const FAN_OUT: usize,
 
                         if has_overflow_next {
                             self.do_overflow_correction(
+                                &fence,
                                 &mut current_guard,
                                 child_pos,
                                 next_guard)
                         } else {
-                            match self.do_underflow_correction(
+                            if self.do_underflow_correction(
                                 &fence,
                                 curr_level,
                                 attempt,
                                 lock_level,
                                 &mut current_guard,
                                 child_pos,
-                                next_guard)
+                                next_guard)?.is_err()
                             {
-                                Err(..) => return (node_visits, Err((curr_level - 1, attempt + 1))),
-                                _ => {}
+                                return Ok((node_visits, Err((curr_level - 1, attempt + 1))));
                             }
                         }
                     } else {
@@ -890,43 +1126,67 @@ impl<const FAN_OUT: usize,
                         }
 
                         current_guard = next_guard;
-                        _curr_block = next_node;
+                        _curr_block = next_held;
                     }
                 }
-                _ => return if current_guard.upgrade_write_lock() {
-                    (node_visits, Ok(current_guard))
+                _ => return Ok(if current_guard.upgrade_write_lock() {
+                    (node_visits, Ok((current_guard, self.fence_upper_inclusive(fence.upper))))
                 } else {
                     (node_visits, Err((curr_level - 1, attempt + 1)))
-                },
+                }),
             }
         }
     }
 
     #[inline]
-    pub(crate) fn traversal_write(&self, key: Key) -> (NodeVisits, BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>) {
+    pub(crate) fn traversal_write(&self, key: Key) -> Result<(NodeVisits, BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>), BlockID> {
+        let (node_visits, guard, ..)
+            = self.traversal_write_fenced(key)?;
+
+        Ok((node_visits, guard))
+    }
+
+    /// Like `traversal_write`, but also returns the leaf's upper fence, i.e. the greatest key
+    /// the leaf holds, see `fence_upper_inclusive`. The fence stays while the leaf is latched.
+    /// Fails with the ID of a frame that could not be faulted in, see `BufferPool`.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn traversal_write_fenced(&self, key: Key) -> Result<(NodeVisits, BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>, Key), BlockID> {
         let mut attempt = 0;
         let mut lock_level = MAX_TREE_HEIGHT;
         let mut node_visits = 0;
 
         loop {
-            match self.traversal_write_internal(lock_level, attempt, key) {
+            match self.traversal_write_internal(lock_level, attempt, key)? {
                 (visits, Err((n_lock_level, n_attempt))) => {
                     attempt = n_attempt;
                     lock_level = n_lock_level;
                     node_visits += visits;
+
+                    self.record_restart(n_lock_level + 1);
                 }
-                (visits, Ok(guard)) => break (node_visits + visits, guard),
+                (visits, Ok((guard, fence_upper))) => break Ok((node_visits + visits, guard, fence_upper)),
             }
         }
     }
 
+    /// The greatest key a node bounded by the separator `upper` holds, since keys equal
+    /// to a separator are routed right. The tree's maximum bounds the rightmost nodes, hence it is kept.
+    #[inline(always)]
+    pub(crate) fn fence_upper_inclusive(&self, upper: Key) -> Key {
+        match upper == self.max_key {
+            true => upper,
+            false => (self.dec_key)(upper)
+        }
+    }
+
     #[inline]
-    pub(crate) fn traversal_read(&self, key: Key) -> (NodeVisits, BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>) {
-        let mut current_block
-            = self.root.block();
+    pub(crate) fn traversal_read(&self, key: Key) -> Result<(NodeVisits, BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>), BlockID> {
+        let mut _current_block
+            = None;
 
         let mut current_guard
-            = self.lock_reader(&current_block);
+            = self.lock_reader(&self.root.block, INIT_TREE_HEIGHT)?;
 
         let mut node_visits = 1;
 
@@ -937,45 +1197,36 @@ impl<const FAN_OUT: usize,
                     node_visits += 1;
 
                     match index_page.keys().binary_search(&key) {
-                        Ok(pos) => unsafe {
+                        Ok(pos) | Err(pos) => unsafe {
                             let next_block
-                                = index_page.get_child_unsafe_cloned(pos);
+                                = index_page.get_child_unsafe(pos);
 
                             let next_guard
-                                = self.lock_reader(&next_block);
+                                = self.lock_reader(next_block, node_visits as _)?;
 
+                            _current_block = self.hold_child(next_block);
                             current_guard = next_guard;
-                            current_block = next_block;
-                        },
-                        Err(pos) => unsafe {
-                            let next_block
-                                = index_page.get_child_unsafe_cloned(pos);
-
-                            let next_guard
-                                = self.lock_reader(&next_block);
-
-                            current_guard = next_guard;
-                            current_block = next_block;
                         }
                     }
                 }
-                _ => break (node_visits, current_guard),
+                _ => break Ok((node_visits, current_guard)),
             }
         }
     }
 
     #[inline]
+    #[allow(clippy::type_complexity)]
     pub(crate) fn traversal_read_range(
         &self,
         current_range: &Interval<Key>)
-        -> (NodeVisits,
-            Vec<(BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>, BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>)>)
+        -> Result<(NodeVisits,
+            Vec<(BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>, BlockGuard<'_, FAN_OUT, NUM_RECORDS, Key, Payload>)>), BlockID>
     {
         let mut current_block
             = self.root.block();
 
         let mut current_guard
-            = self.lock_reader(&current_block);
+            = self.lock_reader(&current_block, INIT_TREE_HEIGHT)?;
 
         let mut node_visits
             = 1;
@@ -983,13 +1234,13 @@ impl<const FAN_OUT: usize,
         let mut path
             = VecDeque::new();
 
-        path.push_back((current_block, current_guard));
+        path.push_back((INIT_TREE_HEIGHT, current_block, current_guard));
 
         let mut results
             = Vec::new();
 
         while !path.is_empty() {
-            let (n_current_block, n_current_guard)
+            let (curr_level, n_current_block, n_current_guard)
                 = path.pop_front().unwrap();
 
             current_guard = n_current_guard;
@@ -1011,15 +1262,153 @@ impl<const FAN_OUT: usize,
                     };
 
                     node_visits += last_pos - first_pos + 1;
-                    path.extend(index_page.children()
-                        .get_unchecked(first_pos..=last_pos)
-                        .iter()
-                        .map(|child| (child.clone(), self.lock_reader(child))));
+                    for child in index_page.children().get_unchecked(first_pos..=last_pos) {
+                        path.push_back((curr_level + 1, child.clone(), self.lock_reader(child, curr_level + 1)?));
+                    }
                 }
                 _ => results.push((current_block, current_guard)),
             }
         }
 
-        (node_visits, results)
+        Ok((node_visits, results))
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + Display + 'static,
+    Payload: Default + Clone + Sync + Display + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Descends to the leaf covering the key under the tree's locking strategy
+    /// and hands the leaf with its fence to the reader. Latches are coupled on the way down,
+    /// hence at most the leaf's latch and its parent's are held at a time. Optimistic reads
+    /// are repeated until the leaf validates, so `read` must not have side effects.
+    /// Fails with the ID of a frame that could not be faulted in, see `BufferPool`.
+    pub(crate) fn read_leaf<R>(&self,
+                               key: Key,
+                               read: impl Fn(&Node<FAN_OUT, NUM_RECORDS, Key, Payload>, &Interval<Key>) -> R)
+                               -> Result<R, BlockID>
+    {
+        self.read_leaf_with(key, 0, key, read).map(|(.., result)| result)
+    }
+
+    /// Reads the leaf covering the key like `read_leaf`, and prefetches up to `prefetch` children
+    /// right of the path at each level, as far as they cover keys up to `prefetch_until`.
+    /// Hence, the traversals following for greater keys find their nodes cached.
+    /// Copy-on-write trees do not prefetch.
+    pub(crate) fn read_leaf_with<R>(&self,
+                                    key: Key,
+                                    prefetch: usize,
+                                    prefetch_until: Key,
+                                    read: impl Fn(&Node<FAN_OUT, NUM_RECORDS, Key, Payload>, &Interval<Key>) -> R)
+                                    -> Result<(NodeVisits, R), BlockID>
+    {
+        if self.locking_strategy.is_copy_on_write() {
+            return self.read_leaf_cow(key, |leaf, fence| read(leaf.as_ref(), fence));
+        }
+
+        let _epoch
+            = self.locking_strategy.is_optimistic().then(epoch::pin);
+
+        let mut attempt = 0;
+        let mut node_visits = 0;
+        let mut curr_level = INIT_TREE_HEIGHT;
+
+        let search_key
+            = (self.inc_key)(key);
+
+        let prefetch_key
+            = (self.inc_key)(prefetch_until);
+
+        'restart: loop {
+            if attempt > 0 {
+                self.record_restart(curr_level);
+                sched_yield(attempt);
+            }
+
+            attempt += 1;
+            curr_level = INIT_TREE_HEIGHT;
+
+            let mut fence
+                = Interval::new(self.min_key, self.max_key);
+
+            let mut current_block
+                = self.root.block();
+
+            let mut current_guard
+                = self.lock_reader(&current_block, INIT_TREE_HEIGHT)?;
+
+            loop {
+                node_visits += 1;
+
+                let current
+                    = unsafe { current_guard.deref_unsafe() };
+
+                let (read_ok, current_reader_version)
+                    = current_guard.is_read_not_obsolete_result();
+
+                if current.is_none() || !read_ok {
+                    continue 'restart;
+                }
+
+                match current.unwrap().as_ref() {
+                    leaf @ Node::Leaf(..) => {
+                        let result
+                            = read(leaf, &fence);
+
+                        if !current_guard.is_valid() {
+                            continue 'restart;
+                        }
+
+                        break 'restart Ok((node_visits, result))
+                    }
+                    Node::Index(index_page) => unsafe {
+                        let keys
+                            = index_page.keys();
+
+                        let pos = match keys.binary_search(&search_key) {
+                            Ok(pos) | Err(pos) => pos
+                        };
+
+                        let next_fence = Interval::new(
+                            pos.checked_sub(1)
+                                .and_then(|lower| keys.get(lower).cloned())
+                                .unwrap_or(fence.lower()),
+                            keys.get(pos)
+                                .map(|upper| (self.dec_key)(*upper))
+                                .unwrap_or(fence.upper()));
+
+                        if prefetch > 0 && prefetch_key > search_key {
+                            let last = match keys.binary_search(&prefetch_key) {
+                                Ok(last) | Err(last) => last
+                            };
+
+                            (pos + 1..=last.min(pos + prefetch).min(keys.len()))
+                                .for_each(|next| index_page.get_child_result(next).assume_init_ref().prefetch());
+                        }
+
+                        let next_node
+                            = index_page.get_child_result(pos);
+
+                        let (read_ok, read_version)
+                            = current_guard.is_read_not_obsolete_result();
+
+                        if !read_ok || read_version != current_reader_version {
+                            continue 'restart;
+                        }
+
+                        fence = next_fence;
+                        curr_level += 1;
+
+                        let next_block
+                            = next_node.assume_init_ref().clone();
+
+                        current_guard = self.lock_reader(&next_block, curr_level)?;
+                        current_block = next_block;
+                    }
+                }
+            }
+        }
     }
-}
\ No newline at end of file
+}
@@ -1,3 +1,6 @@
+// the crate and some strategy constructors are named after their acronyms
+#![allow(non_snake_case)]
+
 pub mod block;
 pub mod crud_model;
 pub mod locking;
@@ -42,26 +45,30 @@ impl Deref for BTreeApiExport {
     }
 }
 
-use std::ffi::{c_int, c_void, CString};
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
 use std::{mem, ptr};
 use std::ops::Deref;
+#[cfg(target_os = "linux")]
+use std::path::Path;
 use crate::crud_model::crud_operation::CRUDOperation;
 use crate::crud_model::crud_operation_result::CRUDOperationResult;
 use crate::crud_model::crud_api::CRUDDispatcher;
-use crate::locking::locking_strategy::{hybrid_lock_attempts, LHL_read_write, LockingStrategy, orwc, orwc_attempts};
+use crate::locking::locking_strategy::{copy_on_write, hybrid_lock_attempts, LHL_read_write, LockingStrategy, optiql, orwc, orwc_attempts, ticket_orwc_attempts};
 use crate::record_model::record_point::RecordPoint;
 use crate::test::{INDEX, MAKE_INDEX};
+#[cfg(target_os = "linux")]
+use crate::test::{dec_key, inc_key, Key};
 use crate::utils::interval::Interval;
 
 impl BTreeApiExport {
     #[inline(always)]
     fn find(&self, key: *const u8, _sz: usize, value_out: *mut u8) -> bool {
         match self.dispatch(CRUDOperation::Point(
-            unsafe { ptr::read(mem::transmute(key)) }))
+            unsafe { ptr::read(key.cast()) }))
         {
             (.., CRUDOperationResult::MatchedRecord(Some(result)))
              => unsafe {
-                ptr::write(mem::transmute(value_out), result.payload);
+                ptr::write(value_out.cast(), result.payload);
                 true
             },
             _ => false
@@ -71,13 +78,13 @@ impl BTreeApiExport {
     #[inline(always)]
     fn insert(&self, key: *const u8, _key_sz: usize, value: *const u8, _value_sz: usize) -> bool {
         match self.dispatch(CRUDOperation::Insert(
-            unsafe { ptr::read(mem::transmute(key)) },
-            unsafe { ptr::read(mem::transmute(value)) }))
+            unsafe { ptr::read(key.cast()) },
+            unsafe { ptr::read(value.cast()) }))
         {
             (.., CRUDOperationResult::Inserted(..)) => true,
             _ => {
-                // let (key_n, value_n): (u64, u64)  = (unsafe { ptr::read(mem::transmute(key)) },
-                //                                      unsafe { ptr::read(mem::transmute(value)) });
+                // let (key_n, value_n): (u64, u64)  = (unsafe { ptr::read(key.cast()) },
+                //                                      unsafe { ptr::read(value.cast()) });
                 // print!("Locking Strategy: {}", self.locking_strategy());
                 // 
                 // println!("{e}: Key: {key_n}, value: {value_n}");
@@ -99,27 +106,21 @@ impl BTreeApiExport {
 
     #[inline(always)]
     fn update(&self, key: *const u8, _key_sz: usize, value: *const u8, _value_sz: usize) -> bool {
-        match self.dispatch(CRUDOperation::Update(
-            unsafe { ptr::read(mem::transmute(key)) },
-            unsafe { ptr::read(mem::transmute(value)) }))
-        {
-            (.., CRUDOperationResult::Updated(..)) => true,
-            _ => false
-        }
+        matches!(self.dispatch(CRUDOperation::Update(
+            unsafe { ptr::read(key.cast()) },
+            unsafe { ptr::read(value.cast()) })),
+            (.., CRUDOperationResult::Updated(..)))
     }
 
     #[inline(always)]
     fn remove(&self, key: *const u8, _key_sz: usize) -> bool {
-        match self.dispatch(CRUDOperation::Delete(
-            unsafe { ptr::read(mem::transmute(key)) }))
-        {
-            (.., CRUDOperationResult::Deleted(..)) => true,
-            _ => false
-        }
+        matches!(self.dispatch(CRUDOperation::Delete(
+            unsafe { ptr::read(key.cast()) })),
+            (.., CRUDOperationResult::Deleted(..)))
     }
 
     #[inline(always)]
-    fn scan(&self, key: *const u8, _key_sz: usize, mut scan_sz: i32, mut values_out: *mut *mut u8) -> i32 {
+    fn scan(&self, key: *const u8, _key_sz: usize, scan_sz: i32, values_out: *mut *mut u8) -> i32 {
         let mut result
             = Vec::<*mut RecordPoint<u64, f64>>::with_capacity(scan_sz as _);
 
@@ -127,7 +128,7 @@ impl BTreeApiExport {
         let key_end = key_start + scan_sz as u64 - 1;
 
         match self.dispatch(CRUDOperation::Range(Interval::new(key_start, key_end))) {
-            (.., CRUDOperationResult::MatchedRecords(mut buff)) if !buff.is_empty() => unsafe {
+            (.., CRUDOperationResult::MatchedRecords(mut buff)) if !buff.is_empty() => {
                 buff.shrink_to_fit();
 
                 buff.iter()
@@ -157,22 +158,83 @@ pub const LHL: c_int = 2;
 pub const MONO: c_int = 3;
 pub const HL: c_int = 4;
 pub const LC: c_int = 5;
+pub const TORWC: c_int = 6;
+pub const OPTIQL: c_int = 7;
+pub const COW: c_int = 8;
 
-#[no_mangle]
-pub extern "C" fn init_tree(p: c_int, e1: c_int, e2: c_int) -> *mut c_void {
-    let lp = match p {
+fn locking_strategy_of(p: c_int, e1: c_int, e2: c_int) -> LockingStrategy {
+    match p {
         ORWC => orwc_attempts(e1 as _),
         OLC => LockingStrategy::OLC,
         LHL => LHL_read_write(e1 as _, e2 as _),
         MONO => LockingStrategy::MonoWriter,
         HL => hybrid_lock_attempts(e1 as _),
         LC => LockingStrategy::LockCoupling,
+        TORWC => ticket_orwc_attempts(e1 as _),
+        OPTIQL => optiql(),
+        COW => copy_on_write(),
         _ => orwc(),
-    };
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn init_tree(p: c_int, e1: c_int, e2: c_int) -> *mut c_void {
+    let lp = locking_strategy_of(p, e1, e2);
     
     Box::into_raw(Box::new(BTreeApiExport(MAKE_INDEX(lp)))) as _
 }
 
+/// Reopens the block pool at `pool_path`, or creates it with `pool_size` bytes.
+/// Either way, the tree is latched by the locking strategy of `p`, `e1` and `e2`, see `init_tree`.
+/// Returns null, if the pool can't be opened.
+/// Mutations become durable with `tree_api_checkpoint_pool`, a crash reopens the pool
+/// to the last complete checkpoint, see `BlockPool`.
+///
+/// # Safety
+/// `pool_path` must point to a nul-terminated string.
+#[cfg(target_os = "linux")]
+#[no_mangle]
+pub unsafe extern "C" fn init_tree_pooled(p: c_int,
+                                          e1: c_int,
+                                          e2: c_int,
+                                          pool_path: *const c_char,
+                                          pool_size: usize) -> *mut c_void
+{
+    let pool_path = match unsafe { CStr::from_ptr(pool_path) }.to_str() {
+        Ok(pool_path) => pool_path,
+        Err(..) => return ptr::null_mut()
+    };
+
+    let locking_strategy
+        = locking_strategy_of(p, e1, e2);
+
+    let index = match Path::new(pool_path).exists() {
+        true => INDEX::open_pooled_with(pool_path, locking_strategy, inc_key, dec_key),
+        false => INDEX::create_pooled(pool_path,
+                                      pool_size,
+                                      locking_strategy,
+                                      Key::MIN,
+                                      Key::MAX,
+                                      inc_key,
+                                      dec_key)
+    };
+
+    match index {
+        Ok(index) => Box::into_raw(Box::new(BTreeApiExport(index))) as _,
+        Err(..) => ptr::null_mut()
+    }
+}
+
+/// Checkpoints the tree to its block pool, the tree must be quiescent.
+#[cfg(target_os = "linux")]
+#[no_mangle]
+pub extern "C" fn tree_api_checkpoint_pool(
+    api: *mut c_void) -> bool
+{
+    let api = unsafe { &*(api as *mut BTreeApiExport) };
+    api.checkpoint_pool().is_ok()
+}
+
 #[no_mangle]
 pub extern "C" fn destroy_tree_api(
     api: *mut c_void)
@@ -8,6 +8,16 @@ pub const fn OLC() -> LockingStrategy {
     LockingStrategy::OLC
 }
 
+#[inline(always)]
+pub const fn optiql() -> LockingStrategy {
+    LockingStrategy::OptiQL
+}
+
+#[inline(always)]
+pub const fn copy_on_write() -> LockingStrategy {
+    LockingStrategy::CopyOnWrite
+}
+
 #[inline(always)]
 pub const fn hybrid_lock() -> LockingStrategy {
     hybrid_lock_attempts(1)
@@ -73,6 +83,16 @@ pub const fn orwc_attempts(attempts: Attempts) -> LockingStrategy {
     LockingStrategy::ORWC { write_level: 1f32, write_attempt: attempts }
 }
 
+#[inline(always)]
+pub const fn ticket_orwc() -> LockingStrategy {
+    ticket_orwc_attempts(4)
+}
+
+#[inline(always)]
+pub const fn ticket_orwc_attempts(attempts: Attempts) -> LockingStrategy {
+    LockingStrategy::TicketORWC { write_level: 1f32, write_attempt: attempts }
+}
+
 pub trait LevelExtras {
     fn is_lock(&self, height: Height, lock_from: f32) -> bool;
 }
@@ -103,6 +123,12 @@ pub enum LockingStrategy {
     HybridLocking {
         read_attempt: Attempts,
     },
+    TicketORWC {
+        write_level: f32,
+        write_attempt: Attempts,
+    },
+    OptiQL,
+    CopyOnWrite,
 }
 
 pub type CRUDProtocol = LockingStrategy;
@@ -134,6 +160,10 @@ impl Display for LockingStrategy {
                         read_attempt, read_level),
             LockingStrategy::HybridLocking { read_attempt } =>
                 write!(f, "HL(Attempts={})", read_attempt),
+            LockingStrategy::TicketORWC { write_level, write_attempt } =>
+                write!(f, "TicketORWC(Attempts={};Level={}*height)", write_attempt, write_level),
+            LockingStrategy::OptiQL => write!(f, "OptiQL"),
+            LockingStrategy::CopyOnWrite => write!(f, "CopyOnWrite"),
         }
     }
 }
@@ -161,59 +191,44 @@ impl LockingStrategy {
             LockingStrategy::LightweightHybridLock { .. } =>
                 LatchType::LightWeightHybrid,
             LockingStrategy::HybridLocking { .. } =>
-                LatchType::Hybrid
+                LatchType::Hybrid,
+            LockingStrategy::TicketORWC { .. } =>
+                LatchType::Ticket,
+            LockingStrategy::OptiQL =>
+                LatchType::OptimisticQueue,
+            LockingStrategy::CopyOnWrite =>
+                LatchType::LightWeightHybrid
         }
     }
 
     #[inline(always)]
     pub(crate) const fn is_optimistic(&self) -> bool {
-        match self {
-            Self::OLC => true,
-            Self::HybridLocking { .. } => true,
-            Self::LightweightHybridLock { .. } => true,
-            _ => false
-        }
+        matches!(self,
+            Self::OLC |
+            Self::OptiQL |
+            Self::CopyOnWrite |
+            Self::HybridLocking { .. } |
+            Self::LightweightHybridLock { .. })
     }
 
     #[inline(always)]
     pub(crate) const fn is_mono_writer(&self) -> bool {
-        match self {
-            Self::MonoWriter => true,
-            _ => false
-        }
+        matches!(self, Self::MonoWriter)
     }
 
     #[inline(always)]
     pub(crate) const fn is_orwc(&self) -> bool {
-        match self {
-            Self::ORWC { .. } => true,
-            _ => false
-        }
+        matches!(self, Self::ORWC { .. } | Self::TicketORWC { .. })
     }
 
     #[inline(always)]
-    pub(crate) const fn is_hybrid_lock(&self) -> bool {
-        match self {
-            Self::HybridLocking { .. } => true,
-            _ => false
-        }
-    }
-
-    #[inline(always)]
-    pub(crate) const fn is_lightweight_hybrid_lock(&self) -> bool {
-        match self {
-            Self::LightweightHybridLock { .. } => true,
-            _ => false
-        }
+    pub(crate) const fn is_copy_on_write(&self) -> bool {
+        matches!(self, Self::CopyOnWrite)
     }
 
     #[inline(always)]
     pub const fn additional_lock_required(&self) -> bool {
-        match self {
-            Self::MonoWriter => false,
-            Self::LockCoupling => false,
-            _ => true,
-        }
+        !matches!(self, Self::MonoWriter | Self::LockCoupling)
     }
 
     // #[inline(always)]
@@ -1 +1,2 @@
-pub mod locking_strategy;
\ No newline at end of file
+pub mod locking_strategy;
+pub mod strategy_override;
\ No newline at end of file
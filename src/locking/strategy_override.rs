@@ -0,0 +1,86 @@
+use std::cell::Cell;
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+use serde::{Deserialize, Serialize};
+use crate::locking::locking_strategy::LockingStrategy;
+
+/// Per-operation deviation from the tree's locking strategy.
+/// Overrides only change which latch mode is requested, blocks keep the tree's latch type.
+/// Hence, not every strategy supports every override, see `is_supported_by`.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StrategyOverride {
+    #[default]
+    Inherit,
+    /// Latches every node on the write path from the root on, i.e. never restarts optimistically.
+    /// Lightweight hybrid latches are pinned, readers-writer latches taken exclusively and
+    /// optimistic write latches, e.g. of `OLC`, held exclusively once their holders released them.
+    /// Pessimistic strategies latch so anyway.
+    PessimisticWrite,
+    /// Reads stay optimistic regardless of attempts or level, i.e. never fall back to
+    /// shared or pinned latches. Only supported by `LightweightHybridLock` and `HybridLocking`,
+    /// optimistic strategies read so anyway and pessimistic ones cannot.
+    OptimisticRead,
+}
+
+impl Display for StrategyOverride {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StrategyOverride::Inherit => write!(f, "Inherit"),
+            StrategyOverride::PessimisticWrite => write!(f, "PessimisticWrite"),
+            StrategyOverride::OptimisticRead => write!(f, "OptimisticRead"),
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<StrategyOverride> = const { Cell::new(StrategyOverride::Inherit) };
+}
+
+/// Keeps an override active on the current thread, the previous one is restored on drop.
+pub struct OverrideGuard {
+    previous: StrategyOverride,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for OverrideGuard {
+    #[inline(always)]
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.set(self.previous));
+    }
+}
+
+/// Activates the override for the current thread, see `OverrideGuard`.
+#[inline(always)]
+pub fn scoped(strategy_override: StrategyOverride) -> OverrideGuard {
+    OverrideGuard {
+        previous: CURRENT.with(|current| current.replace(strategy_override)),
+        _not_send: PhantomData,
+    }
+}
+
+#[inline(always)]
+pub fn current() -> StrategyOverride {
+    CURRENT.with(Cell::get)
+}
+
+impl StrategyOverride {
+    #[inline(always)]
+    pub const fn is_pessimistic_write(&self) -> bool {
+        matches!(self, Self::PessimisticWrite)
+    }
+
+    #[inline(always)]
+    pub const fn is_optimistic_read(&self) -> bool {
+        matches!(self, Self::OptimisticRead)
+    }
+
+    /// Whether trees latched by `locking_strategy` honor the override, see `CRUDDispatcher::dispatch_with`.
+    pub const fn is_supported_by(&self, locking_strategy: &LockingStrategy) -> bool {
+        match self {
+            Self::Inherit |
+            Self::PessimisticWrite => true,
+            Self::OptimisticRead => matches!(locking_strategy,
+                LockingStrategy::LightweightHybridLock { .. } | LockingStrategy::HybridLocking { .. })
+        }
+    }
+}
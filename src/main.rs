@@ -1,16 +1,10 @@
-use std::{env, fs};
-use std::sync::Arc;
+// the binary re-declares the library's modules but only drives the paper tests, hence their
+// public API is unused and exempt from the lints clippy only applies to private items
+#![allow(dead_code, non_snake_case, clippy::upper_case_acronyms, clippy::enum_variant_names)]
+
+use std::fs;
 use chrono::{DateTime, Local};
-use parking_lot::RwLock;
-use crate::tree::bplus_tree;
-use crate::crud_model::crud_api::{CRUDDispatcher, NodeVisits};
-use crate::crud_model::crud_operation::CRUDOperation;
-use crate::crud_model::crud_operation_result::CRUDOperationResult;
-use crate::locking::locking_strategy::{CRUDProtocol, orwc};
-use crate::locking::locking_strategy::LockingStrategy::*;
-use crate::test::{dec_key, inc_key, INDEX, Key, MAKE_INDEX, Payload, start_paper_tests};
-use crate::tree::bplus_tree::BPlusTree;
-use crate::utils::interval::Interval;
+use crate::test::start_paper_tests;
 use crate::utils::smart_cell::ENABLE_YIELD;
 
 mod block;
@@ -205,7 +199,7 @@ fn make_splash() {
 
 
 pub fn hle() -> &'static str {
-    if cfg!(feature = "hardware-lock-elision") {
+    if cfg!(feature = "olc-hle") {
         if cfg!(any(target_arch = "x86", target_arch = "x86_64")) {
             "ON    "
         } else {
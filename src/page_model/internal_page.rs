@@ -1,9 +1,10 @@
 use std::hash::Hash;
 use std::marker::PhantomData;
-use std::{mem, ptr};
+use std::mem;
 use std::cell::Cell;
 use std::mem::MaybeUninit;
-use std::ptr::null_mut;
+use std::sync::atomic::AtomicPtr;
+use std::sync::atomic::Ordering::AcqRel;
 use crate::page_model::{BlockRef, ObjectCount};
 use crate::utils::shadow_vec::ShadowVec;
 
@@ -33,6 +34,17 @@ impl<const FAN_OUT: usize,
     }
 }
 
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash,
+    Payload: Default + Clone
+> Default for InternalPage<FAN_OUT, NUM_RECORDS, Key, Payload> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<const FAN_OUT: usize,
     const NUM_RECORDS: usize,
     Key: Default + Ord + Copy + Hash,
@@ -96,6 +108,8 @@ impl<const FAN_OUT: usize,
         }
     }
 
+    /// # Safety
+    /// The index must be within the children's length.
     #[inline(always)]
     pub unsafe fn get_child_unsafe(&self, index: usize) -> &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload> {
         self.children_array
@@ -103,6 +117,34 @@ impl<const FAN_OUT: usize,
             .assume_init_ref()
     }
 
+    /// Atomically swaps the child at index, concurrent optimistic readers either observe
+    /// the old or the new child. Returns the unlinked child.
+    ///
+    /// # Safety
+    /// The index must be within the children's length and the caller must exclude
+    /// concurrent writers of this page, e.g. by a pin.
+    #[inline(always)]
+    pub(crate) unsafe fn publish_child(&self, index: usize, child: BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>)
+                                       -> BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>
+    {
+        let slot
+            = self.children_array.as_ptr().add(index) as *mut *mut ();
+
+        debug_assert!(slot.is_aligned());
+
+        let child_raw: *mut ()
+            = mem::transmute_copy(&child);
+
+        mem::forget(child);
+
+        let old_raw = AtomicPtr::from_ptr(slot)
+            .swap(child_raw, AcqRel);
+
+        mem::transmute_copy(&old_raw)
+    }
+
+    /// # Safety
+    /// The index must be within the children's length.
     #[inline(always)]
     pub unsafe fn get_child_unsafe_cloned(&self, index: usize) -> BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload> {
         self.get_child_unsafe(index)
@@ -2,7 +2,7 @@ use std::cell::Cell;
 use std::hash::Hash;
 use std::marker::PhantomData;
 use std::mem;
-use std::mem::{ManuallyDrop, MaybeUninit};
+use std::mem::MaybeUninit;
 use crate::page_model::ObjectCount;
 use crate::record_model::record_point::RecordPoint;
 use crate::utils::safe_cell::SafeCell;
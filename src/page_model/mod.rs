@@ -1,12 +1,13 @@
 // use std::fmt::{Display, Formatter};
 use std::hash::Hash;
-use std::sync::Arc;
-// use std::sync::atomic::{AtomicU32, AtomicU64};
+use std::sync::atomic::AtomicU32;
 use parking_lot::lock_api::{Mutex, RwLock};
 use crate::block::block::Block;
 // use serde::{Deserialize, Serialize};
 use crate::utils::safe_cell::SafeCell;
-use crate::utils::smart_cell::{OptCell, SmartCell, SmartFlavor};
+use crate::utils::smart_cell::{LatchType, OptCell, SmartCell, SmartFlavor};
+use crate::utils::ticket_lock::TicketRwLock;
+use crate::utils::mcs_lock::McsLock;
 
 pub mod internal_page;
 pub mod leaf_page;
@@ -14,7 +15,7 @@ pub mod node;
 
 pub type ObjectCount = u16;
 pub type BlockID = u32;
-// pub type AtomicBlockID = AtomicU32;
+pub type AtomicBlockID = AtomicU32;
 pub type Level = u16;
 pub type Height = Level;
 pub type Attempts = u32;
@@ -31,43 +32,72 @@ impl<const FAN_OUT: usize,
     Key: Default + Ord + Hash + Copy,
     Payload: Default + Clone
 > Block<FAN_OUT, NUM_RECORDS, Key, Payload> {
+    /// Wraps the block into the latch flavor, which `BlockManager::new_cell` moves into a slab slot.
+    #[inline(always)]
+    pub fn into_flavor(self, latch: LatchType) -> SmartFlavor<Block<FAN_OUT, NUM_RECORDS, Key, Payload>> {
+        match latch {
+            LatchType::Exclusive => SmartFlavor::ExclusiveCell(
+                Mutex::new(()),
+                SafeCell::new(self)),
+            LatchType::ReadersWriter => SmartFlavor::ReadersWriterCell(
+                RwLock::new(()),
+                SafeCell::new(self)),
+            LatchType::Optimistic => SmartFlavor::OLCCell(
+                OptCell::new(self)),
+            LatchType::Hybrid => SmartFlavor::HybridCell(
+                OptCell::new(self),
+                RwLock::new(())),
+            LatchType::Ticket => SmartFlavor::TicketCell(
+                TicketRwLock::new(),
+                SafeCell::new(self)),
+            LatchType::OptimisticQueue => SmartFlavor::OptiQLCell(
+                OptCell::new(self),
+                McsLock::new()),
+            LatchType::None => SmartFlavor::FreeCell(
+                SafeCell::new(self)),
+            LatchType::LightWeightHybrid => SmartFlavor::LightWeightHybridCell(
+                OptCell::new(self))
+        }
+    }
+
     #[inline(always)]
     pub fn into_rw(self) -> SmartCell<Block<FAN_OUT, NUM_RECORDS, Key, Payload>> {
-        SmartCell(Arc::new(SmartFlavor::ReadersWriterCell(
-            RwLock::new(()),
-            SafeCell::new(self))))
+        self.into_cell(LatchType::ReadersWriter)
     }
 
     #[inline(always)]
     pub fn into_free(self) -> SmartCell<Block<FAN_OUT, NUM_RECORDS, Key, Payload>> {
-        SmartCell(Arc::new(SmartFlavor::FreeCell(
-            SafeCell::new(self))))
+        self.into_cell(LatchType::None)
     }
 
     #[inline(always)]
     pub fn into_olc(self) -> SmartCell<Block<FAN_OUT, NUM_RECORDS, Key, Payload>> {
-        SmartCell(Arc::new(SmartFlavor::OLCCell(
-            OptCell::new(self))))
+        self.into_cell(LatchType::Optimistic)
     }
 
     #[inline(always)]
     pub fn into_lightweight_hybrid(self) -> SmartCell<Block<FAN_OUT, NUM_RECORDS, Key, Payload>> {
-        SmartCell(Arc::new(SmartFlavor::LightWeightHybridCell(
-            OptCell::new(self))))
+        self.into_cell(LatchType::LightWeightHybrid)
     }
 
     #[inline(always)]
     pub fn into_exclusive(self) -> SmartCell<Block<FAN_OUT, NUM_RECORDS, Key, Payload>> {
-        SmartCell(Arc::new(SmartFlavor::ExclusiveCell(
-            Mutex::new(()),
-            SafeCell::new(self))))
+        self.into_cell(LatchType::Exclusive)
+    }
+
+    #[inline(always)]
+    pub fn into_ticket(self) -> SmartCell<Block<FAN_OUT, NUM_RECORDS, Key, Payload>> {
+        self.into_cell(LatchType::Ticket)
+    }
+
+    #[inline(always)]
+    pub fn into_optiql(self) -> SmartCell<Block<FAN_OUT, NUM_RECORDS, Key, Payload>> {
+        self.into_cell(LatchType::OptimisticQueue)
     }
 
     #[inline(always)]
     pub fn into_hybrid(self) -> SmartCell<Block<FAN_OUT, NUM_RECORDS, Key, Payload>> {
-        SmartCell(Arc::new(SmartFlavor::HybridCell(
-            OptCell::new(self),
-            RwLock::new(()))))
+        self.into_cell(LatchType::Hybrid)
     }
 }
 
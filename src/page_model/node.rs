@@ -1,9 +1,8 @@
-use std::fmt::{Debug, Display, Formatter};
+use std::fmt::{Display, Formatter};
 use std::hash::Hash;
 use std::mem;
 use itertools::Itertools;
 use crate::utils::shadow_vec::ShadowVec;
-use serde::{Serialize, Deserialize};
 use crate::page_model::BlockRef;
 use crate::page_model::internal_page::InternalPage;
 use crate::page_model::leaf_page::LeafPage;
@@ -40,7 +39,7 @@ impl<const FAN_OUT: usize,
                            .join(","),
                        index_page.children()
                            .iter()
-                           .map(|c| format!("{}", c.unsafe_borrow().to_string()))
+                           .map(|c| format!("#{}", c.unsafe_borrow().block_id()))
                            .join(",")),
             Node::Leaf(records) => write!(f, "Leaf({})", records
                 .as_records()
@@ -65,10 +64,7 @@ pub enum NodeUnsafeDegree {
 impl NodeUnsafeDegree {
     #[inline]
     pub const fn is_ok(&self) -> bool {
-        match self {
-            Self::Ok => true,
-            _ => false
-        }
+        matches!(self, Self::Ok)
     }
     
     #[inline(always)]
@@ -77,17 +73,11 @@ impl NodeUnsafeDegree {
     }
 
     pub const fn is_overflow(&self) -> bool {
-        match self {
-            Self::Overflow => true,
-            _ => false
-        }
+        matches!(self, Self::Overflow)
     }
 
     pub const fn is_underflow(&self) -> bool {
-        match self {
-            Self::Underflow => true,
-            _ => false
-        }
+        matches!(self, Self::Underflow)
     }
 }
 
@@ -103,6 +93,7 @@ impl<const FAN_OUT: usize,
         self.len() >= allocation
     }
 
+    /// Underflow under the default fill policy, trees apply their own, see `FillPolicy`.
     #[inline(always)]
     pub fn is_underflow(&self, allocation: usize) -> bool {
         // debug_assert!(allocation > 0 && allocation >= self.len());
@@ -110,6 +101,7 @@ impl<const FAN_OUT: usize,
         self.len() < allocation / 2
     }
 
+    /// Degree under the default fill policy, see `is_underflow`.
     #[inline(always)]
     pub fn unsafe_degree(&self, allocation: usize) -> NodeUnsafeDegree {
         let len = self.len();
@@ -125,10 +117,7 @@ impl<const FAN_OUT: usize,
 
     #[inline(always)]
     pub const fn is_leaf(&self) -> bool {
-        match self {
-            Node::Index(..) => false,
-            _ => true
-        }
+        !matches!(self, Node::Index(..))
     }
 
     #[inline(always)]
@@ -220,12 +209,10 @@ impl<const FAN_OUT: usize,
             Node::Leaf(events_page) => events_page
                 .as_records()
                 .binary_search_by_key(&key, |e| e.key)
-                .map(|found| unsafe {
-                    mem::replace(events_page
+                .map(|found| mem::replace(events_page
                     .as_records_mut()
                     .get_unchecked_mut(found)
-                    .payload_mut(), payload)
-                })
+                    .payload_mut(), payload))
                 .ok(),
             _ => None
         }
@@ -305,6 +292,11 @@ impl<const FAN_OUT: usize,
             // Node::MultiVersionLeaf(record_lists_page) => record_lists_page.len()
         }
     }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 impl<const FAN_OUT: usize,
@@ -313,7 +305,7 @@ impl<const FAN_OUT: usize,
     Payload: Default + Clone
 > AsRef<Node<FAN_OUT, NUM_RECORDS, Key, Payload>> for Node<FAN_OUT, NUM_RECORDS, Key, Payload> {
     fn as_ref(&self) -> &Node<FAN_OUT, NUM_RECORDS, Key, Payload> {
-        &self
+        self
     }
 }
 
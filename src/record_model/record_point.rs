@@ -1,7 +1,6 @@
 use std::hash::Hash;
 use std::mem;
 use std::fmt::{Display, Formatter};
-use std::ptr::{addr_of, addr_of_mut};
 use crate::record_model::unsafe_clone::UnsafeClone;
 
 #[derive(Default)]
@@ -1,3 +1,8 @@
 pub trait UnsafeClone {
+    /// Bitwise copy, which may be taken while the original is written concurrently.
+    ///
+    /// # Safety
+    /// The copy aliases any memory owned by the original, e.g. of payloads allocated on the heap,
+    /// and is torn unless validated after copying.
     unsafe fn unsafe_clone(&self) -> Self;
 }
\ No newline at end of file
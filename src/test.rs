@@ -14,6 +14,7 @@ use crate::crud_model::crud_api::{CRUDDispatcher, NodeVisits};
 use crate::locking::locking_strategy::{CRUDProtocol, LockingStrategy, OLC, orwc_attempts};
 use crate::crud_model::crud_operation::CRUDOperation;
 use crate::crud_model::crud_operation_result::CRUDOperationResult;
+use crate::locking::strategy_override::StrategyOverride;
 use crate::locking::locking_strategy::LockingStrategy::MonoWriter;
 use crate::page_model::node::Node;
 
@@ -78,6 +79,11 @@ impl CRUDDispatcher<Key, Payload> for TreeDispatcher {
             }
         }
     }
+
+    #[inline(always)]
+    fn supports(&self, strategy_override: StrategyOverride) -> bool {
+        self.as_index().supports(strategy_override)
+    }
 }
 
 // unsafe impl Send for TreeDispatcher {}
@@ -144,6 +150,61 @@ pub fn bulk_crud(worker_threads: usize, tree: Tree, operations_queue: &[CRUDOper
     (time_elapsed.as_millis(), dups, node_visits)
 }
 
+/// Inserts the operations' inserts as a single batch by `BPlusTree::insert_batch`, reports like `bulk_crud`,
+/// i.e. the time elapsed, the inserts failed and the node visits.
+pub fn batch_crud(worker_threads: usize, tree: Tree, operations_queue: &[CRUDOperation<Key, Payload>]) -> (u128, u64, NodeVisits) {
+    let records = operations_queue
+        .iter()
+        .filter_map(|operation| match operation {
+            CRUDOperation::Insert(key, payload) => Some((*key, *payload)),
+            _ => None
+        })
+        .collect::<Vec<_>>();
+
+    let start = SystemTime::now();
+
+    let result = tree
+        .as_index()
+        .insert_batch(records, worker_threads);
+
+    let time_elapsed
+        = SystemTime::now().duration_since(start).unwrap();
+
+    (time_elapsed.as_millis(), (result.duplicates.len() + result.rejected.len()) as u64, result.node_visits)
+}
+
+/// Creates the tree from the operations' inserts by `BPlusTree::bulk_load`, reports like `bulk_crud`,
+/// i.e. the time elapsed, the inserts dropped as duplicates and no node visits.
+pub fn bulk_load(protocol: CRUDProtocol, operations_queue: &[CRUDOperation<Key, Payload>]) -> (Tree, u128, u64, NodeVisits) {
+    let records = operations_queue
+        .iter()
+        .filter_map(|operation| match operation {
+            CRUDOperation::Insert(key, payload) => Some((*key, *payload)),
+            _ => None
+        })
+        .collect::<Vec<_>>();
+
+    let start = SystemTime::now();
+
+    let index = MAKE_INDEX(protocol.clone())
+        .bulk_load(records.iter().cloned());
+
+    let time_elapsed
+        = SystemTime::now().duration_since(start).unwrap();
+
+    let dups
+        = records.len() as u64 - index.stats().records as u64;
+
+    let tree = Arc::new(if let MonoWriter = protocol {
+        TreeDispatcher::Wrapper(RwLock::new(index))
+    }
+    else {
+        TreeDispatcher::Ref(index)
+    });
+
+    (tree, time_elapsed.as_millis(), dups, 0)
+}
+
 fn make_leaf_hits_map(tree: Tree) -> Vec<(Interval<Key>, usize)> {
     let retrieve_fence_right = |key: Key| {
         let mut fence_right = Key::MAX;
@@ -191,38 +252,35 @@ fn make_leaf_hits_map(tree: Tree) -> Vec<(Interval<Key>, usize)> {
     while !queue.is_empty() {
         let next = queue.pop_front().unwrap();
 
-        match next.as_ref() {
-            Node::Index(index_page) => unsafe {
+        if let Node::Index(index_page) = next { unsafe {
+            index_page
+                .children()
+                .iter()
+                .filter(|c| c.unsafe_borrow().is_directory())
+                .for_each(|child|
+                    queue.push_back(child.unsafe_borrow().as_ref()));
+
+            if index_page.get_child_unsafe(0).0.as_ref().is_leaf() {
+                let mut prev: Interval<Key> = Default::default();
                 index_page
-                    .children()
+                    .keys()
                     .iter()
-                    .filter(|c| c.unsafe_borrow().is_directory())
-                    .for_each(|child|
-                        queue.push_back(child.unsafe_borrow().as_ref()));
-
-                if index_page.get_child_unsafe(0).0.as_ref().is_leaf() {
-                    let mut prev: Interval<Key> = Default::default();
-                    index_page
-                        .keys()
-                        .iter()
-                        .chain([retrieve_fence_right(*index_page.keys().last().unwrap())].as_slice())
-                        .for_each(|k| {
-                            prev = (start, *k).into();
-                            map.push((prev.clone(), 0usize));
-                            start = *k + 1;
-                        });
-
-                    if queue.is_empty() {
-                        let (val, _)
-                            = map.last_mut().unwrap();
-
-                        // right most node we ignore father fence, since it must be max
-                        val.set_upper(Key::MAX);
-                    }
+                    .chain([retrieve_fence_right(*index_page.keys().last().unwrap())].as_slice())
+                    .for_each(|k| {
+                        prev = (start, *k).into();
+                        map.push((prev.clone(), 0usize));
+                        start = *k + 1;
+                    });
+
+                if queue.is_empty() {
+                    let (val, _)
+                        = map.last_mut().unwrap();
+
+                    // right most node we ignore father fence, since it must be max
+                    val.set_upper(Key::MAX);
                 }
             }
-            _ => {}
-        }
+        } }
     }
 
     map
@@ -235,6 +293,14 @@ pub fn start_paper_tests() {
     const RQ_ENABLED: bool
     = false;
 
+    // creates trees by bulk loading instead of concurrent inserts, see `bulk_load`
+    const BULK_LOAD: bool
+    = false;
+
+    // otherwise inserts them as a single batch instead of one by one, see `batch_crud`
+    const INSERT_BATCH: bool
+    = false;
+
     const N: u64
     = 10_000_000;
 
@@ -369,35 +435,34 @@ pub fn start_paper_tests() {
     for protocol in protocols {
         for lambda in 0..LAMBDAS.len() {
             for thread in THREADS {
-                let tree
-                    = TREE(protocol.clone());
+                let (tree, create_time, errs, create_node_visits) = if BULK_LOAD {
+                    bulk_load(protocol.clone(), data_lambdas[lambda].as_slice())
+                }
+                else {
+                    let tree
+                        = TREE(protocol.clone());
+
+                    let (create_time, errs, create_node_visits) = if INSERT_BATCH {
+                        batch_crud(thread,
+                                   tree.clone(),
+                                   data_lambdas[lambda].as_slice())
+                    }
+                    else {
+                        bulk_crud(thread,
+                                  tree.clone(),
+                                  data_lambdas[lambda].as_slice())
+                    };
 
-                // unsafe {
-                //     COUNTERS.0.store(0, SeqCst);
-                //     COUNTERS.1.store(0, SeqCst);
-                // }
+                    (tree, create_time, errs, create_node_visits)
+                };
 
                 // thread::sleep(Duration::from_millis(10));
 
-                // for _ in 0..5 {
-                let (create_time, errs, create_node_visits)
-                    = bulk_crud(thread,
-                                tree.clone(),
-                                data_lambdas[lambda].as_slice());
-                // }
-
                 // let (create_time, errs, create_node_visits)
                 //     = (0, 0, 0);
 
                 // thread::sleep(Duration::from_secs(1));
 
-                // unsafe {
-                //     let (pause, yields)
-                //         = (COUNTERS.0.load(SeqCst), COUNTERS.1.load(SeqCst));
-                // 
-                //     println!("{},{},{},{},{}", protocol, pause, yields, LAMBDAS[lambda], thread);
-                // }
-
                 for ut in UPDATES_THRESHOLD {
                     if RQ_ENABLED {
                         for rq in RQ_PROBABILITY {
@@ -441,11 +506,11 @@ pub fn start_paper_tests() {
 }
 
 pub fn format_insertions(i: usize) -> String {
-    if i % 1_000_000_000 == 0 {
+    if i.is_multiple_of(1_000_000_000) {
         format!("{} B", i as f64 / 1_000_000_000_f64)
-    } else if i % 1_000_000 == 0 {
+    } else if i.is_multiple_of(1_000_000) {
         format!("{} Mio", i as f64 / 1_000_000_f64)
-    } else if i % 1_000 == 0 {
+    } else if i.is_multiple_of(1_000) {
         format!("{} K", i as f64 / 1_000_f64)
     } else {
         i.to_string()
@@ -456,7 +521,7 @@ fn make_hist(lambda: f64, map: &mut Vec<(Interval<Key>, usize)>, n: u64, key_ran
     let stats_lambda_leaf_hits
         = format!("leaf_hits_lambda_{}.csv", lambda);
 
-    fs::remove_file(stats_lambda_leaf_hits.as_str());
+    let _ = fs::remove_file(stats_lambda_leaf_hits.as_str());
 
     // map.values_mut().for_each(|count| *count = 0);
 
@@ -476,7 +541,7 @@ fn make_hist(lambda: f64, map: &mut Vec<(Interval<Key>, usize)>, n: u64, key_ran
                 let (.., i)
                     = map.get_mut(pos).unwrap();
 
-                *i = *i + 1;
+                *i += 1;
             }
         }
     };
@@ -498,6 +563,7 @@ fn make_hist(lambda: f64, map: &mut Vec<(Interval<Key>, usize)>, n: u64, key_ran
     fs::write(stats_lambda_leaf_hits, s).unwrap();
 }
 
+#[allow(clippy::too_many_arguments)]
 fn mixed_test_new(
     create_node_visits: NodeVisits,
     create_time: u128,
@@ -593,7 +659,7 @@ fn mixed_test_new(
     for run in 1..=runs {
         let start = SystemTime::now();
         let node_visits = (0..threads)
-            .map(|which| (worker)(which))
+            .map(worker)
             .collect::<Vec<_>>()
             .into_iter()
             .map(|handle| handle.join().unwrap())
@@ -632,7 +698,7 @@ pub fn gen_rand_key(i: u64, range_start: u64, range_end: u64, lambda: f64, rnd:
     #[inline(always)]
     fn sample_next(lambda: f64, rnd: &mut StdRng) -> f64 {
         let num
-            = rnd.gen_range(0_f64..1_f64);
+            = rnd.random_range(0_f64..1_f64);
 
         (1_f64 - num)
             .ln()
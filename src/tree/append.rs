@@ -0,0 +1,123 @@
+use std::fmt::Display;
+use std::hash::Hash;
+use parking_lot::Mutex;
+use crate::block::block::BlockGuard;
+use crate::page_model::BlockRef;
+use crate::page_model::node::Node;
+use crate::record_model::Version;
+use crate::tree::bplus_tree::BPlusTree;
+use crate::utils::metrics::{self, Counter};
+use crate::utils::smart_cell::SmartGuard;
+
+/// The rightmost leaf of a tree set up for sequential inserts, see `BPlusTree::with_sequential_inserts`.
+/// The leaf is remembered with the version it has once its writer is released.
+pub(crate) struct AppendHint<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash,
+    Payload: Default + Clone
+> {
+    rightmost: Mutex<Option<(BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>, Version)>>,
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + Display + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Optimizes for ascending keys, e.g. time-ordered ones. Rightmost nodes split leaving their left
+    /// node full, since ascending inserts do not revisit it, see `is_append_target`.
+    /// Inserts greater than all keys of the rightmost leaf skip the traversal and latch the leaf directly,
+    /// as long as it validates, see `append`. Only trees whose writers release a known version, i.e.
+    /// `OLC`, `OptiQL` and `LightweightHybridLock`, take this fast path, and only if not paged.
+    /// `HybridLocking`, pessimistic and copy-on-write trees always traverse. Fast path hits are
+    /// counted as appends by the metrics.
+    pub fn with_sequential_inserts(mut self) -> Self {
+        self.append = Some(AppendHint {
+            rightmost: Mutex::new(None)
+        });
+
+        self
+    }
+
+    /// Inserts into the remembered rightmost leaf without traversal. The leaf is write latched
+    /// at its remembered version, which fails once the leaf was written, split or merged meanwhile.
+    /// Hence, the leaf is still the rightmost one and covers the key, if the key is greater than its last.
+    /// Appends queue up on the hint, since they latch the same leaf anyway. Returns `None`, if a traversal
+    /// is required, e.g. since the leaf changed or overflows.
+    #[inline]
+    pub(crate) fn append<R>(&self,
+                            key: Key,
+                            insert: impl FnOnce(&mut Node<FAN_OUT, NUM_RECORDS, Key, Payload>) -> R) -> Option<R>
+    {
+        let mut rightmost = self.append
+            .as_ref()?
+            .rightmost
+            .lock();
+
+        let (leaf, version)
+            = rightmost.as_ref()?;
+
+        let mut guard: BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>
+            = SmartGuard::OLCReader(Some((leaf.clone(), *version)));
+
+        if !guard.upgrade_write_lock() {
+            return None;
+        }
+
+        let leaf = guard
+            .deref_mut()
+            .unwrap();
+
+        let covered = !self.has_overflow(leaf) && leaf
+            .as_records()
+            .last()
+            .is_some_and(|last| key > last.key);
+
+        *rightmost = guard.release_version();
+
+        covered.then(|| {
+            metrics::record(Counter::Append);
+            insert(leaf)
+        })
+    }
+
+    /// Whether the child at `child_pos` of a parent ending at `fence_upper` is a rightmost node
+    /// of a tree set up for sequential inserts. Such nodes split leaving the right one nearly empty
+    /// and are not corrected above `append_min_len`, since ascending inserts refill them.
+    #[inline(always)]
+    pub(crate) fn is_append_target(&self, fence_upper: Key, child_pos: usize, parent_len: usize) -> bool {
+        self.append.is_some() && fence_upper == self.max_key && child_pos == parent_len
+    }
+
+    /// Entries a node appended to holds at least, i.e. one record or two keys, see `min_len`.
+    #[inline(always)]
+    pub(crate) const fn append_min_len(is_leaf: bool) -> usize {
+        match is_leaf {
+            true => 1,
+            false => 2
+        }
+    }
+
+    /// Remembers the write latched leaf as the rightmost one, if its fence ends at the tree's maximum.
+    #[inline(always)]
+    pub(crate) fn remember_rightmost(&self,
+                                     guard: &BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                                     fence_upper: Key)
+    {
+        let append = match &self.append {
+            Some(append) if fence_upper == self.max_key => append,
+            _ => return
+        };
+
+        // paged leaves are marked dirty when latched, see `latch_resident`
+        #[cfg(target_os = "linux")]
+        if self.buffer.is_some() {
+            return;
+        }
+
+        if let Some(rightmost) = guard.release_version() {
+            *append.rightmost.lock() = Some(rightmost);
+        }
+    }
+}
@@ -1,15 +1,35 @@
+use std::borrow::Cow;
 use std::hash::Hash;
 use std::mem;
+use std::sync::Arc;
+use parking_lot::Mutex;
 use crate::block::block_manager::BlockManager;
+#[cfg(target_os = "linux")]
+use crate::block::buffer_pool::BufferPool;
+use crate::tree::append::AppendHint;
+use crate::tree::fill_policy::FillPolicy;
+use crate::tree::memory::MemoryAccount;
+use crate::tree::observer::TreeObserver;
 use crate::tree::root::Root;
+use crate::tree::stats::TreeStats;
+use crate::tree::wal::WriteAheadLog;
 use crate::locking::locking_strategy::{LockingStrategy, LevelExtras};
-use crate::page_model::{Attempts, BlockRef, Height, Level, ObjectCount};
-use crate::block::block::{Block, BlockGuard};
+use crate::locking::strategy_override;
+use crate::page_model::{Attempts, BlockID, BlockRef, Height, Level, ObjectCount};
+use crate::page_model::node::Node;
+use crate::block::block::{Block, BlockGuard, FRAME_UNLINKED};
 use crate::test::{dec_key, inc_key};
+use crate::utils::epoch;
+use crate::utils::metrics::{self, Counter, TreeMetrics};
+use crate::utils::profile;
 use crate::utils::un_cell::UnCell;
 
 pub type LockLevel = ObjectCount;
 
+/// Latched block, or the ID of an evicted frame that could not be faulted in, see `BufferPool`.
+pub(crate) type Latched<'a, const FAN_OUT: usize, const NUM_RECORDS: usize, Key, Payload>
+    = Result<BlockGuard<'a, FAN_OUT, NUM_RECORDS, Key, Payload>, BlockID>;
+
 pub const INIT_TREE_HEIGHT: Height = 1;
 pub const MAX_TREE_HEIGHT: Height = Height::MAX;
 
@@ -27,6 +47,15 @@ pub struct BPlusTree<
     pub(crate) max_key: Key,
     pub(crate) inc_key: fn(Key) -> Key,
     pub(crate) dec_key: fn(Key) -> Key,
+    pub(crate) metrics: TreeMetrics,
+    pub(crate) observer: Option<Arc<dyn TreeObserver<Key>>>,
+    pub(crate) log: Option<Arc<WriteAheadLog<Key, Payload>>>,
+    pub(crate) memory: MemoryAccount<Payload>,
+    pub(crate) append: Option<AppendHint<FAN_OUT, NUM_RECORDS, Key, Payload>>,
+    pub(crate) fill_policy: FillPolicy,
+    pub(crate) cached_stats: Mutex<Option<Arc<TreeStats>>>,
+    #[cfg(target_os = "linux")]
+    pub(crate) buffer: Option<BufferPool<FAN_OUT, NUM_RECORDS, Key, Payload>>,
 }
 
 
@@ -62,29 +91,38 @@ impl<const FAN_OUT: usize,
     Payload: Default + Clone + Sync
 > BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
 {
+    /// Installs the new root of a root split.
     #[inline(always)]
     pub(crate) fn set_new_root(&self, new_root: Block<FAN_OUT, NUM_RECORDS, Key, Payload>, new_height: Height) {
+        metrics::record(Counter::Split);
+        metrics::record(Counter::RootChange);
+        self.observe(|observer| observer.on_root_change(new_root.block_id(), self.root.height(), new_height));
+
         self.root.get_mut().height = new_height;
 
-        mem::drop(mem::replace(
+        let old_root = mem::replace(
             self.root.block.unsafe_borrow_mut(),
-            new_root,
-        ));
+            new_root);
+
+        // optimistic readers may still read records or children of the old root
+        if self.locking_strategy.is_optimistic() {
+            self.retire_block(self.block_manager.new_cell(old_root, self.locking_strategy.latch_type()))
+        }
     }
 
-    fn make(block_manager: BlockManager<FAN_OUT, NUM_RECORDS, Key, Payload>,
-            locking_strategy: LockingStrategy,
-            min_key: Key,
-            max_key: Key,
-            inc_key: fn(Key) -> Key,
-            dec_key: fn(Key) -> Key) -> Self
+    pub(crate) fn make(block_manager: BlockManager<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                       locking_strategy: LockingStrategy,
+                       min_key: Key,
+                       max_key: Key,
+                       inc_key: fn(Key) -> Key,
+                       dec_key: fn(Key) -> Key) -> Self
     {
         let empty_node
             = block_manager.new_empty_leaf();
 
         Self {
             root: UnCell::new(Root::new(
-                empty_node.into_cell(locking_strategy.latch_type()),
+                block_manager.new_cell(empty_node, locking_strategy.latch_type()),
                 INIT_TREE_HEIGHT,
             )),
             locking_strategy,
@@ -93,6 +131,15 @@ impl<const FAN_OUT: usize,
             max_key,
             inc_key,
             dec_key,
+            metrics: TreeMetrics::default(),
+            observer: None,
+            log: None,
+            memory: MemoryAccount::default(),
+            append: None,
+            fill_policy: FillPolicy::default(),
+            cached_stats: Mutex::new(None),
+            #[cfg(target_os = "linux")]
+            buffer: None,
         }
     }
 
@@ -110,6 +157,27 @@ impl<const FAN_OUT: usize,
         Self::new_with(LockingStrategy::default(), min_key, max_key, inc_key, dec_key)
     }
 
+    /// Reports structure modifications and restarts to the observer.
+    /// Without observer, events only check for one, their arguments are neither collected nor copied.
+    pub fn with_observer(mut self, observer: Arc<dyn TreeObserver<Key>>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    #[inline(always)]
+    pub(crate) fn observe(&self, event: impl FnOnce(&dyn TreeObserver<Key>)) {
+        if let Some(observer) = &self.observer {
+            event(observer.as_ref())
+        }
+    }
+
+    /// Counts the running operation's restart and reports it to the observer.
+    #[inline(always)]
+    pub(crate) fn record_restart(&self, level: Level) {
+        metrics::record_restart(level);
+        self.observe(|observer| observer.on_restart(metrics::current_operation(), level));
+    }
+
     #[inline(always)]
     pub const fn locking_strategy(&self) -> &LockingStrategy {
         &self.locking_strategy
@@ -120,14 +188,102 @@ impl<const FAN_OUT: usize,
         self.root.height()
     }
 
+    /// Runtime concurrency metrics of this tree, see `TreeMetrics::snapshot` and `TreeMetrics::reset`.
     #[inline(always)]
-    pub(crate) fn lock_reader(&self, node: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>)
-                              -> BlockGuard<'static, FAN_OUT, NUM_RECORDS, Key, Payload>
+    pub const fn metrics(&self) -> &TreeMetrics {
+        &self.metrics
+    }
+
+    #[inline(always)]
+    pub(crate) fn lock_reader(&self, node: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>, curr_level: Level)
+                              -> Latched<'static, FAN_OUT, NUM_RECORDS, Key, Payload>
     {
-        match self.locking_strategy {
+        let guard = self.latch_resident(node, curr_level, false, || match self.locking_strategy {
             LockingStrategy::MonoWriter => node.borrow_free(),
             LockingStrategy::LockCoupling => node.borrow_mut(),
-            _ => node.borrow_read(),
+            _ => self.lock_optimistic(node),
+        })?;
+
+        profile::record_latch(curr_level, &guard);
+        Ok(guard)
+    }
+
+    /// Optimistic readers skip reference counting while the thread is pinned,
+    /// unlinked blocks are then kept alive by `retire_block`.
+    #[inline(always)]
+    fn lock_optimistic(&self, node: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>)
+                       -> BlockGuard<'static, FAN_OUT, NUM_RECORDS, Key, Payload>
+    {
+        if self.locking_strategy.is_optimistic() && epoch::is_pinned() {
+            unsafe { node.borrow_read_epoch() }
+        } else {
+            node.borrow_read()
+        }
+    }
+
+    /// Counted reference of a latched child, kept while traversing below it, since upgrading
+    /// pessimistic latches releases them in between. Pinned optimistic traversals borrow the
+    /// child by the epoch instead, see `lock_optimistic`.
+    #[inline(always)]
+    pub(crate) fn hold_child(&self, child: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>)
+                             -> Option<BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>>
+    {
+        match self.locking_strategy.is_optimistic() && epoch::is_pinned() {
+            true => None,
+            false => Some(child.clone())
+        }
+    }
+
+    /// Latches the block by `latch`, evicted frames are faulted in first, see `BufferPool`.
+    /// Blocks latched for writing are marked dirty, `curr_level` is one-based.
+    /// Fails with the frame's ID if it can't be faulted in, trees without buffer pool never fail.
+    #[inline(always)]
+    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+    pub(crate) fn latch_resident(&self,
+                                 node: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                                 curr_level: Level,
+                                 write: bool,
+                                 latch: impl Fn() -> BlockGuard<'static, FAN_OUT, NUM_RECORDS, Key, Payload>)
+                                 -> Latched<'static, FAN_OUT, NUM_RECORDS, Key, Payload>
+    {
+        #[cfg(target_os = "linux")]
+        if let Some(buffer) = &self.buffer {
+            if let Some(pool) = self.pool() {
+                return self.latch_paged(buffer, pool, node, curr_level, write, latch);
+            }
+        }
+
+        Ok(latch())
+    }
+
+    /// Children of an index block, the children of an evicted frame are read from their slots
+    /// without linking them to the block. Meant for walks over a quiescent tree.
+    /// Fails with the frame's ID, if its children can't be read.
+    pub(crate) fn children_of<'a>(&self, block: &'a Block<FAN_OUT, NUM_RECORDS, Key, Payload>)
+                                  -> Result<Cow<'a, [BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>]>, BlockID>
+    {
+        match block.as_ref() {
+            #[cfg(target_os = "linux")]
+            Node::Index(..) if block.is_evicted() => match (&self.buffer, self.pool()) {
+                (Some(buffer), Some(pool)) => self
+                    .read_frame(buffer, pool, block)
+                    .map(Cow::Owned)
+                    .ok_or(block.block_id()),
+                _ => Err(block.block_id())
+            },
+            Node::Index(index_page) => Ok(Cow::Borrowed(index_page.children())),
+            Node::Leaf(..) => Ok(Cow::Borrowed(&[]))
+        }
+    }
+
+    /// Marks an unlinked block and defers its release until no pinned reader can reach it anymore.
+    #[inline(always)]
+    pub(crate) fn retire_block(&self, block: BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>) {
+        // copies of an unlinked frame share its leaves, evicting it would evict theirs
+        block.unsafe_borrow().set_frame(FRAME_UNLINKED);
+
+        if self.locking_strategy.is_optimistic() {
+            epoch::retire(block)
         }
     }
 
@@ -137,21 +293,42 @@ impl<const FAN_OUT: usize,
                                   curr_level: Level,
                                   attempt: Attempts,
                                   height: Height, )
-                                  -> BlockGuard<'static, FAN_OUT, NUM_RECORDS, Key, Payload>
+                                  -> Latched<'static, FAN_OUT, NUM_RECORDS, Key, Payload>
     {
-        match self.locking_strategy() {
+        let optimistic_read
+            = strategy_override::current().is_optimistic_read();
+
+        let guard = self.latch_resident(node, curr_level + 1, false, || match self.locking_strategy() {
             LockingStrategy::MonoWriter => node.borrow_free(),
             LockingStrategy::LockCoupling => node.borrow_mut(),
+            LockingStrategy::LightweightHybridLock { .. } |
+            LockingStrategy::HybridLocking { .. } if optimistic_read =>
+                self.lock_optimistic(node),
             LockingStrategy::LightweightHybridLock { read_level, read_attempt, .. } =>
                 if *read_level <= 1f32 && (attempt >= *read_attempt || curr_level.is_lock(height, *read_level)) {
+                    Self::record_fallback(attempt, *read_attempt);
                     node.borrow_pin()
                 } else {
-                    node.borrow_read()
+                    self.lock_optimistic(node)
                 }
             LockingStrategy::HybridLocking { read_attempt }
-            if attempt >= *read_attempt =>
-                node.borrow_read_hybrid(),
-            _ => node.borrow_read(),
+            if attempt >= *read_attempt => {
+                metrics::record(Counter::PessimisticFallback);
+                node.borrow_read_hybrid()
+            }
+            _ => self.lock_optimistic(node),
+        })?;
+
+        profile::record_latch(curr_level + 1, &guard); // range scans pass zero-based path positions
+        Ok(guard)
+    }
+
+    /// Counts latches optimistic strategies only take pessimistically because the operation
+    /// already restarted too often, latches due to level or leaf position are by design.
+    #[inline(always)]
+    fn record_fallback(attempt: Attempts, fallback_attempt: Attempts) {
+        if attempt >= fallback_attempt {
+            metrics::record(Counter::PessimisticFallback)
         }
     }
 
@@ -162,28 +339,53 @@ impl<const FAN_OUT: usize,
                                 attempt: Attempts,
                                 height: Level,
                                 block_cc: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>,
-    ) -> BlockGuard<FAN_OUT, NUM_RECORDS, Key, Payload>
+    ) -> Latched<'_, FAN_OUT, NUM_RECORDS, Key, Payload>
     {
-        match self.locking_strategy() {
+        let pessimistic_write
+            = strategy_override::current().is_pessimistic_write();
+
+        let guard = self.latch_resident(block_cc, curr_level, true, || match self.locking_strategy() {
             LockingStrategy::MonoWriter =>
                 block_cc.borrow_free(),
             LockingStrategy::LockCoupling =>
                 block_cc.borrow_mut(),
-            LockingStrategy::ORWC { write_level, write_attempt }
+            LockingStrategy::LightweightHybridLock { .. } |
+            LockingStrategy::CopyOnWrite if pessimistic_write =>
+                block_cc.borrow_pin(),
+            LockingStrategy::ORWC { .. } |
+            LockingStrategy::TicketORWC { .. } if pessimistic_write =>
+                block_cc.borrow_mut(),
+            LockingStrategy::OLC |
+            LockingStrategy::OptiQL |
+            LockingStrategy::HybridLocking { .. } if pessimistic_write =>
+                block_cc.borrow_mut_waiting(),
+            LockingStrategy::ORWC { write_level, write_attempt } |
+            LockingStrategy::TicketORWC { write_level, write_attempt }
             if curr_level >= height
                 || curr_level >= max_level
                 || attempt >= *write_attempt
-                || curr_level.is_lock(height, *write_level) => block_cc.borrow_mut(),
+                || curr_level.is_lock(height, *write_level) => {
+                Self::record_fallback(attempt, *write_attempt);
+                block_cc.borrow_mut()
+            }
             LockingStrategy::LightweightHybridLock { write_level, write_attempt, .. }
             if *write_level <= 1f32 &&
                 (curr_level >= height
                     || curr_level >= max_level
                     || attempt >= *write_attempt
                     || curr_level.is_lock(height, *write_level)
-                ) => block_cc.borrow_pin(),
+                ) => {
+                Self::record_fallback(attempt, *write_attempt);
+                block_cc.borrow_pin()
+            }
             LockingStrategy::HybridLocking { .. } if curr_level >= max_level =>
                 block_cc.borrow_mut(),
-            _ => block_cc.borrow_read()
-        }
+            LockingStrategy::OptiQL if curr_level >= max_level =>
+                block_cc.borrow_mut(),
+            _ => self.lock_optimistic(block_cc)
+        })?;
+
+        profile::record_latch(curr_level, &guard);
+        Ok(guard)
     }
 }
\ No newline at end of file
@@ -0,0 +1,223 @@
+use std::hash::Hash;
+use std::mem;
+use crate::page_model::{BlockRef, Height};
+use crate::record_model::record_point::RecordPoint;
+use crate::tree::bplus_tree::{BPlusTree, INIT_TREE_HEIGHT};
+use crate::tree::root::Root;
+
+/// Bounds of the entries a node of a level holds when built bottom-up.
+#[derive(Clone, Copy)]
+struct Fill {
+    /// Entries a node receives at most, unless fewer nodes are needed to reach `minimum`.
+    capacity: usize,
+    minimum: usize,
+    /// Entries a node holds without overflowing, never exceeded.
+    maximum: usize,
+}
+
+impl Fill {
+    /// Number of nodes holding `len` entries, as many as `capacity` requires.
+    /// If an even spread leaves nodes below `minimum`, fewer and hence fuller nodes are taken,
+    /// as long as none of them exceeds `maximum`.
+    #[inline(always)]
+    fn nodes(&self, len: usize) -> usize {
+        let mut nodes
+            = len.div_ceil(self.capacity.max(1)).max(1);
+
+        while nodes > 1 && len / nodes < self.minimum && len.div_ceil(nodes - 1) <= self.maximum {
+            nodes -= 1;
+        }
+
+        nodes
+    }
+
+    /// Whether `len` entries spread evenly across `nodes` nodes are within `minimum` and `maximum`.
+    /// A single node is the root, which has no minimum.
+    #[inline(always)]
+    const fn fits(&self, len: usize, nodes: usize) -> bool {
+        nodes > 0 && len.div_ceil(nodes) <= self.maximum && (nodes == 1 || len / nodes >= self.minimum)
+    }
+
+    /// Like `nodes`, but a single node above takes the nodes as its children, or their count
+    /// can be spread across the level above within the `parent` bounds. Otherwise, e.g. one
+    /// child more than an index page holds but too few for two, a node more or less is taken.
+    fn nodes_below(&self, len: usize, parent: &Fill) -> usize {
+        let nodes
+            = self.nodes(len);
+
+        let fits_parent
+            = |nodes: usize| nodes == 1 || parent.fits(nodes, parent.nodes(nodes));
+
+        if fits_parent(nodes) {
+            return nodes;
+        }
+
+        [nodes + 1, nodes - 1]
+            .into_iter()
+            .find(|nodes| self.fits(len, *nodes) && fits_parent(*nodes))
+            .unwrap_or(nodes)
+    }
+}
+
+/// Sizes of `nodes` nodes holding `len` entries, spread evenly.
+#[inline(always)]
+fn spread(len: usize, nodes: usize) -> impl Iterator<Item=usize> {
+    let (base, extra)
+        = (len / nodes, len % nodes);
+
+    (0..nodes).map(move |node| base + usize::from(node < extra))
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Records a leaf holds at most when built bottom-up, i.e. one below its overflow.
+    #[inline(always)]
+    pub(crate) const fn bulk_leaf_capacity(&self) -> usize {
+        self.block_manager.allocation_leaf() - 1
+    }
+
+    /// Children an index page holds at most when built bottom-up, i.e. one below its overflow.
+    #[inline(always)]
+    pub(crate) const fn bulk_index_capacity(&self) -> usize {
+        self.block_manager.allocation_directory()
+    }
+
+    /// Builds the tree from records bottom-up, filling nodes as full as `BPlusTree::bulk_load_with`
+    /// does for a fill factor of 1.
+    #[inline(always)]
+    pub fn bulk_load(self, records: impl IntoIterator<Item=(Key, Payload)>) -> Self {
+        self.bulk_load_with(records, 1.0)
+    }
+
+    /// Builds the tree from records bottom-up instead of inserting them one by one, the tree's
+    /// previous content is dropped. Records are sorted by key unless ascending already, of records
+    /// with equal keys the first one is kept like repeated inserts would.
+    /// Nodes are filled up to `fill_factor` of their capacity, e.g. 0.7 leaves room for later inserts
+    /// without splitting right away. Nodes are not filled below half of their capacity though.
+    /// Neither the memory limit is checked nor is a structure modification observed or logged.
+    pub fn bulk_load_with(self,
+                          records: impl IntoIterator<Item=(Key, Payload)>,
+                          fill_factor: f64) -> Self
+    {
+        assert!(fill_factor > 0.0 && fill_factor <= 1.0, "Fill factor must be within (0, 1]!");
+
+        let mut records = records
+            .into_iter()
+            .map(|(key, payload)| RecordPoint::new(key, payload))
+            .collect::<Vec<_>>();
+
+        if records.windows(2).any(|pair| pair[0].key >= pair[1].key) {
+            records.sort_by_key(RecordPoint::key);
+            records.dedup_by_key(|record| record.key);
+        }
+
+        let leaf_capacity
+            = (self.bulk_leaf_capacity() as f64 * fill_factor).ceil() as usize;
+
+        let index_capacity
+            = (self.bulk_index_capacity() as f64 * fill_factor).ceil() as usize;
+
+        self.build_bottom_up(records, leaf_capacity.max(1), index_capacity.max(2));
+        self
+    }
+
+    /// Replaces the tree's content by strictly ascending records, building the leaves first
+    /// and each index level on top of the previous one. Leaves receive up to `leaf_capacity` records
+    /// and index pages up to `index_capacity` children, spread evenly across each level,
+    /// while no node is left underflowing or overflowing, see `Fill`.
+    /// No structure modification is recorded, hence the tree must not be shared yet.
+    pub(crate) fn build_bottom_up(&self,
+                                  records: Vec<RecordPoint<Key, Payload>>,
+                                  leaf_capacity: usize,
+                                  index_capacity: usize)
+    {
+        debug_assert!(records.windows(2).all(|pair| pair[0].key < pair[1].key),
+                      "Bulk build requires strictly ascending keys!");
+
+        let latch_type
+            = self.locking_strategy.latch_type();
+
+        let leaf_fill = Fill {
+            capacity: leaf_capacity,
+            minimum: self.min_len(true),
+            maximum: self.bulk_leaf_capacity(),
+        };
+
+        let index_fill = Fill {
+            capacity: index_capacity.max(2),
+            minimum: self.min_len(false) + 1,
+            maximum: self.bulk_index_capacity(),
+        };
+
+        let len
+            = records.len();
+
+        self.memory.reset(records
+            .iter()
+            .map(|record| self.memory.payload_bytes(record.payload_ref()))
+            .sum());
+
+        let mut records
+            = records.into_iter();
+
+        let mut level: Vec<(Key, BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>)> = spread(len, leaf_fill.nodes_below(len, &index_fill))
+            .map(|size| {
+                let leaf
+                    = self.block_manager.new_empty_leaf();
+
+                leaf.records_mut().extend(records.by_ref().take(size));
+
+                let lower = leaf
+                    .as_records()
+                    .first()
+                    .map(RecordPoint::key)
+                    .unwrap_or(self.min_key);
+
+                (lower, self.block_manager.new_cell(leaf, latch_type))
+            })
+            .collect();
+
+        let mut height: Height
+            = INIT_TREE_HEIGHT;
+
+        while level.len() > 1 {
+            let mut entries
+                = level.into_iter();
+
+            level = spread(entries.len(), index_fill.nodes_below(entries.len(), &index_fill))
+                .map(|size| {
+                    let index_block
+                        = self.block_manager.new_empty_index_block();
+
+                    let (separators, children): (Vec<_>, Vec<_>)
+                        = entries.by_ref().take(size).unzip();
+
+                    index_block
+                        .children_mut()
+                        .extend(children);
+
+                    index_block
+                        .keys_mut()
+                        .extend(separators.iter().skip(1).cloned());
+
+                    (separators[0], self.block_manager.new_cell(index_block, latch_type))
+                })
+                .collect();
+
+            height += 1;
+        }
+
+        let (.., root_block) = level
+            .pop()
+            .expect("Bulk build without root");
+
+        let old_root
+            = mem::replace(self.root.get_mut(), Root::new(root_block, height));
+
+        self.retire_block(old_root.block);
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt::Display;
+use std::fs;
+use std::hash::Hash;
+use std::io;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use crate::record_model::record_point::RecordPoint;
+use crate::tree::bplus_tree::BPlusTree;
+use crate::tree::snapshot::{SnapshotError, SnapshotWriter};
+use crate::tree::wal::WalError;
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + Send + Display + Serialize + DeserializeOwned + 'static,
+    Payload: Default + Clone + Sync + Send + Display + Serialize + DeserializeOwned + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Saves a snapshot while operations keep running and returns the number of records saved.
+    /// The log is rotated first, then leaves are copied one by one in key order, each under its own
+    /// latch only. Every record of the snapshot is at least as recent as the rotation, hence replaying
+    /// the records logged after it yields the current state, see `BPlusTree::open_durable`.
+    /// The previous segment is removed, once the snapshot is saved.
+    pub fn checkpoint(&self) -> Result<usize, WalError> {
+        let log = match &self.log {
+            Some(log) => log,
+            None => return Err(WalError::NotDurable)
+        };
+
+        let _checkpoint
+            = log.checkpoint.lock();
+
+        let lsn
+            = log.rotate()?;
+
+        let mut writer
+            = SnapshotWriter::create(&log.config().snapshot_path, &self.snapshot_header(lsn))?;
+
+        let mut lower
+            = self.min_key;
+
+        loop {
+            let read = {
+                #[cfg(target_os = "linux")]
+                let _pins
+                    = self.pin_scope();
+
+                self.read_leaf(lower, |leaf, fence| (leaf
+                    .as_records()
+                    .iter()
+                    .skip_while(|record| record.key < lower)
+                    .take_while(|record| record.key <= fence.upper())
+                    .cloned()
+                    .collect::<Vec<RecordPoint<Key, Payload>>>(), fence.upper()))
+            };
+
+            #[cfg(target_os = "linux")]
+            if self.buffer.is_some() {
+                let _ = self.release_frames();
+            }
+
+            let (chunk, fence_upper)
+                = read.map_err(SnapshotError::UnreadableFrame)?;
+
+            writer.write_chunk(&chunk)?;
+
+            if fence_upper >= self.max_key {
+                break;
+            }
+
+            lower = (self.inc_key)(fence_upper);
+        }
+
+        let records
+            = writer.finish()?;
+
+        match fs::remove_file(log.config().previous_log_path()) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(records)
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use crate::block::block::Block;
+use crate::page_model::{BlockID, BlockRef, Height};
+use crate::page_model::node::Node;
+use crate::record_model::Version;
+use crate::tree::bplus_tree::BPlusTree;
+use crate::utils::smart_cell::{LatchState, SmartCell};
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    Index,
+    Leaf,
+}
+
+/// Node of an exported tree, children are nested in key order.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NodeExport<Key> {
+    pub kind: NodeKind,
+    /// Identifies the node in events and later exports, see `TreeObserver`.
+    #[serde(default)]
+    pub block_id: BlockID,
+    pub latch: LatchState,
+    /// Latch version of optimistic flavors, without flags.
+    pub version: Option<Version>,
+    /// Inclusive lower bound routed to this node by its ancestors.
+    pub lower: Key,
+    /// Exclusive upper bound routed to this node, none means up to the tree's max key.
+    pub upper: Option<Key>,
+    /// Separator keys for index pages, record keys for leaves.
+    pub keys: Vec<Key>,
+    pub children: Vec<NodeExport<Key>>,
+}
+
+/// Point in time copy of a tree's shape, see `BPlusTree::export`.
+/// Exports taken before and after a structure modification can be diffed as JSON.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TreeExport<Key> {
+    pub locking_strategy: String,
+    pub height: Height,
+    pub root: NodeExport<Key>,
+}
+
+impl<Key: Serialize> TreeExport<Key> {
+    #[inline]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl<Key: Display> TreeExport<Key> {
+    /// Renders the tree as Graphviz DOT, index pages show their separators with one port per child,
+    /// leaves their routed range, key span and record count.
+    pub fn to_dot(&self) -> String {
+        let mut dot = format!("digraph BPlusTree {{\n\
+            \tlabel=\"{} (height={})\";\n\
+            \tnode [shape=record, fontname=\"monospace\"];\n",
+                              escape_dot(&self.locking_strategy),
+                              self.height);
+
+        let mut next_id = 0;
+        Self::write_dot_node(&self.root, &mut next_id, &mut dot);
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn write_dot_node(node: &NodeExport<Key>, next_id: &mut usize, dot: &mut String) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+
+        let range = match &node.upper {
+            Some(upper) => format!("[{}, {})", node.lower, upper),
+            None => format!("[{}, max]", node.lower)
+        };
+
+        let latch = match node.version {
+            Some(version) => format!("{} v{}", node.latch, version),
+            None => node.latch.to_string()
+        };
+
+        match node.kind {
+            NodeKind::Index => {
+                let separators = (0..node.children.len())
+                    .map(|pos| match node.keys.get(pos) {
+                        Some(key) => format!("<c{}>|{}", pos, escape_dot(&key.to_string())),
+                        None => format!("<c{}>", pos)
+                    })
+                    .join("|");
+
+                let _ = writeln!(dot, "\tn{} [label=\"{{Index #{} {}|{}|{{{}}}}}\"];",
+                                 id,
+                                 node.block_id,
+                                 escape_dot(&range),
+                                 latch,
+                                 separators);
+
+                node.children
+                    .iter()
+                    .enumerate()
+                    .for_each(|(pos, child)| {
+                        let child_id
+                            = Self::write_dot_node(child, next_id, dot);
+
+                        let _ = writeln!(dot, "\tn{}:c{} -> n{};", id, pos, child_id);
+                    });
+            }
+            NodeKind::Leaf => {
+                let span = match (node.keys.first(), node.keys.last()) {
+                    (Some(first), Some(last)) => format!("{}..{}", first, last),
+                    _ => "empty".to_string()
+                };
+
+                let _ = writeln!(dot, "\tn{} [label=\"{{Leaf #{} {}|{}|{} ({} records)}}\"];",
+                                 id,
+                                 node.block_id,
+                                 escape_dot(&range),
+                                 latch,
+                                 escape_dot(&span),
+                                 node.keys.len());
+            }
+        }
+
+        id
+    }
+}
+
+#[inline(always)]
+fn escape_dot(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| match c {
+            '{' | '}' | '|' | '<' | '>' | '"' | '\\' => vec!['\\', c],
+            _ => vec![c]
+        })
+        .collect()
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Copies the tree's shape including each node's latch state.
+    /// Nodes are read without latching, hence the tree should be quiescent.
+    /// Evicted frames whose leaves can't be read are exported without children, see `BPlusTree::validate`.
+    pub fn export(&self) -> TreeExport<Key> {
+        let root
+            = self.root.get();
+
+        TreeExport {
+            locking_strategy: self.locking_strategy.to_string(),
+            height: root.height(),
+            root: self.export_node(&root.block, self.min_key, None),
+        }
+    }
+
+    /// Looks a block up by its ID, e.g. one reported by a `TreeObserver`, by walking the tree.
+    /// Meant for diagnostics only, since there is no index of IDs and each lookup takes O(n)
+    /// in the number of blocks. Nodes are read without latching, hence the tree should be quiescent.
+    pub fn find_block(&self, block_id: BlockID) -> Option<BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>> {
+        self.locate_block(&self.root.get().block, self.min_key, None, block_id)
+            .map(|(block, ..)| block)
+    }
+
+    /// Exports the subtree of the block with the given ID, O(n) like `BPlusTree::find_block`.
+    pub fn export_block(&self, block_id: BlockID) -> Option<NodeExport<Key>> {
+        self.locate_block(&self.root.get().block, self.min_key, None, block_id)
+            .map(|(block, lower, upper)| self.export_node(&block, lower, upper))
+    }
+
+    fn locate_block(&self,
+                    block: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                    lower: Key,
+                    upper: Option<Key>,
+                    block_id: BlockID) -> Option<(BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>, Key, Option<Key>)>
+    {
+        if block.unsafe_borrow().block_id() == block_id {
+            return Some((block.clone(), lower, upper));
+        }
+
+        match block.unsafe_borrow().as_ref() {
+            Node::Index(index_page) => {
+                let keys
+                    = index_page.keys();
+
+                self.children_of(block.unsafe_borrow())
+                    .unwrap_or_default()
+                    .iter()
+                    .enumerate()
+                    .find_map(|(pos, child)| self.locate_block(
+                        child,
+                        pos.checked_sub(1)
+                            .and_then(|pos| keys.get(pos).cloned())
+                            .unwrap_or(lower),
+                        keys.get(pos).cloned().or(upper),
+                        block_id))
+            }
+            Node::Leaf(..) => None
+        }
+    }
+
+    fn export_node(&self,
+                   block: &SmartCell<Block<FAN_OUT, NUM_RECORDS, Key, Payload>>,
+                   lower: Key,
+                   upper: Option<Key>) -> NodeExport<Key>
+    {
+        let (latch, version)
+            = block.latch_state();
+
+        match block.unsafe_borrow().as_ref() {
+            Node::Index(index_page) => {
+                let keys
+                    = index_page.keys();
+
+                NodeExport {
+                    kind: NodeKind::Index,
+                    block_id: block.unsafe_borrow().block_id(),
+                    latch,
+                    version,
+                    lower,
+                    upper,
+                    keys: keys.to_vec(),
+                    children: self
+                        .children_of(block.unsafe_borrow())
+                        .unwrap_or_default()
+                        .iter()
+                        .enumerate()
+                        .map(|(pos, child)| self.export_node(
+                            child,
+                            pos.checked_sub(1)
+                                .and_then(|pos| keys.get(pos).cloned())
+                                .unwrap_or(lower),
+                            keys.get(pos).cloned().or(upper)))
+                        .collect(),
+                }
+            }
+            Node::Leaf(leaf_page) => NodeExport {
+                kind: NodeKind::Leaf,
+                block_id: block.unsafe_borrow().block_id(),
+                latch,
+                version,
+                lower,
+                upper,
+                keys: leaf_page
+                    .as_records()
+                    .iter()
+                    .map(|record| record.key)
+                    .collect(),
+                children: vec![],
+            }
+        }
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + Serialize + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    #[inline]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        self.export().to_json()
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + Display + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    #[inline]
+    pub fn to_dot(&self) -> String {
+        self.export().to_dot()
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + Display + 'static,
+    Payload: Default + Clone + Sync + Display + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Renders one line per node in level order, index pages list their children's IDs.
+    /// Nodes are read without latching, hence the tree should be quiescent.
+    pub fn level_order(&self) -> String {
+        let mut queue: VecDeque<BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>>
+            = VecDeque::from([self.root.get().block.clone()]);
+
+        let mut lines = String::new();
+        while let Some(block) = queue.pop_front() {
+            queue.extend(self.children_of(block.unsafe_borrow()).unwrap_or_default().iter().cloned());
+
+            let _ = writeln!(lines, "{}", block.unsafe_borrow());
+        }
+
+        lines
+    }
+}
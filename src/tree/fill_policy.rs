@@ -0,0 +1,129 @@
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use crate::tree::bplus_tree::BPlusTree;
+
+/// How an underflowing node is corrected with its sibling, see `FillPolicy`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rebalance {
+    /// Merges both nodes if they fit into one, otherwise redistributes their entries evenly.
+    #[default]
+    Merge,
+    /// Redistributes the entries evenly if neither node underflows afterwards, otherwise merges.
+    /// Hence, nodes refilled by later inserts are not merged and split again.
+    Borrow,
+}
+
+impl Display for Rebalance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rebalance::Merge => write!(f, "Merge"),
+            Rebalance::Borrow => write!(f, "Borrow"),
+        }
+    }
+}
+
+/// Split point, minimum fill and underflow correction of a tree's nodes, see `BPlusTree::with_fill_policy`.
+/// The default splits in the middle and keeps nodes at least half full.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FillPolicy {
+    /// Share of its entries an overflowing node keeps on the left when split.
+    pub split_ratio: f64,
+    /// Share of its allocation a non-root node holds at least, below it underflows.
+    /// Empty nodes always underflow, hence zero only frees empty nodes, i.e. deletes lazily.
+    pub min_fill: f64,
+    pub rebalance: Rebalance,
+}
+
+impl Default for FillPolicy {
+    fn default() -> Self {
+        Self {
+            split_ratio: 0.5,
+            min_fill: 0.5,
+            rebalance: Rebalance::Merge,
+        }
+    }
+}
+
+impl Display for FillPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "FillPolicy(split_ratio={};min_fill={};rebalance={})",
+               self.split_ratio,
+               self.min_fill,
+               self.rebalance)
+    }
+}
+
+impl FillPolicy {
+    /// Frees empty nodes only, e.g. for queues draining by `PopMin` or `PopMax`.
+    pub fn lazy() -> Self {
+        Self::default().with_min_fill(0.0)
+    }
+
+    /// Keeps `split_ratio` of the entries left on a split, e.g. 0.9 for mostly ascending keys.
+    /// Either node keeps at least the minimum fill regardless.
+    pub fn with_split_ratio(mut self, split_ratio: f64) -> Self {
+        assert!(split_ratio > 0.0 && split_ratio < 1.0, "Split ratio must be within (0, 1)!");
+
+        self.split_ratio = split_ratio;
+        self
+    }
+
+    /// Lets nodes underflow below `min_fill` of their allocation, e.g. 0.25.
+    /// At most half, since two nodes not fitting into one are then at least half full once redistributed.
+    pub fn with_min_fill(mut self, min_fill: f64) -> Self {
+        assert!((0.0..=0.5).contains(&min_fill), "Minimum fill must be within [0, 0.5]!");
+
+        self.min_fill = min_fill;
+        self
+    }
+
+    pub fn with_rebalance(mut self, rebalance: Rebalance) -> Self {
+        self.rebalance = rebalance;
+        self
+    }
+
+    /// Entries a non-root node of `allocation` holds at least.
+    #[inline(always)]
+    pub fn min_len(&self, allocation: usize) -> usize {
+        ((allocation as f64 * self.min_fill) as usize).max(1)
+    }
+
+    /// Position splitting `len` entries by the split ratio, such that the left node keeps at least `left_min`
+    /// entries and the right one at least `right_min`, as far as possible.
+    #[inline(always)]
+    pub(crate) fn split_pos(&self, len: usize, left_min: usize, right_min: usize) -> usize {
+        ((len as f64 * self.split_ratio).round() as usize)
+            .min(len.saturating_sub(right_min))
+            .max(left_min.min(len / 2))
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Splits, underflows and corrects nodes by the policy instead of the default one.
+    /// Nodes are not refilled for a greater minimum fill, hence the tree must be empty yet.
+    pub fn with_fill_policy(mut self, fill_policy: FillPolicy) -> Self {
+        self.fill_policy = fill_policy;
+        self
+    }
+
+    #[inline(always)]
+    pub fn fill_policy(&self) -> &FillPolicy {
+        &self.fill_policy
+    }
+
+    /// Entries, i.e. records or keys, a non-root node holds at least under the tree's fill policy.
+    /// Index nodes keep two keys at least, since merging two of their children removes one
+    /// and an index node without keys has no children left.
+    #[inline(always)]
+    pub(crate) fn min_len(&self, is_leaf: bool) -> usize {
+        match is_leaf {
+            true => self.fill_policy.min_len(self.block_manager.allocation_leaf() - 1),
+            false => self.fill_policy.min_len(self.block_manager.allocation_directory()).max(2)
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::mem;
+use std::sync::Arc;
+use std::sync::atomic::AtomicIsize;
+use std::sync::atomic::Ordering::Relaxed;
+use crate::block::block::Block;
+use crate::crud_model::crud_operation_result::CRUDOperationResult;
+use crate::tree::bplus_tree::BPlusTree;
+use crate::utils::{epoch, slab};
+
+/// Memory held by a tree, see `BPlusTree::memory_usage`.
+/// Slots of a block pool and the buffers of a write-ahead log are not included.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Data of all blocks in use, i.e. their keys, children and records.
+    pub blocks: usize,
+    /// Latches, reference counts and padding of all blocks in use, plus the tree itself.
+    /// Unlike `bsz_alignment_min`, which estimates it when sizing blocks, measured from the slab's slots.
+    pub metadata: usize,
+    /// Heap memory owned by payloads, zero unless set up by `BPlusTree::with_payload_heap`.
+    pub payload_heap: usize,
+    /// Memory of the slab not held by any block, which is reused before the slab grows.
+    pub reserved_free: usize,
+    /// Bytes the tree may use, see `BPlusTree::with_memory_limit`.
+    pub limit: Option<usize>,
+}
+
+impl MemoryUsage {
+    /// Memory counted towards the limit, i.e. all but `reserved_free`.
+    #[inline(always)]
+    pub fn used_bytes(&self) -> usize {
+        self.blocks + self.metadata + self.payload_heap
+    }
+
+    /// Memory held for the tree, including slab memory not in use.
+    #[inline(always)]
+    pub fn total_bytes(&self) -> usize {
+        self.used_bytes() + self.reserved_free
+    }
+}
+
+impl Display for MemoryUsage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MemoryUsage(blocks={};metadata={};payload_heap={};reserved_free={};used_bytes={};limit={})",
+               self.blocks,
+               self.metadata,
+               self.payload_heap,
+               self.reserved_free,
+               self.used_bytes(),
+               self.limit.map_or("none".to_string(), |limit| limit.to_string()))
+    }
+}
+
+/// Called with the tree's usage once an insert would exceed its limit. No latch is held meanwhile,
+/// hence the callback may remove records from this or any other tree. Returns true if it freed memory,
+/// the insert then proceeds if the tree fits its limit again.
+pub type EvictionCallback = Arc<dyn Fn(&MemoryUsage) -> bool + Send + Sync>;
+
+/// Bytes a tree may use, see `BPlusTree::with_memory_limit`.
+#[derive(Clone)]
+pub struct MemoryLimit {
+    pub bytes: usize,
+    evict: Option<EvictionCallback>,
+}
+
+impl MemoryLimit {
+    #[inline(always)]
+    pub fn new(bytes: usize) -> Self {
+        Self {
+            bytes,
+            evict: None,
+        }
+    }
+
+    /// Calls `evict` once per insert exceeding the limit, before the insert is rejected.
+    pub fn with_eviction(mut self, evict: impl Fn(&MemoryUsage) -> bool + Send + Sync + 'static) -> Self {
+        self.evict = Some(Arc::new(evict));
+        self
+    }
+}
+
+/// Pads stripes to separate cache lines, since each is updated by other threads.
+#[repr(align(128))]
+struct PaddedCounter(AtomicIsize);
+
+/// Payload heap bytes and limit of a tree. Threads count into their own stripe,
+/// hence a stripe is negative if its threads removed more than they stored.
+pub(crate) struct MemoryAccount<Payload> {
+    payload_heap: Option<fn(&Payload) -> usize>,
+    stripes: Box<[PaddedCounter]>,
+    limit: Option<MemoryLimit>,
+}
+
+impl<Payload> Default for MemoryAccount<Payload> {
+    fn default() -> Self {
+        Self {
+            payload_heap: None,
+            stripes: (0..num_cpus::get().next_power_of_two())
+                .map(|_| PaddedCounter(AtomicIsize::new(0)))
+                .collect(),
+            limit: None,
+        }
+    }
+}
+
+impl<Payload> MemoryAccount<Payload> {
+    /// Heap bytes owned by the payload, zero without payload heap function.
+    #[inline(always)]
+    pub(crate) fn payload_bytes(&self, payload: &Payload) -> usize {
+        self.payload_heap.map_or(0, |payload_heap| payload_heap(payload))
+    }
+
+    #[inline(always)]
+    fn add(&self, bytes: isize) {
+        if bytes != 0 {
+            self.stripes[slab::shard_index() & (self.stripes.len() - 1)].0.fetch_add(bytes, Relaxed);
+        }
+    }
+
+    /// Sums up all stripes, concurrent operations may be partially counted.
+    #[inline(always)]
+    fn payload_heap_bytes(&self) -> usize {
+        self.stripes
+            .iter()
+            .map(|stripe| stripe.0.load(Relaxed))
+            .sum::<isize>()
+            .max(0) as usize
+    }
+
+    /// Replaces the count, e.g. once the tree's content was replaced.
+    pub(crate) fn reset(&self, bytes: usize) {
+        self.stripes
+            .iter()
+            .enumerate()
+            .for_each(|(stripe, counter)| counter.0.store(if stripe == 0 { bytes as _ } else { 0 }, Relaxed));
+    }
+
+    /// Counts the payload an operation stored, `stored` bytes, and the one it removed.
+    #[inline(always)]
+    pub(crate) fn account<Key>(&self, stored: usize, result: &CRUDOperationResult<Key, Payload>)
+        where Key: Default + Ord + Copy + Hash, Payload: Default + Clone
+    {
+        if self.payload_heap.is_none() {
+            return;
+        }
+
+        match result {
+            CRUDOperationResult::Inserted(..) =>
+                self.add(stored as isize),
+            CRUDOperationResult::Updated(_, old) =>
+                self.add(stored as isize - self.payload_bytes(old) as isize),
+            CRUDOperationResult::Deleted(_, old) =>
+                self.add(-(self.payload_bytes(old) as isize)),
+            _ => {}
+        }
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Counts the heap memory owned by payloads, e.g. `|payload: &String| payload.capacity()`.
+    /// Stored payloads are counted once now, hence the tree must not be shared yet.
+    pub fn with_payload_heap(mut self, payload_heap: fn(&Payload) -> usize) -> Self {
+        self.memory.payload_heap = Some(payload_heap);
+
+        let mut bytes = 0;
+        // leaves that can't be read are not counted
+        let _ = self.try_for_each_leaf(&self.root.get().block.clone(), &mut |records| {
+            bytes += records
+                .iter()
+                .map(|record| payload_heap(record.payload_ref()))
+                .sum::<usize>();
+
+            Ok(())
+        }, |_| ());
+
+        self.memory.reset(bytes);
+        self
+    }
+
+    /// Rejects inserts with `CRUDOperationResult::MemoryLimitExceeded` once they would exceed
+    /// the limit, see `MemoryUsage::used_bytes`. Updates and structure modifications are not
+    /// rejected, hence the tree may exceed the limit by the blocks split or copied meanwhile.
+    pub fn with_memory_limit(mut self, limit: MemoryLimit) -> Self {
+        self.memory.limit = Some(limit);
+        self
+    }
+
+    /// Memory held by the tree. Retired blocks are counted until no reader can reach them anymore.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let slab
+            = self.block_manager.slab_usage();
+
+        let block_bytes
+            = mem::size_of::<Block<FAN_OUT, NUM_RECORDS, Key, Payload>>();
+
+        MemoryUsage {
+            blocks: slab.slots_in_use * block_bytes,
+            metadata: slab.slots_in_use * (slab.slot_bytes - block_bytes) + mem::size_of::<Self>(),
+            payload_heap: self.memory.payload_heap_bytes(),
+            reserved_free: slab.reserved_bytes().saturating_sub(slab.used_bytes()),
+            limit: self.memory.limit.as_ref().map(|limit| limit.bytes),
+        }
+    }
+
+    /// Bytes counted towards the limit, without locking the slab.
+    #[inline(always)]
+    fn used_bytes_relaxed(&self) -> usize {
+        self.block_manager.cells_in_use() * self.block_manager.cell_bytes()
+            + mem::size_of::<Self>()
+            + self.memory.payload_heap_bytes()
+    }
+
+    /// Returns true if storing `stored` more payload heap bytes exceeds the limit, without calling back.
+    #[inline(always)]
+    pub(crate) fn exceeds_limit(&self, stored: usize) -> bool {
+        self.memory.limit
+            .as_ref()
+            .is_some_and(|limit| self.used_bytes_relaxed() + stored > limit.bytes)
+    }
+
+    /// Admits an insert storing `stored` payload heap bytes, unless it exceeds the limit
+    /// even after the eviction callback ran. Called before latching, see `EvictionCallback`.
+    #[inline(always)]
+    pub(crate) fn admit_insert(&self, stored: usize) -> Result<(), MemoryUsage> {
+        let limit = match &self.memory.limit {
+            Some(limit) if self.exceeds_limit(stored) => limit,
+            _ => return Ok(())
+        };
+
+        // blocks retired by this thread, e.g. replaced leaf copies, may be freed already
+        if self.locking_strategy.is_optimistic() && !epoch::is_pinned() {
+            epoch::collect();
+
+            if !self.exceeds_limit(stored) {
+                return Ok(());
+            }
+        }
+
+        let usage
+            = self.memory_usage();
+
+        match &limit.evict {
+            Some(evict) if evict(&usage) => match self.exceeds_limit(stored) {
+                true => Err(self.memory_usage()),
+                false => Ok(())
+            },
+            _ => Err(usage)
+        }
+    }
+}
@@ -1,3 +1,20 @@
+pub mod append;
 pub mod bplus_tree;
+pub mod bulk_load;
+pub mod checkpoint;
+pub mod export;
+pub mod fill_policy;
+pub mod memory;
+pub mod observer;
+#[cfg(target_os = "linux")]
+pub mod paging;
+#[cfg(target_os = "linux")]
+pub mod pool;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
 pub mod root;
+pub mod snapshot;
+pub mod stats;
+pub mod validation;
+pub mod wal;
 // pub mod settings;
@@ -0,0 +1,31 @@
+use crate::page_model::{BlockID, Height, Level};
+use crate::tree::export::NodeKind;
+use crate::utils::metrics::OperationKind;
+
+/// Receives structure modifications and restarts of a tree, see `BPlusTree::with_observer`.
+/// Callbacks run inline on the operating thread, mostly while latches are held,
+/// hence they should return quickly and must not access the tree.
+/// Structure modifications replace nodes with new blocks, events name the `BlockID`s replaced
+/// and the ones replacing them, which allows following a node across events and exports.
+/// Copy-on-write updates keep the ID, the copy replaces the leaf in place.
+pub trait TreeObserver<Key>: Send + Sync {
+    /// An overflowing node was split into a left and right node, `separator` is the key pushed
+    /// into its parent. Root splits are additionally reported as root change.
+    fn on_split(&self, _node: NodeKind, _from: BlockID, _into: (BlockID, BlockID), _separator: Key) {}
+
+    /// Underflowing siblings were merged into a single node, in key order.
+    fn on_merge(&self, _node: NodeKind, _from: &[BlockID], _into: BlockID) {}
+
+    /// Entries of two siblings were redistributed, since they do not fit into a single node.
+    fn on_redistribute(&self, _node: NodeKind, _from: (BlockID, BlockID), _into: (BlockID, BlockID)) {}
+
+    /// The root grew by a root split or shrank by collapsing into its only child,
+    /// `root` is the new root's block.
+    fn on_root_change(&self, _root: BlockID, _old_height: Height, _new_height: Height) {}
+
+    /// An operation restarted after detecting a conflict at the given level, the root is level 1.
+    fn on_restart(&self, _operation: OperationKind, _level: Level) {}
+
+    /// A node was marked obsolete, i.e. optimistic readers of it restart from now on.
+    fn on_obsolete(&self, _node: NodeKind, _block_id: BlockID) {}
+}
@@ -0,0 +1,320 @@
+use std::hash::Hash;
+use std::mem;
+use std::path::Path;
+use std::sync::atomic::fence;
+use std::sync::atomic::Ordering::SeqCst;
+use crate::block::block::{Block, BlockGuard, FRAME_DIRTY, FRAME_EVICTED, FRAME_REFERENCED};
+use crate::block::block_pool::{BlockPool, PoolData, PoolError};
+use crate::block::buffer_pool::{BufferPool, PinScope};
+use crate::page_model::{BlockID, BlockRef, Level};
+use crate::page_model::node::Node;
+use crate::tree::bplus_tree::{BPlusTree, INIT_TREE_HEIGHT, Latched};
+use crate::utils::smart_cell::{LatchType, sched_yield};
+
+/// Frames tried per `BPlusTree::release_frames`, operations fault in few frames each.
+const EVICTION_ATTEMPTS: usize = 8;
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + PoolData,
+    Payload: Default + Sync + PoolData
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Keeps at most `capacity` frames of the pooled tree resident, see `BufferPool`.
+    ///
+    /// # Panics
+    /// The tree was not created by `BPlusTree::create_pooled` or `BPlusTree::open_pooled`.
+    pub fn with_buffer_pool(mut self, capacity: usize) -> Self {
+        assert!(self.pool().is_some(), "Buffer pool of a tree without block pool");
+
+        self.buffer = Some(Self::new_buffer_pool(capacity, self.locking_strategy.latch_type()));
+        self
+    }
+
+    /// Reopens a pool like `BPlusTree::open_pooled`, but reads blocks below the root's children
+    /// not before they are accessed and keeps at most `capacity` frames resident, see `BufferPool`.
+    #[inline(always)]
+    pub fn open_paged(path: impl AsRef<Path>,
+                      capacity: usize,
+                      inc_key: fn(Key) -> Key,
+                      dec_key: fn(Key) -> Key) -> Result<Self, PoolError>
+    {
+        Self::open_pool(path, None, inc_key, dec_key, Some(capacity))
+    }
+
+    #[inline(always)]
+    pub(crate) fn new_buffer_pool(capacity: usize, latch_type: LatchType) -> BufferPool<FAN_OUT, NUM_RECORDS, Key, Payload> {
+        BufferPool::new(capacity,
+                        latch_type,
+                        Self::read_pooled_child,
+                        Self::read_pooled_child_ids,
+                        Self::write_pooled_slot)
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    #[inline(always)]
+    pub fn buffer_pool(&self) -> Option<&BufferPool<FAN_OUT, NUM_RECORDS, Key, Payload>> {
+        self.buffer.as_ref()
+    }
+
+    /// Pins the blocks an operation latches until the scope ends, see `PinScope`.
+    /// Trees without buffer pool pin nothing.
+    #[inline(always)]
+    pub(crate) fn pin_scope(&self) -> Option<PinScope> {
+        self.buffer.as_ref().map(|_| PinScope::enter())
+    }
+
+    /// Pins and latches the block, after faulting it in if evicted. The block may be evicted again
+    /// before it is latched, it is then faulted in once more.
+    pub(crate) fn latch_paged(&self,
+                              buffer: &BufferPool<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                              pool: &BlockPool,
+                              node: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                              curr_level: Level,
+                              write: bool,
+                              latch: impl Fn() -> BlockGuard<'static, FAN_OUT, NUM_RECORDS, Key, Payload>)
+                              -> Latched<'static, FAN_OUT, NUM_RECORDS, Key, Payload>
+    {
+        let block
+            = node.unsafe_borrow();
+
+        // reached through an evicted frame's block read before eviction, latching it fails
+        if block.is_evicted() && (block.is_leaf() || block.is_unlinked()) {
+            return Ok(latch());
+        }
+
+        PinScope::pin(node);
+
+        loop {
+            if block.is_evicted() {
+                self.fault_in(buffer, pool, node)?;
+            }
+
+            let guard
+                = latch();
+
+            // evicted again meanwhile, or dropped with its evicted parent, then latching it failed
+            if block.is_evicted() {
+                match block.is_unlinked() {
+                    true => break Ok(guard),
+                    false => continue
+                }
+            }
+
+            if write {
+                block.set_frame(FRAME_DIRTY);
+            }
+
+            if curr_level > INIT_TREE_HEIGHT && !block.is_leaf() {
+                buffer.touch(node);
+            }
+
+            break Ok(guard)
+        }
+    }
+
+    /// Reads the children of an evicted frame from their slots and links them to the frame's block,
+    /// unless another thread did so before. Children that are index blocks are read as evicted frames.
+    /// A corrupt slot fails with the frame's ID and leaves the frame evicted, the operation returns
+    /// the failure up its traversal, releasing its latches.
+    fn fault_in(&self,
+                buffer: &BufferPool<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                pool: &BlockPool,
+                frame: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>) -> Result<(), BlockID>
+    {
+        // faulted in by another thread meanwhile, which may have split or merged it already,
+        // or dropped with its evicted parent
+        let mut attempt = 0;
+        while frame.unsafe_borrow().is_evicted() && !frame.unsafe_borrow().is_unlinked() {
+            let guard
+                = frame.borrow_mut();
+
+            let block = match guard.deref_mut() {
+                Some(block) if block.is_evicted() => block,
+                Some(..) => return Ok(()),
+                None => {
+                    mem::drop(guard);
+
+                    attempt += 1;
+                    sched_yield(attempt);
+                    continue;
+                }
+            };
+
+            let children_read = match self.read_frame(buffer, pool, block) {
+                Some(children_read) => children_read,
+                None => {
+                    let block_id
+                        = block.block_id();
+
+                    mem::drop(guard);
+                    buffer.record_failure();
+                    return Err(block_id);
+                }
+            };
+
+            let children
+                = block.children_mut();
+
+            children_read
+                .into_iter()
+                .enumerate()
+                .for_each(|(pos, child)| *children.get_unchecked_mut(pos) = child);
+
+            mem::drop(children);
+
+            block.clear_frame(FRAME_EVICTED);
+            buffer.record_fault();
+            return Ok(());
+        }
+
+        Ok(())
+    }
+
+    /// Reads the children of an evicted frame from their slots, index blocks as evicted frames.
+    /// None if a slot is corrupt or holds fewer children than the frame has.
+    pub(crate) fn read_frame(&self,
+                             buffer: &BufferPool<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                             pool: &BlockPool,
+                             frame: &Block<FAN_OUT, NUM_RECORDS, Key, Payload>)
+                             -> Option<Vec<BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>>>
+    {
+        (buffer.read_child_ids)(pool, frame.block_id())
+            .and_then(|child_ids| child_ids
+                .into_iter()
+                .map(|child_id| (buffer.read_child)(pool, child_id, &buffer.evicted)
+                    .map(|child| self.block_manager.new_cell(child, self.locking_strategy.latch_type())))
+                .collect::<Result<Vec<_>, _>>())
+            .ok()
+            .filter(|children| children.len() == frame.children().len())
+    }
+
+    /// Evicts frames until at most the buffer pool's capacity is resident and returns the number
+    /// of frames evicted. Operations call it once they completed, hence the calling thread must
+    /// not hold latches or pins. Frames pinned by running operations are skipped, failures keep
+    /// the frame resident. At most `EVICTION_ATTEMPTS` frames are tried, later operations
+    /// release the remaining ones.
+    pub fn release_frames(&self) -> Result<usize, PoolError> {
+        let (buffer, pool) = match (&self.buffer, self.pool()) {
+            (Some(buffer), Some(pool)) => (buffer, pool),
+            _ => return Ok(0)
+        };
+
+        let mut evicted = 0;
+        for _ in 0..EVICTION_ATTEMPTS {
+            if buffer.resident() <= buffer.capacity() {
+                break;
+            }
+
+            match buffer.next_victim(&self.root.block) {
+                Some(frame) if self.evict_frame(buffer, pool, &frame)? => evicted += 1,
+                Some(..) => {}
+                None => break
+            }
+        }
+
+        Ok(evicted)
+    }
+
+    /// Writes the frame's dirty blocks back and unlinks its children, returns whether the frame
+    /// was evicted. A frame is evicted once its children are leaves or evicted frames. Busy frames
+    /// and frames with resident index children are listed again, blocks turned out to be no frame are not.
+    fn evict_frame(&self,
+                   buffer: &BufferPool<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                   pool: &BlockPool,
+                   frame: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>) -> Result<bool, PoolError>
+    {
+        let guard
+            = frame.borrow_mut();
+
+        let block = match guard.deref_mut() {
+            Some(block) => block,
+            None => {
+                buffer.touch(frame);
+                return Ok(false);
+            }
+        };
+
+        // an unlinked frame shares its leaves with the copy that replaced it
+        let index_page = match block.as_ref() {
+            Node::Index(index_page) if !block.is_evicted() && !block.is_unlinked() => index_page,
+            _ => return Ok(false)
+        };
+
+        // resident index children are evicted first, they are listed themselves
+        if index_page.children().iter().any(|child| !child.unsafe_borrow().is_leaf() && !child.unsafe_borrow().is_evicted()) {
+            buffer.touch(frame);
+            return Ok(false);
+        }
+
+        // operations pin before latching, hence a running one reading the frame's children is seen now
+        fence(SeqCst);
+        if block.is_pinned() || index_page.children().iter().any(|child| child.unsafe_borrow().is_pinned()) {
+            buffer.touch(frame);
+            return Ok(false);
+        }
+
+        let mut child_guards = index_page
+            .children()
+            .iter()
+            .map(|child| child.borrow_mut())
+            .collect::<Vec<_>>();
+
+        // index children may have been faulted in before they were latched
+        if child_guards.iter().any(|child_guard| child_guard
+            .deref_mut()
+            .is_none_or(|child| !child.is_leaf() && !child.is_evicted()))
+        {
+            mem::drop(child_guards);
+            buffer.touch(frame);
+            return Ok(false);
+        }
+
+        let dirty = child_guards
+            .iter()
+            .filter_map(|child_guard| child_guard.deref())
+            .chain([&*block])
+            .filter(|dirty| dirty.clear_frame(FRAME_DIRTY) != 0)
+            .collect::<Vec<&Block<FAN_OUT, NUM_RECORDS, Key, Payload>>>();
+
+        // written to the slots of the checkpoint in progress, the last complete one stays intact
+        let written = pool
+            .reserve()
+            .and_then(|_| dirty.iter().try_for_each(|dirty| (buffer.write_block)(pool, dirty)));
+
+        if let Err(error) = written {
+            dirty.iter().for_each(|dirty| dirty.set_frame(FRAME_DIRTY));
+            mem::drop(child_guards);
+            buffer.touch(frame);
+            return Err(error);
+        }
+
+        let write_backs
+            = dirty.len();
+
+        child_guards
+            .iter_mut()
+            .for_each(|child_guard| child_guard.mark_obsolete());
+
+        mem::drop(child_guards);
+
+        let children
+            = block.children_mut();
+
+        (0..index_page.children().len()).for_each(|pos| self.retire_block(
+            mem::replace(children.get_unchecked_mut(pos), buffer.evicted.clone())));
+
+        mem::drop(children);
+
+        block.clear_frame(FRAME_REFERENCED);
+        block.set_frame(FRAME_EVICTED);
+        buffer.record_eviction(write_backs);
+        Ok(true)
+    }
+}
@@ -0,0 +1,485 @@
+use std::hash::Hash;
+use std::mem;
+use std::path::Path;
+use std::ptr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicU8};
+use std::sync::atomic::Ordering::Relaxed;
+use bincode::Options;
+use crate::block::block::{Block, FRAME_DIRTY, FRAME_EVICTED};
+use crate::block::block_manager::BlockManager;
+use crate::block::block_pool::{BlockPool, PoolData, PoolError};
+use crate::locking::locking_strategy::LockingStrategy;
+use crate::page_model::{BlockID, BlockRef, Height, ObjectCount};
+use crate::page_model::internal_page::InternalPage;
+use crate::page_model::leaf_page::LeafPage;
+use crate::page_model::node::Node;
+use crate::record_model::record_point::RecordPoint;
+use crate::tree::bplus_tree::{BPlusTree, INIT_TREE_HEIGHT};
+use crate::tree::root::Root;
+use crate::utils::smart_cell::LatchType;
+
+const SLOT_LEAF: u8 = 1;
+const SLOT_INDEX: u8 = 2;
+
+/// Kind, padding, number of records or keys and the block's ID precede the node's content.
+const SLOT_HEADER_LEN: usize = 8;
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    #[inline(always)]
+    pub fn pool(&self) -> Option<&BlockPool> {
+        self.block_manager.pool.as_deref()
+    }
+}
+
+/// Nodes are checkpointed into fixed-size slots of a `BlockPool`: leaves as records,
+/// index pages as keys followed by the `BlockID`s of their children.
+/// Keys and payloads are copied bitwise, hence both must be `PoolData`.
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + PoolData,
+    Payload: Default + Sync + PoolData
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    #[inline(always)]
+    const fn pool_slot_size() -> usize {
+        let leaf
+            = NUM_RECORDS * (mem::size_of::<Key>() + mem::size_of::<Payload>());
+
+        let index
+            = FAN_OUT * (mem::size_of::<Key>() + mem::size_of::<BlockID>());
+
+        BlockPool::slot_size_of(SLOT_HEADER_LEN + if leaf > index { leaf } else { index })
+    }
+
+    /// Pools are only reopened by trees of the same node sizes, key and payload types.
+    #[inline(always)]
+    fn pool_layout() -> u64 {
+        crc32fast::hash(format!("{}/{}/{}:{}/{}:{}",
+                                FAN_OUT,
+                                NUM_RECORDS,
+                                Key::describe(),
+                                mem::size_of::<Key>(),
+                                Payload::describe(),
+                                mem::size_of::<Payload>()).as_bytes()) as _
+    }
+
+    /// Creates a tree backed by a new pool file of initially `pool_size` bytes, which must not exist yet.
+    /// Mutations are applied to the blocks in memory, `BPlusTree::checkpoint_pool` makes them durable.
+    pub fn create_pooled(path: impl AsRef<Path>,
+                         pool_size: usize,
+                         locking_strategy: LockingStrategy,
+                         min_key: Key,
+                         max_key: Key,
+                         inc_key: fn(Key) -> Key,
+                         dec_key: fn(Key) -> Key) -> Result<Self, PoolError>
+    {
+        let pool = Arc::new(BlockPool::create(
+            path,
+            pool_size,
+            Self::pool_slot_size(),
+            Self::pool_layout())?);
+
+        let mut config
+            = vec![0u8; 2 * mem::size_of::<Key>()];
+
+        unsafe {
+            ptr::write_unaligned(config.as_mut_ptr() as *mut Key, min_key);
+            ptr::write_unaligned(config.as_mut_ptr().add(mem::size_of::<Key>()) as *mut Key, max_key);
+        }
+
+        bincode::options().serialize_into(&mut config, &locking_strategy)?;
+        pool.set_config(&config)?;
+
+        let tree = Self::make(
+            BlockManager::with_pool(pool),
+            locking_strategy,
+            min_key,
+            max_key,
+            inc_key,
+            dec_key);
+
+        tree.checkpoint_pool()?;
+        Ok(tree)
+    }
+
+    /// Reopens a pool with the locking strategy and key bounds it was created with, the tree is
+    /// read into memory and consists of the blocks and IDs of the last `BPlusTree::checkpoint_pool`.
+    /// Since functions can't be saved, `inc_key` and `dec_key` must be supplied again.
+    #[inline(always)]
+    pub fn open_pooled(path: impl AsRef<Path>,
+                       inc_key: fn(Key) -> Key,
+                       dec_key: fn(Key) -> Key) -> Result<Self, PoolError>
+    {
+        Self::open_pool(path, None, inc_key, dec_key, None)
+    }
+
+    /// Reopens a pool like `BPlusTree::open_pooled`, but latches the tree by `locking_strategy`
+    /// instead of the one it was created with.
+    #[inline(always)]
+    pub fn open_pooled_with(path: impl AsRef<Path>,
+                            locking_strategy: LockingStrategy,
+                            inc_key: fn(Key) -> Key,
+                            dec_key: fn(Key) -> Key) -> Result<Self, PoolError>
+    {
+        Self::open_pool(path, Some(locking_strategy), inc_key, dec_key, None)
+    }
+
+    /// Reopens a pool, blocks below the root's children are left in their slots if a buffer pool's capacity is given.
+    pub(crate) fn open_pool(path: impl AsRef<Path>,
+                            locking_strategy: Option<LockingStrategy>,
+                            inc_key: fn(Key) -> Key,
+                            dec_key: fn(Key) -> Key,
+                            buffer_capacity: Option<usize>) -> Result<Self, PoolError>
+    {
+        let pool = Arc::new(BlockPool::open(
+            path,
+            Self::pool_slot_size(),
+            Self::pool_layout())?);
+
+        let config
+            = pool.config();
+
+        if config.len() < 2 * mem::size_of::<Key>() {
+            return Err(PoolError::NotAPool);
+        }
+
+        let (min_key, max_key): (Key, Key) = unsafe {
+            (ptr::read_unaligned(config.as_ptr() as *const Key),
+             ptr::read_unaligned(config.as_ptr().add(mem::size_of::<Key>()) as *const Key))
+        };
+
+        let locking_strategy = match locking_strategy {
+            Some(locking_strategy) => locking_strategy,
+            None => bincode::options().deserialize(&config[2 * mem::size_of::<Key>()..])?
+        };
+
+        let header
+            = pool.header();
+
+        let mut live
+            = vec![false; pool.block_ids() as usize];
+
+        let buffer = buffer_capacity
+            .map(|capacity| Self::new_buffer_pool(capacity, locking_strategy.latch_type()));
+
+        let block_manager
+            = BlockManager::with_pool(pool.clone());
+
+        // the root stays resident, index blocks below it are faulted in once accessed
+        let root_block = match &buffer {
+            Some(buffer) if header.height as Height > INIT_TREE_HEIGHT => Self::read_pooled_root(
+                &block_manager,
+                header.root as _,
+                locking_strategy.latch_type(),
+                &buffer.evicted,
+                &mut live)?,
+            _ => Self::read_pooled_block(
+                &block_manager,
+                header.root as _,
+                header.height as _,
+                locking_strategy.latch_type(),
+                &mut live)?
+        };
+
+        pool.set_free(Self::unused_block_ids(&live));
+
+        let mut tree = Self::make(
+            block_manager,
+            locking_strategy,
+            min_key,
+            max_key,
+            inc_key,
+            dec_key);
+
+        *tree.root.get_mut() = Root::new(root_block, header.height as _);
+        tree.buffer = buffer;
+        Ok(tree)
+    }
+
+    /// Checkpoints all blocks to their slots and makes the pool reopen to the current tree,
+    /// returns the number of blocks in the tree. Slots of blocks dropped since the last checkpoint
+    /// are handed out again. Nodes are read without latching, hence the tree must be quiescent.
+    /// A crash while checkpointing reopens the pool to the previous checkpoint, see `BlockPool`.
+    pub fn checkpoint_pool(&self) -> Result<usize, PoolError> {
+        let pool = match self.pool() {
+            Some(pool) => pool,
+            None => return Err(PoolError::NotPooled)
+        };
+
+        pool.reserve()?;
+
+        let mut live
+            = vec![false; pool.block_ids() as usize];
+
+        let root
+            = self.root.block();
+
+        Self::write_pooled_block(pool, &root, &mut live)?;
+        pool.end_checkpoint(root.unsafe_borrow().block_id(), self.height() as _)?;
+        pool.set_free(Self::unused_block_ids(&live));
+
+        Ok(live.iter().filter(|live| **live).count())
+    }
+
+    #[inline(always)]
+    fn unused_block_ids(live: &[bool]) -> Vec<BlockID> {
+        live.iter()
+            .enumerate()
+            .filter(|(.., live)| !**live)
+            .map(|(block_id, ..)| block_id as _)
+            .collect()
+    }
+
+    /// Writes the block and all blocks below it. Evicted frames and the blocks below them
+    /// are already written, only their IDs are marked as live.
+    fn write_pooled_block(pool: &BlockPool,
+                          block: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                          live: &mut [bool]) -> Result<(), PoolError>
+    {
+        let block
+            = block.unsafe_borrow();
+
+        if block.is_evicted() {
+            return Self::mark_pooled_live(pool, block.block_id(), live);
+        }
+
+        live[block.block_id() as usize] = true;
+
+        Self::write_pooled_slot(pool, block)?;
+        block.clear_frame(FRAME_DIRTY);
+
+        match block.as_ref() {
+            Node::Index(index_page) => index_page
+                .children()
+                .iter()
+                .try_for_each(|child| Self::write_pooled_block(pool, child, live)),
+            Node::Leaf(..) => Ok(())
+        }
+    }
+
+    /// Writes the block to its slot, children are referenced by their IDs.
+    pub(crate) fn write_pooled_slot(pool: &BlockPool, block: &Block<FAN_OUT, NUM_RECORDS, Key, Payload>) -> Result<(), PoolError> {
+        let block_id
+            = block.block_id();
+
+        pool.with_slot_mut(block_id, |slot| unsafe {
+            ptr::write_unaligned(slot.add(4) as *mut BlockID, block_id);
+
+            match block.as_ref() {
+                Node::Leaf(leaf_page) => {
+                    let records
+                        = leaf_page.as_records();
+
+                    *slot = SLOT_LEAF;
+                    ptr::write_unaligned(slot.add(2) as *mut ObjectCount, records.len() as _);
+
+                    records.iter().enumerate().for_each(|(pos, record)| {
+                        let offset
+                            = SLOT_HEADER_LEN + pos * (mem::size_of::<Key>() + mem::size_of::<Payload>());
+
+                        ptr::write_unaligned(slot.add(offset) as *mut Key, record.key);
+                        ptr::write_unaligned(slot.add(offset + mem::size_of::<Key>()) as *mut Payload, record.payload);
+                    });
+                }
+                Node::Index(index_page) => {
+                    let keys
+                        = index_page.keys();
+
+                    *slot = SLOT_INDEX;
+                    ptr::write_unaligned(slot.add(2) as *mut ObjectCount, keys.len() as _);
+
+                    keys.iter().enumerate().for_each(|(pos, key)|
+                        ptr::write_unaligned(slot.add(SLOT_HEADER_LEN + pos * mem::size_of::<Key>()) as *mut Key, *key));
+
+                    index_page.children().iter().enumerate().for_each(|(pos, child)|
+                        ptr::write_unaligned(slot
+                            .add(SLOT_HEADER_LEN + FAN_OUT * mem::size_of::<Key>() + pos * mem::size_of::<BlockID>())
+                            as *mut BlockID, child.unsafe_borrow().block_id()));
+                }
+            }
+        })
+    }
+
+    /// Reads the block and all blocks below it, `height` is the number of levels including the block's.
+    fn read_pooled_block(block_manager: &BlockManager<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                         block_id: BlockID,
+                         height: Height,
+                         latch_type: LatchType,
+                         live: &mut [bool]) -> Result<BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>, PoolError>
+    {
+        if height < INIT_TREE_HEIGHT || live.get(block_id as usize) != Some(&false) {
+            return Err(PoolError::CorruptSlot(block_id));
+        }
+
+        live[block_id as usize] = true;
+
+        let pool = block_manager.pool
+            .as_ref()
+            .ok_or(PoolError::NotPooled)?;
+
+        if height == INIT_TREE_HEIGHT {
+            return Self::read_pooled_leaf(pool, block_id)
+                .map(|leaf| block_manager.new_cell(leaf, latch_type));
+        }
+
+        let (keys, child_ids)
+            = Self::read_pooled_index(pool, block_id)?;
+
+        let children = child_ids
+            .into_iter()
+            .map(|child_id| Self::read_pooled_block(block_manager, child_id, height - 1, latch_type, live))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(block_manager.new_cell(Self::new_pooled_index(block_id, keys, children), latch_type))
+    }
+
+    /// Reads the root with its children, index blocks below the root are read as evicted frames
+    /// referring to `evicted`, all blocks below the root are marked as live.
+    fn read_pooled_root(block_manager: &BlockManager<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                        block_id: BlockID,
+                        latch_type: LatchType,
+                        evicted: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                        live: &mut [bool]) -> Result<BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>, PoolError>
+    {
+        let pool = block_manager.pool
+            .as_ref()
+            .ok_or(PoolError::NotPooled)?;
+
+        Self::mark_pooled_live(pool, block_id, live)?;
+
+        let (keys, child_ids)
+            = Self::read_pooled_index(pool, block_id)?;
+
+        let children = child_ids
+            .into_iter()
+            .map(|child_id| Self::read_pooled_child(pool, child_id, evicted)
+                .map(|child| block_manager.new_cell(child, latch_type)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(block_manager.new_cell(Self::new_pooled_index(block_id, keys, children), latch_type))
+    }
+
+    /// Marks the block and all blocks below it as live, only the slots of index blocks are read.
+    fn mark_pooled_live(pool: &BlockPool, block_id: BlockID, live: &mut [bool]) -> Result<(), PoolError> {
+        match live.get_mut(block_id as usize) {
+            Some(live) if !*live => *live = true,
+            _ => return Err(PoolError::CorruptSlot(block_id))
+        }
+
+        Self::read_pooled_child_ids(pool, block_id)?
+            .into_iter()
+            .try_for_each(|child_id| Self::mark_pooled_live(pool, child_id, live))
+    }
+
+    /// Reads a leaf from its slot, or an index block as evicted frame whose children refer to `evicted`.
+    pub(crate) fn read_pooled_child(pool: &BlockPool,
+                                    block_id: BlockID,
+                                    evicted: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>)
+                                    -> Result<Block<FAN_OUT, NUM_RECORDS, Key, Payload>, PoolError>
+    {
+        if pool.with_slot(block_id, |slot| unsafe { *slot })? == SLOT_LEAF {
+            return Self::read_pooled_leaf(pool, block_id);
+        }
+
+        let (keys, child_ids)
+            = Self::read_pooled_index(pool, block_id)?;
+
+        let index_block
+            = Self::new_pooled_index(block_id, keys, vec![evicted.clone(); child_ids.len()]);
+
+        index_block.frame.store(FRAME_EVICTED, Relaxed);
+        Ok(index_block)
+    }
+
+    /// Reads the keys and the IDs of the children of an index block from its slot.
+    fn read_pooled_index(pool: &BlockPool, block_id: BlockID) -> Result<(Vec<Key>, Vec<BlockID>), PoolError> {
+        let (kind, len, stored_id) = pool.with_slot(block_id, |slot| unsafe {
+            (*slot,
+             ptr::read_unaligned(slot.add(2) as *const ObjectCount) as usize,
+             ptr::read_unaligned(slot.add(4) as *const BlockID))
+        })?;
+
+        if kind != SLOT_INDEX || stored_id != block_id || len >= FAN_OUT {
+            return Err(PoolError::CorruptSlot(block_id));
+        }
+
+        let keys: Vec<Key> = pool.with_slot(block_id, |slot| (0..len)
+            .map(|pos| unsafe { ptr::read_unaligned(slot.add(SLOT_HEADER_LEN + pos * mem::size_of::<Key>()) as *const Key) })
+            .collect())?;
+
+        Ok((keys, Self::read_pooled_child_ids(pool, block_id)?))
+    }
+
+    /// Index block read from its slot, it is clean until changed.
+    fn new_pooled_index(block_id: BlockID,
+                        keys: Vec<Key>,
+                        children: Vec<BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>>) -> Block<FAN_OUT, NUM_RECORDS, Key, Payload>
+    {
+        let index_block = Block {
+            node_data: Node::Index(InternalPage::new()),
+            block_id,
+            frame: AtomicU8::new(0),
+            pins: AtomicU32::new(0),
+        };
+
+        // the number of children follows the number of keys, hence both are set at once
+        index_block.children_mut().extend(children);
+        index_block.keys_mut().extend(keys);
+        index_block
+    }
+
+    /// Reads a leaf from its slot, the leaf is clean until changed.
+    pub(crate) fn read_pooled_leaf(pool: &BlockPool,
+                                   block_id: BlockID) -> Result<Block<FAN_OUT, NUM_RECORDS, Key, Payload>, PoolError>
+    {
+        let (kind, len, stored_id) = pool.with_slot(block_id, |slot| unsafe {
+            (*slot,
+             ptr::read_unaligned(slot.add(2) as *const ObjectCount) as usize,
+             ptr::read_unaligned(slot.add(4) as *const BlockID))
+        })?;
+
+        if kind != SLOT_LEAF || stored_id != block_id || len > NUM_RECORDS {
+            return Err(PoolError::CorruptSlot(block_id));
+        }
+
+        let leaf = Block {
+            node_data: Node::Leaf(LeafPage::new()),
+            block_id,
+            frame: AtomicU8::new(0),
+            pins: AtomicU32::new(0),
+        };
+
+        pool.with_slot(block_id, |slot| leaf.records_mut().extend((0..len).map(|pos| unsafe {
+            let offset
+                = SLOT_HEADER_LEN + pos * (mem::size_of::<Key>() + mem::size_of::<Payload>());
+
+            RecordPoint::new(
+                ptr::read_unaligned(slot.add(offset) as *const Key),
+                ptr::read_unaligned(slot.add(offset + mem::size_of::<Key>()) as *const Payload))
+        })))?;
+
+        Ok(leaf)
+    }
+
+    /// Reads the IDs of an index block's children from its slot.
+    pub(crate) fn read_pooled_child_ids(pool: &BlockPool, block_id: BlockID) -> Result<Vec<BlockID>, PoolError> {
+        pool.with_slot(block_id, |slot| unsafe {
+            let len = match *slot {
+                SLOT_INDEX => (ptr::read_unaligned(slot.add(2) as *const ObjectCount) as usize + 1).min(FAN_OUT),
+                _ => 0
+            };
+
+            (0..len)
+                .map(|pos| ptr::read_unaligned(slot
+                    .add(SLOT_HEADER_LEN + FAN_OUT * mem::size_of::<Key>() + pos * mem::size_of::<BlockID>())
+                    as *const BlockID))
+                .collect()
+        })
+    }
+}
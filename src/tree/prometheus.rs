@@ -0,0 +1,279 @@
+use std::fmt::Write as FmtWrite;
+use std::hash::Hash;
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::{Acquire, Release};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use crate::page_model::Height;
+use crate::tree::bplus_tree::BPlusTree;
+use crate::tree::stats::TreeStats;
+use crate::utils::metrics::{MetricsSnapshot, OperationKind};
+
+const PREFIX: &str = "ccbplustree";
+
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Clients not sending their request head or reading the response in time are dropped,
+/// hence a stalled client delays the next request and `MetricsEndpoint::drop` at most this long.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Longer request heads, i.e. request line and headers, are cut off, the remaining request is never read.
+const MAX_REQUEST_HEAD: u64 = 8 * 1024;
+
+/// Renders counters, the tree's height and, if given, its structural statistics
+/// in the Prometheus text exposition format. All samples carry the locking strategy as label.
+pub fn render(locking_strategy: &str,
+              height: Height,
+              snapshot: &MetricsSnapshot,
+              stats: Option<&TreeStats>) -> String
+{
+    let strategy
+        = format!("strategy=\"{}\"", escape_label(locking_strategy));
+
+    let mut text
+        = String::new();
+
+    let mut family = |name: &str, kind: &str, help: &str, samples: &[(String, String)]| {
+        let _ = writeln!(text, "# HELP {}_{} {}", PREFIX, name, help);
+        let _ = writeln!(text, "# TYPE {}_{} {}", PREFIX, name, kind);
+
+        samples.iter().for_each(|(labels, value)| match labels.is_empty() {
+            true => { let _ = writeln!(text, "{}_{}{{{}}} {}", PREFIX, name, strategy, value); }
+            false => { let _ = writeln!(text, "{}_{}{{{},{}}} {}", PREFIX, name, strategy, labels, value); }
+        });
+    };
+
+    let single = |value: String|
+        [(String::new(), value)];
+
+    family("height", "gauge", "Height of the tree, a single leaf root has height 1.",
+           &single(height.to_string()));
+
+    family("operations_total", "counter", "Dispatched operations by kind.",
+           &OperationKind::ALL
+               .iter()
+               .map(|kind| (format!("operation=\"{}\"", kind), snapshot.operations_of(*kind).to_string()))
+               .collect::<Vec<_>>());
+
+    family("restarts_total", "counter", "Restarts by operation kind and level, the root is level 1.",
+           &OperationKind::ALL
+               .iter()
+               .flat_map(|kind| snapshot.restarts[*kind as usize]
+                   .iter()
+                   .enumerate()
+                   .filter(|(.., restarts)| **restarts > 0)
+                   .map(move |(level, restarts)| (format!("operation=\"{}\",level=\"{}\"", kind, level + 1),
+                                                  restarts.to_string())))
+               .collect::<Vec<_>>());
+
+    family("upgrades_total", "counter", "Successful upgrades of shared or optimistic latches.",
+           &single(snapshot.upgrades.to_string()));
+
+    family("upgrade_failures_total", "counter", "Failed upgrades of shared or optimistic latches.",
+           &single(snapshot.upgrade_failures.to_string()));
+
+    family("splits_total", "counter", "Node splits including root splits.",
+           &single(snapshot.splits.to_string()));
+
+    family("merges_total", "counter", "Merges of underflowing siblings including root collapses.",
+           &single(snapshot.merges.to_string()));
+
+    family("redistributions_total", "counter", "Redistributions of underflowing siblings.",
+           &single(snapshot.redistributions.to_string()));
+
+    family("root_changes_total", "counter", "Root splits and root collapses.",
+           &single(snapshot.root_changes.to_string()));
+
+    family("appends_total", "counter", "Sequential inserts appended without traversal.",
+           &single(snapshot.appends.to_string()));
+
+    family("pessimistic_fallbacks_total", "counter",
+           "Latches optimistic strategies took pessimistically after too many restarts.",
+           &single(snapshot.pessimistic_fallbacks.to_string()));
+
+    family("backoff_spins_total", "counter", "Spinning backoffs between retries.",
+           &single(snapshot.spins.to_string()));
+
+    family("backoff_yields_total", "counter", "Yielding backoffs between retries.",
+           &single(snapshot.yields.to_string()));
+
+    family("latch_wait_seconds_total", "counter", "Time spent blocking in latch acquisitions and yields.",
+           &single(snapshot.latch_wait.as_secs_f64().to_string()));
+
+    if let Some(stats) = stats {
+        family("pages", "gauge", "Pages by kind.",
+               &[("kind=\"index\"".to_string(), stats.index_pages.to_string()),
+                   ("kind=\"leaf\"".to_string(), stats.leaf_pages.to_string())]);
+
+        family("records", "gauge", "Records held by all leaves.",
+               &single(stats.records.to_string()));
+
+        family("allocated_bytes", "gauge", "Memory held by all blocks.",
+               &single(stats.bytes_allocated.to_string()));
+
+        family("level_fill_ratio", "gauge", "Average fill of all nodes of a level.",
+               &stats.levels
+                   .iter()
+                   .map(|level| (format!("level=\"{}\"", level.level), level.avg_fill.to_string()))
+                   .collect::<Vec<_>>());
+
+        family("level_min_fill_ratio", "gauge", "Fill of the emptiest node of a level.",
+               &stats.levels
+                   .iter()
+                   .map(|level| (format!("level=\"{}\"", level.level), level.min_fill.to_string()))
+                   .collect::<Vec<_>>());
+    }
+
+    text
+}
+
+#[inline(always)]
+fn escape_label(value: &str) -> String {
+    value.chars()
+        .flat_map(|c| match c {
+            '\\' => vec!['\\', '\\'],
+            '"' => vec!['\\', '"'],
+            '\n' => vec!['\\', 'n'],
+            _ => vec![c]
+        })
+        .collect()
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Renders the concurrency counters and the height, safe while operations are running.
+    pub fn to_prometheus(&self) -> String {
+        render(&self.locking_strategy.to_string(),
+               self.height(),
+               &self.metrics.snapshot(),
+               None)
+    }
+
+    /// Additionally renders the structural statistics cached by `BPlusTree::refresh_stats`, if any.
+    /// Safe while operations are running, the statistics are those of a single snapshot taken
+    /// at the last refresh, not of the tree's current state.
+    pub fn to_prometheus_with_stats(&self) -> String {
+        render(&self.locking_strategy.to_string(),
+               self.height(),
+               &self.metrics.snapshot(),
+               self.cached_stats().as_deref())
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + Send + 'static,
+    Payload: Default + Clone + Sync + Send + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Serves `BPlusTree::to_prometheus_with_stats` via HTTP on the loopback interface, port 0 picks a free one.
+    /// Requests are answered one by one on a single background thread, each within `REQUEST_TIMEOUT`.
+    /// Structural statistics are served as of the last `BPlusTree::refresh_stats`, none before.
+    pub fn serve_metrics(self: &Arc<Self>, port: u16) -> io::Result<MetricsEndpoint> {
+        let listener
+            = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+
+        let addr
+            = listener.local_addr()?;
+
+        let shutdown
+            = Arc::new(AtomicBool::new(false));
+
+        let tree
+            = self.clone();
+
+        let handle = thread::Builder::new()
+            .name("ccbplustree-metrics".to_string())
+            .spawn({
+                let shutdown = shutdown.clone();
+
+                move || for stream in listener.incoming() {
+                    if shutdown.load(Acquire) {
+                        break;
+                    }
+
+                    if let Ok(stream) = stream {
+                        let _ = respond(stream, || tree.to_prometheus_with_stats());
+                    }
+                }
+            })?;
+
+        Ok(MetricsEndpoint {
+            addr,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+}
+
+fn respond(mut stream: TcpStream, body: impl FnOnce() -> String) -> io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut head
+        = BufReader::new(&stream).take(MAX_REQUEST_HEAD);
+
+    let mut request_line
+        = String::new();
+
+    head.read_line(&mut request_line)?;
+
+    // closing with unread headers resets the connection, possibly before the client read the response
+    let mut header
+        = String::new();
+
+    while head.read_line(&mut header)? > 0 && !header.trim_end().is_empty() {
+        header.clear();
+    }
+
+    let mut parts
+        = request_line.split_whitespace();
+
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics" | "/")) => ("200 OK", body()),
+        (Some("GET"), ..) => ("404 Not Found", String::new()),
+        _ => ("405 Method Not Allowed", String::new())
+    };
+
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+           status,
+           CONTENT_TYPE,
+           body.len(),
+           body)?;
+
+    stream.flush()
+}
+
+/// Running metrics endpoint, see `BPlusTree::serve_metrics`. Stops serving on drop.
+pub struct MetricsEndpoint {
+    addr: SocketAddr,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MetricsEndpoint {
+    #[inline(always)]
+    pub const fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for MetricsEndpoint {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Release);
+
+        if let Some(handle) = self.handle.take() {
+            let _ = TcpStream::connect(self.addr); // wakes up the blocking accept
+            let _ = handle.join();
+        }
+    }
+}
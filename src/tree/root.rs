@@ -28,7 +28,7 @@ impl<const FAN_OUT: usize,
     Payload: Default + Clone
 > Display for Root<FAN_OUT, NUM_RECORDS, Key, Payload> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Root(height={})", self.height())
+        write!(f, "Root(id={};height={})", self.block_id(), self.height())
     }
 }
 
@@ -43,10 +43,10 @@ impl<const FAN_OUT: usize,
     const NUM_RECORDS: usize,
     Key: Default + Ord + Copy + Hash,
     Payload: Default + Clone
-> Into<Root<FAN_OUT, NUM_RECORDS, Key, Payload>> for (BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>, Height) {
+> From<(BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>, Height)> for Root<FAN_OUT, NUM_RECORDS, Key, Payload> {
     #[inline(always)]
-    fn into(self) -> Root<FAN_OUT, NUM_RECORDS, Key, Payload> {
-        Root::new(self.0, self.1)
+    fn from((block, height): (BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>, Height)) -> Self {
+        Root::new(block, height)
     }
 }
 
@@ -0,0 +1,399 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::fs::File;
+use std::hash::Hash;
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use crate::locking::locking_strategy::LockingStrategy;
+use crate::page_model::node::Node;
+use crate::page_model::{BlockID, BlockRef};
+use crate::record_model::record_point::RecordPoint;
+use crate::tree::bplus_tree::BPlusTree;
+use crate::tree::wal::Lsn;
+
+pub const SNAPSHOT_MAGIC: [u8; 8] = *b"CCBPTREE";
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/// Bytes of `SNAPSHOT_MAGIC` and `SNAPSHOT_VERSION` preceding the checksummed body.
+const SNAPSHOT_PREAMBLE_LEN: u64 = (SNAPSHOT_MAGIC.len() + 4) as u64;
+
+/// Tree configuration preceding the records of a snapshot, see `BPlusTree::save_to`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SnapshotHeader<Key> {
+    pub fan_out: u64,
+    pub num_records: u64,
+    pub locking_strategy: LockingStrategy,
+    pub min_key: Key,
+    pub max_key: Key,
+    /// Log position the snapshot contains all mutations up to, see `BPlusTree::checkpoint`.
+    pub lsn: Lsn,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Encoding(bincode::Error),
+    /// The file does not start with `SNAPSHOT_MAGIC`.
+    NotASnapshot,
+    UnsupportedVersion(u32),
+    ChecksumMismatch {
+        stored: u32,
+        computed: u32,
+    },
+    /// Records are not strictly ascending, although their checksum matched.
+    UnsortedRecords,
+    /// The leaves of the evicted frame could not be read from the block pool, see `BufferPool`.
+    UnreadableFrame(BlockID),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Io(error) => write!(f, "Snapshot I/O failed: {}", error),
+            SnapshotError::Encoding(error) => write!(f, "Snapshot encoding failed: {}", error),
+            SnapshotError::NotASnapshot => write!(f, "Not a snapshot file"),
+            SnapshotError::UnsupportedVersion(version) =>
+                write!(f, "Unsupported snapshot version {}, expected {}", version, SNAPSHOT_VERSION),
+            SnapshotError::ChecksumMismatch { stored, computed } =>
+                write!(f, "Snapshot checksum mismatch: stored={:#010x}, computed={:#010x}", stored, computed),
+            SnapshotError::UnsortedRecords => write!(f, "Snapshot records are not strictly ascending"),
+            SnapshotError::UnreadableFrame(block_id) => write!(f, "Leaves of block {} could not be read", block_id)
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SnapshotError::Io(error) => Some(error),
+            SnapshotError::Encoding(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(error: io::Error) -> Self {
+        SnapshotError::Io(error)
+    }
+}
+
+impl From<bincode::Error> for SnapshotError {
+    fn from(error: bincode::Error) -> Self {
+        SnapshotError::Encoding(error)
+    }
+}
+
+/// Computes the CRC32 of all bytes passing through.
+struct Checksummed<S> {
+    inner: S,
+    hasher: crc32fast::Hasher,
+}
+
+impl<S> Checksummed<S> {
+    #[inline(always)]
+    fn new(inner: S) -> Self {
+        Self {
+            inner,
+            hasher: crc32fast::Hasher::new(),
+        }
+    }
+
+    #[inline(always)]
+    fn checksum(&self) -> u32 {
+        self.hasher.clone().finalize()
+    }
+}
+
+impl<W: Write> Write for Checksummed<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written
+            = self.inner.write(buf)?;
+
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Visits all leaves in key order until `visit` fails, or the children of an evicted frame
+    /// can't be read, which fails by `unreadable`.
+    pub(crate) fn try_for_each_leaf<E>(&self,
+                            block: &BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                            visit: &mut impl FnMut(&[RecordPoint<Key, Payload>]) -> Result<(), E>,
+                            unreadable: fn(BlockID) -> E) -> Result<(), E>
+    {
+        match block.unsafe_borrow().as_ref() {
+            Node::Index(..) => self
+                .children_of(block.unsafe_borrow())
+                .map_err(unreadable)?
+                .iter()
+                .try_for_each(|child| self.try_for_each_leaf(child, visit, unreadable)),
+            Node::Leaf(leaf_page) => visit(leaf_page.as_records())
+        }
+    }
+}
+
+/// Writes a snapshot aside and renames it once synced, hence an existing snapshot is replaced atomically.
+/// Records are written in chunks, each preceded by its length, an empty chunk ends them.
+pub(crate) struct SnapshotWriter {
+    path: PathBuf,
+    temp_path: PathBuf,
+    body: Checksummed<BufWriter<File>>,
+    records: usize,
+}
+
+impl SnapshotWriter {
+    pub(crate) fn create<Key: Serialize>(path: &Path, header: &SnapshotHeader<Key>) -> Result<Self, SnapshotError> {
+        let mut temp_path
+            = path.as_os_str().to_owned();
+
+        temp_path.push(".tmp");
+
+        let mut writer
+            = BufWriter::new(File::create(&temp_path)?);
+
+        writer.write_all(&SNAPSHOT_MAGIC)?;
+        writer.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+
+        let mut body
+            = Checksummed::new(writer);
+
+        bincode::options().serialize_into(&mut body, header)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            temp_path: temp_path.into(),
+            body,
+            records: 0,
+        })
+    }
+
+    /// Appends strictly ascending records, all greater than the ones written before.
+    pub(crate) fn write_chunk<Key, Payload>(&mut self, records: &[RecordPoint<Key, Payload>]) -> Result<(), SnapshotError>
+        where Key: Ord + Copy + Hash + Default + Serialize, Payload: Clone + Default + Serialize
+    {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        bincode::options().serialize_into(&mut self.body, &(records.len() as u64))?;
+
+        records
+            .iter()
+            .try_for_each(|record| bincode::options()
+                .serialize_into(&mut self.body, &(record.key_ref(), record.payload_ref())))?;
+
+        self.records += records.len();
+        Ok(())
+    }
+
+    /// Ends the records, appends the checksum and replaces the snapshot, returns the number of records.
+    pub(crate) fn finish(mut self) -> Result<usize, SnapshotError> {
+        bincode::options().serialize_into(&mut self.body, &0u64)?;
+
+        let checksum
+            = self.body.checksum();
+
+        let mut writer
+            = self.body.inner;
+
+        writer.write_all(&checksum.to_le_bytes())?;
+
+        writer.into_inner()
+            .map_err(|error| error.into_error())?
+            .sync_all()?;
+
+        fs::rename(&self.temp_path, &self.path)?;
+        Ok(self.records)
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    #[inline(always)]
+    pub(crate) fn snapshot_header(&self, lsn: Lsn) -> SnapshotHeader<Key> {
+        SnapshotHeader {
+            fan_out: FAN_OUT as _,
+            num_records: NUM_RECORDS as _,
+            locking_strategy: self.locking_strategy.clone(),
+            min_key: self.min_key,
+            max_key: self.max_key,
+            lsn,
+        }
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + Serialize + 'static,
+    Payload: Default + Clone + Sync + Serialize + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Writes all records in key order together with the tree's configuration and returns their number.
+    /// The file holds `SNAPSHOT_MAGIC` and `SNAPSHOT_VERSION`, followed by the header, the records
+    /// and the CRC32 of both, see `SnapshotWriter`. Nodes are read without latching, hence the tree
+    /// must be quiescent. Durable trees may use `BPlusTree::checkpoint` while operations are running.
+    #[inline]
+    pub fn save_to(&self, path: impl AsRef<Path>) -> Result<usize, SnapshotError> {
+        let lsn = self
+            .log()
+            .map(|log| log.appended())
+            .unwrap_or_default();
+
+        self.save_snapshot(path.as_ref(), lsn)
+    }
+
+    /// Saves a snapshot claiming to cover the log up to `lsn`.
+    pub(crate) fn save_snapshot(&self, path: &Path, lsn: Lsn) -> Result<usize, SnapshotError> {
+        let mut writer
+            = SnapshotWriter::create(path, &self.snapshot_header(lsn))?;
+
+        self.try_for_each_leaf(
+            &self.root.get().block.clone(),
+            &mut |leaf| writer.write_chunk(leaf),
+            SnapshotError::UnreadableFrame)?;
+
+        writer.finish()
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + DeserializeOwned + 'static,
+    Payload: Default + Clone + Sync + DeserializeOwned + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Reads a snapshot written by `save_to` and bulk builds a tree from it with the saved
+    /// locking strategy and key bounds. The saved FAN_OUT and NUM_RECORDS are informational,
+    /// records are rebuilt into this tree's node sizes. Since functions can't be saved,
+    /// `inc_key` and `dec_key` must be supplied again.
+    #[inline]
+    pub fn load_from(path: impl AsRef<Path>,
+                     inc_key: fn(Key) -> Key,
+                     dec_key: fn(Key) -> Key) -> Result<Self, SnapshotError>
+    {
+        Self::load_snapshot(path.as_ref(), inc_key, dec_key).map(|(tree, ..)| tree)
+    }
+
+    pub(crate) fn load_snapshot(path: &Path,
+                                inc_key: fn(Key) -> Key,
+                                dec_key: fn(Key) -> Key) -> Result<(Self, SnapshotHeader<Key>), SnapshotError>
+    {
+        let file
+            = File::open(path)?;
+
+        // the body lies between the magic and version and the trailing checksum
+        let body_len = file
+            .metadata()?
+            .len()
+            .saturating_sub(SNAPSHOT_PREAMBLE_LEN + 4);
+
+        let mut reader
+            = BufReader::new(file);
+
+        let mut magic
+            = [0u8; SNAPSHOT_MAGIC.len()];
+
+        reader.read_exact(&mut magic)?;
+
+        if magic != SNAPSHOT_MAGIC {
+            return Err(SnapshotError::NotASnapshot);
+        }
+
+        let mut version
+            = [0u8; 4];
+
+        reader.read_exact(&mut version)?;
+
+        match u32::from_le_bytes(version) {
+            SNAPSHOT_VERSION => {}
+            version => return Err(SnapshotError::UnsupportedVersion(version))
+        }
+
+        // verified over the raw body before decoding it, a corrupted length prefix
+        // would otherwise be trusted, e.g. to allocate, before the mismatch is noticed
+        let mut checksum
+            = Checksummed::new(io::sink());
+
+        io::copy(&mut reader.by_ref().take(body_len), &mut checksum)?;
+
+        let computed
+            = checksum.checksum();
+
+        let mut stored
+            = [0u8; 4];
+
+        reader.read_exact(&mut stored)?;
+
+        let stored
+            = u32::from_le_bytes(stored);
+
+        if stored != computed {
+            return Err(SnapshotError::ChecksumMismatch { stored, computed });
+        }
+
+        reader.seek(SeekFrom::Start(SNAPSHOT_PREAMBLE_LEN))?;
+
+        // no value decoded exceeds the body, even if written by a different build
+        let options = bincode::options()
+            .with_limit(body_len);
+
+        let mut body
+            = reader.take(body_len);
+
+        let header: SnapshotHeader<Key>
+            = options.deserialize_from(&mut body)?;
+
+        let mut records
+            = vec![];
+
+        loop {
+            let chunk: u64
+                = options.deserialize_from(&mut body)?;
+
+            if chunk == 0 {
+                break;
+            }
+
+            for _ in 0..chunk {
+                let (key, payload)
+                    = options.deserialize_from(&mut body)?;
+
+                records.push(RecordPoint::new(key, payload));
+            }
+        }
+
+        if records.windows(2).any(|pair| pair[0].key >= pair[1].key) {
+            return Err(SnapshotError::UnsortedRecords);
+        }
+
+        let tree = Self::new_with(
+            header.locking_strategy.clone(),
+            header.min_key,
+            header.max_key,
+            inc_key,
+            dec_key);
+
+        tree.build_bottom_up(records, tree.bulk_leaf_capacity(), tree.bulk_index_capacity());
+        Ok((tree, header))
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::mem;
+use std::sync::Arc;
+use itertools::Itertools;
+use crate::block::block::Block;
+use crate::page_model::{BlockID, BlockRef, Height, Level};
+use crate::page_model::node::Node;
+use crate::tree::bplus_tree::{BPlusTree, INIT_TREE_HEIGHT};
+use crate::utils::slab::SlabUsage;
+
+/// Occupancy of all nodes of a single level, the root is located at level 1.
+#[derive(Clone, Debug, Default)]
+pub struct LevelStats {
+    pub level: Level,
+    pub nodes: usize,
+    /// Keys for index pages, records for leaves.
+    pub entries: usize,
+    /// Entries a single node of this level can hold before it overflows.
+    pub allocation: usize,
+    pub min_fill: f64,
+    /// Node filled least, i.e. the first one filled `min_fill`.
+    pub min_fill_block: BlockID,
+    pub avg_fill: f64,
+}
+
+/// Structural snapshot of a tree, see `BPlusTree::stats` and `BPlusTree::refresh_stats`.
+#[derive(Clone, Debug, Default)]
+pub struct TreeStats {
+    pub height: Height,
+    pub levels: Vec<LevelStats>,
+    pub index_pages: usize,
+    pub leaf_pages: usize,
+    pub records: usize,
+    /// IDs handed out by the tree's block manager so far, see `BlockManager::block_ids`.
+    pub block_ids: BlockID,
+    /// Size of a single block's data.
+    pub block_bytes: usize,
+    /// Size of a single block's slab slot including its latch and reference count.
+    pub cell_bytes: usize,
+    /// Total memory held by all blocks, i.e. `cell_bytes` for each node.
+    pub bytes_allocated: usize,
+    /// Number of leaves by record count, i.e. `records_per_leaf[n]` leaves hold n records.
+    pub records_per_leaf: Vec<usize>,
+}
+
+impl TreeStats {
+    #[inline(always)]
+    pub fn nodes(&self) -> usize {
+        self.index_pages + self.leaf_pages
+    }
+}
+
+impl Display for LevelStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Level(level={};nodes={};entries={};allocation={};min_fill={:.3};min_fill_block={};avg_fill={:.3})",
+               self.level,
+               self.nodes,
+               self.entries,
+               self.allocation,
+               self.min_fill,
+               self.min_fill_block,
+               self.avg_fill)
+    }
+}
+
+impl Display for TreeStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TreeStats(height={};index_pages={};leaf_pages={};records={};block_ids={};\
+                   block_bytes={};cell_bytes={};bytes_allocated={})",
+               self.height,
+               self.index_pages,
+               self.leaf_pages,
+               self.records,
+               self.block_ids,
+               self.block_bytes,
+               self.cell_bytes,
+               self.bytes_allocated)?;
+
+        self.levels
+            .iter()
+            .try_for_each(|level| write!(f, "\n\t{}", level))?;
+
+        write!(f, "\n\tRecordsPerLeaf([{}])", self.records_per_leaf
+            .iter()
+            .enumerate()
+            .filter(|(.., leaves)| **leaves > 0)
+            .map(|(records, leaves)| format!("{}:{}", records, leaves))
+            .join(","))
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Walks the tree in level order and collects its structural statistics.
+    /// Nodes are read without latching, hence the tree must be quiescent.
+    /// Leaves of evicted frames that can't be read are left out, see `BPlusTree::validate`.
+    pub fn stats(&self) -> TreeStats {
+        let root
+            = self.root.get();
+
+        let block_bytes
+            = mem::size_of::<Block<FAN_OUT, NUM_RECORDS, Key, Payload>>();
+
+        let cell_bytes
+            = self.block_manager.cell_bytes();
+
+        let mut stats = TreeStats {
+            height: root.height(),
+            block_ids: self.block_manager.block_ids(),
+            block_bytes,
+            cell_bytes,
+            records_per_leaf: vec![0; NUM_RECORDS + 1],
+            ..TreeStats::default()
+        };
+
+        let mut queue: VecDeque<(Level, BlockRef<FAN_OUT, NUM_RECORDS, Key, Payload>)>
+            = VecDeque::new();
+
+        queue.push_back((INIT_TREE_HEIGHT, root.block.clone()));
+
+        while let Some((level, block)) = queue.pop_front() {
+            let node: &Node<FAN_OUT, NUM_RECORDS, Key, Payload>
+                = block.unsafe_borrow().as_ref();
+
+            let allocation = match node {
+                Node::Index(..) => {
+                    stats.index_pages += 1;
+
+                    queue.extend(self
+                        .children_of(block.unsafe_borrow())
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|child| (level + 1, child.clone())));
+
+                    self.block_manager.allocation_directory()
+                }
+                Node::Leaf(leaf_page) => {
+                    stats.leaf_pages += 1;
+                    stats.records += leaf_page.len();
+
+                    if let Some(leaves) = stats.records_per_leaf.get_mut(leaf_page.len()) {
+                        *leaves += 1;
+                    }
+
+                    self.block_manager.allocation_leaf()
+                }
+            };
+
+            let fill
+                = node.len() as f64 / allocation as f64;
+
+            if stats.levels.last().map(|last| last.level != level).unwrap_or(true) {
+                stats.levels.push(LevelStats {
+                    level,
+                    allocation,
+                    min_fill: fill,
+                    min_fill_block: block.unsafe_borrow().block_id(),
+                    ..LevelStats::default()
+                });
+            }
+
+            let level_stats
+                = stats.levels.last_mut().unwrap();
+
+            level_stats.nodes += 1;
+            level_stats.entries += node.len();
+            if fill < level_stats.min_fill {
+                level_stats.min_fill = fill;
+                level_stats.min_fill_block = block.unsafe_borrow().block_id();
+            }
+        }
+
+        stats.levels
+            .iter_mut()
+            .for_each(|level| level.avg_fill =
+                level.entries as f64 / (level.nodes * level.allocation) as f64);
+
+        stats.bytes_allocated
+            = stats.nodes() * cell_bytes;
+
+        stats
+    }
+
+    /// Takes a new snapshot by `stats` and caches it for `cached_stats`, hence the tree must be quiescent,
+    /// e.g. right after a bulk load or a checkpoint.
+    pub fn refresh_stats(&self) -> Arc<TreeStats> {
+        let stats
+            = Arc::new(self.stats());
+
+        *self.cached_stats.lock() = Some(stats.clone());
+        stats
+    }
+
+    /// Snapshot taken by the last `refresh_stats`, if any. Safe while operations are running.
+    pub fn cached_stats(&self) -> Option<Arc<TreeStats>> {
+        self.cached_stats
+            .lock()
+            .clone()
+    }
+
+    /// Memory reserved and used by the slab holding the tree's blocks, see `Slab::usage`.
+    /// Slots of unlinked blocks are counted until their last reference drops.
+    pub fn slab_usage(&self) -> SlabUsage {
+        self.block_manager.slab_usage()
+    }
+}
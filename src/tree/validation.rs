@@ -0,0 +1,245 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use itertools::Itertools;
+use crate::block::block::Block;
+use crate::page_model::{BlockID, Height};
+use crate::page_model::node::Node;
+use crate::tree::bplus_tree::{BPlusTree, INIT_TREE_HEIGHT};
+
+/// Child positions from the root down to a node, the root's path is empty.
+pub type NodePath = Vec<usize>;
+
+/// Single invariant violation found by `BPlusTree::validate`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation<Key> {
+    /// Leaf records are not strictly ascending at `position`.
+    UnsortedLeafKeys { path: NodePath, position: usize },
+    /// Separator keys are not strictly ascending at `position`.
+    UnsortedIndexKeys { path: NodePath, position: usize },
+    /// A key lies outside the bounds its ancestors' separators route to the node,
+    /// i.e. `lower <= key < upper`, where no upper bound means up to the tree's max key.
+    SeparatorBound { path: NodePath, key: Key, lower: Key, upper: Option<Key> },
+    /// A leaf is not located at the root's height.
+    LeafDepth { path: NodePath, depth: Height, height: Height },
+    /// An index page does not hold exactly one child more than keys.
+    ChildrenCount { path: NodePath, keys: usize, children: usize },
+    /// A non-root node is filled below the minimum fill of the tree's `FillPolicy`,
+    /// given the allocation the underflow correction is based on. Rightmost nodes of trees
+    /// set up for sequential inserts are exempt, unless nearly empty.
+    Underflow { path: NodePath, len: usize, allocation: usize },
+    /// The children of an evicted frame can't be read from the block pool, see `BufferPool`.
+    UnreadableFrame { path: NodePath, block_id: BlockID },
+}
+
+impl<Key: Display> Display for Violation<Key> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::UnsortedLeafKeys { path, position } =>
+                write!(f, "UnsortedLeafKeys(path=[{}];position={})", path.iter().join(","), position),
+            Violation::UnsortedIndexKeys { path, position } =>
+                write!(f, "UnsortedIndexKeys(path=[{}];position={})", path.iter().join(","), position),
+            Violation::SeparatorBound { path, key, lower, upper: Some(upper) } =>
+                write!(f, "SeparatorBound(path=[{}];key={} not in [{}, {}))",
+                       path.iter().join(","), key, lower, upper),
+            Violation::SeparatorBound { path, key, lower, upper: None } =>
+                write!(f, "SeparatorBound(path=[{}];key={} not in [{}, max])",
+                       path.iter().join(","), key, lower),
+            Violation::LeafDepth { path, depth, height } =>
+                write!(f, "LeafDepth(path=[{}];depth={};height={})", path.iter().join(","), depth, height),
+            Violation::ChildrenCount { path, keys, children } =>
+                write!(f, "ChildrenCount(path=[{}];keys={};children={})", path.iter().join(","), keys, children),
+            Violation::Underflow { path, len, allocation } =>
+                write!(f, "Underflow(path=[{}];len={};allocation={})", path.iter().join(","), len, allocation),
+            Violation::UnreadableFrame { path, block_id } =>
+                write!(f, "UnreadableFrame(path=[{}];block_id={})", path.iter().join(","), block_id),
+        }
+    }
+}
+
+/// Outcome of `BPlusTree::validate`, lists every violation instead of stopping at the first one.
+#[derive(Clone, Debug, Default)]
+pub struct ValidationReport<Key> {
+    pub height: Height,
+    pub index_pages: usize,
+    pub leaf_pages: usize,
+    pub records: usize,
+    pub violations: Vec<Violation<Key>>,
+}
+
+impl<Key> ValidationReport<Key> {
+    #[inline(always)]
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl<Key: Display> Display for ValidationReport<Key> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ValidationReport(height={};index_pages={};leaf_pages={};records={};violations={})",
+               self.height,
+               self.index_pages,
+               self.leaf_pages,
+               self.records,
+               self.violations.len())?;
+
+        self.violations
+            .iter()
+            .try_for_each(|violation| write!(f, "\n\t{}", violation))
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + Display + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Walks the whole tree and reports every invariant violation.
+    /// Nodes are read without latching, hence the tree must be quiescent, e.g. between
+    /// the phases of a stress test.
+    pub fn validate(&self) -> ValidationReport<Key> {
+        let root
+            = self.root.get();
+
+        let mut report = ValidationReport {
+            height: root.height(),
+            ..ValidationReport::default()
+        };
+
+        self.validate_node(
+            root.block.unsafe_borrow(),
+            &mut vec![],
+            INIT_TREE_HEIGHT,
+            self.min_key,
+            None,
+            &mut report);
+
+        report
+    }
+
+    fn validate_node(&self,
+                     block: &Block<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                     path: &mut NodePath,
+                     depth: Height,
+                     lower: Key,
+                     upper: Option<Key>,
+                     report: &mut ValidationReport<Key>)
+    {
+        let node: &Node<FAN_OUT, NUM_RECORDS, Key, Payload>
+            = block.as_ref();
+
+        let out_of_bounds = |key: Key|
+            key < lower || upper.map(|upper| key >= upper).unwrap_or(key > self.max_key);
+
+        // rightmost nodes of sequentially inserting trees underflow until refilled, see `is_append_target`
+        let append_target = upper.is_none() &&
+            self.append.is_some() &&
+            node.len() >= Self::append_min_len(node.is_leaf());
+
+        if !path.is_empty() && self.has_underflow(node) && !append_target {
+            report.violations.push(Violation::Underflow {
+                path: path.clone(),
+                len: node.len(),
+                allocation: match node {
+                    Node::Leaf(..) => self.block_manager.allocation_leaf() - 1,
+                    Node::Index(..) => self.block_manager.allocation_directory()
+                },
+            });
+        }
+
+        match node {
+            Node::Leaf(leaf_page) => {
+                report.leaf_pages += 1;
+                report.records += leaf_page.len();
+
+                if depth != report.height {
+                    report.violations.push(Violation::LeafDepth {
+                        path: path.clone(),
+                        depth,
+                        height: report.height,
+                    });
+                }
+
+                let records
+                    = leaf_page.as_records();
+
+                records.iter()
+                    .tuple_windows()
+                    .positions(|(left, right)| left.key >= right.key)
+                    .for_each(|position| report.violations.push(Violation::UnsortedLeafKeys {
+                        path: path.clone(),
+                        position: position + 1,
+                    }));
+
+                records.iter()
+                    .filter(|record| out_of_bounds(record.key))
+                    .for_each(|record| report.violations.push(Violation::SeparatorBound {
+                        path: path.clone(),
+                        key: record.key,
+                        lower,
+                        upper,
+                    }));
+            }
+            Node::Index(index_page) => {
+                report.index_pages += 1;
+
+                let keys
+                    = index_page.keys();
+
+                let children = match self.children_of(block) {
+                    Ok(children) => children,
+                    Err(block_id) => {
+                        report.violations.push(Violation::UnreadableFrame { path: path.clone(), block_id });
+                        Cow::Borrowed(&[][..])
+                    }
+                };
+
+                if children.len() != keys.len() + 1 {
+                    report.violations.push(Violation::ChildrenCount {
+                        path: path.clone(),
+                        keys: keys.len(),
+                        children: children.len(),
+                    });
+                }
+
+                keys.iter()
+                    .tuple_windows()
+                    .positions(|(left, right)| left >= right)
+                    .for_each(|position| report.violations.push(Violation::UnsortedIndexKeys {
+                        path: path.clone(),
+                        position: position + 1,
+                    }));
+
+                keys.iter()
+                    .filter(|key| out_of_bounds(**key))
+                    .for_each(|key| report.violations.push(Violation::SeparatorBound {
+                        path: path.clone(),
+                        key: *key,
+                        lower,
+                        upper,
+                    }));
+
+                children.iter().enumerate().for_each(|(pos, child)| {
+                    let child_lower = pos.checked_sub(1)
+                        .and_then(|pos| keys.get(pos).cloned())
+                        .unwrap_or(lower);
+
+                    let child_upper = keys.get(pos)
+                        .cloned()
+                        .or(upper);
+
+                    path.push(pos);
+                    self.validate_node(
+                        child.unsafe_borrow(),
+                        path,
+                        depth + 1,
+                        child_lower,
+                        child_upper,
+                        report);
+                    path.pop();
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,748 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::hash::Hash;
+use std::io;
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::Duration;
+use bincode::Options;
+use parking_lot::{Condvar, Mutex, MutexGuard};
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use crate::crud_model::crud_api::CRUDDispatcher;
+use crate::crud_model::crud_operation::CRUDOperation;
+use crate::crud_model::crud_operation_result::CRUDOperationResult;
+use crate::locking::locking_strategy::LockingStrategy;
+use crate::page_model::node::Node;
+use crate::tree::bplus_tree::BPlusTree;
+use crate::tree::snapshot::SnapshotError;
+
+pub const WAL_MAGIC: [u8; 8] = *b"CCBPTWAL";
+pub const WAL_VERSION: u32 = 2;
+
+/// Magic, version and the LSN preceding the first frame, i.e. the segment's base, precede the frames of a log.
+const HEADER_LEN: u64 = (WAL_MAGIC.len() + mem::size_of::<u32>() + mem::size_of::<Lsn>()) as u64;
+
+/// Each frame starts with the length and CRC32 of its encoded record.
+const FRAME_HEADER_LEN: usize = 2 * mem::size_of::<u32>();
+
+/// Position of a record in the log, counted from 1 since the log was created.
+/// Snapshots carry the position they cover, see `BPlusTree::checkpoint`.
+pub type Lsn = u64;
+
+/// Records read from the log along with their positions.
+type PositionedRecords<Key, Payload> = Vec<(Lsn, LogRecord<'static, Key, Payload>)>;
+
+/// Effect of a successful mutation. Inserts and updates are both logged as put, hence replaying
+/// a record yields the same state regardless of whether the record was applied before.
+#[derive(Serialize, Deserialize, Clone)]
+pub enum LogRecord<'a, Key, Payload: Clone> {
+    Put(Key, Cow<'a, Payload>),
+    Delete(Key),
+}
+
+/// When appended records are written and synced to disk.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FsyncPolicy {
+    /// Mutations return once their record is synced. Concurrent mutations are committed
+    /// as group, i.e. a single thread writes and syncs the records of all waiting ones.
+    Always,
+    /// Records are written and synced by a background thread in the given interval,
+    /// mutations return immediately. A crash loses at most the last interval.
+    Interval(Duration),
+    /// Mutations return once their record is written to the OS, which decides when to sync.
+    /// Survives crashes of the process, but not of the machine.
+    Never,
+}
+
+/// Files and fsync policy of a durable tree, see `BPlusTree::open_durable`.
+#[derive(Clone, Debug)]
+pub struct DurabilityConfig {
+    pub snapshot_path: PathBuf,
+    pub log_path: PathBuf,
+    pub fsync: FsyncPolicy,
+}
+
+impl DurabilityConfig {
+    #[inline(always)]
+    pub fn new(snapshot_path: impl Into<PathBuf>, log_path: impl Into<PathBuf>) -> Self {
+        Self {
+            snapshot_path: snapshot_path.into(),
+            log_path: log_path.into(),
+            fsync: FsyncPolicy::Always,
+        }
+    }
+
+    #[inline(always)]
+    pub fn with_fsync(mut self, fsync: FsyncPolicy) -> Self {
+        self.fsync = fsync;
+        self
+    }
+
+    /// Segment holding the records of a running checkpoint, i.e. the log path suffixed by `.prev`.
+    /// Removed once the checkpoint's snapshot is saved.
+    #[inline(always)]
+    pub fn previous_log_path(&self) -> PathBuf {
+        Self::suffixed(&self.log_path, ".prev")
+    }
+
+    #[inline(always)]
+    fn suffixed(path: &Path, suffix: &str) -> PathBuf {
+        let mut path
+            = path.as_os_str().to_owned();
+
+        path.push(suffix);
+        path.into()
+    }
+}
+
+#[derive(Debug)]
+pub enum WalError {
+    Io(io::Error),
+    Encoding(bincode::Error),
+    Snapshot(SnapshotError),
+    /// The file does not start with `WAL_MAGIC`.
+    NotALog,
+    UnsupportedVersion(u32),
+    /// The tree was not opened by `BPlusTree::open_durable`.
+    NotDurable,
+    /// The record of a successful mutation was not found in its leaf, hence not logged.
+    MissingRecord,
+}
+
+impl Display for WalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WalError::Io(error) => write!(f, "Log I/O failed: {}", error),
+            WalError::Encoding(error) => write!(f, "Log encoding failed: {}", error),
+            WalError::Snapshot(error) => write!(f, "{}", error),
+            WalError::NotALog => write!(f, "Not a write-ahead log file"),
+            WalError::UnsupportedVersion(version) =>
+                write!(f, "Unsupported log version {}, expected {}", version, WAL_VERSION),
+            WalError::NotDurable => write!(f, "Tree without write-ahead log"),
+            WalError::MissingRecord => write!(f, "Mutated record not found in its leaf")
+        }
+    }
+}
+
+impl Error for WalError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WalError::Io(error) => Some(error),
+            WalError::Encoding(error) => Some(error),
+            WalError::Snapshot(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for WalError {
+    fn from(error: io::Error) -> Self {
+        WalError::Io(error)
+    }
+}
+
+impl From<bincode::Error> for WalError {
+    fn from(error: bincode::Error) -> Self {
+        WalError::Encoding(error)
+    }
+}
+
+impl From<SnapshotError> for WalError {
+    fn from(error: SnapshotError) -> Self {
+        WalError::Snapshot(error)
+    }
+}
+
+struct LogState {
+    /// Frames appended but not yet written.
+    buffer: Vec<u8>,
+    appended: Lsn,
+    written: Lsn,
+    synced: Lsn,
+    /// Bytes of the current segment written entirely.
+    len: u64,
+    /// A committing thread is writing, all others wait for it.
+    writing: bool,
+    failure: Option<String>,
+}
+
+/// Failed write of a group commit, see `WriteAheadLog::write_appended`.
+enum WriteFailure {
+    /// Torn frames were cut off the log, hence the records can be written again.
+    Write(io::Error),
+    /// The log is in an unknown state, all further commits fail.
+    Fatal(io::Error),
+}
+
+/// Append-only log of a tree's mutations, see `BPlusTree::open_durable`.
+/// Records are appended to an in-memory buffer, committing writes the buffer as a whole.
+pub struct WriteAheadLog<Key, Payload: Clone> {
+    config: DurabilityConfig,
+    encode: fn(&LogRecord<'_, Key, Payload>, &mut Vec<u8>) -> bincode::Result<()>,
+    state: Mutex<LogState>,
+    written: Condvar,
+    file: Mutex<File>,
+    /// Serializes checkpoints, since each rotates the log.
+    pub(crate) checkpoint: Mutex<()>,
+}
+
+fn encode<Key: Serialize, Payload: Serialize + Clone>(record: &LogRecord<'_, Key, Payload>,
+                                                     buffer: &mut Vec<u8>) -> bincode::Result<()>
+{
+    bincode::options().serialize_into(buffer, record)
+}
+
+impl<Key, Payload: Clone> WriteAheadLog<Key, Payload> {
+    #[inline(always)]
+    pub const fn config(&self) -> &DurabilityConfig {
+        &self.config
+    }
+
+    /// Position of the last record appended.
+    #[inline(always)]
+    pub fn appended(&self) -> Lsn {
+        self.state.lock().appended
+    }
+
+    /// Frames the record into the buffer, called while the mutated leaf is latched.
+    /// Hence, records of the same key are appended in the order they were applied.
+    /// Records are encoded before the buffer is locked, failing ones are not appended.
+    pub(crate) fn append(&self, record: &LogRecord<'_, Key, Payload>) -> Result<(), WalError> {
+        let mut frame
+            = vec![0; FRAME_HEADER_LEN];
+
+        (self.encode)(record, &mut frame)?;
+
+        let frame_len
+            = (frame.len() - FRAME_HEADER_LEN) as u32;
+
+        let checksum
+            = crc32fast::hash(&frame[FRAME_HEADER_LEN..]);
+
+        frame[..4].copy_from_slice(&frame_len.to_le_bytes());
+        frame[4..FRAME_HEADER_LEN].copy_from_slice(&checksum.to_le_bytes());
+
+        let mut state
+            = self.state.lock();
+
+        state.buffer.extend_from_slice(&frame);
+        state.appended += 1;
+        Ok(())
+    }
+
+    /// Returns once all records appended so far are as durable as the fsync policy demands,
+    /// called after the mutation released its latches.
+    pub(crate) fn commit(&self) -> Result<(), WalError> {
+        match self.config.fsync {
+            FsyncPolicy::Interval(..) => match &self.state.lock().failure {
+                Some(failure) => Err(io::Error::other(failure.clone()).into()),
+                None => Ok(())
+            },
+            FsyncPolicy::Always => self.write_appended(true),
+            FsyncPolicy::Never => self.write_appended(false)
+        }
+    }
+
+    /// Writes and syncs all records appended so far, regardless of the fsync policy.
+    #[inline(always)]
+    pub fn flush(&self) -> Result<(), WalError> {
+        self.write_appended(true)
+    }
+
+    /// Group commit: the first thread to arrive writes the buffer of all appended records,
+    /// late arrivals wait for it and write the records appended meanwhile with the next round.
+    /// A failed write is cut off the log and its records are written again by the next commit.
+    /// A failed sync fails the log for good, since the OS may have dropped the unsynced pages,
+    /// i.e. syncing again could succeed without them.
+    fn write_appended(&self, sync: bool) -> Result<(), WalError> {
+        let mut state
+            = self.state.lock();
+
+        let target
+            = state.appended;
+
+        while state.written < target || (sync && state.synced < target) {
+            if let Some(failure) = &state.failure {
+                return Err(io::Error::other(failure.clone()).into());
+            }
+
+            if state.writing {
+                self.written.wait(&mut state);
+                continue;
+            }
+
+            state.writing = true;
+
+            let buffer
+                = mem::take(&mut state.buffer);
+
+            let (appended, len)
+                = (state.appended, state.len);
+
+            let result = MutexGuard::unlocked(&mut state, || {
+                let mut file
+                    = self.file.lock();
+
+                if let Err(error) = file.write_all(&buffer) {
+                    // records appended later must not follow a torn frame
+                    return Err(match file.set_len(len).and_then(|_| file.seek(SeekFrom::Start(len))) {
+                        Ok(..) => WriteFailure::Write(error),
+                        Err(..) => WriteFailure::Fatal(error)
+                    });
+                }
+
+                match sync {
+                    true => file.sync_data().map_err(WriteFailure::Fatal),
+                    false => Ok(())
+                }
+            });
+
+            state.writing = false;
+            self.written.notify_all();
+
+            match result {
+                Ok(()) => {
+                    state.written = appended;
+                    state.len = len + buffer.len() as u64;
+
+                    if sync {
+                        state.synced = appended;
+                    }
+                }
+                Err(WriteFailure::Write(error)) => {
+                    let appended_meanwhile
+                        = mem::replace(&mut state.buffer, buffer);
+
+                    state.buffer.extend_from_slice(&appended_meanwhile);
+                    return Err(error.into());
+                }
+                Err(WriteFailure::Fatal(error)) => {
+                    state.failure = Some(error.to_string());
+                    return Err(error.into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes and syncs all records appended so far and starts a new segment after them.
+    /// The current segment becomes the previous one, which a checkpoint removes once its snapshot
+    /// covers the returned position. Committing waits meanwhile, appending continues.
+    pub(crate) fn rotate(&self) -> io::Result<Lsn> {
+        let mut state
+            = self.state.lock();
+
+        while state.writing {
+            self.written.wait(&mut state);
+        }
+
+        if let Some(failure) = &state.failure {
+            return Err(io::Error::other(failure.clone()));
+        }
+
+        state.writing = true;
+
+        let buffer
+            = mem::take(&mut state.buffer);
+
+        let appended
+            = state.appended;
+
+        let result = MutexGuard::unlocked(&mut state, || -> io::Result<()> {
+            let mut file
+                = self.file.lock();
+
+            file.write_all(&buffer)?;
+            file.sync_data()?;
+
+            let segment_path
+                = DurabilityConfig::suffixed(&self.config.log_path, ".tmp");
+
+            let segment
+                = create_segment(&segment_path, appended)?;
+
+            let previous_path
+                = self.config.previous_log_path();
+
+            match previous_path.exists() {
+                false => fs::rename(&self.config.log_path, &previous_path)?,
+                true => {
+                    // a failed checkpoint left it, hence no snapshot covers its records yet
+                    let mut previous = OpenOptions::new()
+                        .append(true)
+                        .open(&previous_path)?;
+
+                    file.seek(SeekFrom::Start(HEADER_LEN))?;
+                    io::copy(&mut *file, &mut previous)?;
+                    previous.sync_data()?;
+                }
+            }
+
+            fs::rename(&segment_path, &self.config.log_path)?;
+            *file = segment;
+            Ok(())
+        });
+
+        state.writing = false;
+
+        match &result {
+            Ok(()) => {
+                state.written = appended;
+                state.synced = appended;
+                state.len = HEADER_LEN;
+            }
+            Err(error) => state.failure = Some(error.to_string()),
+        }
+
+        self.written.notify_all();
+        result.map(|_| appended)
+    }
+
+    fn spawn_flusher(log: &Arc<Self>) -> io::Result<()>
+        where Key: 'static, Payload: 'static
+    {
+        let interval = match log.config.fsync {
+            FsyncPolicy::Interval(interval) => interval,
+            _ => return Ok(())
+        };
+
+        let log
+            = Arc::downgrade(log);
+
+        thread::Builder::new()
+            .name("ccbplustree-wal".to_string())
+            .spawn(move || loop {
+                thread::sleep(interval);
+
+                // failures are reported to the next commit or flush
+                match Weak::upgrade(&log) {
+                    Some(log) => { let _ = log.flush(); }
+                    None => break
+                }
+            })
+            .map(|_| ())
+    }
+}
+
+impl<Key: Serialize + DeserializeOwned, Payload: Serialize + DeserializeOwned + Clone> WriteAheadLog<Key, Payload> {
+    /// Opens or creates the log and returns the intact records of the previous and the current segment
+    /// with their positions. A torn or corrupt frame, e.g. of a crash while writing, ends a segment
+    /// and is truncated with all frames following it. New segments start after `snapshot_lsn`.
+    fn open(config: DurabilityConfig, snapshot_lsn: Lsn)
+        -> Result<(Self, PositionedRecords<Key, Payload>), WalError>
+    {
+        let mut records
+            = vec![];
+
+        let previous_path
+            = config.previous_log_path();
+
+        if previous_path.exists() {
+            let previous = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&previous_path)?;
+
+            records.extend(Self::read_segment(&previous)?.1);
+        }
+
+        let mut last_lsn = records
+            .last()
+            .map(|(lsn, ..)| *lsn)
+            .unwrap_or(snapshot_lsn)
+            .max(snapshot_lsn);
+
+        let file = match config.log_path.exists() {
+            true => {
+                let file = OpenOptions::new()
+                    .read(true)
+                    .append(true)
+                    .open(&config.log_path)?;
+
+                let (end, segment)
+                    = Self::read_segment(&file)?;
+
+                match end < last_lsn {
+                    // the snapshot covers records never written, so positions restart after it
+                    true => create_segment(&config.log_path, last_lsn)?,
+                    false => {
+                        records.extend(segment
+                            .into_iter()
+                            .filter(|(lsn, ..)| *lsn > last_lsn));
+
+                        file
+                    }
+                }
+            }
+            false => create_segment(&config.log_path, last_lsn)?
+        };
+
+        let len
+            = file.metadata()?.len();
+
+        last_lsn = records
+            .last()
+            .map(|(lsn, ..)| *lsn)
+            .unwrap_or(last_lsn)
+            .max(last_lsn);
+
+        Ok((Self {
+            config,
+            encode: encode::<Key, Payload>,
+            state: Mutex::new(LogState {
+                buffer: vec![],
+                appended: last_lsn,
+                written: last_lsn,
+                synced: last_lsn,
+                len,
+                writing: false,
+                failure: None,
+            }),
+            written: Condvar::new(),
+            file: Mutex::new(file),
+            checkpoint: Mutex::new(()),
+        }, records))
+    }
+
+    /// Reads all intact records of a segment with their positions and truncates what follows them.
+    /// Returns the position of the segment's last record, i.e. its base if empty, along with them.
+    fn read_segment(file: &File) -> Result<(Lsn, PositionedRecords<Key, Payload>), WalError> {
+        let mut reader
+            = BufReader::new(file);
+
+        let mut magic
+            = [0u8; WAL_MAGIC.len()];
+
+        reader.read_exact(&mut magic)?;
+
+        if magic != WAL_MAGIC {
+            return Err(WalError::NotALog);
+        }
+
+        let mut version
+            = [0u8; 4];
+
+        reader.read_exact(&mut version)?;
+
+        match u32::from_le_bytes(version) {
+            WAL_VERSION => {}
+            version => return Err(WalError::UnsupportedVersion(version))
+        }
+
+        let mut base
+            = [0u8; mem::size_of::<Lsn>()];
+
+        reader.read_exact(&mut base)?;
+
+        let mut lsn
+            = Lsn::from_le_bytes(base);
+
+        let mut records
+            = vec![];
+
+        let mut valid_len
+            = HEADER_LEN;
+
+        let mut frame_header
+            = [0u8; FRAME_HEADER_LEN];
+
+        let mut frame
+            = vec![];
+
+        while reader.read_exact(&mut frame_header).is_ok() {
+            let frame_len
+                = u32::from_le_bytes(frame_header[..4].try_into().unwrap()) as usize;
+
+            let checksum
+                = u32::from_le_bytes(frame_header[4..].try_into().unwrap());
+
+            frame.resize(frame_len, 0);
+
+            if reader.read_exact(&mut frame).is_err() || crc32fast::hash(&frame) != checksum {
+                break;
+            }
+
+            match bincode::options().deserialize(&frame) {
+                Ok(record) => {
+                    lsn += 1;
+                    records.push((lsn, record))
+                }
+                Err(..) => break
+            }
+
+            valid_len += (FRAME_HEADER_LEN + frame_len) as u64;
+        }
+
+        if valid_len < file.metadata()?.len() {
+            file.set_len(valid_len)?;
+            file.sync_all()?;
+        }
+
+        Ok((lsn, records))
+    }
+}
+
+/// Creates an empty segment whose first record follows `base`.
+fn create_segment(path: &Path, base: Lsn) -> io::Result<File> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+
+    file.write_all(&WAL_MAGIC)?;
+    file.write_all(&WAL_VERSION.to_le_bytes())?;
+    file.write_all(&base.to_le_bytes())?;
+    file.sync_all()?;
+    Ok(file)
+}
+
+impl<Key, Payload: Clone> Drop for WriteAheadLog<Key, Payload> {
+    fn drop(&mut self) {
+        let state
+            = self.state.get_mut();
+
+        if state.failure.is_none() && !state.buffer.is_empty() {
+            let file
+                = self.file.get_mut();
+
+            let _ = file.write_all(&state.buffer)
+                .and_then(|_| file.sync_data());
+        }
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + 'static,
+    Payload: Default + Clone + Sync + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Appends the effect of a successful mutation to the log, if any.
+    /// Called while `leaf`, i.e. the leaf the mutation was applied to, is latched.
+    /// Mutations whose record can't be appended are reported as `NotDurable`.
+    #[inline(always)]
+    pub(crate) fn logged(&self,
+                         leaf: &Node<FAN_OUT, NUM_RECORDS, Key, Payload>,
+                         result: CRUDOperationResult<Key, Payload>) -> CRUDOperationResult<Key, Payload>
+    {
+        let appended = match (&self.log, &result) {
+            (Some(log), CRUDOperationResult::Inserted(key) | CRUDOperationResult::Updated(key, ..)) => {
+                let records
+                    = leaf.as_records();
+
+                match records.binary_search_by_key(key, |record| record.key) {
+                    Ok(pos) => log.append(&LogRecord::Put(*key, Cow::Borrowed(records[pos].payload_ref()))),
+                    Err(..) => Err(WalError::MissingRecord)
+                }
+            }
+            (Some(log), CRUDOperationResult::Deleted(key, ..)) =>
+                log.append(&LogRecord::Delete(*key)),
+            _ => Ok(())
+        };
+
+        match appended {
+            Ok(()) => result,
+            Err(error) => CRUDOperationResult::NotDurable(Box::new(result), Arc::new(error))
+        }
+    }
+
+    /// Waits for the mutation's record to become durable, called once all latches are released.
+    /// Mutations whose record can't be committed are reported as `NotDurable`, their record
+    /// is committed along with later ones, unless the log failed for good.
+    #[inline(always)]
+    pub(crate) fn commit_log(&self, result: CRUDOperationResult<Key, Payload>) -> CRUDOperationResult<Key, Payload> {
+        match (&self.log, &result) {
+            (Some(log), CRUDOperationResult::Inserted(..)
+                | CRUDOperationResult::Updated(..)
+                | CRUDOperationResult::Deleted(..)) => match log.commit() {
+                Ok(()) => result,
+                Err(error) => CRUDOperationResult::NotDurable(Box::new(result), Arc::new(error))
+            },
+            _ => result
+        }
+    }
+
+    #[inline(always)]
+    pub fn log(&self) -> Option<&WriteAheadLog<Key, Payload>> {
+        self.log.as_deref()
+    }
+}
+
+impl<const FAN_OUT: usize,
+    const NUM_RECORDS: usize,
+    Key: Default + Ord + Copy + Hash + Sync + Send + Display + Serialize + DeserializeOwned + 'static,
+    Payload: Default + Clone + Sync + Send + Display + Serialize + DeserializeOwned + 'static
+> BPlusTree<FAN_OUT, NUM_RECORDS, Key, Payload>
+{
+    /// Loads the snapshot if present, replays the log records it doesn't cover and logs all further mutations.
+    /// Without snapshot, the tree starts empty with the given locking strategy and key bounds,
+    /// otherwise the snapshot's ones are taken. The segment of an interrupted checkpoint is
+    /// saved into a new snapshot, before it is removed.
+    pub fn open_durable(config: DurabilityConfig,
+                        locking_strategy: LockingStrategy,
+                        min_key: Key,
+                        max_key: Key,
+                        inc_key: fn(Key) -> Key,
+                        dec_key: fn(Key) -> Key) -> Result<Self, WalError>
+    {
+        let (mut tree, snapshot_lsn) = match config.snapshot_path.exists() {
+            true => Self::load_snapshot(&config.snapshot_path, inc_key, dec_key)
+                .map(|(tree, header)| (tree, header.lsn))?,
+            false => (Self::new_with(locking_strategy, min_key, max_key, inc_key, dec_key), 0)
+        };
+
+        let (log, records)
+            = WriteAheadLog::open(config, snapshot_lsn)?;
+
+        records
+            .into_iter()
+            .filter(|(lsn, ..)| *lsn > snapshot_lsn)
+            .for_each(|(.., record)| tree.replay(record));
+
+        let previous_path
+            = log.config.previous_log_path();
+
+        if previous_path.exists() {
+            if snapshot_lsn < log.appended() {
+                tree.save_snapshot(&log.config.snapshot_path, log.appended())?;
+            }
+
+            fs::remove_file(previous_path)?;
+        }
+
+        let log
+            = Arc::new(log);
+
+        WriteAheadLog::spawn_flusher(&log)?;
+
+        tree.log = Some(log);
+        Ok(tree)
+    }
+
+    /// Applies a logged record, i.e. puts upsert.
+    pub(crate) fn replay(&self, record: LogRecord<'_, Key, Payload>) {
+        match record {
+            LogRecord::Put(key, payload) => {
+                let payload
+                    = payload.into_owned();
+
+                if let (.., CRUDOperationResult::Error) = self.dispatch(CRUDOperation::Update(key, payload.clone())) {
+                    self.dispatch(CRUDOperation::Insert(key, payload));
+                }
+            }
+            LogRecord::Delete(key) => {
+                self.dispatch(CRUDOperation::Delete(key));
+            }
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
+use std::mem;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, fence};
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release, SeqCst};
+use parking_lot::{const_mutex, Mutex};
+
+/// Epochs advance in steps of two, the lowest bit marks a participant as pinned.
+const EPOCH_STEP: usize = 2;
+const PINNED: usize = 1;
+
+/// Garbage is safe to free once the global epoch advanced twice past its retirement.
+const RECLAIM_DISTANCE: usize = 2 * EPOCH_STEP;
+
+/// Number of retired objects a thread buffers before trying to reclaim.
+const COLLECT_THRESHOLD: usize = 64;
+
+type Garbage = (usize, Box<dyn Send>);
+
+static GLOBAL_EPOCH: AtomicUsize = AtomicUsize::new(0);
+
+static PARTICIPANTS: Mutex<Vec<Arc<Participant>>> = const_mutex(Vec::new());
+
+/// Garbage left behind by exited threads.
+static ORPHANS: Mutex<Vec<Garbage>> = const_mutex(Vec::new());
+
+#[derive(Default)]
+struct Participant {
+    epoch: AtomicUsize,
+}
+
+/// Per-thread epoch state, registered on first use and unregistered on thread exit.
+struct Local {
+    participant: Arc<Participant>,
+    pin_depth: Cell<usize>,
+    bag: RefCell<Vec<Garbage>>,
+}
+
+impl Local {
+    fn register() -> Self {
+        let participant
+            = Arc::new(Participant::default());
+
+        PARTICIPANTS.lock().push(participant.clone());
+
+        Self {
+            participant,
+            pin_depth: Cell::new(0),
+            bag: RefCell::new(Vec::new()),
+        }
+    }
+
+    #[inline(always)]
+    fn pin(&self) {
+        let depth
+            = self.pin_depth.get();
+
+        if depth == 0 {
+            self.participant.epoch.store(GLOBAL_EPOCH.load(Relaxed) | PINNED, Relaxed);
+            fence(SeqCst);
+        }
+
+        self.pin_depth.set(depth + 1);
+    }
+
+    #[inline(always)]
+    fn unpin(&self) {
+        let depth
+            = self.pin_depth.get() - 1;
+
+        if depth == 0 {
+            self.participant.epoch.store(0, Release);
+        }
+
+        self.pin_depth.set(depth);
+    }
+
+    #[inline(always)]
+    fn retire(&self, garbage: Box<dyn Send>) {
+        fence(SeqCst);
+
+        let len = {
+            let mut bag
+                = self.bag.borrow_mut();
+
+            bag.push((GLOBAL_EPOCH.load(Relaxed), garbage));
+            bag.len()
+        };
+
+        if len >= COLLECT_THRESHOLD {
+            try_advance();
+            self.collect();
+        }
+    }
+
+    fn collect(&self) {
+        let global
+            = GLOBAL_EPOCH.load(Acquire);
+
+        let expired = {
+            let mut bag
+                = self.bag.borrow_mut();
+
+            let (expired, alive): (Vec<_>, Vec<_>) = mem::take(&mut *bag)
+                .into_iter()
+                .partition(|(epoch, ..)| is_expired(*epoch, global));
+
+            *bag = alive;
+            expired
+        };
+
+        mem::drop(expired);
+
+        if let Some(mut orphans) = ORPHANS.try_lock() {
+            let (expired, alive): (Vec<_>, Vec<_>) = mem::take(&mut *orphans)
+                .into_iter()
+                .partition(|(epoch, ..)| is_expired(*epoch, global));
+
+            *orphans = alive;
+            mem::drop(orphans);
+            mem::drop(expired);
+        }
+    }
+}
+
+impl Drop for Local {
+    fn drop(&mut self) {
+        self.participant.epoch.store(0, Release);
+
+        PARTICIPANTS.lock()
+            .retain(|participant| !Arc::ptr_eq(participant, &self.participant));
+
+        ORPHANS.lock()
+            .append(self.bag.get_mut());
+    }
+}
+
+thread_local! {
+    static LOCAL: Local = Local::register();
+}
+
+/// Garbage retired after `global` was read, e.g. orphans of a thread exiting meanwhile, is never expired.
+#[inline(always)]
+const fn is_expired(retired: usize, global: usize) -> bool {
+    global.wrapping_sub(retired) as isize >= RECLAIM_DISTANCE as isize
+}
+
+/// Advances the global epoch if every pinned participant observed the current one.
+fn try_advance() {
+    fence(SeqCst);
+
+    let global
+        = GLOBAL_EPOCH.load(Relaxed);
+
+    let all_observed = PARTICIPANTS
+        .lock()
+        .iter()
+        .map(|participant| participant.epoch.load(Relaxed))
+        .all(|local| local & PINNED == 0 || local & !PINNED == global);
+
+    if all_observed {
+        let _ = GLOBAL_EPOCH.compare_exchange(
+            global,
+            global.wrapping_add(EPOCH_STEP),
+            Release,
+            Relaxed);
+    }
+}
+
+/// Keeps the current thread pinned in the epoch it observed when pinning.
+/// Objects retired while any thread is pinned are not freed before that thread unpins.
+/// Pinning is reentrant, only the outermost guard unpins the thread.
+pub struct EpochGuard {
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for EpochGuard {
+    #[inline(always)]
+    fn drop(&mut self) {
+        let _ = LOCAL.try_with(|local| local.unpin());
+    }
+}
+
+/// Pins the current thread, see `EpochGuard`.
+#[inline(always)]
+pub fn pin() -> EpochGuard {
+    LOCAL.with(|local| local.pin());
+
+    EpochGuard { _not_send: PhantomData }
+}
+
+/// Returns true if the current thread holds at least one `EpochGuard`.
+#[inline(always)]
+pub fn is_pinned() -> bool {
+    LOCAL.try_with(|local| local.pin_depth.get() > 0)
+        .unwrap_or(false)
+}
+
+/// Defers dropping the garbage until no pinned thread may still observe it.
+#[inline(always)]
+pub fn retire<T: Send + 'static>(garbage: T) {
+    let mut garbage: Option<Box<dyn Send>>
+        = Some(Box::new(garbage));
+
+    let _ = LOCAL.try_with(|local| local.retire(garbage.take().unwrap()));
+
+    if let Some(garbage) = garbage { // thread is exiting, the epoch still has to pass
+        fence(SeqCst);
+        ORPHANS.lock().push((GLOBAL_EPOCH.load(Relaxed), garbage));
+    }
+}
+
+/// Tries to advance the global epoch and frees the current thread's expired garbage,
+/// regardless of how much garbage it buffers.
+pub fn collect() {
+    let _ = LOCAL.try_with(|local| {
+        try_advance();
+        local.collect()
+    });
+}
@@ -13,6 +13,7 @@ pub struct Interval<E: Ord + Copy + Hash> {
     pub upper: E,
 }
 
+#[allow(dead_code)]
 impl U64interval {
     #[inline(always)]
     pub(crate) const fn blank() -> Self {
@@ -121,10 +122,13 @@ impl<E: Ord + Copy + Hash> Interval<E> {
 
     #[inline(always)]
     pub fn covers_or_merge(&mut self, other: &Self) -> bool {
-        self.covers(other).then(|| true).unwrap_or_else(|| {
-            self.merged(other);
-            false
-        })
+        match self.covers(other) {
+            true => true,
+            false => {
+                self.merged(other);
+                false
+            }
+        }
     }
 
     #[inline(always)]
@@ -148,27 +152,27 @@ impl<E: Ord + Copy + Hash> Interval<E> {
     }
 }
 
-impl<E: Ord + Copy + Hash> Into<Interval<E>> for (E, E) {
-    fn into(self) -> Interval<E> {
-        Interval::new(self.0, self.1)
+impl<E: Ord + Copy + Hash> From<(E, E)> for Interval<E> {
+    fn from((lower, upper): (E, E)) -> Self {
+        Interval::new(lower, upper)
     }
 }
 
-impl<E: Ord + Copy + Hash> Into<Interval<E>> for RangeInclusive<E> {
-    fn into(self) -> Interval<E> {
-        Interval::new(*self.start(), *self.end())
+impl<E: Ord + Copy + Hash> From<RangeInclusive<E>> for Interval<E> {
+    fn from(range: RangeInclusive<E>) -> Self {
+        Interval::new(*range.start(), *range.end())
     }
 }
 
-impl Into<U64interval> for Range<u64> {
-    fn into(self) -> U64interval {
-        U64interval::new(self.start, self.end.checked_add(1).unwrap_or(u64::MAX))
+impl From<Range<u64>> for U64interval {
+    fn from(range: Range<u64>) -> Self {
+        U64interval::new(range.start, range.end.saturating_add(1))
     }
 }
 
 impl<E: Ord + Copy + Hash> PartialOrd<Self> for Interval<E> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.lower.partial_cmp(&other.lower)
+        Some(self.cmp(other))
     }
 }
 
@@ -0,0 +1,157 @@
+use std::cell::RefCell;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr};
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
+use crate::utils::smart_cell::sched_yield;
+
+/// Queue node, each waiter spins on its own node only.
+struct McsNode {
+    next: AtomicPtr<McsNode>,
+    locked: AtomicBool,
+}
+
+/// Recycled queue nodes of a thread, avoids allocations on the hot path.
+struct McsNodePool(Vec<*mut McsNode>);
+
+impl Drop for McsNodePool {
+    fn drop(&mut self) {
+        self.0
+            .drain(..)
+            .for_each(|node| unsafe { drop(Box::from_raw(node)) })
+    }
+}
+
+thread_local! {
+    static MCS_NODES: RefCell<McsNodePool> = const { RefCell::new(McsNodePool(Vec::new())) };
+}
+
+#[inline(always)]
+fn acquire_node() -> *mut McsNode {
+    let node = MCS_NODES
+        .with(|nodes| nodes.borrow_mut().0.pop())
+        .unwrap_or_else(|| Box::into_raw(Box::new(McsNode {
+            next: AtomicPtr::new(ptr::null_mut()),
+            locked: AtomicBool::new(false),
+        })));
+
+    unsafe {
+        (*node).next.store(ptr::null_mut(), Relaxed);
+        (*node).locked.store(true, Relaxed);
+    }
+
+    node
+}
+
+#[inline(always)]
+fn release_node(node: *mut McsNode) {
+    MCS_NODES.with(|nodes| nodes.borrow_mut().0.push(node));
+}
+
+/// MCS queue lock (Mellor-Crummey and Scott).
+/// Contending threads enqueue themselves and spin locally on their own queue node,
+/// the lock is handed over in FIFO order without any CAS storm on a shared word.
+pub struct McsLock {
+    tail: AtomicPtr<McsNode>,
+    holder: AtomicPtr<McsNode>,
+}
+
+impl Default for McsLock {
+    fn default() -> Self {
+        McsLock::new()
+    }
+}
+
+unsafe impl Sync for McsLock {}
+
+unsafe impl Send for McsLock {}
+
+impl McsLock {
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            tail: AtomicPtr::new(ptr::null_mut()),
+            holder: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Enqueues the caller and blocks until the lock is handed over.
+    #[inline(always)]
+    pub fn lock(&self) {
+        let node
+            = acquire_node();
+
+        let pred
+            = self.tail.swap(node, AcqRel);
+
+        if !pred.is_null() {
+            unsafe {
+                (*pred).next.store(node, Release);
+
+                let mut attempt = 0;
+                while (*node).locked.load(Acquire) {
+                    sched_yield(attempt);
+                    attempt += 1;
+                }
+            }
+        }
+
+        self.holder.store(node, Relaxed);
+    }
+
+    /// Grabs the lock only if nobody holds or waits for it.
+    #[inline(always)]
+    pub fn try_lock(&self) -> bool {
+        let node
+            = acquire_node();
+
+        match self.tail.compare_exchange(ptr::null_mut(), node, AcqRel, Relaxed) {
+            Ok(..) => {
+                self.holder.store(node, Relaxed);
+                true
+            }
+            Err(..) => {
+                release_node(node);
+                false
+            }
+        }
+    }
+
+    /// Hands the lock over to the next waiter, if any.
+    #[inline(always)]
+    pub fn unlock(&self) {
+        let node
+            = self.holder.load(Relaxed);
+
+        unsafe {
+            let mut next
+                = (*node).next.load(Acquire);
+
+            if next.is_null() {
+                if self.tail.compare_exchange(node, ptr::null_mut(), AcqRel, Relaxed).is_ok() {
+                    release_node(node);
+                    return;
+                }
+
+                let mut attempt = 0;
+                loop {
+                    next = (*node).next.load(Acquire);
+                    if !next.is_null() {
+                        break;
+                    }
+
+                    sched_yield(attempt);
+                    attempt += 1;
+                }
+            }
+
+            (*next).locked.store(false, Release);
+        }
+
+        release_node(node);
+    }
+
+    #[inline(always)]
+    pub fn is_locked(&self) -> bool {
+        !self.tail.load(Relaxed).is_null()
+    }
+}
@@ -0,0 +1,390 @@
+use std::cell::{Cell, RefCell};
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ptr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize};
+use std::sync::atomic::Ordering::Relaxed;
+use std::time::{Duration, Instant};
+use itertools::Itertools;
+use parking_lot::Mutex;
+use crate::crud_model::crud_operation::CRUDOperation;
+use crate::page_model::Level;
+use crate::utils::profile;
+
+/// Restarts are tracked per level up to this height, deeper levels share the last bucket.
+pub const LEVEL_BUCKETS: usize = 16;
+
+pub const OPERATION_KINDS: usize = 11;
+
+#[repr(usize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OperationKind {
+    Empty,
+    Insert,
+    Update,
+    Delete,
+    Point,
+    PeekMin,
+    PeekMax,
+    PopMin,
+    PopMax,
+    Range,
+    Pred,
+}
+
+impl OperationKind {
+    pub const ALL: [OperationKind; OPERATION_KINDS] = [
+        OperationKind::Empty,
+        OperationKind::Insert,
+        OperationKind::Update,
+        OperationKind::Delete,
+        OperationKind::Point,
+        OperationKind::PeekMin,
+        OperationKind::PeekMax,
+        OperationKind::PopMin,
+        OperationKind::PopMax,
+        OperationKind::Range,
+        OperationKind::Pred,
+    ];
+}
+
+impl Display for OperationKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationKind::Empty => write!(f, "Empty"),
+            OperationKind::Insert => write!(f, "Insert"),
+            OperationKind::Update => write!(f, "Update"),
+            OperationKind::Delete => write!(f, "Delete"),
+            OperationKind::Point => write!(f, "Point"),
+            OperationKind::PeekMin => write!(f, "PeekMin"),
+            OperationKind::PeekMax => write!(f, "PeekMax"),
+            OperationKind::PopMin => write!(f, "PopMin"),
+            OperationKind::PopMax => write!(f, "PopMax"),
+            OperationKind::Range => write!(f, "Range"),
+            OperationKind::Pred => write!(f, "Pred"),
+        }
+    }
+}
+
+impl<Key: Ord + Copy + Hash, Payload: Clone> From<&CRUDOperation<Key, Payload>> for OperationKind {
+    fn from(operation: &CRUDOperation<Key, Payload>) -> Self {
+        match operation {
+            CRUDOperation::Empty => OperationKind::Empty,
+            CRUDOperation::Insert(..) => OperationKind::Insert,
+            CRUDOperation::Update(..) => OperationKind::Update,
+            CRUDOperation::Delete(..) => OperationKind::Delete,
+            CRUDOperation::Point(..) => OperationKind::Point,
+            CRUDOperation::PeekMin => OperationKind::PeekMin,
+            CRUDOperation::PeekMax => OperationKind::PeekMax,
+            CRUDOperation::PopMin => OperationKind::PopMin,
+            CRUDOperation::PopMax => OperationKind::PopMax,
+            CRUDOperation::Range(..) => OperationKind::Range,
+            CRUDOperation::Pred(..) => OperationKind::Pred,
+        }
+    }
+}
+
+/// Events counted independent of the operation.
+#[repr(usize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Counter {
+    Upgrade,
+    UpgradeFailure,
+    Split,
+    Merge,
+    Redistribution,
+    RootChange,
+    Append,
+    PessimisticFallback,
+    Spin,
+    Yield,
+    LatchWaitNanos,
+}
+
+const COUNTERS: usize = 11;
+
+/// Counters of a single thread for a single tree, only written by its owning thread.
+struct MetricShard {
+    counters: [AtomicU64; COUNTERS],
+    operations: [AtomicU64; OPERATION_KINDS],
+    restarts: [[AtomicU64; LEVEL_BUCKETS]; OPERATION_KINDS],
+}
+
+impl Default for MetricShard {
+    fn default() -> Self {
+        Self {
+            counters: [const { AtomicU64::new(0) }; COUNTERS],
+            operations: [const { AtomicU64::new(0) }; OPERATION_KINDS],
+            restarts: [const { [const { AtomicU64::new(0) }; LEVEL_BUCKETS] }; OPERATION_KINDS],
+        }
+    }
+}
+
+impl MetricShard {
+    fn reset(&self) {
+        self.counters
+            .iter()
+            .chain(self.operations.iter())
+            .chain(self.restarts.iter().flatten())
+            .for_each(|counter| counter.store(0, Relaxed));
+    }
+}
+
+static NEXT_TREE_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Shard and operation of the innermost running dispatch on this thread.
+    static CURRENT: Cell<(*const MetricShard, OperationKind)>
+        = const { Cell::new((ptr::null(), OperationKind::Empty)) };
+
+    /// This thread's shards by tree id.
+    static SHARDS: RefCell<Vec<(usize, Arc<MetricShard>)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runtime metrics of a tree, collected in thread-local shards and aggregated on demand.
+pub struct TreeMetrics {
+    id: usize,
+    shards: Mutex<Vec<Arc<MetricShard>>>,
+}
+
+impl Default for TreeMetrics {
+    fn default() -> Self {
+        Self {
+            id: NEXT_TREE_ID.fetch_add(1, Relaxed),
+            shards: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl TreeMetrics {
+    /// Routes all events of the current thread to this tree's shard until the scope is dropped.
+    /// Nested scopes of the same tree, e.g. internal re-dispatches, keep the outer operation.
+    pub(crate) fn enter(&self, kind: OperationKind) -> MetricsScope {
+        let shard = SHARDS.with(|shards| {
+            let mut shards
+                = shards.borrow_mut();
+
+            shards.retain(|(.., shard)| Arc::strong_count(shard) > 1); // trees dropped meanwhile
+
+            match shards.iter().find(|(id, ..)| *id == self.id) {
+                Some((.., shard)) => Arc::as_ptr(shard),
+                None => {
+                    let shard
+                        = Arc::new(MetricShard::default());
+
+                    self.shards.lock().push(shard.clone());
+                    shards.push((self.id, shard.clone()));
+                    Arc::as_ptr(&shard)
+                }
+            }
+        });
+
+        let previous
+            = CURRENT.get();
+
+        if previous.0 != shard {
+            unsafe { (*shard).operations[kind as usize].fetch_add(1, Relaxed); }
+            CURRENT.set((shard, kind));
+        }
+
+        MetricsScope {
+            previous,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Sums up the shards of all threads, concurrently running operations may be partially counted.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let mut snapshot
+            = MetricsSnapshot::default();
+
+        self.shards.lock().iter().for_each(|shard| {
+            snapshot.upgrades += shard.counters[Counter::Upgrade as usize].load(Relaxed);
+            snapshot.upgrade_failures += shard.counters[Counter::UpgradeFailure as usize].load(Relaxed);
+            snapshot.splits += shard.counters[Counter::Split as usize].load(Relaxed);
+            snapshot.merges += shard.counters[Counter::Merge as usize].load(Relaxed);
+            snapshot.redistributions += shard.counters[Counter::Redistribution as usize].load(Relaxed);
+            snapshot.root_changes += shard.counters[Counter::RootChange as usize].load(Relaxed);
+            snapshot.appends += shard.counters[Counter::Append as usize].load(Relaxed);
+            snapshot.pessimistic_fallbacks += shard.counters[Counter::PessimisticFallback as usize].load(Relaxed);
+            snapshot.spins += shard.counters[Counter::Spin as usize].load(Relaxed);
+            snapshot.yields += shard.counters[Counter::Yield as usize].load(Relaxed);
+            snapshot.latch_wait += Duration::from_nanos(
+                shard.counters[Counter::LatchWaitNanos as usize].load(Relaxed));
+
+            snapshot.operations
+                .iter_mut()
+                .zip(shard.operations.iter())
+                .for_each(|(sum, counter)| *sum += counter.load(Relaxed));
+
+            snapshot.restarts
+                .iter_mut()
+                .flatten()
+                .zip(shard.restarts.iter().flatten())
+                .for_each(|(sum, counter)| *sum += counter.load(Relaxed));
+        });
+
+        snapshot
+    }
+
+    /// Zeroes all counters, events of concurrently running operations may survive the reset.
+    pub fn reset(&self) {
+        self.shards
+            .lock()
+            .iter()
+            .for_each(|shard| shard.reset());
+    }
+}
+
+/// Restores the previously running dispatch's shard on drop, see `TreeMetrics::enter`.
+pub(crate) struct MetricsScope {
+    previous: (*const MetricShard, OperationKind),
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for MetricsScope {
+    #[inline(always)]
+    fn drop(&mut self) {
+        CURRENT.set(self.previous);
+    }
+}
+
+#[inline(always)]
+fn with_shard(f: impl FnOnce(&MetricShard, OperationKind)) {
+    let (shard, kind)
+        = CURRENT.try_with(Cell::get).unwrap_or((ptr::null(), OperationKind::Empty));
+
+    if !shard.is_null() {
+        f(unsafe { &*shard }, kind)
+    }
+}
+
+/// Kind of the operation running on this thread, `Empty` outside of any dispatch.
+#[inline(always)]
+pub fn current_operation() -> OperationKind {
+    CURRENT.try_with(Cell::get).map(|(.., kind)| kind).unwrap_or(OperationKind::Empty)
+}
+
+#[inline(always)]
+pub(crate) fn record(counter: Counter) {
+    with_shard(|shard, _| { shard.counters[counter as usize].fetch_add(1, Relaxed); });
+    profile::record(counter)
+}
+
+#[inline(always)]
+pub(crate) fn record_upgrade(success: bool) {
+    record(if success { Counter::Upgrade } else { Counter::UpgradeFailure })
+}
+
+/// Counts a restart of the running operation detected at the given level, the root is level 1.
+#[inline(always)]
+pub(crate) fn record_restart(level: Level) {
+    with_shard(|shard, kind| {
+        let bucket
+            = (level as usize).clamp(1, LEVEL_BUCKETS) - 1;
+
+        shard.restarts[kind as usize][bucket].fetch_add(1, Relaxed);
+    });
+
+    profile::record_restart(level)
+}
+
+/// Runs a blocking latch acquisition and accounts the time spent as latch wait.
+#[inline(always)]
+pub(crate) fn wait<R>(acquire: impl FnOnce() -> R) -> R {
+    let start
+        = Instant::now();
+
+    let acquired
+        = acquire();
+
+    let elapsed
+        = start.elapsed();
+
+    with_shard(|shard, _| {
+        shard.counters[Counter::LatchWaitNanos as usize].fetch_add(elapsed.as_nanos() as u64, Relaxed);
+    });
+
+    profile::record_wait(elapsed);
+    acquired
+}
+
+/// Aggregated metrics of a tree, see `TreeMetrics::snapshot`.
+#[derive(Clone, Debug, Default)]
+pub struct MetricsSnapshot {
+    /// Dispatched operations by `OperationKind`.
+    pub operations: [u64; OPERATION_KINDS],
+    /// Restarts by `OperationKind` and level, i.e. `restarts[kind][level - 1]`.
+    pub restarts: [[u64; LEVEL_BUCKETS]; OPERATION_KINDS],
+    pub upgrades: u64,
+    pub upgrade_failures: u64,
+    pub splits: u64,
+    pub merges: u64,
+    /// Underflow corrections moving keys between siblings instead of merging them.
+    pub redistributions: u64,
+    pub root_changes: u64,
+    /// Inserts appended to the remembered rightmost leaf without traversal,
+    /// see `BPlusTree::with_sequential_inserts`.
+    pub appends: u64,
+    /// Latches optimistic strategies take pessimistically once an operation's attempts
+    /// reach the strategy's threshold.
+    pub pessimistic_fallbacks: u64,
+    pub spins: u64,
+    pub yields: u64,
+    /// Time spent blocking in latch acquisitions and yielding backoffs.
+    pub latch_wait: Duration,
+}
+
+impl MetricsSnapshot {
+    #[inline(always)]
+    pub fn operations_of(&self, kind: OperationKind) -> u64 {
+        self.operations[kind as usize]
+    }
+
+    #[inline(always)]
+    pub fn restarts_of(&self, kind: OperationKind) -> u64 {
+        self.restarts[kind as usize].iter().sum()
+    }
+
+    #[inline]
+    pub fn restarts_per_operation(&self, kind: OperationKind) -> f64 {
+        match self.operations_of(kind) {
+            0 => 0f64,
+            operations => self.restarts_of(kind) as f64 / operations as f64
+        }
+    }
+}
+
+impl Display for MetricsSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Metrics(upgrades={};upgrade_failures={};splits={};merges={};redistributions={};\
+                   root_changes={};appends={};pessimistic_fallbacks={};spins={};yields={};latch_wait={:?})",
+               self.upgrades,
+               self.upgrade_failures,
+               self.splits,
+               self.merges,
+               self.redistributions,
+               self.root_changes,
+               self.appends,
+               self.pessimistic_fallbacks,
+               self.spins,
+               self.yields,
+               self.latch_wait)?;
+
+        OperationKind::ALL
+            .iter()
+            .filter(|kind| self.operations_of(**kind) > 0)
+            .try_for_each(|kind| write!(f, "\n\t{}(operations={};restarts={};levels=[{}])",
+                                        kind,
+                                        self.operations_of(*kind),
+                                        self.restarts_of(*kind),
+                                        self.restarts[*kind as usize]
+                                            .iter()
+                                            .enumerate()
+                                            .filter(|(.., restarts)| **restarts > 0)
+                                            .map(|(level, restarts)| format!("{}:{}", level + 1, restarts))
+                                            .join(",")))
+    }
+}
@@ -2,4 +2,10 @@ pub mod smart_cell;
 pub mod shadow_vec;
 pub mod un_cell;
 pub mod interval;
-pub mod safe_cell;
\ No newline at end of file
+pub mod safe_cell;
+pub mod ticket_lock;
+pub mod mcs_lock;
+pub mod epoch;
+pub mod metrics;
+pub mod profile;
+pub mod slab;
\ No newline at end of file
@@ -0,0 +1,202 @@
+use std::cell::{Cell, RefCell};
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+use itertools::Itertools;
+use crate::page_model::Level;
+use crate::utils::metrics::{Counter, OperationKind};
+use crate::utils::smart_cell::{LatchMode, SmartGuard};
+
+/// Latch taken while traversing, `attempt` counts the restarts preceding it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LatchRecord {
+    pub attempt: usize,
+    pub level: Level,
+    pub mode: LatchMode,
+}
+
+/// Execution profile of a single operation, see `CRUDDispatcher::dispatch_profiled`.
+#[derive(Clone, Debug)]
+pub struct OperationProfile {
+    pub kind: OperationKind,
+    pub elapsed: Duration,
+    /// Latches taken along the traversal paths in acquisition order, including those of
+    /// attempts that restarted. Latches of structure modifications are not listed.
+    pub latches: Vec<LatchRecord>,
+    /// Level of each restart in order, the root is level 1.
+    pub restarts: Vec<Level>,
+    pub upgrades: usize,
+    pub upgrade_failures: usize,
+    pub splits: usize,
+    pub merges: usize,
+    pub redistributions: usize,
+    pub root_changes: usize,
+    /// One if the insert was appended without traversal, see `BPlusTree::with_sequential_inserts`.
+    pub appends: usize,
+    pub pessimistic_fallbacks: usize,
+    pub spins: usize,
+    pub yields: usize,
+    pub latch_wait: Duration,
+}
+
+impl OperationProfile {
+    fn new(kind: OperationKind) -> Self {
+        Self {
+            kind,
+            elapsed: Duration::ZERO,
+            latches: vec![],
+            restarts: vec![],
+            upgrades: 0,
+            upgrade_failures: 0,
+            splits: 0,
+            merges: 0,
+            redistributions: 0,
+            root_changes: 0,
+            appends: 0,
+            pessimistic_fallbacks: 0,
+            spins: 0,
+            yields: 0,
+            latch_wait: Duration::ZERO,
+        }
+    }
+
+    #[inline(always)]
+    pub fn attempts(&self) -> usize {
+        self.restarts.len() + 1
+    }
+
+    /// True if the operation split or merged any node.
+    #[inline(always)]
+    pub fn modified_structure(&self) -> bool {
+        self.splits + self.merges + self.redistributions > 0
+    }
+}
+
+impl Display for OperationProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Profile(kind={};elapsed={:?};attempts={};restarts=[{}];upgrades={};upgrade_failures={};\
+                   splits={};merges={};redistributions={};root_changes={};appends={};pessimistic_fallbacks={};\
+                   spins={};yields={};latch_wait={:?})",
+               self.kind,
+               self.elapsed,
+               self.attempts(),
+               self.restarts.iter().join(","),
+               self.upgrades,
+               self.upgrade_failures,
+               self.splits,
+               self.merges,
+               self.redistributions,
+               self.root_changes,
+               self.appends,
+               self.pessimistic_fallbacks,
+               self.spins,
+               self.yields,
+               self.latch_wait)?;
+
+        self.latches
+            .iter()
+            .chunk_by(|latch| latch.attempt)
+            .into_iter()
+            .try_for_each(|(attempt, latches)| write!(f, "\n\tAttempt({};latches=[{}])",
+                                                      attempt + 1,
+                                                      latches
+                                                          .map(|latch| format!("{}:{}", latch.level, latch.mode))
+                                                          .join(",")))
+    }
+}
+
+thread_local! {
+    static PROFILING: Cell<bool> = const { Cell::new(false) };
+    static CURRENT: RefCell<Option<OperationProfile>> = const { RefCell::new(None) };
+}
+
+/// Collects the current thread's events until `finish`, a running profile is resumed on drop.
+pub struct ProfileScope {
+    previous: Option<OperationProfile>,
+    started: Instant,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl ProfileScope {
+    pub fn finish(self) -> OperationProfile {
+        let elapsed
+            = self.started.elapsed();
+
+        let mut profile = CURRENT
+            .with(|current| current.borrow_mut().take())
+            .expect("Profile scope without profile");
+
+        profile.elapsed = elapsed;
+        profile
+    }
+}
+
+impl Drop for ProfileScope {
+    fn drop(&mut self) {
+        let previous
+            = self.previous.take();
+
+        PROFILING.set(previous.is_some());
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// Starts profiling a single operation on the current thread, see `ProfileScope`.
+pub fn start(kind: OperationKind) -> ProfileScope {
+    let previous = CURRENT.with(|current| current
+        .borrow_mut()
+        .replace(OperationProfile::new(kind)));
+
+    PROFILING.set(true);
+
+    ProfileScope {
+        previous,
+        started: Instant::now(),
+        _not_send: PhantomData,
+    }
+}
+
+#[inline(always)]
+fn with_profile(f: impl FnOnce(&mut OperationProfile)) {
+    if PROFILING.try_with(Cell::get).unwrap_or(false) {
+        CURRENT.with(|current| if let Some(profile) = current.borrow_mut().as_mut() {
+            f(profile)
+        });
+    }
+}
+
+#[inline(always)]
+pub(crate) fn record(counter: Counter) {
+    with_profile(|profile| match counter {
+        Counter::Upgrade => profile.upgrades += 1,
+        Counter::UpgradeFailure => profile.upgrade_failures += 1,
+        Counter::Split => profile.splits += 1,
+        Counter::Merge => profile.merges += 1,
+        Counter::Redistribution => profile.redistributions += 1,
+        Counter::RootChange => profile.root_changes += 1,
+        Counter::Append => profile.appends += 1,
+        Counter::PessimisticFallback => profile.pessimistic_fallbacks += 1,
+        Counter::Spin => profile.spins += 1,
+        Counter::Yield => profile.yields += 1,
+        Counter::LatchWaitNanos => {}
+    })
+}
+
+#[inline(always)]
+pub(crate) fn record_restart(level: Level) {
+    with_profile(|profile| profile.restarts.push(level))
+}
+
+#[inline(always)]
+pub(crate) fn record_wait(wait: Duration) {
+    with_profile(|profile| profile.latch_wait += wait)
+}
+
+#[inline(always)]
+pub(crate) fn record_latch<E: Default + 'static>(level: Level, guard: &SmartGuard<'_, E>) {
+    with_profile(|profile| profile.latches.push(LatchRecord {
+        attempt: profile.restarts.len(),
+        level,
+        mode: guard.latch_mode(),
+    }))
+}
@@ -1,7 +1,7 @@
 use std::cell::UnsafeCell;
 use std::ops::{Deref, DerefMut};
 
-/**
+/*
  *
  * Created by   Amir El-Shaikh on 04.03.2021.
  * E-Mail: elshaikh@mathematik.uni-marburg.de
@@ -33,6 +33,7 @@ impl<E> SafeCell<E> {
 
     /// Unsafely gets the wrapped object as mutable reference.
     #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
     pub fn get_mut(&self) -> &mut E {
         unsafe { &mut *self.inner.get() }
     }
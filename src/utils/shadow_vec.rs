@@ -1,5 +1,5 @@
 use std::cell::Cell;
-use std::{ptr, slice};
+use std::ptr;
 use std::ptr::slice_from_raw_parts_mut;
 use crate::page_model::ObjectCount;
 
@@ -10,6 +10,7 @@ pub struct ShadowVec<E: Default + Clone> {
 }
 
 impl<E: Default + Clone> ShadowVec<E> {
+    #[allow(clippy::mut_from_ref)]
     pub fn get_unchecked_mut(&self, index: usize) -> &mut E {
         unsafe {
             &mut *self.ptr.add(index)
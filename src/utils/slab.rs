@@ -0,0 +1,479 @@
+use std::alloc::{self, Layout};
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicUsize, fence};
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use parking_lot::Mutex;
+
+const CACHE_LINE: usize = 64;
+const PAGE_SIZE: usize = 4096;
+
+/// Chunks are carved into at least as many slots, larger slots get larger chunks.
+const MIN_CHUNK_SLOTS: usize = 16;
+const MIN_CHUNK_BYTES: usize = 64 * 1024;
+
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Shard this thread allocates from and frees into, assigned round-robin.
+    static SHARD: usize = NEXT_SHARD.fetch_add(1, Relaxed);
+}
+
+/// Shard index of the current thread, also used to stripe counters updated by all threads.
+#[inline(always)]
+pub(crate) fn shard_index() -> usize {
+    SHARD.with(|shard| *shard)
+}
+
+/// Precedes every value of a `SlabArc`, free slots keep their chunk.
+#[repr(C)]
+struct SlotHeader {
+    strong: AtomicUsize,
+    /// Null for values allocated on the heap.
+    chunk: *const Chunk,
+}
+
+#[repr(C)]
+struct SlabBox<T> {
+    header: SlotHeader,
+    value: T,
+}
+
+/// Start of each chunk, followed by its slots.
+#[repr(C, align(64))]
+struct Chunk {
+    /// Slots in use, plus one as long as the slab lives.
+    live: AtomicUsize,
+    slab: Arc<SlabShared>,
+}
+
+struct Shard {
+    free: Vec<NonNull<SlotHeader>>,
+    /// Next slot never handed out of the shard's latest chunk.
+    bump: *mut u8,
+    end: *mut u8,
+    chunks: Vec<NonNull<Chunk>>,
+}
+
+/// Pads shards to separate cache lines, since each is locked by other threads.
+#[repr(align(128))]
+struct PaddedShard {
+    shard: Mutex<Shard>,
+    /// Slots allocated minus slots freed by the shard's threads, negative if freed more than allocated.
+    in_use: AtomicIsize,
+}
+
+struct SlabShared {
+    slot_bytes: usize,
+    slot_align: usize,
+    slots_offset: usize,
+    slots_per_chunk: usize,
+    chunk_layout: Layout,
+    shards: Box<[PaddedShard]>,
+    /// Freed slots are not recycled anymore.
+    dropped: AtomicBool,
+}
+
+unsafe impl Send for SlabShared {}
+unsafe impl Sync for SlabShared {}
+
+/// Memory held by a `Slab`, see `BlockManager::slab_usage`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SlabUsage {
+    /// Size of a single slot, i.e. a value including its reference count and padding.
+    pub slot_bytes: usize,
+    pub chunk_bytes: usize,
+    pub chunks: usize,
+    pub slots_in_use: usize,
+    /// Slots freed and waiting to be recycled.
+    pub slots_free: usize,
+}
+
+impl SlabUsage {
+    /// Memory reserved from the global allocator.
+    #[inline(always)]
+    pub fn reserved_bytes(&self) -> usize {
+        self.chunks * self.chunk_bytes
+    }
+
+    #[inline(always)]
+    pub fn used_bytes(&self) -> usize {
+        self.slots_in_use * self.slot_bytes
+    }
+}
+
+impl Display for SlabUsage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SlabUsage(slot_bytes={};chunk_bytes={};chunks={};slots_in_use={};slots_free={};reserved_bytes={})",
+               self.slot_bytes,
+               self.chunk_bytes,
+               self.chunks,
+               self.slots_in_use,
+               self.slots_free,
+               self.reserved_bytes())
+    }
+}
+
+/// Hands out fixed-size slots for `SlabArc`s from page-aligned chunks. Slots are aligned
+/// to cache lines, slots of a page or more to pages. Each thread allocates from and frees into
+/// its own shard, hence threads rarely contend on a lock.
+///
+/// A slot is recycled once the last `SlabArc` to it is dropped. Blocks unlinked from a tree are
+/// retired through the epoch, hence optimistic readers not holding a reference are gone by then.
+/// Recycled slots are only reused by the same slab, i.e. a stale read still hits a value of the
+/// same type. Chunks are not returned before the slab is dropped and all their slots are freed.
+pub struct Slab {
+    shared: Arc<SlabShared>,
+}
+
+impl Slab {
+    /// Slab of slots fitting a `SlabArc<T>`.
+    pub fn new<T>() -> Self {
+        let layout
+            = Layout::new::<SlabBox<T>>();
+
+        let (slot_bytes, slot_align) = if layout.size() >= PAGE_SIZE {
+            (layout.size().next_multiple_of(PAGE_SIZE), PAGE_SIZE.max(layout.align()))
+        } else {
+            let slot_align
+                = CACHE_LINE.max(layout.align());
+
+            (layout.size().next_multiple_of(slot_align), slot_align)
+        };
+
+        let slots_offset
+            = mem::size_of::<Chunk>().next_multiple_of(slot_align);
+
+        let chunk_bytes = (slots_offset + MIN_CHUNK_SLOTS * slot_bytes)
+            .max(MIN_CHUNK_BYTES)
+            .next_multiple_of(PAGE_SIZE);
+
+        let shards = (0..num_cpus::get().next_power_of_two())
+            .map(|_| PaddedShard {
+                shard: Mutex::new(Shard {
+                    free: Vec::new(),
+                    bump: ptr::null_mut(),
+                    end: ptr::null_mut(),
+                    chunks: Vec::new(),
+                }),
+                in_use: AtomicIsize::new(0),
+            })
+            .collect();
+
+        Self {
+            shared: Arc::new(SlabShared {
+                slot_bytes,
+                slot_align,
+                slots_offset,
+                slots_per_chunk: (chunk_bytes - slots_offset) / slot_bytes,
+                chunk_layout: Layout::from_size_align(chunk_bytes, PAGE_SIZE.max(slot_align)).unwrap(),
+                shards,
+                dropped: AtomicBool::new(false),
+            })
+        }
+    }
+
+    #[inline(always)]
+    pub fn slot_bytes(&self) -> usize {
+        self.shared.slot_bytes
+    }
+
+    /// Sums up all shards, concurrent allocations may be partially counted.
+    pub fn usage(&self) -> SlabUsage {
+        let mut usage = SlabUsage {
+            slot_bytes: self.shared.slot_bytes,
+            chunk_bytes: self.shared.chunk_layout.size(),
+            ..SlabUsage::default()
+        };
+
+        let mut never_used = 0;
+        self.shared.shards.iter().for_each(|shard| {
+            let shard
+                = shard.shard.lock();
+
+            usage.chunks += shard.chunks.len();
+            usage.slots_free += shard.free.len();
+            never_used += (shard.end as usize - shard.bump as usize) / self.shared.slot_bytes;
+        });
+
+        usage.slots_in_use = (usage.chunks * self.shared.slots_per_chunk)
+            .saturating_sub(usage.slots_free + never_used);
+
+        usage
+    }
+
+    /// Slots in use without locking any shard, hence cheap enough to be checked per operation.
+    /// Concurrent allocations and frees may be partially counted.
+    #[inline(always)]
+    pub fn slots_in_use(&self) -> usize {
+        self.shared.shards
+            .iter()
+            .map(|shard| shard.in_use.load(Relaxed))
+            .sum::<isize>()
+            .max(0) as usize
+    }
+
+    /// Hands out a recycled slot of the current thread's shard, or carves a new one.
+    fn allocate(&self) -> NonNull<SlotHeader> {
+        let padded
+            = self.shared.shard();
+
+        padded.in_use.fetch_add(1, Relaxed);
+
+        let mut shard
+            = padded.shard.lock();
+
+        if let Some(slot) = shard.free.pop() {
+            unsafe { (*slot.as_ref().chunk).live.fetch_add(1, Relaxed); }
+            return slot;
+        }
+
+        if shard.bump == shard.end {
+            let chunk = unsafe {
+                alloc::alloc(self.shared.chunk_layout) as *mut Chunk
+            };
+
+            let chunk = NonNull::new(chunk)
+                .unwrap_or_else(|| alloc::handle_alloc_error(self.shared.chunk_layout));
+
+            unsafe {
+                ptr::write(chunk.as_ptr(), Chunk {
+                    live: AtomicUsize::new(1),
+                    slab: self.shared.clone(),
+                });
+
+                shard.bump = (chunk.as_ptr() as *mut u8).add(self.shared.slots_offset);
+                shard.end = shard.bump.add(self.shared.slots_per_chunk * self.shared.slot_bytes);
+            }
+
+            shard.chunks.push(chunk);
+        }
+
+        let chunk = *shard.chunks
+            .last()
+            .unwrap();
+
+        let slot = shard.bump as *mut SlotHeader;
+        unsafe {
+            shard.bump = shard.bump.add(self.shared.slot_bytes);
+            chunk.as_ref().live.fetch_add(1, Relaxed);
+
+            ptr::write(slot, SlotHeader {
+                strong: AtomicUsize::new(0),
+                chunk: chunk.as_ptr(),
+            });
+
+            NonNull::new_unchecked(slot)
+        }
+    }
+}
+
+impl SlabShared {
+    #[inline(always)]
+    fn shard(&self) -> &PaddedShard {
+        &self.shards[shard_index() & (self.shards.len() - 1)]
+    }
+}
+
+impl Drop for Slab {
+    /// Releases the chunks without slots in use, the remaining ones are released with their last slot.
+    fn drop(&mut self) {
+        self.shared.dropped.store(true, Relaxed);
+
+        let chunks = self.shared.shards
+            .iter()
+            .flat_map(|shard| {
+                let mut shard
+                    = shard.shard.lock();
+
+                shard.free.clear();
+                shard.bump = ptr::null_mut();
+                shard.end = ptr::null_mut();
+                mem::take(&mut shard.chunks)
+            })
+            .collect::<Vec<_>>();
+
+        chunks.into_iter().for_each(|chunk| unsafe { Chunk::release(chunk.as_ptr()) });
+    }
+}
+
+impl Chunk {
+    /// Drops a reference to the chunk and deallocates it, if it was the last one.
+    #[inline(always)]
+    unsafe fn release(chunk: *mut Chunk) {
+        if (*chunk).live.fetch_sub(1, Release) != 1 {
+            return;
+        }
+
+        fence(Acquire);
+
+        let slab: &SlabShared
+            = &(*chunk).slab;
+
+        let chunk_layout
+            = slab.chunk_layout;
+
+        ptr::drop_in_place(chunk);
+        alloc::dealloc(chunk as *mut u8, chunk_layout);
+    }
+
+    /// Returns the slot to the current thread's shard, unless the slab is dropped already.
+    #[inline(always)]
+    unsafe fn free(slot: NonNull<SlotHeader>) {
+        let chunk
+            = slot.as_ref().chunk as *mut Chunk;
+
+        // the slot keeps the chunk and hence the shared state alive until released
+        let slab
+            = &(*chunk).slab;
+
+        if !slab.dropped.load(Relaxed) {
+            let padded
+                = slab.shard();
+
+            padded.in_use.fetch_sub(1, Relaxed);
+            padded.shard.lock().free.push(slot);
+        }
+
+        Chunk::release(chunk)
+    }
+}
+
+/// Reference counted pointer like `Arc`, either to a slot of a `Slab` or to the heap.
+/// Weak references are not supported.
+pub struct SlabArc<T> {
+    ptr: NonNull<SlabBox<T>>,
+    _marker: PhantomData<SlabBox<T>>,
+}
+
+unsafe impl<T: Send + Sync> Send for SlabArc<T> {}
+unsafe impl<T: Send + Sync> Sync for SlabArc<T> {}
+
+impl<T> SlabArc<T> {
+    #[inline(always)]
+    pub fn new(value: T) -> Self {
+        let slab_box = Box::new(SlabBox {
+            header: SlotHeader {
+                strong: AtomicUsize::new(1),
+                chunk: ptr::null(),
+            },
+            value,
+        });
+
+        Self {
+            ptr: unsafe { NonNull::new_unchecked(Box::into_raw(slab_box)) },
+            _marker: PhantomData,
+        }
+    }
+
+    /// Moves the value into a slot of the slab.
+    ///
+    /// # Panics
+    /// The slab's slots do not fit `T`, i.e. it was not created by `Slab::new::<T>`.
+    #[inline(always)]
+    pub fn new_in(value: T, slab: &Slab) -> Self {
+        let layout
+            = Layout::new::<SlabBox<T>>();
+
+        assert!(layout.size() <= slab.shared.slot_bytes && layout.align() <= slab.shared.slot_align,
+                "Slab slots do not fit the value");
+
+        let slot
+            = slab.allocate();
+
+        unsafe {
+            slot.as_ref().strong.store(1, Relaxed);
+            ptr::write(ptr::addr_of_mut!((*(slot.as_ptr() as *mut SlabBox<T>)).value), value);
+        }
+
+        Self {
+            ptr: slot.cast(),
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn strong_count(this: &Self) -> usize {
+        this.header().strong.load(Acquire)
+    }
+
+    #[inline(always)]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.ptr == other.ptr
+    }
+
+    #[inline(always)]
+    pub fn as_ptr(this: &Self) -> *const T {
+        unsafe { ptr::addr_of!((*this.ptr.as_ptr()).value) }
+    }
+
+    /// Whether the value is located in a slab.
+    #[inline(always)]
+    pub fn is_slab(this: &Self) -> bool {
+        !this.header().chunk.is_null()
+    }
+
+    #[inline(always)]
+    fn header(&self) -> &SlotHeader {
+        unsafe { &self.ptr.as_ref().header }
+    }
+}
+
+impl<T> Clone for SlabArc<T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        self.header().strong.fetch_add(1, Relaxed);
+
+        Self {
+            ptr: self.ptr,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for SlabArc<T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if self.header().strong.fetch_sub(1, Release) != 1 {
+            return;
+        }
+
+        fence(Acquire);
+
+        unsafe {
+            if self.header().chunk.is_null() {
+                mem::drop(Box::from_raw(self.ptr.as_ptr()));
+            } else {
+                ptr::drop_in_place(ptr::addr_of_mut!((*self.ptr.as_ptr()).value));
+                Chunk::free(self.ptr.cast());
+            }
+        }
+    }
+}
+
+impl<T> Deref for SlabArc<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &self.ptr.as_ref().value }
+    }
+}
+
+impl<T> AsRef<T> for SlabArc<T> {
+    #[inline(always)]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T: Default> Default for SlabArc<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
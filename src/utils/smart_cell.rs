@@ -1,18 +1,20 @@
 use std::fmt::{Display, Formatter};
 use std::{hint, mem, ptr};
-use std::mem::{transmute, transmute_copy};
+use std::mem::{ManuallyDrop, transmute, transmute_copy};
 use std::ops::{Deref, DerefMut};
-use std::sync::Arc;
-use std::sync::atomic::AtomicUsize;
-use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release, SeqCst};
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
 use parking_lot::lock_api::{MutexGuard, RwLockReadGuard, RwLockWriteGuard};
 use parking_lot::{Mutex, RawMutex, RawRwLock, RwLock};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use crate::page_model::Attempts;
 use crate::record_model::{AtomicVersion, Version};
 use crate::utils::safe_cell::SafeCell;
-use crate::utils::smart_cell::SmartFlavor::{ExclusiveCell, FreeCell, HybridCell, LightWeightHybridCell, OLCCell, ReadersWriterCell};
-use crate::utils::smart_cell::SmartGuard::{HybridRwReader, HybridRwWriter, LockFree, MutExclusive, OLCReader, OLCReaderPin, OLCWriter, RwReader, RwWriter};
+use crate::utils::ticket_lock::TicketRwLock;
+use crate::utils::mcs_lock::McsLock;
+use crate::utils::metrics::{self, Counter};
+use crate::utils::slab::SlabArc;
+use crate::utils::smart_cell::SmartFlavor::{ExclusiveCell, FreeCell, HybridCell, LightWeightHybridCell, OLCCell, OptiQLCell, ReadersWriterCell, TicketCell};
+use crate::utils::smart_cell::SmartGuard::{HybridRwReader, HybridRwWriter, LockFree, MutExclusive, OLCReader, OLCReaderEpoch, OLCReaderPin, OLCWriter, RwReader, RwWriter, TicketReader, TicketWriter};
 
 pub const CPU_THREADS: bool = true;
 pub const ENABLE_YIELD: bool = !CPU_THREADS;
@@ -77,21 +79,65 @@ pub enum LatchType {
     Optimistic,
     Hybrid,
     LightWeightHybrid,
+    Ticket,
+    OptimisticQueue,
     None,
 }
-pub static mut COUNTERS: (AtomicUsize, AtomicUsize) =
-    (AtomicUsize::new(0), AtomicUsize::new(0));
+
+/// Latch state of a cell as observed without latching it, meant for diagnostics only.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LatchState {
+    Free,
+    Shared,
+    Exclusive,
+    Pinned,
+    Obsolete,
+}
+
+impl Display for LatchState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LatchState::Free => write!(f, "Free"),
+            LatchState::Shared => write!(f, "Shared"),
+            LatchState::Exclusive => write!(f, "Exclusive"),
+            LatchState::Pinned => write!(f, "Pinned"),
+            LatchState::Obsolete => write!(f, "Obsolete"),
+        }
+    }
+}
+
+/// Latch mode held by a guard, `Failed` if an optimistic latch could not be taken.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LatchMode {
+    Free,
+    Optimistic,
+    Shared,
+    Pinned,
+    Exclusive,
+    Failed,
+}
+
+impl Display for LatchMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LatchMode::Free => write!(f, "Free"),
+            LatchMode::Optimistic => write!(f, "Optimistic"),
+            LatchMode::Shared => write!(f, "Shared"),
+            LatchMode::Pinned => write!(f, "Pinned"),
+            LatchMode::Exclusive => write!(f, "Exclusive"),
+            LatchMode::Failed => write!(f, "Failed"),
+        }
+    }
+}
 
 #[inline(always)]
 #[cfg(target_os = "linux")]
 pub fn sched_yield(attempt: Attempts) {
     if attempt > 3 {
-        unsafe {
-            COUNTERS.1.fetch_add(1, Relaxed);
-            libc::sched_yield();
-        }
+        metrics::record(Counter::Yield);
+        metrics::wait(|| unsafe { libc::sched_yield() });
     } else {
-        unsafe { COUNTERS.0.fetch_add(1, Relaxed); }
+        metrics::record(Counter::Spin);
         hint::spin_loop();
     }
 }
@@ -102,8 +148,10 @@ pub const FORCE_YIELD: Attempts = 4;
 #[cfg(not(target_os = "linux"))]
 pub fn sched_yield(attempt: usize) {
     if attempt > 3 {
-        std::thread::yield_now();
+        metrics::record(Counter::Yield);
+        metrics::wait(std::thread::yield_now);
     } else {
+        metrics::record(Counter::Spin);
         hint::spin_loop();
     }
 }
@@ -228,6 +276,20 @@ impl<E: Default> OptCell<E> {
         read_latch == load_version & !PIN_FLAG_VERSION && load_version & WRITE_OBSOLETE_FLAG_VERSION == 0
     }
 
+    /// Pins exactly the given read version, fails if the cell changed or is pinned meanwhile.
+    #[inline(always)]
+    pub fn pin_lock_strong(&self, read_version: LatchVersion) -> Option<LatchVersion> {
+        match self.cell_version.compare_exchange(
+            read_version,
+            read_version | PIN_FLAG_VERSION,
+            AcqRel,
+            Relaxed)
+        {
+            Ok(..) => Some(read_version | PIN_FLAG_VERSION),
+            Err(..) => None
+        }
+    }
+
     #[inline(always)]
     pub fn pin_write_lock(&self, read_version_pin: LatchVersion) -> LatchVersion {
         let pin_write
@@ -327,7 +389,7 @@ impl<E: Default> OptCell<E> {
 }
 
 #[derive(Default)]
-pub struct SmartCell<E: Default>(pub Arc<SmartFlavor<E>>);
+pub struct SmartCell<E: Default>(pub SlabArc<SmartFlavor<E>>);
 
 impl<E: Default> Clone for SmartCell<E> {
     #[inline(always)]
@@ -343,6 +405,8 @@ pub enum SmartFlavor<E: Default> {
     OLCCell(OptCell<E>),
     LightWeightHybridCell(OptCell<E>),
     HybridCell(OptCell<E>, RwLock<()>),
+    TicketCell(TicketRwLock, SafeCell<E>),
+    OptiQLCell(OptCell<E>, McsLock),
 }
 
 impl<E: Default> Default for SmartFlavor<E> {
@@ -355,7 +419,7 @@ impl<E: Default> SmartFlavor<E> {
     #[inline(always)]
     fn is_read_valid(&self, read_version: LatchVersion) -> bool {
         match self {
-            OLCCell(opt) | LightWeightHybridCell(opt) =>
+            OLCCell(opt) | LightWeightHybridCell(opt) | OptiQLCell(opt, ..) =>
                 opt.is_read_valid(read_version),
             HybridCell(opt, rw) => {
                 let reader
@@ -370,7 +434,7 @@ impl<E: Default> SmartFlavor<E> {
     #[inline(always)]
     fn is_read_not_obsolete(&self) -> bool {
         match self {
-            OLCCell(opt) | LightWeightHybridCell(opt) =>
+            OLCCell(opt) | LightWeightHybridCell(opt) | OptiQLCell(opt, ..) =>
                 opt.is_read_not_obsolete(),
             HybridCell(opt, rw) => {
                 let reader
@@ -385,7 +449,7 @@ impl<E: Default> SmartFlavor<E> {
     #[inline(always)]
     fn is_read_not_obsolete_result(&self) -> (bool, LatchVersion) {
         match self {
-            OLCCell(opt) | LightWeightHybridCell(opt) =>
+            OLCCell(opt) | LightWeightHybridCell(opt) | OptiQLCell(opt, ..) =>
                 opt.is_read_not_obsolete_result(),
             HybridCell(opt, rw) => {
                 let reader
@@ -414,9 +478,10 @@ impl<E: Default + 'static> Deref for SmartFlavor<E> {
             ExclusiveCell(.., ptr) => ptr.get_mut(),
             OLCCell(opt) | LightWeightHybridCell(opt) =>
                 opt.cell.as_ref(),
-            HybridCell(opt, _) => opt.cell.as_ref(),
+            HybridCell(opt, _) | OptiQLCell(opt, _) => opt.cell.as_ref(),
             FreeCell(ptr) => ptr.get_mut(),
             ReadersWriterCell(.., ptr) => ptr.get_mut(),
+            TicketCell(.., ptr) => ptr.get_mut(),
         }
     }
 }
@@ -428,9 +493,10 @@ impl<E: Default + 'static> DerefMut for SmartFlavor<E> {
             ExclusiveCell(.., ptr) => ptr.get_mut(),
             OLCCell(opt) | LightWeightHybridCell(opt) =>
                 opt.cell.get_mut(),
-            HybridCell(opt, _) => opt.cell.get_mut(),
+            HybridCell(opt, _) | OptiQLCell(opt, _) => opt.cell.get_mut(),
             FreeCell(ptr) => ptr.get_mut(),
-            ReadersWriterCell(.., ptr) => ptr.get_mut()
+            ReadersWriterCell(.., ptr) => ptr.get_mut(),
+            TicketCell(.., ptr) => ptr.get_mut()
         }
     }
 }
@@ -441,16 +507,22 @@ pub enum SmartGuard<'a, E: Default> {
     RwWriter(RwLockWriteGuard<'a, RawRwLock, ()>, *mut E),
     MutExclusive(MutexGuard<'a, RawMutex, ()>, *mut E),
     OLCReader(Option<(SmartCell<E>, LatchVersion)>),
+    OLCReaderEpoch(ManuallyDrop<SmartCell<E>>, LatchVersion),
     OLCWriter(SmartCell<E>, LatchVersion),
     OLCReaderPin(SmartCell<E>, LatchVersion),
     HybridRwReader(RwLockReadGuard<'a, RawRwLock, ()>, &'a OptCell<E>, LatchVersion),
     HybridRwWriter(RwLockWriteGuard<'a, RawRwLock, ()>, &'a OptCell<E>, LatchVersion),
+    TicketReader(&'a TicketRwLock, *const E),
+    TicketWriter(&'a TicketRwLock, *mut E),
 }
 
-impl<'a, E: Default + 'static> Clone for SmartGuard<'_, E> {
+impl<E: Default + 'static> Clone for SmartGuard<'_, E> {
     fn clone(&self) -> Self {
         match self {
             OLCReader(inner) => OLCReader(inner.clone()),
+            OLCReaderEpoch(cell, read_latch) => unsafe {
+                OLCReaderEpoch(ManuallyDrop::new(ptr::read(&**cell)), *read_latch)
+            }
             OLCReaderPin(inner, read_latch) =>
                 OLCReader(Some((inner.clone(), (*read_latch & !PIN_FLAG_VERSION)))),
             RwReader(guard, ptr) => RwReader(
@@ -458,18 +530,26 @@ impl<'a, E: Default + 'static> Clone for SmartGuard<'_, E> {
                     .read(),
                 *ptr),
             LockFree(ptr) => LockFree(*ptr),
+            TicketReader(lock, ptr) => {
+                lock.read();
+                TicketReader(lock, *ptr)
+            }
             _ => OLCReader(None)
         }
     }
 }
 
-impl<'a, E: Default + 'static> SmartGuard<'_, E> {
+impl<E: Default + 'static> SmartGuard<'_, E> {
     #[inline(always)]
     pub(crate) fn mark_obsolete(&mut self) {
         match self {
             OLCWriter(cell, latch) => match cell.0.as_ref() {
                 OLCCell(opt) | HybridCell(opt, ..) =>
                     opt.write_obsolete(),
+                OptiQLCell(opt, ..) => {
+                    opt.write_obsolete_with_latch(*latch);
+                    *latch |= OBSOLETE_FLAG_VERSION;
+                }
                 LightWeightHybridCell(opt) => {
                     opt.write_obsolete();
                     *latch = ZEROED_FLAG_VERSION
@@ -496,6 +576,13 @@ impl<'a, E: Default + 'static> SmartGuard<'_, E> {
 
                 ptr::write(self, s_guard)
             },
+            TicketWriter(lock, ptr) => unsafe {
+                let (lock, ptr)
+                    = (*lock, *ptr as *const E);
+
+                lock.downgrade();
+                ptr::write(self, TicketReader(lock, ptr))
+            },
             OLCWriter(cell, latch)
             if *latch & OBSOLETE_FLAG_VERSION == 0 => unsafe {
                 if let OLCCell(opt) = cell.0.as_ref() {
@@ -510,6 +597,7 @@ impl<'a, E: Default + 'static> SmartGuard<'_, E> {
         }
     }
 
+    /// Upgrades of non-exclusive guards are counted in the running operation's metrics.
     #[inline(always)]
     pub fn upgrade_write_lock(&mut self) -> bool {
         match self {
@@ -518,6 +606,41 @@ impl<'a, E: Default + 'static> SmartGuard<'_, E> {
             HybridRwWriter(..) => true,
             MutExclusive(..) => true,
             OLCWriter(..) => true,
+            TicketWriter(..) => true,
+            _ => {
+                let upgraded
+                    = self.upgrade_shared_lock();
+
+                metrics::record_upgrade(upgraded);
+                upgraded
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn upgrade_shared_lock(&mut self) -> bool {
+        match self {
+            OLCReaderEpoch(cell, read_latch) => unsafe {
+                let reader
+                    = OLCReader(Some(((**cell).clone(), *read_latch)));
+
+                ptr::write(self, reader);
+                self.upgrade_shared_lock()
+            }
+            TicketReader(lock, ptr) => unsafe {
+                let (lock, ptr)
+                    = (*lock, *ptr as *mut E);
+
+                lock.read_unlock();
+
+                if lock.try_write() {
+                    ptr::write(self, TicketWriter(lock, ptr));
+                    return true;
+                }
+
+                ptr::write(self, OLCReader(None));
+                false
+            }
             RwReader(reader, ptr) => unsafe {
                 let rw = RwLockReadGuard::rwlock(reader);
                 rw.force_unlock_read();
@@ -540,6 +663,17 @@ impl<'a, E: Default + 'static> SmartGuard<'_, E> {
                         ptr::write(self, writer);
                         return true;
                     },
+                    // Upgrades may hold latches further down, queueing up behind top-down writers
+                    // would deadlock.
+                    OptiQLCell(opt, queue) if opt.is_read_valid(*read_latch) && queue.try_lock() => {
+                        if let Some(write_latch) = opt.write_lock_strong(*read_latch) {
+                            let writer = OLCWriter(transmute_copy(cell), write_latch);
+                            ptr::write(self, writer);
+                            return true;
+                        }
+
+                        queue.unlock();
+                    }
                     // LightWeightHybridCell(opt) => if let Some(write_latch)
                     //     = opt.write_lock(*read_latch)
                     // {
@@ -563,23 +697,22 @@ impl<'a, E: Default + 'static> SmartGuard<'_, E> {
                     HybridCell(opt, rw) => if let Some(guard)
                         = rw.try_write()
                     {
-                        let read_latch
-                            = opt.load_version();
-
-                        if read_latch & WRITE_OBSOLETE_FLAG_VERSION != 0 {
-                            return false;
-                        }
-
-                        if let Some(write_latch) = opt.write_lock_strong(read_latch) {
+                        // the node may have changed since read optimistically
+                        if let Some(write_latch) = opt.write_lock_strong(*read_latch) {
                             // mem::drop(guard);
                             // let writer = OLCWriter(transmute_copy(cell), write_latch);
                             // ptr::write(self as *const _ as *mut Self, writer);
-                            let writer = transmute(HybridRwWriter(
+                            // the writer borrows the cell like any hybrid guard, the reader's clone is released
+                            let reader_cell
+                                = ptr::read(cell);
+
+                            let writer = transmute::<SmartGuard<'_, E>, Self>(HybridRwWriter(
                                 guard,
                                 opt,
                                 write_latch));
 
                             ptr::write(self as *const _ as *mut Self, writer);
+                            mem::drop(reader_cell);
                             return true;
                         }
                     }
@@ -604,36 +737,70 @@ impl<'a, E: Default + 'static> SmartGuard<'_, E> {
         }
     }
 
+    /// Turns an optimistic reader into a pin, which excludes other pins and writers
+    /// without invalidating concurrent optimistic readers. Writers are already exclusive.
     #[inline(always)]
-    pub const fn is_write_lock(&self) -> bool {
+    pub fn upgrade_pin_lock(&mut self) -> bool {
         match self {
-            RwWriter(..) => true,
-            MutExclusive(..) => true,
+            OLCReaderPin(..) => true,
             OLCWriter(..) => true,
-            HybridRwWriter(..) => true,
+            OLCReaderEpoch(cell, read_latch) => unsafe {
+                let reader
+                    = OLCReader(Some(((**cell).clone(), *read_latch)));
+
+                ptr::write(self, reader);
+                self.upgrade_pin_lock()
+            }
+            OLCReader(Some((ref cell, read_latch))) => unsafe {
+                if let LightWeightHybridCell(opt) = cell.0.as_ref() {
+                    if let Some(pin_latch) = opt.pin_lock_strong(*read_latch) {
+                        let pin = OLCReaderPin(transmute_copy::<SmartCell<E>, SmartCell<E>>(cell), pin_latch);
+                        ptr::write(self, pin);
+                        return true;
+                    }
+                }
+
+                false
+            }
             _ => false
         }
     }
 
+    #[inline(always)]
+    pub const fn is_write_lock(&self) -> bool {
+        matches!(self, RwWriter(..) | MutExclusive(..) | OLCWriter(..) | HybridRwWriter(..) | TicketWriter(..))
+    }
+
     #[inline(always)]
     pub const fn is_reader_lock(&self) -> bool {
         !self.is_write_lock()
     }
 
     #[inline(always)]
-    pub const fn is_olc_lock(&self) -> bool {
+    pub const fn latch_mode(&self) -> LatchMode {
         match self {
-            OLCReader(..) => true,
-            OLCWriter(..) => true,
-            _ => false
+            LockFree(..) => LatchMode::Free,
+            OLCReader(Some(..)) | OLCReaderEpoch(..) => LatchMode::Optimistic,
+            OLCReader(None) => LatchMode::Failed,
+            RwReader(..) | HybridRwReader(..) | TicketReader(..) => LatchMode::Shared,
+            OLCReaderPin(..) => LatchMode::Pinned,
+            RwWriter(..) | MutExclusive(..) | OLCWriter(..) | HybridRwWriter(..) | TicketWriter(..) =>
+                LatchMode::Exclusive,
         }
     }
 
+    #[inline(always)]
+    pub const fn is_olc_lock(&self) -> bool {
+        matches!(self, OLCReader(..) | OLCReaderEpoch(..) | OLCWriter(..))
+    }
+
     #[inline(always)]
     pub fn is_valid(&self) -> bool {
         match self {
             OLCReader(Some((cell, latch))) => cell.0
                 .is_read_valid(*latch),
+            OLCReaderEpoch(cell, latch) => cell.0
+                .is_read_valid(*latch),
             OLCReader(None) => false,
             HybridRwReader(.., opt, latch) =>
                 opt.is_read_valid(*latch),
@@ -646,6 +813,7 @@ impl<'a, E: Default + 'static> SmartGuard<'_, E> {
     pub fn is_read_not_obsolete(&self) -> bool {
         match self {
             OLCReader(Some((cell, ..))) => cell.0.is_read_not_obsolete(),
+            OLCReaderEpoch(cell, ..) => cell.0.is_read_not_obsolete(),
             OLCReader(None) => false,
             HybridRwReader(.., opt, _) |
             HybridRwWriter(.., opt, _) => opt.is_read_not_obsolete(),
@@ -653,9 +821,24 @@ impl<'a, E: Default + 'static> SmartGuard<'_, E> {
         }
     }
 
+    /// The cell of an optimistic writer and the version it has once the writer is released,
+    /// hence a reader latching the cell at this version later validates only if nothing was written meanwhile.
+    /// Other guards, pinned and obsoleted writers have no such version.
+    #[inline(always)]
+    pub(crate) fn release_version(&self) -> Option<(SmartCell<E>, Version)> {
+        match self {
+            OLCWriter(cell, write_latch)
+            if *write_latch & WRITE_PIN_OBSOLETE_FLAG_VERSION == WRITE_FLAG_VERSION =>
+                Some((cell.clone(), (*write_latch + 1) ^ WRITE_FLAG_VERSION)),
+            _ => None
+        }
+    }
+
+    /// # Safety
+    /// `read_latch` must be a version read from the guarded cell.
     #[inline(always)]
     pub unsafe fn update_read_latch(&mut self, read_latch: LatchVersion) {
-        if let OLCReader(Some((.., latched))) = self {
+        if let OLCReader(Some((.., latched))) | OLCReaderEpoch(.., latched) = self {
             *latched = read_latch
         }
     }
@@ -664,6 +847,7 @@ impl<'a, E: Default + 'static> SmartGuard<'_, E> {
     pub fn is_read_not_obsolete_result(&self) -> (IsRead, LatchVersion) {
         match self {
             OLCReader(Some((cell, ..))) => cell.0.is_read_not_obsolete_result(),
+            OLCReaderEpoch(cell, ..) => cell.0.is_read_not_obsolete_result(),
             OLCReader(None) => (false, LatchVersion::MIN),
             OLCReaderPin(.., latch) => (true, *latch & !PIN_FLAG_VERSION),
             HybridRwReader(.., opt, _) |
@@ -680,8 +864,12 @@ impl<'a, E: Default + 'static> SmartGuard<'_, E> {
             RwReader(.., ptr) => unsafe { ptr.as_ref() },
             RwWriter(.., ptr) => unsafe { ptr.as_ref() },
             MutExclusive(.., ptr) => unsafe { ptr.as_ref() },
+            TicketReader(.., ptr) => unsafe { ptr.as_ref() },
+            TicketWriter(.., ptr) => unsafe { ptr.as_ref() },
             OLCReader(Some((cell, latch))) if cell.0.is_read_valid(*latch) =>
                 Some(cell.0.as_ref()),
+            OLCReaderEpoch(cell, latch) if cell.0.is_read_valid(*latch) =>
+                Some(cell.0.as_ref()),
             OLCWriter(cell, ..) => Some(cell.0.as_ref()),
             OLCReaderPin(cell, ..) => Some(cell.0.as_ref()),
             HybridRwReader(.., opt, _) | HybridRwWriter(_, opt, ..) =>
@@ -690,6 +878,8 @@ impl<'a, E: Default + 'static> SmartGuard<'_, E> {
         }
     }
 
+    /// # Safety
+    /// The content may be concurrently modified, optimistic reads must be validated afterwards.
     #[inline(always)]
     pub unsafe fn deref_unsafe(&self) -> Option<&'_ E> {
         match self {
@@ -697,7 +887,10 @@ impl<'a, E: Default + 'static> SmartGuard<'_, E> {
             RwReader(.., ptr) => ptr.as_ref(),
             RwWriter(.., ptr) => ptr.as_ref(),
             MutExclusive(.., ptr) => ptr.as_ref(),
+            TicketReader(.., ptr) => ptr.as_ref(),
+            TicketWriter(.., ptr) => ptr.as_ref(),
             OLCReader(Some((cell, ..))) => Some(cell.0.as_ref()),
+            OLCReaderEpoch(cell, ..) => Some(cell.0.as_ref()),
             OLCWriter(cell, ..) => Some(cell.0.as_ref()),
             OLCReaderPin(cell, ..) =>
                 Some(cell.0.as_ref()),
@@ -708,11 +901,13 @@ impl<'a, E: Default + 'static> SmartGuard<'_, E> {
     }
 
     #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
     pub fn deref_mut(&self) -> Option<&mut E> {
         match self {
             LockFree(ptr) => unsafe { ptr.as_mut() },
             RwWriter(.., ptr) => unsafe { ptr.as_mut() },
             MutExclusive(.., ptr) => unsafe { ptr.as_mut() },
+            TicketWriter(.., ptr) => unsafe { ptr.as_mut() },
             OLCWriter(cell, ..) => Some(cell.unsafe_borrow_mut()),
             // OLCReaderPin(cell, ..) =>
             //     if let LightWeightHybridCell(opt) = cell.0.as_ref() {
@@ -727,15 +922,67 @@ impl<'a, E: Default + 'static> SmartGuard<'_, E> {
 }
 
 impl<E: Default> SmartCell<E> {
+    /// Peeks at the latch state and, for optimistic flavors, the version without its flags.
+    /// The result may be outdated as soon as it is returned.
+    pub fn latch_state(&self) -> (LatchState, Option<Version>) {
+        let optimistic_state = |opt: &OptCell<E>| {
+            let version
+                = opt.load_version();
+
+            let state = if version & OBSOLETE_FLAG_VERSION != 0 {
+                LatchState::Obsolete
+            } else if version & WRITE_FLAG_VERSION != 0 {
+                LatchState::Exclusive
+            } else if version & PIN_FLAG_VERSION != 0 {
+                LatchState::Pinned
+            } else {
+                LatchState::Free
+            };
+
+            (state, Some(version & !WRITE_PIN_OBSOLETE_FLAG_VERSION))
+        };
+
+        match self.0.as_ref() {
+            FreeCell(..) => (LatchState::Free, None),
+            ExclusiveCell(mutex, ..) if mutex.is_locked() => (LatchState::Exclusive, None),
+            ExclusiveCell(..) => (LatchState::Free, None),
+            ReadersWriterCell(rw, ..) if rw.is_locked_exclusive() => (LatchState::Exclusive, None),
+            ReadersWriterCell(rw, ..) if rw.is_locked() => (LatchState::Shared, None),
+            ReadersWriterCell(..) => (LatchState::Free, None),
+            TicketCell(lock, ..) if lock.is_locked_exclusive() => (LatchState::Exclusive, None),
+            TicketCell(lock, ..) if lock.is_locked() => (LatchState::Shared, None),
+            TicketCell(..) => (LatchState::Free, None),
+            HybridCell(opt, rw) => match optimistic_state(opt) {
+                (LatchState::Free, version) if rw.is_locked() => (LatchState::Shared, version),
+                state => state
+            },
+            OLCCell(opt) |
+            LightWeightHybridCell(opt) |
+            OptiQLCell(opt, ..) => optimistic_state(opt),
+        }
+    }
+
+    /// Hints the CPU to load the cell's first cache line, i.e. its latch, before it is latched.
+    /// Prefetches never fault, hence cells read without validation may be passed.
+    #[inline(always)]
+    pub fn prefetch(&self) {
+        #[cfg(target_arch = "x86_64")]
+        unsafe {
+            use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+            _mm_prefetch::<_MM_HINT_T0>(SlabArc::as_ptr(&self.0) as *const i8)
+        }
+    }
+
     #[inline(always)]
     pub fn unsafe_borrow(&self) -> &E {
         match self.0.as_ref() {
             ExclusiveCell(.., ptr) => ptr.as_ref(),
             OLCCell(opt) | LightWeightHybridCell(opt) =>
                 opt.cell.as_ref(),
-            HybridCell(opt, _) => opt.cell.as_ref(),
+            HybridCell(opt, _) | OptiQLCell(opt, _) => opt.cell.as_ref(),
             FreeCell(ptr) => ptr.as_ref(),
             ReadersWriterCell(.., ptr) => ptr.as_ref(),
+            TicketCell(.., ptr) => ptr.as_ref(),
         }
     }
 
@@ -745,9 +992,10 @@ impl<E: Default> SmartCell<E> {
             ExclusiveCell(.., ptr) => ptr.get_mut(),
             OLCCell(opt) | LightWeightHybridCell(opt) =>
                 opt.cell.get_mut(),
-            HybridCell(opt, ..) => opt.cell.get_mut(),
+            HybridCell(opt, ..) | OptiQLCell(opt, ..) => opt.cell.get_mut(),
             FreeCell(ptr) => ptr.get_mut(),
-            ReadersWriterCell(.., ptr) => ptr.get_mut()
+            ReadersWriterCell(.., ptr) => ptr.get_mut(),
+            TicketCell(.., ptr) => ptr.get_mut()
         }
     }
 
@@ -763,8 +1011,8 @@ impl<E: Default> SmartCell<E> {
     pub fn borrow_read_hybrid(&self) -> SmartGuard<'static, E> {
         match self.0.deref() {
             HybridCell(opt, rw) => unsafe {
-                transmute(HybridRwReader(
-                    rw.read(),
+                transmute::<SmartGuard<'_, E>, SmartGuard<'static, E>>(HybridRwReader(
+                    rw.try_read().unwrap_or_else(|| metrics::wait(|| rw.read())),
                     opt,
                     opt.load_version()))
             }
@@ -777,23 +1025,48 @@ impl<E: Default> SmartCell<E> {
         match self.0.deref() {
             OLCCell(opt) |
             HybridCell(opt, ..) |
-            LightWeightHybridCell(opt) => {
+            LightWeightHybridCell(opt) |
+            OptiQLCell(opt, ..) => {
                 let (success, read)
                     = opt.read_lock();
 
                 OLCReader(success.then(|| (self.clone(), read)))
             }
             ExclusiveCell(mutex, ptr) => unsafe {
-                MutExclusive(transmute(mutex.lock()),
+                MutExclusive(transmute::<MutexGuard<'_, RawMutex, ()>, MutexGuard<'static, RawMutex, ()>>(mutex.try_lock().unwrap_or_else(|| metrics::wait(|| mutex.lock()))),
                              ptr.get_mut())
             },
             ReadersWriterCell(rw, ptr) => unsafe {
-                RwReader(transmute(rw.read()), ptr.as_ref())
+                RwReader(transmute::<RwLockReadGuard<'_, RawRwLock, ()>, RwLockReadGuard<'static, RawRwLock, ()>>(rw.try_read().unwrap_or_else(|| metrics::wait(|| rw.read()))), ptr.as_ref())
+            },
+            TicketCell(lock, ptr) => unsafe {
+                lock.read();
+                TicketReader(&*(lock as *const TicketRwLock), ptr.as_ref())
             },
             FreeCell(ptr) => LockFree(ptr.get_mut()),
         }
     }
 
+    /// Optimistic read latch without touching the reference count.
+    /// Non-optimistic flavors fall back to `borrow_read`.
+    ///
+    /// # Safety
+    /// The calling thread must stay pinned (see `epoch::pin`) for the guard's lifetime,
+    /// and unlinked cells must be handed to `epoch::retire` instead of being dropped.
+    #[inline(always)]
+    pub unsafe fn borrow_read_epoch(&self) -> SmartGuard<'static, E> {
+        match self.0.deref() {
+            OLCCell(opt) |
+            HybridCell(opt, ..) |
+            LightWeightHybridCell(opt) |
+            OptiQLCell(opt, ..) => match opt.read_lock() {
+                (true, read) => OLCReaderEpoch(ManuallyDrop::new(ptr::read(self)), read),
+                _ => OLCReader(None)
+            }
+            _ => self.borrow_read()
+        }
+    }
+
     #[inline(always)]
     pub fn borrow_pin(&self) -> SmartGuard<'static, E> {
         match self.0.deref() {
@@ -813,8 +1086,8 @@ impl<E: Default> SmartCell<E> {
         match self.0.deref() {
             FreeCell(ptr) => LockFree(ptr.get_mut()),
             ReadersWriterCell(rw, ptr) => unsafe {
-                transmute(RwWriter(
-                    transmute(rw.write()),
+                transmute::<SmartGuard<'_, E>, SmartGuard<'static, E>>(RwWriter(
+                    transmute::<RwLockWriteGuard<'_, RawRwLock, ()>, RwLockWriteGuard<'static, RawRwLock, ()>>(rw.try_write().unwrap_or_else(|| metrics::wait(|| rw.write()))),
                     ptr.get_mut(),
                 ))
             },
@@ -839,7 +1112,7 @@ impl<E: Default> SmartCell<E> {
                     if !read {
                         OLCReader(None)
                     } else if let Some(write_latch) = opt.write_lock_strong(read_version) {
-                        transmute(HybridRwWriter(writer,
+                        transmute::<SmartGuard<'_, E>, SmartGuard<'static, E>>(HybridRwWriter(writer,
                                                  opt,
                                                  write_latch))
                     } else {
@@ -848,11 +1121,48 @@ impl<E: Default> SmartCell<E> {
                 }
             }
             ExclusiveCell(mutex, ptr) => unsafe {
-                transmute(MutExclusive(
-                    transmute(mutex.lock()),
+                transmute::<SmartGuard<'_, E>, SmartGuard<'static, E>>(MutExclusive(
+                    transmute::<MutexGuard<'_, RawMutex, ()>, MutexGuard<'static, RawMutex, ()>>(mutex.try_lock().unwrap_or_else(|| metrics::wait(|| mutex.lock()))),
                     ptr.get_mut(),
                 ))
             }
+            TicketCell(lock, ptr) => unsafe {
+                lock.write();
+                TicketWriter(&*(lock as *const TicketRwLock), ptr.get_mut())
+            }
+            OptiQLCell(opt, queue) => {
+                queue.lock();
+
+                let (read, read_version)
+                    = opt.is_read_not_obsolete_result();
+
+                if !read {
+                    queue.unlock();
+                    OLCReader(None)
+                } else if let Some(write_latch) = opt.write_lock_strong(read_version) {
+                    OLCWriter(self.clone(), write_latch)
+                } else {
+                    queue.unlock();
+                    OLCReader(None)
+                }
+            }
+        }
+    }
+
+    /// Like `borrow_mut`, but waits for optimistic write latches held by others instead of failing,
+    /// see `StrategyOverride::PessimisticWrite`. Fails only if the cell is obsolete.
+    #[inline(always)]
+    pub fn borrow_mut_waiting(&self) -> SmartGuard<'static, E> {
+        let mut attempt = 0;
+
+        loop {
+            match self.borrow_mut() {
+                OLCReader(None) if self.latch_state().0 != LatchState::Obsolete =>
+                    sched_yield(attempt),
+                guard => break guard
+            }
+
+            attempt += 1;
         }
     }
 }
@@ -881,6 +1191,9 @@ impl<'a, E: Default> Drop for SmartGuard<'a, E> {
                     }
                 } else if let OLCCell(opt) | HybridCell(opt, ..) = cell.0.as_ref() {
                     opt.write_unlock(*write_version)
+                } else if let OptiQLCell(opt, queue) = cell.0.as_ref() {
+                    opt.write_unlock(*write_version);
+                    queue.unlock()
                 }
             OLCReaderPin(cell, pin_version) =>
                 if let LightWeightHybridCell(opt) = cell.0.as_ref() {
@@ -888,6 +1201,10 @@ impl<'a, E: Default> Drop for SmartGuard<'a, E> {
                 }
             HybridRwWriter(.., opt, latch) =>
                 opt.write_unlock(*latch),
+            TicketReader(lock, ..) =>
+                lock.read_unlock(),
+            TicketWriter(lock, ..) =>
+                lock.write_unlock(),
             _ => {}
         }
     }
@@ -0,0 +1,136 @@
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
+use crate::utils::smart_cell::sched_yield;
+
+const READER_INC: u32 = 0x100;
+const WRITER_BITS: u32 = 0x3;
+const WRITER_PRESENT: u32 = 0x2;
+const WRITER_PHASE: u32 = 0x1;
+
+/// Phase-fair ticket readers-writer lock (PF-T, Brandenburg and Anderson).
+/// Writers are admitted in FIFO ticket order, readers and writers alternate in phases,
+/// hence neither side is able to starve the other and hand-offs are strictly fair.
+#[derive(Default)]
+pub struct TicketRwLock {
+    reader_in: AtomicU32,
+    reader_out: AtomicU32,
+    writer_in: AtomicU32,
+    writer_out: AtomicU32,
+}
+
+impl TicketRwLock {
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            reader_in: AtomicU32::new(0),
+            reader_out: AtomicU32::new(0),
+            writer_in: AtomicU32::new(0),
+            writer_out: AtomicU32::new(0),
+        }
+    }
+
+    /// Blocks until a shared latch is granted.
+    #[inline(always)]
+    pub fn read(&self) {
+        let writer_phase
+            = self.reader_in.fetch_add(READER_INC, Acquire) & WRITER_BITS;
+
+        if writer_phase != 0 {
+            let mut attempt = 0;
+            while self.reader_in.load(Acquire) & WRITER_BITS == writer_phase {
+                sched_yield(attempt);
+                attempt += 1;
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn read_unlock(&self) {
+        self.reader_out.fetch_add(READER_INC, Release);
+    }
+
+    /// Blocks until the exclusive latch is granted, i.e. until its ticket is served and
+    /// all readers of the preceding reader phase left.
+    #[inline(always)]
+    pub fn write(&self) {
+        let ticket
+            = self.writer_in.fetch_add(1, Relaxed);
+
+        let mut attempt = 0;
+        while self.writer_out.load(Acquire) != ticket {
+            sched_yield(attempt);
+            attempt += 1;
+        }
+
+        self.await_readers(ticket);
+    }
+
+    /// Tries to grab the exclusive latch without queuing.
+    /// Fails if any writer holds or waits for the latch, or if any reader is present.
+    #[inline(always)]
+    pub fn try_write(&self) -> bool {
+        let ticket
+            = self.writer_out.load(Acquire);
+
+        if self.reader_in.load(Relaxed) & !WRITER_BITS != self.reader_out.load(Relaxed) {
+            return false;
+        }
+
+        if self.writer_in.compare_exchange(
+            ticket,
+            ticket.wrapping_add(1),
+            Acquire,
+            Relaxed).is_err()
+        {
+            return false;
+        }
+
+        let reader_ticket = self.reader_in
+            .fetch_add(WRITER_PRESENT | (ticket & WRITER_PHASE), AcqRel);
+
+        if self.reader_out.load(Acquire) != reader_ticket {
+            self.write_unlock();
+            return false;
+        }
+
+        true
+    }
+
+    #[inline(always)]
+    pub fn write_unlock(&self) {
+        self.reader_in.fetch_and(!WRITER_BITS, Release);
+        self.writer_out.fetch_add(1, Release);
+    }
+
+    /// Atomically turns the held exclusive latch into a shared latch.
+    #[inline(always)]
+    pub fn downgrade(&self) {
+        self.reader_in.fetch_add(READER_INC, Acquire);
+        self.write_unlock();
+    }
+
+    /// True if a writer holds or waits for the latch.
+    #[inline(always)]
+    pub fn is_locked_exclusive(&self) -> bool {
+        self.writer_in.load(Relaxed) != self.writer_out.load(Relaxed)
+    }
+
+    /// True if any reader or writer holds or waits for the latch.
+    #[inline(always)]
+    pub fn is_locked(&self) -> bool {
+        self.is_locked_exclusive() ||
+            self.reader_in.load(Relaxed) & !WRITER_BITS != self.reader_out.load(Relaxed)
+    }
+
+    #[inline(always)]
+    fn await_readers(&self, ticket: u32) {
+        let reader_ticket = self.reader_in
+            .fetch_add(WRITER_PRESENT | (ticket & WRITER_PHASE), AcqRel);
+
+        let mut attempt = 0;
+        while self.reader_out.load(Acquire) != reader_ticket {
+            sched_yield(attempt);
+            attempt += 1;
+        }
+    }
+}
@@ -44,6 +44,7 @@ impl<E: Default> UnCell<E> {
 
     /// Unsafely gets the wrapped object as mutable reference.
     #[inline]
+    #[allow(clippy::mut_from_ref)]
     pub fn get_mut(&self) -> &mut E {
         unsafe { &mut *self.inner.get() }
     }
@@ -0,0 +1,156 @@
+mod common;
+
+use common::{assert_consistent, delete, get, insert, shuffled, strategies, tree, Tree};
+use CCBPlusTree::locking::locking_strategy::OLC;
+use CCBPlusTree::test::{dec_key, inc_key};
+use CCBPlusTree::tree::bplus_tree::BPlusTree;
+use CCBPlusTree::tree::export::NodeExport;
+use CCBPlusTree::utils::epoch;
+
+/// Optimistic readers pinned before the bulk load may still traverse the previous blocks.
+#[test]
+fn bulk_load_retires_previous_blocks() {
+    let tree
+        = tree(OLC());
+
+    (0..10_000).for_each(|key| insert(&tree, key, key));
+
+    let previous
+        = tree.slab_usage().slots_in_use;
+
+    let _epoch
+        = epoch::pin();
+
+    let tree = tree
+        .bulk_load((0..100).map(|key| (key, key)));
+
+    assert!(tree.slab_usage().slots_in_use > previous, "{}", tree.slab_usage().slots_in_use);
+}
+
+fn leaf_fill(tree: &Tree) -> f64 {
+    tree.stats()
+        .levels
+        .last()
+        .unwrap()
+        .avg_fill
+}
+
+#[test]
+fn bulk_load_builds_full_nodes() {
+    for locking_strategy in strategies() {
+        let tree = tree(locking_strategy.clone())
+            .bulk_load((0..50_000).map(|key| (key, key)));
+
+        assert!(tree.validate().is_valid(), "{locking_strategy}");
+        assert!(leaf_fill(&tree) > 0.9, "{locking_strategy}: {}", leaf_fill(&tree));
+        assert!((0..50_000).all(|key| get(&tree, key) == Some(key)), "{locking_strategy}");
+
+        // later writes split and merge the bulk built nodes like any other
+        (50_000..60_000).for_each(|key| insert(&tree, key, key));
+        (0..60_000).step_by(2).for_each(|key| delete(&tree, key));
+        assert_consistent(&tree, 30_000);
+    }
+}
+
+#[test]
+fn bulk_load_leaves_room_by_fill_factor() {
+    let tree = tree(OLC())
+        .bulk_load_with((0..50_000).map(|key| (key, key)), 0.7);
+
+    assert!(tree.validate().is_valid());
+    assert!((0.6..0.8).contains(&leaf_fill(&tree)), "{}", leaf_fill(&tree));
+
+    // fill factors below one half still keep nodes from underflowing
+    let tree = tree
+        .bulk_load_with((0..50_000).map(|key| (key, key)), 0.1);
+
+    assert!(tree.validate().is_valid());
+    assert!((0..50_000).all(|key| get(&tree, key) == Some(key)));
+}
+
+#[test]
+fn bulk_load_sorts_unsorted_records() {
+    let records = shuffled(0..10_000, 1)
+        .into_iter()
+        .flat_map(|key| [(key, key), (key, key + 1)]);
+
+    let tree = tree(OLC())
+        .bulk_load(records);
+
+    assert!(tree.validate().is_valid());
+    assert!((0..10_000).all(|key| get(&tree, key) == Some(key)));
+}
+
+#[test]
+fn bulk_load_small_inputs() {
+    for len in [0, 1, 2, 15, 16, 17] {
+        let tree = tree(OLC())
+            .bulk_load((0..len).map(|key| (key, key)));
+
+        assert!(tree.validate().is_valid(), "{len}");
+        assert!((0..len).all(|key| get(&tree, key) == Some(key)), "{len}");
+
+        insert(&tree, len, len);
+        assert_consistent(&tree, len as usize + 1);
+    }
+}
+
+#[test]
+fn bulk_load_keeps_first_of_equal_keys() {
+    // ascending apart from repeated keys, which are not skipped by sorting
+    let tree = tree(OLC())
+        .bulk_load((0..1_000).flat_map(|key| [(key, key), (key, key + 1)]));
+
+    assert!(tree.validate().is_valid());
+    assert_eq!(tree.stats().records, 1_000);
+    assert!((0..1_000).all(|key| get(&tree, key) == Some(key)));
+}
+
+#[test]
+fn bulk_load_fills_index_levels() {
+    let tree = tree(OLC())
+        .bulk_load((0..100_000).map(|key| (key, key)));
+
+    let stats
+        = tree.stats();
+
+    // all levels but the root are filled up to one below their overflow
+    stats.levels
+        .iter()
+        .skip(1)
+        .for_each(|level| assert!(level.avg_fill > 0.9, "{}", level.avg_fill));
+}
+
+#[test]
+#[should_panic(expected = "Fill factor must be within (0, 1]!")]
+fn bulk_load_rejects_fill_factor_above_one() {
+    let _ = tree(OLC())
+        .bulk_load_with((0..100).map(|key| (key, key)), 1.5);
+}
+
+/// Index pages hold one key less than FAN_OUT - 1 before they overflow, i.e. up to six children.
+/// An even number of keys leaves seven children too few for two pages of at least four.
+type OddTree = BPlusTree<7, 8, u64, u64>;
+
+fn max_children(node: &NodeExport<u64>) -> usize {
+    node.children
+        .iter()
+        .map(max_children)
+        .max()
+        .unwrap_or_default()
+        .max(node.children.len())
+}
+
+#[test]
+fn bulk_load_odd_fan_out_never_overflows_index_pages() {
+    for fill_factor in [1.0, 0.7, 0.3] {
+        for len in 0..2_000 {
+            let tree = OddTree::new_with(OLC(), u64::MIN, u64::MAX, inc_key, dec_key)
+                .bulk_load_with((0..len).map(|key| (key, key)), fill_factor);
+
+            assert!(tree.validate().is_valid(), "{fill_factor}: {len}: {:?}", tree.validate().violations);
+            assert!(max_children(&tree.export().root) <= 6, "{fill_factor}: {len}");
+            assert_eq!(tree.stats().records, len as usize, "{fill_factor}: {len}");
+        }
+    }
+}
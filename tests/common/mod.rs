@@ -0,0 +1,144 @@
+#![allow(dead_code)]
+
+use std::{fs, thread};
+use std::ops::Range;
+use std::path::PathBuf;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use CCBPlusTree::crud_model::crud_api::CRUDDispatcher;
+use CCBPlusTree::crud_model::crud_operation::CRUDOperation;
+use CCBPlusTree::crud_model::crud_operation_result::CRUDOperationResult;
+use CCBPlusTree::locking::locking_strategy::*;
+use CCBPlusTree::test::{dec_key, inc_key};
+use CCBPlusTree::tree::bplus_tree::BPlusTree;
+use CCBPlusTree::tree::validation::Violation;
+
+pub type Tree = BPlusTree<16, 16, u64, u64>;
+
+pub fn strategies() -> Vec<LockingStrategy> {
+    vec![
+        LockingStrategy::MonoWriter,
+        LockingStrategy::LockCoupling,
+        orwc(),
+        ticket_orwc(),
+        OLC(),
+        lightweight_hybrid_lock(),
+        hybrid_lock(),
+        optiql(),
+        copy_on_write(),
+    ]
+}
+
+pub fn tree(locking_strategy: LockingStrategy) -> Tree {
+    Tree::new_with(locking_strategy, u64::MIN, u64::MAX, inc_key, dec_key)
+}
+
+/// Fresh directory for the files of a test, emptied if left over by a previous run.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("ccbplustree-{}-{name}", std::process::id()));
+
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+pub fn shuffled(keys: Range<u64>, seed: u64) -> Vec<u64> {
+    let mut keys
+        = keys.collect::<Vec<_>>();
+
+    keys.shuffle(&mut StdRng::seed_from_u64(seed));
+    keys
+}
+
+pub fn get(tree: &Tree, key: u64) -> Option<u64> {
+    match tree.dispatch(CRUDOperation::Point(key)).1 {
+        CRUDOperationResult::MatchedRecord(record) => record.map(|record| record.payload),
+        _ => panic!("Point({key}) failed")
+    }
+}
+
+pub fn insert(tree: &Tree, key: u64, payload: u64) {
+    match tree.dispatch(CRUDOperation::Insert(key, payload)).1 {
+        CRUDOperationResult::Inserted(inserted) => assert_eq!(inserted, key),
+        _ => panic!("Insert({key}) failed")
+    }
+}
+
+pub fn delete(tree: &Tree, key: u64) {
+    match tree.dispatch(CRUDOperation::Delete(key)).1 {
+        CRUDOperationResult::Deleted(deleted, _) => assert_eq!(deleted, key),
+        _ => panic!("Delete({key}) failed")
+    }
+}
+
+/// Underflows are corrected top-down by later writes, any other violation breaks the tree.
+pub fn assert_consistent(tree: &Tree, records: usize) {
+    let report
+        = tree.validate();
+
+    let broken = report.violations
+        .iter()
+        .filter(|violation| !matches!(violation, Violation::Underflow { .. }))
+        .collect::<Vec<_>>();
+
+    assert!(broken.is_empty(), "{}: {:?}", tree.locking_strategy(), broken);
+    assert_eq!(report.records, records, "{}", tree.locking_strategy());
+}
+
+/// Inserts `inserts` shuffled keys, deletes `deletes` of them again at random
+/// and checks that exactly the remaining keys are found.
+pub fn assert_random_deletes(tree: &Tree, inserts: u64, deletes: usize, seed: u64) {
+    let keys
+        = shuffled(0..inserts, seed);
+
+    keys.iter().for_each(|key| insert(tree, *key, *key));
+
+    let mut keys
+        = shuffled(0..inserts, seed + 1);
+
+    let kept
+        = keys.split_off(deletes);
+
+    keys.iter().for_each(|key| delete(tree, *key));
+
+    let strategy
+        = tree.locking_strategy();
+
+    kept.iter().for_each(|key| assert_eq!(get(tree, *key), Some(*key), "{strategy}: lost {key}"));
+    keys.iter().for_each(|key| assert_eq!(get(tree, *key), None, "{strategy}: kept deleted {key}"));
+
+    assert_consistent(tree, kept.len());
+}
+
+/// Inserts and deletes from two threads each while another one keeps reading untouched keys,
+/// then checks that exactly the untouched and inserted keys remain.
+pub fn assert_concurrent_workload(tree: &Tree) {
+    let strategy
+        = tree.locking_strategy();
+
+    (0..20_000).for_each(|key| insert(tree, key, key));
+
+    thread::scope(|scope| {
+        (0..2).for_each(|thread| {
+            scope.spawn(move || shuffled(20_000..40_000, thread)
+                .into_iter()
+                .filter(|key| key % 2 == thread)
+                .for_each(|key| insert(tree, key, key)));
+
+            scope.spawn(move || shuffled(10_000..20_000, thread + 2)
+                .into_iter()
+                .filter(|key| key % 2 == thread)
+                .for_each(|key| delete(tree, key)));
+        });
+
+        scope.spawn(move || shuffled(0..10_000, 4)
+            .into_iter()
+            .for_each(|key| assert_eq!(get(tree, key), Some(key), "{strategy}: lost {key}")));
+    });
+
+    assert!((0..10_000).chain(20_000..40_000).all(|key| get(tree, key) == Some(key)), "{strategy}");
+    assert!((10_000..20_000).all(|key| get(tree, key).is_none()), "{strategy}");
+    assert_consistent(tree, 30_000);
+}
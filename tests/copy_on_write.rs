@@ -0,0 +1,106 @@
+mod common;
+
+use std::{fs, mem, thread};
+use common::{assert_concurrent_workload, assert_consistent, delete, get, insert, shuffled, temp_dir, tree, Tree};
+use CCBPlusTree::crud_model::crud_api::CRUDDispatcher;
+use CCBPlusTree::crud_model::crud_operation::CRUDOperation;
+use CCBPlusTree::crud_model::crud_operation_result::CRUDOperationResult;
+use CCBPlusTree::locking::locking_strategy::copy_on_write;
+use CCBPlusTree::test::{dec_key, inc_key};
+use CCBPlusTree::tree::wal::{DurabilityConfig, FsyncPolicy};
+
+/// Readers keep finding the even keys, while writers republish their leaves
+/// by updating them and by inserting and deleting the odd keys in between.
+#[test]
+fn reads_during_leaf_republishing() {
+    let tree
+        = tree(copy_on_write());
+
+    let even
+        = insert_even(&tree);
+
+    thread::scope(|scope| {
+        republish(scope, &tree);
+
+        for _ in 0..2 {
+            scope.spawn(|| for _ in 0..20 {
+                let result
+                    = tree.get_many(even.iter().cloned());
+
+                assert_eq!(result.matched(), even.len());
+                assert!(result.records
+                    .iter()
+                    .zip(&even)
+                    .all(|(record, key)| record.as_ref().is_some_and(|record| record.payload == *key)));
+
+                assert!(even.iter().step_by(97).all(|key| get(&tree, *key) == Some(*key)));
+            });
+        }
+    });
+
+    assert_consistent(&tree, even.len());
+}
+
+#[test]
+fn concurrent_workload() {
+    assert_concurrent_workload(&tree(copy_on_write()));
+}
+
+/// Checkpoints read leaves outside of operations, while writers republish them.
+#[test]
+fn checkpoint_during_leaf_republishing() {
+    let dir
+        = temp_dir("cow-checkpoint");
+
+    let config = DurabilityConfig::new(dir.join("snapshot"), dir.join("log"))
+        .with_fsync(FsyncPolicy::Never);
+
+    let tree = Tree::open_durable(config.clone(), copy_on_write(), u64::MIN, u64::MAX, inc_key, dec_key)
+        .unwrap();
+
+    let even
+        = insert_even(&tree);
+
+    thread::scope(|scope| {
+        republish(scope, &tree);
+
+        scope.spawn(|| for _ in 0..10 {
+            let records
+                = tree.checkpoint().unwrap();
+
+            assert!(records >= even.len() && records <= 20_000, "{records}");
+        });
+    });
+
+    assert_consistent(&tree, even.len());
+
+    mem::drop(tree);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+fn insert_even(tree: &Tree) -> Vec<u64> {
+    let even
+        = (0..20_000).step_by(2).collect::<Vec<u64>>();
+
+    even.iter().for_each(|key| insert(tree, *key, *key));
+    even
+}
+
+/// Writers inserting the odd keys, updating their even predecessors and deleting the odd keys again.
+fn republish<'scope>(scope: &'scope thread::Scope<'scope, '_>, tree: &'scope Tree) {
+    for seed in 0..2 {
+        scope.spawn(move || {
+            let odd = shuffled(0..10_000, seed)
+                .into_iter()
+                .map(|key| 2 * key + 1)
+                .filter(|key| key % 4 == 2 * seed + 1)
+                .collect::<Vec<_>>();
+
+            odd.iter().for_each(|key| insert(tree, *key, *key));
+            odd.iter().for_each(|key| assert!(matches!(
+                tree.dispatch(CRUDOperation::Update(*key - 1, *key - 1)).1,
+                CRUDOperationResult::Updated(..))));
+            odd.iter().for_each(|key| delete(tree, *key));
+        });
+    }
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::{assert_consistent, assert_random_deletes, delete, get, insert, strategies, tree};
+
+#[test]
+fn random_deletes_keep_remaining_records() {
+    for locking_strategy in strategies() {
+        assert_random_deletes(&tree(locking_strategy), 40_000, 30_000, 7);
+    }
+}
+
+#[test]
+fn ascending_deletes_empty_tree() {
+    for locking_strategy in strategies() {
+        let tree
+            = tree(locking_strategy);
+
+        (0..10_000).for_each(|key| insert(&tree, key, key));
+        (0..10_000).for_each(|key| {
+            delete(&tree, key);
+            assert_eq!(get(&tree, 9_999), (key < 9_999).then_some(9_999));
+        });
+
+        assert_eq!(tree.height(), 1);
+        assert_consistent(&tree, 0);
+    }
+}
+
+#[test]
+fn descending_deletes_empty_tree() {
+    for locking_strategy in strategies() {
+        let tree
+            = tree(locking_strategy);
+
+        (0..10_000).for_each(|key| insert(&tree, key, key));
+        (0..10_000).rev().for_each(|key| delete(&tree, key));
+
+        assert_eq!(tree.height(), 1);
+        assert_consistent(&tree, 0);
+    }
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use std::collections::HashSet;
+use std::thread;
+use common::{insert, shuffled, strategies, temp_dir, tree, Tree};
+use CCBPlusTree::locking::locking_strategy::{LockingStrategy, OLC};
+use CCBPlusTree::page_model::BlockID;
+use CCBPlusTree::test::{dec_key, inc_key};
+use CCBPlusTree::tree::export::{NodeExport, NodeKind, TreeExport};
+
+/// Two leaves of ten records below a single index page, block 0 was the empty root.
+fn two_leaves() -> Tree {
+    tree(OLC()).bulk_load((0..20).map(|key| (key * 10, key)))
+}
+
+fn block_ids(node: &NodeExport<u64>, ids: &mut Vec<BlockID>) {
+    ids.push(node.block_id);
+    node.children.iter().for_each(|child| block_ids(child, ids));
+}
+
+/// IDs of all blocks in pre-order, asserting that no two blocks share an ID
+/// and each is found by `BPlusTree::export_block`.
+fn assert_unique_ids(tree: &Tree) -> Vec<BlockID> {
+    let export
+        = tree.export();
+
+    let mut ids
+        = vec![];
+
+    block_ids(&export.root, &mut ids);
+
+    assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len(), "{}", export.locking_strategy);
+    assert!(ids.iter().all(|id| tree.find_block(*id).is_some()
+        && tree.export_block(*id).map(|node| node.block_id) == Some(*id)), "{}", export.locking_strategy);
+
+    ids
+}
+
+#[test]
+fn dot_of_fixed_tree() {
+    let expected = "\
+digraph BPlusTree {
+\tlabel=\"OLC (height=2)\";
+\tnode [shape=record, fontname=\"monospace\"];
+\tn0 [label=\"{Index #3 [0, max]|Free v0|{<c0>|100|<c1>}}\"];
+\tn1 [label=\"{Leaf #1 [0, 100)|Free v0|0..90 (10 records)}\"];
+\tn0:c0 -> n1;
+\tn2 [label=\"{Leaf #2 [100, max]|Free v0|100..190 (10 records)}\"];
+\tn0:c1 -> n2;
+}
+";
+
+    assert_eq!(two_leaves().to_dot(), expected);
+}
+
+#[test]
+fn json_round_trip() {
+    let export
+        = two_leaves().export();
+
+    let json
+        = export.to_json().unwrap();
+
+    assert_eq!(serde_json::from_str::<TreeExport<u64>>(&json).unwrap(), export);
+
+    let root
+        = &export.root;
+
+    assert_eq!((root.kind, root.block_id, root.keys.as_slice()), (NodeKind::Index, 3, [100].as_slice()));
+    assert_eq!(root.children.len(), 2);
+    assert!(root.children.iter().all(|leaf| leaf.kind == NodeKind::Leaf && leaf.keys.len() == 10));
+    assert_eq!((root.children[0].lower, root.children[0].upper), (0, Some(100)));
+    assert_eq!((root.children[1].lower, root.children[1].upper), (100, None));
+}
+
+#[test]
+fn lookup_by_id() {
+    let tree
+        = two_leaves();
+
+    let export
+        = tree.export();
+
+    assert_eq!(tree.export_block(3).as_ref(), Some(&export.root));
+    assert_eq!(tree.export_block(1).as_ref(), Some(&export.root.children[0]));
+    assert_eq!(tree.export_block(2).as_ref(), Some(&export.root.children[1]));
+
+    // the empty root replaced by the bulk load and IDs never handed out
+    assert!(tree.export_block(0).is_none() && tree.find_block(0).is_none());
+    assert!(tree.export_block(4).is_none() && tree.find_block(4).is_none());
+}
+
+#[test]
+fn ids_are_unique_under_concurrent_splits() {
+    // a single writer at a time, see `LockingStrategy::MonoWriter`
+    for locking_strategy in strategies().into_iter().filter(|s| !matches!(s, LockingStrategy::MonoWriter)) {
+        let tree
+            = tree(locking_strategy);
+
+        let keys
+            = shuffled(0..40_000, 7);
+
+        thread::scope(|scope| keys
+            .chunks(10_000)
+            .for_each(|chunk| {
+                let tree = &tree;
+                scope.spawn(move || chunk.iter().for_each(|key| insert(tree, *key, *key)));
+            }));
+
+        assert!(assert_unique_ids(&tree).len() > 40_000 / 16);
+    }
+}
+
+#[test]
+fn ids_stay_unique_after_bulk_load_and_snapshot() {
+    let dir
+        = temp_dir("export-ids");
+
+    let path
+        = dir.join("snapshot");
+
+    let loaded
+        = tree(OLC()).bulk_load((0..10_000).map(|key| (2 * key, key)));
+
+    let ids
+        = assert_unique_ids(&loaded);
+
+    // saving reads the tree, it keeps its blocks and their IDs
+    loaded.save_to(&path).unwrap();
+    assert_eq!(assert_unique_ids(&loaded), ids);
+
+    // blocks split after loading take fresh IDs, the untouched ones keep theirs
+    let reloaded
+        = Tree::load_from(&path, inc_key, dec_key).unwrap();
+
+    for tree in [&loaded, &reloaded] {
+        let before
+            = assert_unique_ids(tree);
+
+        (0..10_000).step_by(2).for_each(|key| insert(tree, 2 * key + 1, key));
+
+        let after
+            = assert_unique_ids(tree);
+
+        let kept
+            = after.iter().filter(|id| before.contains(id)).count();
+
+        assert!(kept > 0 && after.len() > before.len());
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
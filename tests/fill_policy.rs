@@ -0,0 +1,178 @@
+mod common;
+
+use common::{assert_concurrent_workload, assert_consistent, assert_random_deletes, delete, insert, strategies, tree};
+use CCBPlusTree::crud_model::crud_api::CRUDDispatcher;
+use CCBPlusTree::crud_model::crud_operation::CRUDOperation;
+use CCBPlusTree::crud_model::crud_operation_result::CRUDOperationResult;
+use CCBPlusTree::locking::locking_strategy::*;
+use CCBPlusTree::tree::fill_policy::{FillPolicy, Rebalance};
+
+fn policies() -> [FillPolicy; 7] {
+    [
+        FillPolicy::default(),
+        FillPolicy::default().with_rebalance(Rebalance::Borrow),
+        FillPolicy::default().with_split_ratio(0.9),
+        FillPolicy::default().with_split_ratio(0.9).with_min_fill(0.25),
+        FillPolicy::default().with_split_ratio(0.2).with_rebalance(Rebalance::Borrow),
+        FillPolicy::default().with_min_fill(0.25).with_rebalance(Rebalance::Borrow),
+        FillPolicy::lazy(),
+    ]
+}
+
+#[test]
+fn random_deletes_under_each_policy() {
+    for locking_strategy in [LockingStrategy::MonoWriter, OLC(), lightweight_hybrid_lock(), optiql(), copy_on_write()] {
+        for fill_policy in policies() {
+            let tree = tree(locking_strategy.clone())
+                .with_fill_policy(fill_policy);
+
+            assert_random_deletes(&tree, 40_000, 30_000, 11);
+        }
+    }
+}
+
+#[test]
+fn concurrent_workload_under_each_policy() {
+    for locking_strategy in [OLC(), orwc(), hybrid_lock()] {
+        for fill_policy in policies() {
+            let tree = tree(locking_strategy.clone())
+                .with_fill_policy(fill_policy);
+
+            assert_concurrent_workload(&tree);
+        }
+    }
+}
+
+#[test]
+fn split_ratio_fills_left_leaves() {
+    let leaf_fill = |fill_policy: FillPolicy| {
+        let tree = tree(OLC())
+            .with_fill_policy(fill_policy);
+
+        (0..10_000).for_each(|key| insert(&tree, key, key));
+
+        tree.stats()
+            .levels
+            .last()
+            .unwrap()
+            .avg_fill
+    };
+
+    // the right leaf keeps the minimum fill regardless of the ratio
+    let half
+        = leaf_fill(FillPolicy::default().with_min_fill(0.25));
+
+    let ascending
+        = leaf_fill(FillPolicy::default().with_min_fill(0.25).with_split_ratio(0.9));
+
+    assert!(ascending > half + 0.2, "{ascending} vs {half}");
+}
+
+#[test]
+fn lazy_policy_keeps_sparse_leaves() {
+    let leaf_pages = |fill_policy: FillPolicy| {
+        let tree = tree(OLC())
+            .with_fill_policy(fill_policy);
+
+        (0..1_600).for_each(|key| insert(&tree, key, key));
+        (0..1_600).filter(|key| key % 16 != 0).for_each(|key| delete(&tree, key));
+
+        let report
+            = tree.validate();
+
+        assert_eq!(report.records, 100);
+        report.leaf_pages
+    };
+
+    let lazy
+        = leaf_pages(FillPolicy::lazy());
+
+    let merged
+        = leaf_pages(FillPolicy::default());
+
+    assert!(lazy > 2 * merged, "{lazy} vs {merged}");
+}
+
+#[test]
+fn lazy_policy_drains_queue() {
+    for locking_strategy in strategies() {
+        let tree = tree(locking_strategy.clone())
+            .with_fill_policy(FillPolicy::lazy());
+
+        (0..5_000).for_each(|key| insert(&tree, key, key));
+        (0..5_000).for_each(|key| match tree.dispatch(CRUDOperation::PopMin).1 {
+            CRUDOperationResult::Deleted(popped, _) => assert_eq!(popped, key, "{locking_strategy}"),
+            _ => panic!("{locking_strategy}: PopMin failed before {key}")
+        });
+
+        assert_consistent(&tree, 0);
+    }
+}
+
+#[test]
+fn borrowing_redistributes_instead_of_merging() {
+    let rebalanced = |fill_policy: FillPolicy| {
+        let tree = tree(OLC())
+            .with_fill_policy(fill_policy);
+
+        (0..20_000).for_each(|key| insert(&tree, key, key));
+        tree.metrics().reset();
+
+        (0..20_000).filter(|key| key % 3 != 0).for_each(|key| delete(&tree, key));
+        assert_consistent(&tree, 6_667);
+
+        let snapshot
+            = tree.metrics().snapshot();
+
+        (snapshot.merges, snapshot.redistributions)
+    };
+
+    let (merges, redistributions)
+        = rebalanced(FillPolicy::default());
+
+    let (borrow_merges, borrow_redistributions)
+        = rebalanced(FillPolicy::default().with_rebalance(Rebalance::Borrow));
+
+    assert!(borrow_merges < merges, "{borrow_merges} vs {merges}");
+    assert!(borrow_redistributions > redistributions, "{borrow_redistributions} vs {redistributions}");
+}
+
+#[test]
+fn lazy_policy_saves_merges_of_queues() {
+    let merges = |fill_policy: FillPolicy| {
+        let tree = tree(OLC())
+            .with_fill_policy(fill_policy);
+
+        (0..10_000).for_each(|key| insert(&tree, key, key));
+        tree.metrics().reset();
+
+        // a queue popping the oldest entry and pushing a newer one
+        (0..10_000).for_each(|key| {
+            tree.dispatch(CRUDOperation::PopMin);
+            insert(&tree, key + 10_000, key);
+        });
+
+        assert_consistent(&tree, 10_000);
+        tree.metrics().snapshot().merges
+    };
+
+    let lazy
+        = merges(FillPolicy::lazy());
+
+    let default
+        = merges(FillPolicy::default());
+
+    assert!(lazy < default, "{lazy} vs {default}");
+}
+
+#[test]
+#[should_panic(expected = "Split ratio must be within (0, 1)!")]
+fn split_ratio_of_one_is_rejected() {
+    let _ = FillPolicy::default().with_split_ratio(1.0);
+}
+
+#[test]
+#[should_panic(expected = "Minimum fill must be within [0, 0.5]!")]
+fn min_fill_above_half_is_rejected() {
+    let _ = FillPolicy::default().with_min_fill(0.6);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::thread;
+use common::{get, insert, shuffled, strategies, tree};
+use CCBPlusTree::crud_model::crud_api::CRUDDispatcher;
+use CCBPlusTree::crud_model::crud_operation::CRUDOperation;
+use CCBPlusTree::locking::locking_strategy::{copy_on_write, optiql, OLC};
+use CCBPlusTree::test::{dec_key, inc_key};
+use CCBPlusTree::tree::bplus_tree::BPlusTree;
+
+#[test]
+fn records_follow_key_order() {
+    for locking_strategy in strategies() {
+        let tree
+            = tree(locking_strategy.clone());
+
+        (0..20_000).step_by(2).for_each(|key| insert(&tree, key, key + 1));
+
+        // odd keys are missing, some keys are asked for twice
+        let keys = shuffled(0..20_000, 1)
+            .into_iter()
+            .chain((0..1_000).step_by(7))
+            .collect::<Vec<_>>();
+
+        for interleave in [0, 4] {
+            let result
+                = tree.get_many_with(keys.iter().cloned(), interleave);
+
+            assert_eq!(result.records.len(), keys.len(), "{locking_strategy}");
+            assert_eq!(result.matched(), 10_000 + 72, "{locking_strategy}");
+            assert!(result.leaf_visits < 2_000, "{locking_strategy}: {}", result.leaf_visits);
+
+            keys.iter()
+                .zip(&result.records)
+                .for_each(|(key, record)| assert_eq!(
+                    record.as_ref().map(|record| (record.key, record.payload)),
+                    get(&tree, *key).map(|payload| (*key, payload)),
+                    "{locking_strategy}: {key}"));
+        }
+    }
+
+    assert!(tree(OLC()).get_many([]).records.is_empty());
+}
+
+#[test]
+fn reads_during_inserts() {
+    for locking_strategy in [OLC(), optiql(), copy_on_write()] {
+        let tree
+            = tree(locking_strategy.clone());
+
+        (0..20_000).step_by(2).for_each(|key| insert(&tree, key, key));
+
+        thread::scope(|scope| {
+            (0..2).for_each(|thread| {
+                let tree = &tree;
+
+                scope.spawn(move || (1..20_000)
+                    .step_by(2)
+                    .filter(|key| key % 4 == thread * 2 + 1)
+                    .for_each(|key| insert(tree, key, key)));
+            });
+
+            (0..2).for_each(|seed| {
+                let tree = &tree;
+                let locking_strategy = &locking_strategy;
+
+                scope.spawn(move || for _ in 0..10 {
+                    let result
+                        = tree.get_many_with(shuffled(0..20_000, seed).into_iter().filter(|key| key % 2 == 0), 2);
+
+                    assert_eq!(result.matched(), 10_000, "{locking_strategy}");
+                });
+            });
+        });
+    }
+}
+
+#[test]
+fn records_own_their_payloads() {
+    for locking_strategy in strategies() {
+        let tree: BPlusTree<16, 16, u64, String>
+            = BPlusTree::new_with(locking_strategy.clone(), u64::MIN, u64::MAX, inc_key, dec_key);
+
+        (0..2_000).for_each(|key| {
+            tree.dispatch(CRUDOperation::Insert(key, key.to_string()));
+        });
+
+        let result
+            = tree.get_many_with((0..4_000).rev(), 2);
+
+        // the tree's payloads are dropped before the records read
+        drop(tree);
+
+        assert_eq!(result.matched(), 2_000, "{locking_strategy}");
+        result.records
+            .iter()
+            .zip((0..4_000).rev())
+            .for_each(|(record, key)| assert_eq!(
+                record.as_ref().map(|record| record.payload.clone()),
+                (key < 2_000).then(|| key.to_string()),
+                "{locking_strategy}"));
+    }
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use std::thread;
+use common::{assert_consistent, get, insert, shuffled, strategies, tree};
+use CCBPlusTree::locking::locking_strategy::LockingStrategy;
+use CCBPlusTree::tree::memory::MemoryLimit;
+
+#[test]
+fn batch_fills_leaves_up_to_fences() {
+    for locking_strategy in strategies() {
+        let tree
+            = tree(locking_strategy.clone());
+
+        (0..1_000_000).step_by(100).for_each(|key| insert(&tree, key, key));
+
+        // few new keys, hence leaves are filled up to their fence, which excludes the separator
+        let keys = shuffled(0..1_000_000, 1)
+            .into_iter()
+            .filter(|key| key % 100 == 0 || key % 800 == 799)
+            .collect::<Vec<_>>();
+
+        let result = tree
+            .insert_batch(keys.iter().map(|key| (*key, *key)), 4);
+
+        assert_eq!(result.inserted, 1_250, "{locking_strategy}");
+        assert_eq!(result.duplicates.len(), 10_000, "{locking_strategy}");
+        assert!(result.leaf_visits < 2_500, "{locking_strategy}: {}", result.leaf_visits);
+
+        assert!(keys.iter().all(|key| get(&tree, *key) == Some(*key)), "{locking_strategy}");
+        assert_consistent(&tree, 11_250);
+    }
+}
+
+#[test]
+fn batch_keeps_first_of_equal_keys() {
+    for locking_strategy in strategies() {
+        let tree
+            = tree(locking_strategy.clone());
+
+        let records = shuffled(0..10_000, 2)
+            .into_iter()
+            .flat_map(|key| [(key, key), (key, key + 1)]);
+
+        let result
+            = tree.insert_batch(records, 8);
+
+        assert_eq!(result.inserted, 10_000, "{locking_strategy}");
+        assert_eq!(result.duplicates.len(), 10_000, "{locking_strategy}");
+        assert!(result.rejected.is_empty(), "{locking_strategy}");
+
+        assert!((0..10_000).all(|key| get(&tree, key) == Some(key)), "{locking_strategy}");
+        assert_consistent(&tree, 10_000);
+    }
+}
+
+#[test]
+fn batches_run_alongside_other_writers() {
+    // a single writer at a time, see `LockingStrategy::MonoWriter`
+    for locking_strategy in strategies().into_iter().filter(|s| !matches!(s, LockingStrategy::MonoWriter)) {
+        let tree
+            = tree(locking_strategy.clone());
+
+        (0..10_000).for_each(|key| insert(&tree, key * 4, key));
+
+        thread::scope(|scope| {
+            (1..3).for_each(|offset| {
+                let tree
+                    = &tree;
+
+                scope.spawn(move || {
+                    let result = tree.insert_batch(shuffled(0..10_000, offset)
+                        .into_iter()
+                        .map(|key| (key * 4 + offset, key)), 4);
+
+                    assert_eq!(result.inserted, 10_000);
+                    assert!(result.duplicates.is_empty());
+                });
+            });
+
+            let tree
+                = &tree;
+
+            scope.spawn(move || shuffled(0..10_000, 3)
+                .into_iter()
+                .for_each(|key| insert(tree, key * 4 + 3, key)));
+
+            scope.spawn(move || (0..10_000)
+                .for_each(|key| assert_eq!(get(tree, key * 4), Some(key))));
+        });
+
+        assert!((0..40_000).all(|key| get(&tree, key) == Some(key / 4)), "{locking_strategy}");
+        assert_consistent(&tree, 40_000);
+    }
+}
+
+#[test]
+fn batch_rejects_records_beyond_memory_limit() {
+    for locking_strategy in strategies() {
+        let limit
+            = tree(locking_strategy.clone()).memory_usage().used_bytes() + 32 * 1024;
+
+        let tree = tree(locking_strategy.clone())
+            .with_memory_limit(MemoryLimit::new(limit));
+
+        let result = tree
+            .insert_batch(shuffled(0..100_000, 4).into_iter().map(|key| (key, key)), 4);
+
+        assert!(result.inserted > 0, "{locking_strategy}");
+        assert!(!result.rejected.is_empty(), "{locking_strategy}");
+        assert_eq!(result.inserted + result.rejected.len(), 100_000, "{locking_strategy}");
+
+        assert!(result.rejected.iter().all(|key| get(&tree, *key).is_none()), "{locking_strategy}");
+        assert_consistent(&tree, result.inserted);
+    }
+}
+
+#[test]
+fn empty_batch_inserts_nothing() {
+    for locking_strategy in strategies() {
+        let tree
+            = tree(locking_strategy.clone());
+
+        let result
+            = tree.insert_batch(std::iter::empty(), 4);
+
+        assert_eq!(result.inserted, 0, "{locking_strategy}");
+        assert_eq!(result.leaf_visits, 0, "{locking_strategy}");
+        assert_consistent(&tree, 0);
+    }
+}
@@ -0,0 +1,206 @@
+mod common;
+
+use std::mem;
+use std::sync::{Arc, OnceLock, Weak};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use common::{insert, shuffled, strategies, tree, Tree};
+use CCBPlusTree::block::block::Block;
+use CCBPlusTree::crud_model::crud_api::CRUDDispatcher;
+use CCBPlusTree::crud_model::crud_operation::CRUDOperation;
+use CCBPlusTree::crud_model::crud_operation_result::CRUDOperationResult;
+use CCBPlusTree::locking::locking_strategy::LockingStrategy;
+use CCBPlusTree::test::{dec_key, inc_key};
+use CCBPlusTree::tree::bplus_tree::BPlusTree;
+use CCBPlusTree::tree::memory::MemoryLimit;
+use CCBPlusTree::utils::epoch;
+
+type StringTree = BPlusTree<16, 16, u64, String>;
+
+fn string_tree(locking_strategy: LockingStrategy) -> StringTree {
+    StringTree::new_with(locking_strategy, u64::MIN, u64::MAX, inc_key, dec_key)
+        .with_payload_heap(|payload: &String| payload.capacity())
+}
+
+fn payload(len: usize) -> String {
+    let mut payload
+        = String::with_capacity(len);
+
+    payload.extend((0..len).map(|_| 'x'));
+    payload
+}
+
+#[test]
+fn usage_follows_blocks() {
+    for locking_strategy in strategies() {
+        let tree
+            = tree(locking_strategy.clone());
+
+        let empty
+            = tree.memory_usage();
+
+        assert_eq!(empty.blocks, mem::size_of::<Block<16, 16, u64, u64>>(), "{locking_strategy}");
+        assert_eq!(empty.payload_heap, 0, "{locking_strategy}");
+        assert_eq!(empty.limit, None, "{locking_strategy}");
+
+        shuffled(0..10_000, 3).into_iter().for_each(|key| insert(&tree, key, key));
+        (0..4).for_each(|_| epoch::collect());
+
+        let usage
+            = tree.memory_usage();
+
+        let stats
+            = tree.stats();
+
+        assert_eq!(usage.blocks, stats.nodes() * stats.block_bytes, "{locking_strategy}");
+        assert_eq!(usage.blocks + usage.metadata,
+                   stats.bytes_allocated + mem::size_of::<Tree>(), "{locking_strategy}");
+        assert_eq!(usage.total_bytes(), tree.slab_usage().reserved_bytes() + mem::size_of::<Tree>(),
+                   "{locking_strategy}");
+    }
+}
+
+#[test]
+fn payload_heap_is_counted() {
+    for locking_strategy in strategies() {
+        let tree
+            = string_tree(locking_strategy.clone());
+
+        (0..1_000).for_each(|key| {
+            tree.dispatch(CRUDOperation::Insert(key, payload(100)));
+        });
+
+        assert_eq!(tree.memory_usage().payload_heap, 100_000, "{locking_strategy}");
+
+        (0..1_000).step_by(2).for_each(|key| {
+            tree.dispatch(CRUDOperation::Update(key, payload(300)));
+        });
+
+        assert_eq!(tree.memory_usage().payload_heap, 200_000, "{locking_strategy}");
+
+        (0..500).for_each(|key| {
+            tree.dispatch(CRUDOperation::Delete(key));
+        });
+
+        assert_eq!(tree.memory_usage().payload_heap, 100_000, "{locking_strategy}");
+
+        // existing payloads are counted once the heap function is set
+        let loaded = StringTree::new_with(locking_strategy.clone(), u64::MIN, u64::MAX, inc_key, dec_key)
+            .bulk_load((0..1_000).map(|key| (key, payload(50))))
+            .with_payload_heap(|payload: &String| payload.capacity());
+
+        assert_eq!(loaded.memory_usage().payload_heap, 50_000, "{locking_strategy}");
+    }
+}
+
+#[test]
+fn limit_rejects_inserts() {
+    for locking_strategy in strategies() {
+        let tree = string_tree(locking_strategy.clone())
+            .with_memory_limit(MemoryLimit::new(256 * 1024));
+
+        let mut key
+            = 0;
+
+        let usage = loop {
+            match tree.dispatch(CRUDOperation::Insert(key, payload(1_000))).1 {
+                CRUDOperationResult::Inserted(..) => key += 1,
+                CRUDOperationResult::MemoryLimitExceeded(usage) => break usage,
+                result => panic!("{locking_strategy}: {result}")
+            }
+        };
+
+        assert!(key > 0, "{locking_strategy}");
+        assert_eq!(usage.limit, Some(256 * 1024), "{locking_strategy}");
+        assert!(usage.used_bytes() + 1_000 > 256 * 1024, "{locking_strategy}");
+
+        // updates are admitted, deletes free the payloads for later inserts
+        match tree.dispatch(CRUDOperation::Update(0, payload(10))).1 {
+            CRUDOperationResult::Updated(..) => {}
+            result => panic!("{locking_strategy}: {result}")
+        }
+
+        (1..10).for_each(|key| {
+            tree.dispatch(CRUDOperation::Delete(key));
+        });
+
+        match tree.dispatch(CRUDOperation::Insert(key, payload(1_000))).1 {
+            CRUDOperationResult::Inserted(inserted) => assert_eq!(inserted, key),
+            result => panic!("{locking_strategy}: {result}")
+        }
+    }
+}
+
+#[test]
+fn eviction_frees_memory_for_inserts() {
+    for locking_strategy in strategies() {
+        let evictions
+            = Arc::new(AtomicUsize::new(0));
+
+        let rejecting = {
+            let evictions
+                = evictions.clone();
+
+            string_tree(locking_strategy.clone()).with_memory_limit(MemoryLimit::new(64 * 1024)
+                .with_eviction(move |usage| {
+                    assert!(usage.used_bytes() + 1_000 > 64 * 1024);
+                    evictions.fetch_add(1, Ordering::Relaxed);
+                    false
+                }))
+        };
+
+        let mut key
+            = 0;
+
+        while let CRUDOperationResult::Inserted(..) = rejecting.dispatch(CRUDOperation::Insert(key, payload(1_000))).1 {
+            key += 1;
+        }
+
+        assert_eq!(evictions.load(Ordering::Relaxed), 1, "{locking_strategy}");
+
+        // the callback removes the oldest records of the tree itself
+        let this
+            = Arc::new(OnceLock::<Weak<StringTree>>::new());
+
+        let oldest
+            = Arc::new(AtomicU64::new(0));
+
+        let evicting = Arc::new({
+            let this
+                = this.clone();
+
+            let evictions
+                = evictions.clone();
+
+            string_tree(locking_strategy.clone()).with_memory_limit(MemoryLimit::new(64 * 1024)
+                .with_eviction(move |_| {
+                    evictions.fetch_add(1, Ordering::Relaxed);
+
+                    let tree
+                        = this.get().unwrap().upgrade().unwrap();
+
+                    (0..10).for_each(|_| {
+                        tree.dispatch(CRUDOperation::Delete(oldest.fetch_add(1, Ordering::Relaxed)));
+                    });
+
+                    true
+                }))
+        });
+
+        this.set(Arc::downgrade(&evicting)).unwrap();
+
+        (0..1_000).for_each(|key| match evicting.dispatch(CRUDOperation::Insert(key, payload(1_000))).1 {
+            CRUDOperationResult::Inserted(inserted) => assert_eq!(inserted, key),
+            result => panic!("{locking_strategy}: {result}")
+        });
+
+        assert!(evictions.load(Ordering::Relaxed) > 1, "{locking_strategy}");
+        assert!(evicting.memory_usage().payload_heap <= 64 * 1024, "{locking_strategy}");
+
+        match evicting.dispatch(CRUDOperation::Point(999)).1 {
+            CRUDOperationResult::MatchedRecord(Some(record)) => assert_eq!(record.payload.len(), 1_000),
+            result => panic!("{locking_strategy}: {result}")
+        }
+
+        evictions.store(0, Ordering::Relaxed);
+    }
+}
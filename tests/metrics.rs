@@ -0,0 +1,110 @@
+mod common;
+
+use std::thread;
+use std::time::Duration;
+use common::{get, insert, tree};
+use CCBPlusTree::locking::locking_strategy::*;
+use CCBPlusTree::utils::metrics::{MetricsSnapshot, OperationKind};
+
+fn assert_zeroed(snapshot: &MetricsSnapshot) {
+    assert!(snapshot.operations.iter().all(|operations| *operations == 0), "{snapshot}");
+    assert!(snapshot.restarts.iter().flatten().all(|restarts| *restarts == 0), "{snapshot}");
+    assert_eq!((snapshot.upgrades, snapshot.upgrade_failures), (0, 0), "{snapshot}");
+    assert_eq!((snapshot.splits, snapshot.merges, snapshot.redistributions), (0, 0, 0), "{snapshot}");
+    assert_eq!((snapshot.root_changes, snapshot.appends, snapshot.pessimistic_fallbacks), (0, 0, 0), "{snapshot}");
+    assert_eq!((snapshot.spins, snapshot.yields, snapshot.latch_wait), (0, 0, Duration::ZERO), "{snapshot}");
+}
+
+#[test]
+fn shards_aggregate_across_threads() {
+    for locking_strategy in [OLC(), orwc(), lightweight_hybrid_lock()] {
+        let tree
+            = tree(locking_strategy.clone());
+
+        thread::scope(|scope| (0..4).for_each(|thread| {
+            let tree
+                = &tree;
+
+            scope.spawn(move || (0..5_000)
+                .map(|key| key * 4 + thread)
+                .for_each(|key| {
+                    insert(tree, key, key);
+                    assert_eq!(get(tree, key), Some(key));
+                }));
+        }));
+
+        let snapshot
+            = tree.metrics().snapshot();
+
+        assert_eq!(snapshot.operations_of(OperationKind::Insert), 20_000, "{locking_strategy}");
+        assert_eq!(snapshot.operations_of(OperationKind::Point), 20_000, "{locking_strategy}");
+        assert_eq!(snapshot.operations.iter().sum::<u64>(), 40_000, "{locking_strategy}");
+        assert!(snapshot.splits > 0, "{locking_strategy}: {snapshot}");
+        assert!(snapshot.root_changes > 0 && snapshot.root_changes <= snapshot.splits, "{locking_strategy}: {snapshot}");
+    }
+}
+
+#[test]
+fn reset_zeroes_shards_of_all_threads() {
+    let tree
+        = tree(OLC());
+
+    thread::scope(|scope| (0..4).for_each(|thread| {
+        let tree
+            = &tree;
+
+        scope.spawn(move || (0..2_000)
+            .map(|key| key * 4 + thread)
+            .for_each(|key| insert(tree, key, key)));
+    }));
+
+    assert!(tree.metrics().snapshot().splits > 0);
+
+    tree.metrics().reset();
+    assert_zeroed(&tree.metrics().snapshot());
+
+    // shards stay registered, i.e. threads keep counting into them after the reset
+    thread::scope(|scope| (0..2).for_each(|_| {
+        scope.spawn(|| assert_eq!(get(&tree, 4), Some(4)));
+    }));
+
+    assert_eq!(tree.metrics().snapshot().operations_of(OperationKind::Point), 2);
+}
+
+#[test]
+fn trees_count_separately() {
+    let (counted, other)
+        = (tree(OLC()), tree(OLC()));
+
+    (0..1_000).for_each(|key| insert(&counted, key, key));
+
+    assert_eq!(counted.metrics().snapshot().operations_of(OperationKind::Insert), 1_000);
+    assert_zeroed(&other.metrics().snapshot());
+
+    // a dropped tree's shard is neither reused nor counted by later trees
+    drop(counted);
+
+    let later
+        = tree(OLC());
+
+    insert(&later, 1, 1);
+    assert_eq!(later.metrics().snapshot().operations.iter().sum::<u64>(), 1);
+}
+
+#[test]
+fn redispatched_operations_count_once() {
+    // optimistic point queries are answered as single key ranges
+    let tree
+        = tree(OLC());
+
+    (0..1_000).for_each(|key| insert(&tree, key, key));
+    tree.metrics().reset();
+
+    (0..1_000).for_each(|key| assert_eq!(get(&tree, key), Some(key)));
+
+    let snapshot
+        = tree.metrics().snapshot();
+
+    assert_eq!(snapshot.operations_of(OperationKind::Point), 1_000, "{snapshot}");
+    assert_eq!(snapshot.operations_of(OperationKind::Range), 0, "{snapshot}");
+}
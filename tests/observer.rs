@@ -0,0 +1,213 @@
+mod common;
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use parking_lot::Mutex;
+use common::{delete, insert, shuffled, tree, Tree};
+use CCBPlusTree::locking::locking_strategy::*;
+use CCBPlusTree::page_model::{BlockID, Height, Level};
+use CCBPlusTree::tree::export::{NodeExport, NodeKind};
+use CCBPlusTree::tree::observer::TreeObserver;
+use CCBPlusTree::utils::metrics::OperationKind;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Event {
+    Split(NodeKind, BlockID, (BlockID, BlockID), u64),
+    Merge(NodeKind, Vec<BlockID>, BlockID),
+    Redistribute(NodeKind, (BlockID, BlockID), (BlockID, BlockID)),
+    RootChange(BlockID, Height, Height),
+    Restart(OperationKind, Level),
+    Obsolete(NodeKind, BlockID),
+}
+
+#[derive(Default)]
+struct Recorder(Mutex<Vec<Event>>);
+
+impl Recorder {
+    fn take(&self) -> Vec<Event> {
+        std::mem::take(&mut self.0.lock())
+    }
+}
+
+impl TreeObserver<u64> for Recorder {
+    fn on_split(&self, node: NodeKind, from: BlockID, into: (BlockID, BlockID), separator: u64) {
+        self.0.lock().push(Event::Split(node, from, into, separator))
+    }
+
+    fn on_merge(&self, node: NodeKind, from: &[BlockID], into: BlockID) {
+        self.0.lock().push(Event::Merge(node, from.to_vec(), into))
+    }
+
+    fn on_redistribute(&self, node: NodeKind, from: (BlockID, BlockID), into: (BlockID, BlockID)) {
+        self.0.lock().push(Event::Redistribute(node, from, into))
+    }
+
+    fn on_root_change(&self, root: BlockID, old_height: Height, new_height: Height) {
+        self.0.lock().push(Event::RootChange(root, old_height, new_height))
+    }
+
+    fn on_restart(&self, operation: OperationKind, level: Level) {
+        self.0.lock().push(Event::Restart(operation, level))
+    }
+
+    fn on_obsolete(&self, node: NodeKind, block_id: BlockID) {
+        self.0.lock().push(Event::Obsolete(node, block_id))
+    }
+}
+
+fn observed(locking_strategy: LockingStrategy) -> (Tree, Arc<Recorder>) {
+    let recorder
+        = Arc::new(Recorder::default());
+
+    (tree(locking_strategy).with_observer(recorder.clone()), recorder)
+}
+
+fn block_ids(node: &NodeExport<u64>, ids: &mut BTreeSet<BlockID>) {
+    ids.insert(node.block_id);
+    node.children.iter().for_each(|child| block_ids(child, ids));
+}
+
+#[test]
+fn root_split_names_old_and_new_blocks() {
+    let (tree, recorder)
+        = observed(OLC());
+
+    let old_root
+        = tree.export().root.block_id;
+
+    let events = (0..)
+        .map(|key| {
+            insert(&tree, key, key);
+            recorder.take()
+        })
+        .find(|events| !events.is_empty())
+        .unwrap();
+
+    let root
+        = tree.export().root;
+
+    let [left, right]
+        = [root.children[0].block_id, root.children[1].block_id];
+
+    assert_eq!(events, [
+        Event::Split(NodeKind::Leaf, old_root, (left, right), root.keys[0]),
+        Event::RootChange(root.block_id, 1, 2),
+    ]);
+}
+
+#[test]
+fn events_follow_nodes_across_modifications() {
+    for locking_strategy in [LockingStrategy::LockCoupling, orwc(), OLC(), lightweight_hybrid_lock()] {
+        let (tree, recorder)
+            = observed(locking_strategy.clone());
+
+        let mut root
+            = tree.export().root.block_id;
+
+        let mut live
+            = BTreeSet::from([root]);
+
+        let mut apply = |events: Vec<Event>| events.into_iter().for_each(|event| match event {
+            Event::Split(.., from, (left, right), _) => {
+                assert!(live.remove(&from), "{locking_strategy}: split unknown {from}");
+                assert!(live.insert(left) && live.insert(right), "{locking_strategy}: reused {left} or {right}");
+            }
+            Event::Merge(.., from, into) => {
+                assert!(from.iter().all(|from| live.remove(from)), "{locking_strategy}: merged unknown {from:?}");
+                assert!(live.insert(into), "{locking_strategy}: reused {into}");
+            }
+            Event::Redistribute(.., (from_left, from_right), (left, right)) => {
+                assert!(live.remove(&from_left) && live.remove(&from_right), "{locking_strategy}");
+                assert!(live.insert(left) && live.insert(right), "{locking_strategy}: reused {left} or {right}");
+            }
+            Event::RootChange(new_root, old_height, new_height) => {
+                match new_height > old_height {
+                    true => assert!(live.insert(new_root), "{locking_strategy}: reused {new_root}"),
+                    false => assert!(live.remove(&root), "{locking_strategy}: collapsed unknown {root}")
+                }
+
+                root = new_root;
+            }
+            // obsolete nodes are reported right before the event replacing them
+            Event::Obsolete(.., block_id) =>
+                assert!(live.contains(&block_id), "{locking_strategy}: obsoleted unknown {block_id}"),
+            Event::Restart(..) => {}
+        });
+
+        shuffled(0..3_000, 1).into_iter().for_each(|key| insert(&tree, key, key));
+        apply(recorder.take());
+
+        let mut exported
+            = BTreeSet::new();
+
+        block_ids(&tree.export().root, &mut exported);
+
+        shuffled(0..2_990, 2).into_iter().for_each(|key| delete(&tree, key));
+        let events
+            = recorder.take();
+
+        assert!(events.iter().any(|event| matches!(event, Event::Merge(..))), "{locking_strategy}");
+        assert!(events.iter().any(|event| matches!(event, Event::RootChange(.., old, new) if new < old)), "{locking_strategy}");
+        apply(events);
+
+        let mut shrunk
+            = BTreeSet::new();
+
+        block_ids(&tree.export().root, &mut shrunk);
+
+        assert!(!exported.is_empty() && exported != shrunk, "{locking_strategy}");
+        assert_eq!(live, shrunk, "{locking_strategy}");
+        assert_eq!(tree.export().root.block_id, root, "{locking_strategy}");
+    }
+}
+
+#[test]
+fn splits_of_inner_nodes_obsolete_them_first() {
+    let (tree, recorder)
+        = observed(OLC());
+
+    (0..3_000).for_each(|key| insert(&tree, key, key));
+
+    let events
+        = recorder.take();
+
+    let splits = events
+        .iter()
+        .enumerate()
+        .filter_map(|(pos, event)| match event {
+            Event::Split(kind, from, ..) => Some((pos, *kind, *from)),
+            _ => None
+        })
+        .collect::<Vec<_>>();
+
+    // root splits are followed by a root change instead
+    assert!(splits.iter().any(|(.., kind, _)| *kind == NodeKind::Index));
+    assert!(splits.into_iter().all(|(pos, kind, from)|
+        matches!(events.get(pos + 1), Some(Event::RootChange(..))) ||
+            (pos > 0 && events[pos - 1] == Event::Obsolete(kind, from))));
+}
+
+#[test]
+fn restarts_are_reported_with_operation() {
+    // read-write coupling restarts inserts once a full leaf splits
+    let (tree, recorder)
+        = observed(orwc());
+
+    (0..3_000).for_each(|key| insert(&tree, key, key));
+
+    let restarts = recorder
+        .take()
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::Restart(operation, level) => Some((operation, level)),
+            _ => None
+        })
+        .collect::<Vec<_>>();
+
+    let metrics
+        = tree.metrics().snapshot();
+
+    assert!(!restarts.is_empty());
+    assert!(restarts.iter().all(|(operation, level)| *operation == OperationKind::Insert && *level >= 1));
+    assert_eq!(restarts.len() as u64, metrics.restarts_of(OperationKind::Insert));
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+use std::thread;
+use common::{assert_concurrent_workload, assert_consistent, get, insert, shuffled, tree};
+use CCBPlusTree::locking::locking_strategy::optiql;
+use CCBPlusTree::utils::mcs_lock::McsLock;
+
+/// Increments split into load and store are lost unless the lock is exclusive.
+#[test]
+fn mcs_lock_is_exclusive() {
+    let lock
+        = McsLock::new();
+
+    let counter
+        = AtomicU64::new(0);
+
+    thread::scope(|scope| (0..8).for_each(|_| {
+        scope.spawn(|| for _ in 0..10_000 {
+            lock.lock();
+            counter.store(counter.load(Relaxed) + 1, Relaxed);
+            lock.unlock();
+        });
+    }));
+
+    assert_eq!(counter.load(Relaxed), 80_000);
+    assert!(!lock.is_locked());
+}
+
+#[test]
+fn mcs_try_lock_fails_while_locked() {
+    let lock
+        = McsLock::new();
+
+    assert!(lock.try_lock());
+    assert!(!lock.try_lock());
+
+    thread::scope(|scope| scope.spawn(|| assert!(!lock.try_lock())).join().unwrap());
+
+    lock.unlock();
+    assert!(lock.try_lock());
+    lock.unlock();
+}
+
+#[test]
+fn concurrent_workload() {
+    assert_concurrent_workload(&tree(optiql()));
+}
+
+/// Writers contending on few leaves queue up instead of restarting.
+#[test]
+fn skewed_inserts() {
+    let tree
+        = tree(optiql());
+
+    thread::scope(|scope| (0..8).for_each(|thread| {
+        let tree = &tree;
+
+        scope.spawn(move || shuffled(0..2_000, thread)
+            .into_iter()
+            .filter(|key| key % 8 == thread)
+            .for_each(|key| insert(tree, key, key)));
+    }));
+
+    assert!((0..2_000).all(|key| get(&tree, key) == Some(key)));
+    assert_consistent(&tree, 2_000);
+}
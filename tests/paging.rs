@@ -0,0 +1,267 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use std::fs::OpenOptions;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+use common::{assert_concurrent_workload, assert_consistent, get, insert, shuffled, temp_dir, Tree};
+use CCBPlusTree::crud_model::crud_api::CRUDDispatcher;
+use CCBPlusTree::crud_model::crud_operation::CRUDOperation;
+use CCBPlusTree::crud_model::crud_operation_result::CRUDOperationResult;
+use CCBPlusTree::locking::locking_strategy::*;
+use CCBPlusTree::page_model::BlockID;
+use CCBPlusTree::test::{dec_key, inc_key};
+use CCBPlusTree::tree::export::NodeExport;
+use CCBPlusTree::tree::snapshot::SnapshotError;
+use CCBPlusTree::tree::validation::Violation;
+
+/// Creates a pool holding `records` keys mapped to their successors, checkpointed and closed.
+fn pool_of(path: &Path, locking_strategy: LockingStrategy, records: u64) {
+    let tree
+        = Tree::create_pooled(path, 1 << 16, locking_strategy, u64::MIN, u64::MAX, inc_key, dec_key).unwrap();
+
+    (0..records).for_each(|key| insert(&tree, key, key + 1));
+    tree.checkpoint_pool().unwrap();
+}
+
+/// ID of the frame, i.e. the parent of the leaf, routing `key` and the ID of that leaf.
+fn frame_of(node: &NodeExport<u64>, key: u64) -> (BlockID, BlockID) {
+    let pos = node.keys
+        .iter()
+        .position(|separator| key < *separator)
+        .unwrap_or(node.keys.len());
+
+    let child
+        = &node.children[pos];
+
+    match child.children.is_empty() {
+        true => (node.block_id, child.block_id),
+        false => frame_of(child, key)
+    }
+}
+
+#[test]
+fn evicted_leaves_are_faulted_in_and_written_back() {
+    let dir
+        = temp_dir("paging-round-trip");
+
+    let path
+        = dir.join("pool");
+
+    pool_of(&path, OLC(), 20_000);
+
+    let tree
+        = Tree::open_paged(&path, 4, inc_key, dec_key).unwrap();
+
+    let buffer
+        = tree.buffer_pool().unwrap();
+
+    // frames of the reopened tree stay in their slots until accessed
+    assert!(tree.height() > 2);
+    assert_eq!(buffer.resident(), 0);
+
+    shuffled(0..20_000, 1).into_iter().for_each(|key| {
+        assert_eq!(get(&tree, key), Some(key + 1));
+        assert!(buffer.resident() <= buffer.capacity());
+    });
+
+    let frames
+        = tree.stats().levels[tree.height() as usize - 2].nodes;
+
+    assert!(buffer.faults() > frames, "{} faults of {frames} frames", buffer.faults());
+    assert!(buffer.evictions() + buffer.capacity() >= buffer.faults());
+    assert_eq!(buffer.write_backs(), 0);
+
+    // updated leaves are dirty, hence written back on eviction and read back by the next fault
+    (0..20_000).step_by(2).for_each(|key| match tree.dispatch(CRUDOperation::Update(key, key)).1 {
+        CRUDOperationResult::Updated(updated, old) => assert_eq!((updated, old), (key, key + 1)),
+        result => panic!("Update({key}) failed: {result}")
+    });
+
+    assert!(buffer.write_backs() > 0);
+    assert!(shuffled(0..20_000, 2).into_iter().all(|key| get(&tree, key) == Some(key + key % 2)));
+    assert_consistent(&tree, 20_000);
+
+    tree.checkpoint_pool().unwrap();
+    drop(tree);
+
+    let reopened
+        = Tree::open_pooled(&path, inc_key, dec_key).unwrap();
+
+    assert!((0..20_000).all(|key| get(&reopened, key) == Some(key + key % 2)));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn reopening_after_dirty_evictions_restores_the_last_checkpoint() {
+    let dir
+        = temp_dir("paging-reopen");
+
+    let path
+        = dir.join("pool");
+
+    pool_of(&path, OLC(), 20_000);
+
+    {
+        let tree
+            = Tree::open_paged(&path, 4, inc_key, dec_key).unwrap();
+
+        (0..20_000).step_by(2).for_each(|key| match tree.dispatch(CRUDOperation::Update(key, key)).1 {
+            CRUDOperationResult::Updated(..) => {}
+            result => panic!("Update({key}) failed: {result}")
+        });
+
+        (20_000..30_000).for_each(|key| insert(&tree, key, key + 1));
+
+        let buffer
+            = tree.buffer_pool().unwrap();
+
+        assert!(buffer.write_backs() > 0 && buffer.evictions() > 0);
+    }
+
+    // dropped without checkpoint, as after a crash the written back blocks are not part of the pool
+    for tree in [Tree::open_pooled(&path, inc_key, dec_key).unwrap(), Tree::open_paged(&path, 4, inc_key, dec_key).unwrap()] {
+        assert!((0..20_000).all(|key| get(&tree, key) == Some(key + 1)));
+        assert!((20_000..30_000).all(|key| get(&tree, key).is_none()));
+        assert_consistent(&tree, 20_000);
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn clock_keeps_frames_referenced_since_the_hand_passed() {
+    let dir
+        = temp_dir("paging-clock");
+
+    let path
+        = dir.join("pool");
+
+    pool_of(&path, OLC(), 40_000);
+
+    let tree
+        = Tree::open_paged(&path, 8, inc_key, dec_key).unwrap();
+
+    let buffer
+        = tree.buffer_pool().unwrap();
+
+    // frames of 16 leaves each hold at most 16 * 16 keys
+    let cold = (1..200)
+        .map(|frame| frame * 192)
+        .collect::<Vec<_>>();
+
+    cold.iter().for_each(|key| {
+        assert_eq!(get(&tree, 0), Some(1));
+        assert_eq!(get(&tree, *key), Some(key + 1));
+    });
+
+    // index blocks between the root's children and the frames of the leaves are faulted in once
+    // by the sweep over the cold frames
+    let stats
+        = tree.stats();
+
+    let upper_frames = stats.levels[1..tree.height() as usize - 2]
+        .iter()
+        .map(|level| level.nodes)
+        .sum::<usize>();
+
+    // the hot frames are referenced before each eviction, without second chance they would
+    // be evicted once the hand passed all other frames, i.e. about every capacity cold accesses
+    let refaults
+        = buffer.faults() - cold.len() - upper_frames;
+
+    assert!(refaults < cold.len() / buffer.capacity(), "{refaults} refaults");
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn concurrent_workload_under_eviction() {
+    let dir
+        = temp_dir("paging-concurrent");
+
+    for (pos, locking_strategy) in [LockingStrategy::LockCoupling, orwc(), OLC(), lightweight_hybrid_lock(), optiql()]
+        .into_iter()
+        .enumerate()
+    {
+        let path
+            = dir.join(pos.to_string());
+
+        let tree = Tree::create_pooled(&path, 1 << 16, locking_strategy.clone(), u64::MIN, u64::MAX, inc_key, dec_key)
+            .unwrap()
+            .with_buffer_pool(4);
+
+        assert_concurrent_workload(&tree);
+
+        let buffer
+            = tree.buffer_pool().unwrap();
+
+        assert!(buffer.evictions() > 0 && buffer.faults() > 0, "{locking_strategy}");
+        assert_eq!(buffer.failures(), 0, "{locking_strategy}");
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn corrupt_slots_fail_operations() {
+    let dir
+        = temp_dir("paging-corrupt");
+
+    let path
+        = dir.join("pool");
+
+    pool_of(&path, OLC(), 20_000);
+
+    let (frame, offset) = {
+        let tree
+            = Tree::open_pooled(&path, inc_key, dec_key).unwrap();
+
+        let (frame, leaf)
+            = frame_of(&tree.export().root, 5_000);
+
+        (frame, tree.pool().unwrap().slot_offset(leaf).unwrap())
+    };
+
+    // the slot's kind, following its sequence number, no longer names a leaf
+    OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .write_all_at(&[0], (offset + 8) as u64)
+        .unwrap();
+
+    let tree
+        = Tree::open_paged(&path, 4, inc_key, dec_key).unwrap();
+
+    assert!(matches!(tree.dispatch(CRUDOperation::Point(5_000)).1, CRUDOperationResult::Error));
+    assert!(matches!(tree.dispatch(CRUDOperation::Insert(5_000, 0)).1, CRUDOperationResult::Error));
+    assert_eq!(tree.buffer_pool().unwrap().failures(), 2);
+
+    // other frames and the walks over the tree keep working
+    assert_eq!(get(&tree, 15_000), Some(15_001));
+    insert(&tree, 20_000, 20_001);
+
+    let many
+        = tree.get_many([15_000, 5_000]);
+
+    assert_eq!(many.failed, [5_000]);
+    assert!(many.records[0].is_some() && many.records[1].is_none());
+
+    let batch
+        = tree.insert_batch([(5_001, 0), (20_001, 0)], 1);
+
+    assert_eq!((batch.inserted, batch.failed), (1, vec![5_001]));
+
+    let violations
+        = tree.validate().violations;
+
+    assert!(violations.iter().any(|violation| matches!(violation, Violation::UnreadableFrame { block_id, .. } if *block_id == frame)),
+            "{violations:?}");
+
+    assert!(matches!(tree.save_to(dir.join("snapshot")), Err(SnapshotError::UnreadableFrame(block_id)) if block_id == frame));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
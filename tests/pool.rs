@@ -0,0 +1,305 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::os::unix::fs::FileExt;
+use common::{assert_consistent, delete, get, insert, shuffled, strategies, temp_dir, Tree};
+use CCBPlusTree::block::block_pool::{PoolError, HEADER_COPY_LEN};
+use CCBPlusTree::locking::locking_strategy::*;
+use CCBPlusTree::page_model::BlockID;
+use CCBPlusTree::test::{dec_key, inc_key};
+use CCBPlusTree::tree::bplus_tree::BPlusTree;
+use CCBPlusTree::tree::export::{NodeExport, NodeKind};
+
+/// Nodes in pre-order with their IDs and keys, latch versions restart with each open.
+fn shape(node: &NodeExport<u64>, nodes: &mut Vec<(BlockID, NodeKind, Vec<u64>)>) {
+    nodes.push((node.block_id, node.kind, node.keys.clone()));
+    node.children.iter().for_each(|child| shape(child, nodes));
+}
+
+fn shape_of(tree: &Tree) -> Vec<(BlockID, NodeKind, Vec<u64>)> {
+    let mut nodes
+        = vec![];
+
+    shape(&tree.export().root, &mut nodes);
+    nodes
+}
+
+fn create(path: &std::path::Path, locking_strategy: LockingStrategy) -> Tree {
+    Tree::create_pooled(path, 1 << 16, locking_strategy, u64::MIN, u64::MAX, inc_key, dec_key).unwrap()
+}
+
+#[test]
+fn reopens_to_last_checkpoint() {
+    let dir
+        = temp_dir("pool-round-trip");
+
+    for (pos, locking_strategy) in strategies().into_iter().enumerate() {
+        let path
+            = dir.join(pos.to_string());
+
+        let tree
+            = create(&path, locking_strategy.clone());
+
+        shuffled(0..10_000, 1).into_iter().for_each(|key| insert(&tree, key, key + 1));
+        (0..10_000).step_by(3).for_each(|key| delete(&tree, key));
+
+        let blocks
+            = tree.checkpoint_pool().unwrap();
+
+        assert_eq!(blocks, shape_of(&tree).len(), "{locking_strategy}");
+
+        // writes after the checkpoint are lost by reopening
+        insert(&tree, 0, 0);
+
+        let shape
+            = shape_of(&tree);
+
+        drop(tree);
+
+        let reopened
+            = Tree::open_pooled(&path, inc_key, dec_key).unwrap();
+
+        assert_eq!(reopened.locking_strategy().to_string(), locking_strategy.to_string());
+        assert_eq!(get(&reopened, 0), None, "{locking_strategy}");
+        assert!(shuffled(1..10_000, 2)
+                    .into_iter()
+                    .all(|key| get(&reopened, key) == (key % 3 != 0).then_some(key + 1)), "{locking_strategy}");
+
+        insert(&reopened, 0, 0);
+        assert_eq!(shape_of(&reopened), shape, "{locking_strategy}");
+        assert_consistent(&reopened, 6_667);
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn reuses_slots_of_dropped_blocks() {
+    let dir
+        = temp_dir("pool-reuse");
+
+    let path
+        = dir.join("pool");
+
+    let tree
+        = create(&path, OLC());
+
+    (0..10_000).for_each(|key| insert(&tree, key, key));
+    tree.checkpoint_pool().unwrap();
+
+    let block_ids
+        = tree.pool().unwrap().block_ids();
+
+    (0..10_000).for_each(|key| delete(&tree, key));
+    tree.checkpoint_pool().unwrap();
+    drop(tree);
+
+    // a reopened pool hands out the slots no block held at its last checkpoint
+    let reopened
+        = Tree::open_pooled(&path, inc_key, dec_key).unwrap();
+
+    (0..10_000).for_each(|key| insert(&reopened, key, key));
+    reopened.checkpoint_pool().unwrap();
+
+    // without reuse, each block of the refilled tree took a new ID
+    let blocks
+        = shape_of(&reopened).len() as BlockID;
+
+    assert!(reopened.pool().unwrap().block_ids() < block_ids + blocks / 2);
+    assert_consistent(&reopened, 10_000);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn reopens_with_other_locking_strategy() {
+    let dir
+        = temp_dir("pool-strategy");
+
+    let path
+        = dir.join("pool");
+
+    let tree
+        = create(&path, LockingStrategy::MonoWriter);
+
+    (0..5_000).for_each(|key| insert(&tree, key, key));
+    tree.checkpoint_pool().unwrap();
+    drop(tree);
+
+    let reopened
+        = Tree::open_pooled_with(&path, OLC(), inc_key, dec_key).unwrap();
+
+    assert_eq!(reopened.locking_strategy().to_string(), OLC().to_string());
+    assert!((0..5_000).all(|key| get(&reopened, key) == Some(key)));
+    assert_consistent(&reopened, 5_000);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn existing_files_are_not_overwritten() {
+    let dir
+        = temp_dir("pool-existing");
+
+    let path
+        = dir.join("pool");
+
+    let tree
+        = create(&path, OLC());
+
+    (0..1_000).for_each(|key| insert(&tree, key, key));
+    tree.checkpoint_pool().unwrap();
+    drop(tree);
+
+    let created = Tree::create_pooled(&path, 1 << 16, OLC(), u64::MIN, u64::MAX, inc_key, dec_key)
+        .err()
+        .unwrap();
+
+    assert!(matches!(created, PoolError::Io(ref error) if error.kind() == ErrorKind::AlreadyExists), "{created}");
+
+    let reopened
+        = Tree::open_pooled(&path, inc_key, dec_key).unwrap();
+
+    assert!((0..1_000).all(|key| get(&reopened, key) == Some(key)));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn other_layouts_are_rejected() {
+    let dir
+        = temp_dir("pool-layout");
+
+    let path
+        = dir.join("pool");
+
+    drop(create(&path, OLC()));
+
+    let payload
+        = BPlusTree::<16, 16, u64, i64>::open_pooled(&path, inc_key, dec_key).err().unwrap();
+
+    let fan_out
+        = BPlusTree::<8, 16, u64, u64>::open_pooled(&path, inc_key, dec_key).err().unwrap();
+
+    assert!(matches!(payload, PoolError::LayoutMismatch), "{payload}");
+    assert!(matches!(fan_out, PoolError::LayoutMismatch), "{fan_out}");
+
+    let other
+        = dir.join("other");
+
+    fs::write(&other, vec![0u8; 1 << 13]).unwrap();
+
+    let not_a_pool
+        = Tree::open_pooled(&other, inc_key, dec_key).err().unwrap();
+
+    assert!(matches!(not_a_pool, PoolError::NotAPool), "{not_a_pool}");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn torn_checkpoint_reopens_to_the_previous_one() {
+    let dir
+        = temp_dir("pool-torn");
+
+    let path
+        = dir.join("pool");
+
+    let tree
+        = create(&path, OLC());
+
+    (0..5_000).for_each(|key| insert(&tree, key, key));
+    tree.checkpoint_pool().unwrap();
+
+    let shape
+        = shape_of(&tree);
+
+    // rewrites most blocks, each to the slot not holding its version of the last checkpoint
+    (0..5_000).step_by(2).for_each(|key| delete(&tree, key));
+    (5_000..10_000).for_each(|key| insert(&tree, key, key));
+    tree.checkpoint_pool().unwrap();
+
+    let seq
+        = tree.pool().unwrap().checkpoints();
+
+    drop(tree);
+
+    // a crash while writing the header leaves its copy failing the checksum
+    OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .write_all_at(&[0xFF; 8], (seq as usize % 2 * HEADER_COPY_LEN + 40) as u64)
+        .unwrap();
+
+    let reopened
+        = Tree::open_pooled(&path, inc_key, dec_key).unwrap();
+
+    assert_eq!(reopened.pool().unwrap().checkpoints(), seq - 1);
+    assert_eq!(shape_of(&reopened), shape);
+    assert!((0..10_000).all(|key| get(&reopened, key) == (key < 5_000).then_some(key)));
+    assert_consistent(&reopened, 5_000);
+
+    // slots of the torn checkpoint are discarded, hence not mistaken for newer versions
+    (5_000..8_000).for_each(|key| insert(&reopened, key, key + 1));
+    reopened.checkpoint_pool().unwrap();
+    drop(reopened);
+
+    let reopened
+        = Tree::open_pooled(&path, inc_key, dec_key).unwrap();
+
+    assert!((0..8_000).all(|key| get(&reopened, key) == Some(key + key / 5_000)));
+    assert_consistent(&reopened, 8_000);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn truncated_pools_fail_opening() {
+    let dir
+        = temp_dir("pool-truncated");
+
+    let path
+        = dir.join("pool");
+
+    let tree
+        = create(&path, OLC());
+
+    (0..5_000).for_each(|key| insert(&tree, key, key));
+    tree.checkpoint_pool().unwrap();
+
+    let pool
+        = tree.pool().unwrap();
+
+    assert!(matches!(pool.slot_offset(BlockID::MAX), Err(PoolError::UnmappedSlot(BlockID::MAX))));
+
+    let half
+        = pool.block_ids() / 2;
+
+    let len
+        = pool.slot_offset(half).unwrap();
+
+    drop(tree);
+
+    // slots of the blocks with the higher half of the IDs are cut off
+    OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_len(len as u64)
+        .unwrap();
+
+    for buffer_capacity in [None, Some(4)] {
+        let opened = match buffer_capacity {
+            Some(capacity) => Tree::open_paged(&path, capacity, inc_key, dec_key).err().unwrap(),
+            None => Tree::open_pooled(&path, inc_key, dec_key).err().unwrap()
+        };
+
+        assert!(matches!(opened, PoolError::UnmappedSlot(block_id) if block_id >= half), "{opened}");
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::{insert, tree};
+use CCBPlusTree::crud_model::crud_api::CRUDDispatcher;
+use CCBPlusTree::crud_model::crud_operation::CRUDOperation;
+use CCBPlusTree::crud_model::crud_operation_result::CRUDOperationResult;
+use CCBPlusTree::locking::locking_strategy::*;
+use CCBPlusTree::utils::profile::OperationProfile;
+use CCBPlusTree::utils::smart_cell::LatchMode;
+
+fn levels(profile: &OperationProfile, attempt: usize) -> Vec<(u16, LatchMode)> {
+    profile.latches
+        .iter()
+        .filter(|latch| latch.attempt == attempt)
+        .map(|latch| (latch.level as _, latch.mode))
+        .collect()
+}
+
+#[test]
+fn reads_latch_every_level_once() {
+    let expected = [
+        (orwc(), LatchMode::Shared),
+        (LockingStrategy::LockCoupling, LatchMode::Exclusive),
+        (hybrid_lock(), LatchMode::Optimistic),
+    ];
+
+    for (locking_strategy, mode) in expected {
+        let tree
+            = tree(locking_strategy.clone());
+
+        (0..1_000).for_each(|key| insert(&tree, key, key));
+
+        let (.., result, profile)
+            = tree.dispatch_profiled(CRUDOperation::PeekMin);
+
+        assert!(matches!(result, CRUDOperationResult::MatchedRecord(Some(..))), "{locking_strategy}");
+        assert!(tree.height() > 2, "{locking_strategy}");
+        assert_eq!(levels(&profile, 0),
+                   (1..=tree.height() as u16).map(|level| (level, mode)).collect::<Vec<_>>(),
+                   "{locking_strategy}: {profile}");
+
+        assert_eq!(profile.attempts(), 1, "{locking_strategy}: {profile}");
+        assert!(!profile.modified_structure(), "{locking_strategy}: {profile}");
+    }
+}
+
+#[test]
+fn restarts_are_recorded_at_their_level() {
+    // read-write coupling latches inner nodes shared and restarts once a full leaf splits
+    let tree
+        = tree(orwc());
+
+    let profile = (0..1_000)
+        .map(|key| tree.dispatch_profiled(CRUDOperation::Insert(key, key)).2)
+        .find(|profile| !profile.restarts.is_empty())
+        .expect("no restart");
+
+    assert_eq!(profile.attempts(), 2, "{profile}");
+
+    let [restart]
+        = profile.restarts.as_slice() else { panic!("{profile}") };
+
+    let first
+        = levels(&profile, 0);
+
+    assert_eq!(first.last().map(|(level, ..)| *level), Some(*restart), "{profile}");
+    assert!(first.iter().enumerate().all(|(pos, (level, ..))| *level as usize == pos + 1), "{profile}");
+    assert_eq!(first.first().map(|(.., mode)| *mode), Some(LatchMode::Shared), "{profile}");
+
+    let second
+        = levels(&profile, 1);
+
+    assert_eq!(second.first().map(|(level, ..)| *level), Some(1), "{profile}");
+    assert!(second.iter().all(|(.., mode)| *mode == LatchMode::Exclusive), "{profile}");
+    assert_eq!((profile.splits, profile.upgrades), (1, 1), "{profile}");
+}
+
+#[test]
+fn structure_modifications_are_flagged() {
+    for locking_strategy in [orwc(), OLC(), lightweight_hybrid_lock()] {
+        let tree
+            = tree(locking_strategy.clone());
+
+        let inserts = (0..2_000)
+            .map(|key| tree.dispatch_profiled(CRUDOperation::Insert(key, key)).2)
+            .collect::<Vec<_>>();
+
+        let deletes = (0..2_000)
+            .map(|key| tree.dispatch_profiled(CRUDOperation::Delete(key)).2)
+            .collect::<Vec<_>>();
+
+        let metrics
+            = tree.metrics().snapshot();
+
+        let profiles
+            = inserts.iter().chain(deletes.iter()).cloned().collect::<Vec<_>>();
+
+        assert!(profiles.iter()
+            .all(|profile| profile.modified_structure() == (profile.splits + profile.merges + profile.redistributions > 0)));
+
+        // top-down corrections split and merge on the paths of either writes
+        let sum = |profiles: &[OperationProfile], count: fn(&OperationProfile) -> usize|
+            profiles.iter().map(count).sum::<usize>() as u64;
+
+        assert_eq!(sum(&profiles, |profile| profile.splits), metrics.splits, "{locking_strategy}");
+        assert_eq!(sum(&profiles, |profile| profile.merges), metrics.merges, "{locking_strategy}");
+        assert_eq!(sum(&profiles, |profile| profile.redistributions), metrics.redistributions, "{locking_strategy}");
+        assert_eq!(sum(&profiles, |profile| profile.root_changes), metrics.root_changes, "{locking_strategy}");
+        assert!(metrics.splits > 0 && metrics.merges > 0, "{locking_strategy}: {metrics}");
+
+        assert!(metrics.root_changes > tree.height() as u64, "{locking_strategy}: {metrics}");
+    }
+}
@@ -0,0 +1,273 @@
+#![cfg(feature = "prometheus")]
+
+mod common;
+
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use common::{get, insert, tree};
+use CCBPlusTree::locking::locking_strategy::OLC;
+use CCBPlusTree::tree::prometheus::{render, CONTENT_TYPE, REQUEST_TIMEOUT};
+use CCBPlusTree::utils::metrics::{MetricsSnapshot, OperationKind};
+
+/// Checks the text exposition format line by line and returns the samples as (name, labels, value).
+fn parse(text: &str) -> Vec<(String, String, f64)> {
+    let mut families
+        = HashSet::new();
+
+    let mut current: Option<(String, String)>
+        = None;
+
+    let mut samples
+        = vec![];
+
+    for line in text.lines() {
+        if let Some(help) = line.strip_prefix("# HELP ") {
+            let (name, description)
+                = help.split_once(' ').unwrap_or_else(|| panic!("HELP without text: {line}"));
+
+            assert!(!description.is_empty(), "{line}");
+            assert!(families.insert(name.to_string()), "repeated family: {line}");
+            current = Some((name.to_string(), String::new()));
+        }
+        else if let Some(kind) = line.strip_prefix("# TYPE ") {
+            let (name, kind)
+                = kind.split_once(' ').unwrap();
+
+            let (family, ..)
+                = current.take().unwrap_or_else(|| panic!("TYPE without HELP: {line}"));
+
+            assert_eq!(name, family, "{line}");
+            assert!(matches!(kind, "counter" | "gauge"), "{line}");
+            assert_eq!(kind == "counter", name.ends_with("_total"), "{line}");
+            current = Some((family, kind.to_string()));
+        }
+        else {
+            let (family, kind)
+                = current.as_ref().unwrap_or_else(|| panic!("sample without family: {line}"));
+
+            assert!(!kind.is_empty(), "sample before TYPE: {line}");
+
+            let (name, rest)
+                = line.split_once('{').unwrap_or_else(|| panic!("sample without labels: {line}"));
+
+            let (labels, value)
+                = rest.rsplit_once("} ").unwrap();
+
+            assert_eq!(name, family, "{line}");
+            assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'), "{line}");
+            assert!(labels.starts_with("strategy=\""), "{line}");
+
+            let value
+                = value.parse::<f64>().unwrap_or_else(|_| panic!("malformed value: {line}"));
+
+            assert!(value >= 0.0, "{line}");
+            samples.push((name.to_string(), labels.to_string(), value));
+        }
+    }
+
+    samples
+}
+
+fn sample(samples: &[(String, String, f64)], name: &str, labels: &str) -> Option<f64> {
+    samples
+        .iter()
+        .find(|(sample, sample_labels, ..)| sample == name && sample_labels == labels)
+        .map(|(.., value)| *value)
+}
+
+#[test]
+fn renders_exposition_format() {
+    let mut snapshot
+        = MetricsSnapshot::default();
+
+    snapshot.operations[OperationKind::Insert as usize] = 7;
+    snapshot.restarts[OperationKind::Point as usize][1] = 3;
+    snapshot.splits = 2;
+    snapshot.latch_wait = Duration::from_millis(1_500);
+
+    let text
+        = render("OLC", 3, &snapshot, None);
+
+    let samples
+        = parse(&text);
+
+    let strategy
+        = "strategy=\"OLC\"";
+
+    assert_eq!(sample(&samples, "ccbplustree_height", strategy), Some(3.0));
+    assert_eq!(sample(&samples, "ccbplustree_operations_total", &format!("{strategy},operation=\"Insert\"")), Some(7.0));
+    assert_eq!(sample(&samples, "ccbplustree_operations_total", &format!("{strategy},operation=\"Range\"")), Some(0.0));
+    assert_eq!(sample(&samples, "ccbplustree_splits_total", strategy), Some(2.0));
+    assert_eq!(sample(&samples, "ccbplustree_latch_wait_seconds_total", strategy), Some(1.5));
+
+    // restarts are only listed for levels that restarted
+    let restarts = samples
+        .iter()
+        .filter(|(name, ..)| name == "ccbplustree_restarts_total")
+        .collect::<Vec<_>>();
+
+    assert_eq!(restarts.len(), 1);
+    assert_eq!(restarts[0].1, format!("{strategy},operation=\"Point\",level=\"2\""));
+    assert_eq!(restarts[0].2, 3.0);
+
+    assert!(samples.iter().all(|(name, ..)| !name.starts_with("ccbplustree_level")));
+    assert!(text.ends_with('\n'));
+}
+
+#[test]
+fn escapes_label_values() {
+    let samples
+        = parse(&render("a\"b\\c\nd", 1, &MetricsSnapshot::default(), None));
+
+    assert_eq!(sample(&samples, "ccbplustree_height", r#"strategy="a\"b\\c\nd""#), Some(1.0));
+}
+
+#[test]
+fn renders_tree_with_stats() {
+    let tree
+        = tree(OLC());
+
+    (0..1_000).for_each(|key| insert(&tree, key, key));
+
+    // nothing cached yet
+    assert!(parse(&tree.to_prometheus_with_stats()).iter().all(|(name, ..)| name != "ccbplustree_records"));
+
+    let stats
+        = tree.refresh_stats();
+
+    // later inserts are served once refreshed again
+    insert(&tree, 1_000, 1_000);
+
+    let samples
+        = parse(&tree.to_prometheus_with_stats());
+
+    let strategy
+        = format!("strategy=\"{}\"", tree.locking_strategy());
+
+    assert_eq!(sample(&samples, "ccbplustree_height", &strategy), Some(tree.height() as f64));
+    assert_eq!(sample(&samples, "ccbplustree_operations_total", &format!("{strategy},operation=\"Insert\"")), Some(1_001.0));
+    assert_eq!(sample(&samples, "ccbplustree_records", &strategy), Some(1_000.0));
+    assert_eq!(sample(&samples, "ccbplustree_pages", &format!("{strategy},kind=\"leaf\"")), Some(stats.leaf_pages as f64));
+    assert_eq!(samples.iter().filter(|(name, ..)| name == "ccbplustree_level_fill_ratio").count(), stats.levels.len());
+    assert!(parse(&tree.to_prometheus()).iter().all(|(name, ..)| name != "ccbplustree_records"));
+
+    tree.refresh_stats();
+    assert_eq!(sample(&parse(&tree.to_prometheus_with_stats()), "ccbplustree_records", &strategy), Some(1_001.0));
+}
+
+fn request(endpoint: &std::net::SocketAddr, request: &str) -> String {
+    let mut stream
+        = TcpStream::connect(endpoint).unwrap();
+
+    stream.write_all(request.as_bytes()).unwrap();
+
+    let mut response
+        = String::new();
+
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn serves_metrics_via_http() {
+    let tree
+        = Arc::new(tree(OLC()));
+
+    insert(&tree, 1, 1);
+    assert_eq!(get(&tree, 1), Some(1));
+    tree.refresh_stats();
+
+    let endpoint
+        = tree.serve_metrics(0).unwrap();
+
+    let addr
+        = endpoint.local_addr();
+
+    let response
+        = request(&addr, "GET /metrics HTTP/1.1\r\n\r\n");
+
+    let (head, body)
+        = response.split_once("\r\n\r\n").unwrap();
+
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"), "{head}");
+    assert!(head.contains(&format!("Content-Type: {CONTENT_TYPE}")), "{head}");
+    assert!(head.contains(&format!("Content-Length: {}", body.len())), "{head}");
+    assert_eq!(body, tree.to_prometheus_with_stats());
+    assert_eq!(sample(&parse(body), "ccbplustree_records", &format!("strategy=\"{}\"", tree.locking_strategy())), Some(1.0));
+
+    assert!(request(&addr, "GET /other HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 404 Not Found"));
+    assert!(request(&addr, "POST /metrics HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 405 Method Not Allowed"));
+
+    drop(endpoint);
+    assert!(TcpStream::connect(addr).is_err());
+}
+
+#[test]
+fn reads_request_headers_before_responding() {
+    let tree
+        = Arc::new(tree(OLC()));
+
+    let endpoint
+        = tree.serve_metrics(0).unwrap();
+
+    let mut stream
+        = TcpStream::connect(endpoint.local_addr()).unwrap();
+
+    // the headers arrive after the request line was answered, unless they are awaited
+    stream.write_all(b"GET /metrics HTTP/1.1\r\n").unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    (0..50).for_each(|header| write!(stream, "X-Header-{header}: {}\r\n", "v".repeat(64)).unwrap());
+    stream.write_all(b"\r\n").unwrap();
+
+    let mut response
+        = String::new();
+
+    stream.read_to_string(&mut response).unwrap();
+
+    let (head, body)
+        = response.split_once("\r\n\r\n").unwrap();
+
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"), "{head}");
+    assert!(head.contains(&format!("Content-Length: {}", body.len())), "{head}");
+}
+
+#[test]
+fn stalled_clients_do_not_block_the_endpoint() {
+    let tree
+        = Arc::new(tree(OLC()));
+
+    let endpoint
+        = tree.serve_metrics(0).unwrap();
+
+    let addr
+        = endpoint.local_addr();
+
+    // neither sends its request line completely
+    let mut stalled
+        = TcpStream::connect(addr).unwrap();
+
+    stalled.write_all(b"GET /met").unwrap();
+
+    let silent
+        = TcpStream::connect(addr).unwrap();
+
+    assert!(request(&addr, "GET /metrics HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 200 OK"));
+
+    let silent_again
+        = TcpStream::connect(addr).unwrap();
+
+    let start
+        = Instant::now();
+
+    drop(endpoint);
+
+    assert!(start.elapsed() < 3 * REQUEST_TIMEOUT, "{:?}", start.elapsed());
+    assert!(TcpStream::connect(addr).is_err());
+
+    drop((stalled, silent, silent_again));
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use common::{assert_consistent, assert_random_deletes, delete, get, insert, shuffled, strategies, tree};
+use CCBPlusTree::crud_model::crud_api::CRUDDispatcher;
+use CCBPlusTree::crud_model::crud_operation::CRUDOperation;
+use CCBPlusTree::crud_model::crud_operation_result::CRUDOperationResult;
+use CCBPlusTree::locking::locking_strategy::*;
+
+#[test]
+fn ascending_inserts_fill_left_leaves() {
+    for locking_strategy in strategies() {
+        let tree = tree(locking_strategy.clone())
+            .with_sequential_inserts();
+
+        (0..60_000).for_each(|key| insert(&tree, key, key));
+
+        let stats
+            = tree.stats();
+
+        let leaf_fill = stats.levels
+            .last()
+            .unwrap()
+            .avg_fill;
+
+        assert!(leaf_fill > 0.9, "{locking_strategy}: {leaf_fill}");
+        assert!(tree.validate().is_valid(), "{locking_strategy}");
+        assert!((0..60_000).all(|key| get(&tree, key) == Some(key)), "{locking_strategy}");
+    }
+}
+
+#[test]
+fn ascending_inserts_take_fast_path() {
+    let appending = [OLC(), optiql(), lightweight_hybrid_lock(), LHL_write(4), LHL_read_write(4, 4)];
+
+    for locking_strategy in strategies().into_iter().chain(appending.clone()) {
+        let tree = tree(locking_strategy.clone())
+            .with_sequential_inserts();
+
+        (0..10_000).for_each(|key| insert(&tree, key, key));
+
+        let appends
+            = tree.metrics().snapshot().appends;
+
+        match appending.iter().any(|appending| appending.to_string() == locking_strategy.to_string()) {
+            true => assert!(appends > 9_000, "{locking_strategy}: {appends}"),
+            false => assert_eq!(appends, 0, "{locking_strategy}")
+        }
+    }
+}
+
+#[test]
+fn pop_max_drains_appended_tree() {
+    for locking_strategy in strategies() {
+        let tree = tree(locking_strategy.clone())
+            .with_sequential_inserts();
+
+        (0..5_000).for_each(|key| insert(&tree, key, key));
+        (0..5_000).rev().for_each(|key| match tree.dispatch(CRUDOperation::PopMax).1 {
+            CRUDOperationResult::Deleted(popped, _) => assert_eq!(popped, key, "{locking_strategy}"),
+            _ => panic!("{locking_strategy}: PopMax failed before {key}")
+        });
+
+        assert_consistent(&tree, 0);
+    }
+}
+
+#[test]
+fn random_deletes_after_random_inserts() {
+    for locking_strategy in strategies() {
+        let tree = tree(locking_strategy.clone())
+            .with_sequential_inserts();
+
+        assert_random_deletes(&tree, 20_000, 15_000, 3);
+    }
+}
+
+#[test]
+fn appends_alongside_other_writers() {
+    // a single writer at a time, see `LockingStrategy::MonoWriter`
+    for locking_strategy in strategies().into_iter().filter(|s| !matches!(s, LockingStrategy::MonoWriter)) {
+        let tree = tree(locking_strategy.clone())
+            .with_sequential_inserts();
+
+        (0..20_000).for_each(|key| insert(&tree, key, key));
+
+        thread::scope(|scope| {
+            let tree
+                = &tree;
+
+            scope.spawn(move || (100_000..140_000).for_each(|key| insert(tree, key, key)));
+
+            // writes to the rightmost leaf invalidate the remembered one
+            scope.spawn(move || shuffled(20_000..40_000, 1)
+                .into_iter()
+                .for_each(|key| insert(tree, key, key)));
+
+            scope.spawn(move || shuffled(0..10_000, 2)
+                .into_iter()
+                .for_each(|key| delete(tree, key)));
+
+            scope.spawn(move || (100_000..140_000)
+                .step_by(100)
+                .for_each(|key| while get(tree, key) != Some(key) {
+                    thread::yield_now();
+                }));
+        });
+
+        assert!((10_000..40_000).chain(100_000..140_000).all(|key| get(&tree, key) == Some(key)),
+                "{locking_strategy}");
+        assert_consistent(&tree, 70_000);
+    }
+}
+
+#[test]
+fn appends_while_rightmost_parent_splits() {
+    let appending = [OLC(), optiql(), lightweight_hybrid_lock(), LHL_write(4), LHL_read_write(4, 4)];
+
+    // a single writer at a time, see `LockingStrategy::MonoWriter`
+    for locking_strategy in strategies()
+        .into_iter()
+        .chain(appending)
+        .filter(|s| !matches!(s, LockingStrategy::MonoWriter))
+    {
+        let tree = tree(locking_strategy.clone())
+            .with_sequential_inserts();
+
+        let appended
+            = AtomicU64::new(0);
+
+        thread::scope(|scope| {
+            let (tree, appended)
+                = (&tree, &appended);
+
+            scope.spawn(move || (0..20_000).for_each(|key| {
+                insert(tree, key * 2, key * 2);
+                appended.store(key + 1, Ordering::Release);
+            }));
+
+            // odd keys right behind the appends split the leaves left of the remembered one,
+            // hence their parent, which is the rightmost parent as well
+            scope.spawn(move || {
+                let mut next
+                    = 0;
+
+                while next < 20_000 {
+                    let behind
+                        = appended.load(Ordering::Acquire);
+
+                    (next..behind).for_each(|key| insert(tree, key * 2 + 1, key * 2 + 1));
+
+                    next = behind;
+                    thread::yield_now();
+                }
+            });
+        });
+
+        assert!((0..40_000).all(|key| get(&tree, key) == Some(key)), "{locking_strategy}");
+        assert_consistent(&tree, 40_000);
+    }
+}
+
+#[test]
+fn appends_after_pop_max() {
+    for locking_strategy in strategies() {
+        let tree = tree(locking_strategy.clone())
+            .with_sequential_inserts();
+
+        (0..5_000).for_each(|key| insert(&tree, key, key));
+
+        // the remembered leaf shrinks or is merged, later appends traverse to the new rightmost one
+        (0..2_500).for_each(|_| {
+            tree.dispatch(CRUDOperation::PopMax);
+        });
+
+        (2_500..7_500).for_each(|key| insert(&tree, key, key));
+
+        assert!((0..7_500).all(|key| get(&tree, key) == Some(key)), "{locking_strategy}");
+        assert_consistent(&tree, 7_500);
+    }
+}
@@ -0,0 +1,207 @@
+mod common;
+
+use std::sync::{Arc, Barrier};
+use std::thread;
+use common::{delete, insert, shuffled, strategies, tree};
+use CCBPlusTree::utils::epoch;
+use CCBPlusTree::utils::slab::{Slab, SlabArc};
+
+/// Frees the garbage retired by the current thread, the epoch has to advance twice.
+fn collect_garbage() {
+    (0..4).for_each(|_| epoch::collect());
+}
+
+#[test]
+fn slots_are_aligned() {
+    let slab
+        = Slab::new::<[u8; 100]>();
+
+    assert_eq!(slab.slot_bytes() % 64, 0);
+
+    let values = (0..100u8)
+        .map(|value| SlabArc::new_in([value; 100], &slab))
+        .collect::<Vec<_>>();
+
+    // the header precedes each value at the same offset into its cache-line aligned slot
+    let offset
+        = SlabArc::as_ptr(&values[0]) as usize % 64;
+
+    values.iter().enumerate().for_each(|(value, arc)| {
+        assert!(SlabArc::is_slab(arc));
+        assert_eq!(SlabArc::as_ptr(arc) as usize % 64, offset);
+        assert_eq!(arc[99], value as u8);
+    });
+
+    let pages
+        = Slab::new::<[u8; 5000]>();
+
+    assert_eq!(pages.slot_bytes(), 8192);
+
+    let page
+        = SlabArc::new_in([1u8; 5000], &pages);
+
+    assert_eq!(SlabArc::as_ptr(&page) as usize % 4096, offset);
+}
+
+#[test]
+fn freed_slots_are_recycled() {
+    let slab
+        = Slab::new::<u64>();
+
+    let values = (0..1_000u64)
+        .map(|value| SlabArc::new_in(value, &slab))
+        .collect::<Vec<_>>();
+
+    let usage
+        = slab.usage();
+
+    assert_eq!(usage.slots_in_use, 1_000);
+    assert_eq!(usage.slots_free, 0);
+    assert_eq!(usage.used_bytes(), 1_000 * slab.slot_bytes());
+    assert!(usage.reserved_bytes() >= usage.used_bytes());
+    assert_eq!(slab.slots_in_use(), 1_000);
+
+    // clones share the slot
+    let clone
+        = values[0].clone();
+
+    assert_eq!(SlabArc::strong_count(&clone), 2);
+    assert!(SlabArc::ptr_eq(&clone, &values[0]));
+    drop(values);
+    assert_eq!(*clone, 0);
+
+    let freed
+        = slab.usage();
+
+    assert_eq!(freed.slots_in_use, 1);
+    assert_eq!(freed.slots_free, 999);
+    assert_eq!(freed.chunks, usage.chunks);
+
+    let values = (0..999u64)
+        .map(|value| SlabArc::new_in(value, &slab))
+        .collect::<Vec<_>>();
+
+    let reused
+        = slab.usage();
+
+    assert_eq!(reused.chunks, usage.chunks);
+    assert_eq!(reused.slots_in_use, 1_000);
+    assert_eq!(reused.slots_free, 0);
+    assert!(values.iter().zip(0..).all(|(arc, value)| **arc == value));
+}
+
+#[test]
+fn values_outlive_their_slab() {
+    let slab
+        = Slab::new::<Vec<u64>>();
+
+    let values = (0..100u64)
+        .map(|value| SlabArc::new_in(vec![value; 16], &slab))
+        .collect::<Vec<_>>();
+
+    drop(slab);
+
+    assert!(values.iter().zip(0..).all(|(arc, value)| arc.iter().all(|entry| *entry == value)));
+
+    let heap
+        = SlabArc::new(vec![1u64]);
+
+    assert!(!SlabArc::is_slab(&heap));
+    assert_eq!(heap[0], 1);
+}
+
+#[test]
+fn threads_allocate_and_free_concurrently() {
+    let slab
+        = Arc::new(Slab::new::<[u64; 8]>());
+
+    let threads
+        = 8;
+
+    let barrier
+        = Arc::new(Barrier::new(threads));
+
+    let kept = (0..threads)
+        .map(|thread| {
+            let slab
+                = slab.clone();
+
+            let barrier
+                = barrier.clone();
+
+            thread::spawn(move || {
+                barrier.wait();
+
+                let mut kept
+                    = vec![];
+
+                for round in 0..10_000u64 {
+                    let value
+                        = SlabArc::new_in([thread as u64 + round; 8], &slab);
+
+                    // values freed by other threads end up in this thread's shard
+                    if round % 4 == 0 {
+                        kept.push(value);
+                    }
+                }
+
+                kept
+            })
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .flat_map(|handle| handle.join().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(slab.usage().slots_in_use, kept.len());
+    assert_eq!(slab.slots_in_use(), kept.len());
+
+    let freeing = kept
+        .chunks(kept.len() / threads)
+        .map(|chunk| chunk.to_vec())
+        .collect::<Vec<_>>();
+
+    drop(kept);
+
+    freeing.into_iter()
+        .map(|chunk| thread::spawn(move || drop(chunk)))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .for_each(|handle| handle.join().unwrap());
+
+    assert_eq!(slab.usage().slots_in_use, 0);
+    assert_eq!(slab.slots_in_use(), 0);
+}
+
+#[test]
+fn trees_hold_their_blocks_in_the_slab() {
+    for locking_strategy in strategies() {
+        let tree
+            = tree(locking_strategy.clone());
+
+        shuffled(0..20_000, 7).into_iter().for_each(|key| insert(&tree, key, key));
+        collect_garbage();
+
+        let usage
+            = tree.slab_usage();
+
+        assert_eq!(usage.slots_in_use, tree.stats().nodes(), "{locking_strategy}");
+        assert_eq!(usage.slot_bytes, tree.stats().cell_bytes, "{locking_strategy}");
+
+        (0..20_000).for_each(|key| delete(&tree, key));
+        collect_garbage();
+
+        let deleted
+            = tree.slab_usage();
+
+        // unlinked blocks are freed, once optimistic readers are done with them
+        assert_eq!(deleted.slots_in_use, tree.stats().nodes(), "{locking_strategy}");
+        assert!(deleted.slots_free > 0, "{locking_strategy}");
+        assert_eq!(deleted.chunks, usage.chunks, "{locking_strategy}");
+
+        shuffled(0..20_000, 8).into_iter().for_each(|key| insert(&tree, key, key));
+        collect_garbage();
+
+        assert!(tree.slab_usage().chunks <= usage.chunks + 1, "{locking_strategy}");
+    }
+}
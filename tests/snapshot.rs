@@ -0,0 +1,107 @@
+mod common;
+
+use std::fs;
+use common::{assert_consistent, delete, get, insert, shuffled, strategies, temp_dir, tree, Tree};
+use CCBPlusTree::test::{dec_key, inc_key};
+use CCBPlusTree::tree::snapshot::SnapshotError;
+
+#[test]
+fn snapshot_round_trip() {
+    let dir
+        = temp_dir("snapshot-round-trip");
+
+    for (pos, locking_strategy) in strategies().into_iter().enumerate() {
+        let tree
+            = tree(locking_strategy.clone());
+
+        shuffled(0..10_000, 1).into_iter().for_each(|key| insert(&tree, key, key + 1));
+        (0..10_000).step_by(3).for_each(|key| delete(&tree, key));
+
+        let path
+            = dir.join(pos.to_string());
+
+        let saved
+            = tree.save_to(&path).unwrap();
+
+        assert_eq!(saved, 6_666, "{locking_strategy}");
+
+        let loaded
+            = Tree::load_from(&path, inc_key, dec_key).unwrap();
+
+        assert_eq!(loaded.locking_strategy().to_string(), locking_strategy.to_string());
+        assert!((0..10_000).all(|key| get(&loaded, key) == get(&tree, key)), "{locking_strategy}");
+        assert_consistent(&loaded, 6_666);
+
+        // the loaded tree keeps accepting writes
+        (0..10_000).step_by(3).for_each(|key| insert(&loaded, key, key + 1));
+        assert_consistent(&loaded, 10_000);
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn corrupted_snapshot_is_rejected() {
+    let dir
+        = temp_dir("snapshot-corrupted");
+
+    let path
+        = dir.join("snapshot");
+
+    let tree
+        = tree(Default::default());
+
+    (0..1_000).for_each(|key| insert(&tree, key, key));
+    tree.save_to(&path).unwrap();
+
+    let mut bytes
+        = fs::read(&path).unwrap();
+
+    // the checksum trails the records
+    let last
+        = bytes.len() - 1;
+
+    bytes[last] ^= 0xFF;
+    fs::write(&path, &bytes).unwrap();
+
+    assert!(matches!(Tree::load_from(&path, inc_key, dec_key), Err(SnapshotError::ChecksumMismatch { .. })));
+
+    fs::write(&path, b"not a snapshot").unwrap();
+
+    assert!(matches!(Tree::load_from(&path, inc_key, dec_key), Err(SnapshotError::NotASnapshot)));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn corrupted_length_prefix_is_rejected_before_decoding() {
+    let dir
+        = temp_dir("snapshot-length-prefix");
+
+    let (empty_path, path)
+        = (dir.join("empty"), dir.join("snapshot"));
+
+    // headers of equally configured trees are equal, the first chunk's length prefix follows it
+    tree(Default::default()).save_to(&empty_path).unwrap();
+
+    let first_chunk
+        = fs::read(&empty_path).unwrap().len() - 4 - 1;
+
+    let tree
+        = tree(Default::default());
+
+    (0..1_000).for_each(|key| insert(&tree, key, key));
+    tree.save_to(&path).unwrap();
+
+    let mut bytes
+        = fs::read(&path).unwrap();
+
+    // a varint marker announcing a u64 of all ones, i.e. more records than the file holds
+    bytes[first_chunk] = 0xFD;
+    bytes[first_chunk + 1..first_chunk + 9].fill(0xFF);
+    fs::write(&path, &bytes).unwrap();
+
+    assert!(matches!(Tree::load_from(&path, inc_key, dec_key), Err(SnapshotError::ChecksumMismatch { .. })));
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::tree;
+use CCBPlusTree::locking::locking_strategy::OLC;
+
+#[test]
+fn stats_of_empty_tree() {
+    let stats
+        = tree(OLC()).stats();
+
+    assert_eq!(stats.height, 1);
+    assert_eq!((stats.index_pages, stats.leaf_pages, stats.records), (0, 1, 0));
+    assert_eq!(stats.levels.len(), 1);
+    assert_eq!((stats.levels[0].nodes, stats.levels[0].entries), (1, 0));
+    assert_eq!(stats.levels[0].avg_fill, 0.0);
+    assert_eq!(stats.records_per_leaf[0], 1);
+    assert_eq!(stats.bytes_allocated, stats.cell_bytes);
+}
+
+#[test]
+fn stats_of_known_shape() {
+    let allocation
+        = tree(OLC()).stats().levels[0].allocation;
+
+    // bulk loads fill leaves one below their overflow and spread the remainder evenly,
+    // i.e. two full leaves and two holding one record less below a single index page
+    let capacity
+        = allocation as u64 - 1;
+
+    let tree = tree(OLC())
+        .bulk_load((0..4 * capacity - 2).map(|key| (key, key)));
+
+    let stats
+        = tree.stats();
+
+    assert_eq!(stats.height, 2);
+    assert_eq!((stats.index_pages, stats.leaf_pages), (1, 4));
+    assert_eq!(stats.records, 4 * capacity as usize - 2);
+    assert_eq!(stats.bytes_allocated, 5 * stats.cell_bytes);
+
+    let [index, leaves]
+        = stats.levels.as_slice() else { panic!("{stats}") };
+
+    assert_eq!((index.level, index.nodes, index.entries), (1, 1, 3));
+    assert_eq!(index.min_fill, 3.0 / index.allocation as f64);
+    assert_eq!(index.avg_fill, index.min_fill);
+
+    assert_eq!((leaves.level, leaves.nodes, leaves.entries), (2, 4, stats.records));
+    assert_eq!(leaves.allocation, allocation);
+    assert_eq!(leaves.min_fill, (capacity - 1) as f64 / allocation as f64);
+    assert_eq!(leaves.avg_fill, stats.records as f64 / (4 * allocation) as f64);
+
+    let histogram = stats.records_per_leaf
+        .iter()
+        .enumerate()
+        .filter(|(.., leaves)| **leaves > 0)
+        .map(|(records, leaves)| (records, *leaves))
+        .collect::<Vec<_>>();
+
+    assert_eq!(histogram, [(capacity as usize - 1, 2), (capacity as usize, 2)]);
+}
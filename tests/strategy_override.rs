@@ -0,0 +1,129 @@
+mod common;
+
+use std::thread;
+use common::{assert_consistent, get, insert, shuffled, strategies, tree, Tree};
+use CCBPlusTree::crud_model::crud_api::CRUDDispatcher;
+use CCBPlusTree::crud_model::crud_operation::CRUDOperation;
+use CCBPlusTree::crud_model::crud_operation_result::CRUDOperationResult;
+use CCBPlusTree::locking::locking_strategy::*;
+use CCBPlusTree::locking::strategy_override::{self, StrategyOverride};
+use CCBPlusTree::utils::smart_cell::LatchMode;
+
+const OVERRIDES: [StrategyOverride; 3] = [
+    StrategyOverride::Inherit,
+    StrategyOverride::PessimisticWrite,
+    StrategyOverride::OptimisticRead,
+];
+
+#[test]
+fn unsupported_overrides_are_rejected() {
+    for locking_strategy in strategies() {
+        for strategy_override in OVERRIDES {
+            let tree
+                = tree(locking_strategy.clone());
+
+            let supported
+                = strategy_override.is_supported_by(&locking_strategy);
+
+            match tree.dispatch_with(CRUDOperation::Insert(1, 1), strategy_override).1 {
+                CRUDOperationResult::Inserted(1) => assert!(supported, "{locking_strategy}: {strategy_override}"),
+                CRUDOperationResult::UnsupportedOverride(rejected) =>
+                    assert!(!supported && rejected == strategy_override, "{locking_strategy}: {strategy_override}"),
+                result => panic!("{locking_strategy}: {strategy_override}: {result}")
+            }
+
+            assert_eq!(get(&tree, 1).is_some(), supported, "{locking_strategy}: {strategy_override}");
+        }
+    }
+}
+
+/// Dispatcher implementing `dispatch` only, i.e. supporting overrides by default.
+struct Forwarding(Tree);
+
+impl CRUDDispatcher<u64, u64> for Forwarding {
+    fn dispatch(&self, operation: CRUDOperation<u64, u64>) -> (usize, CRUDOperationResult<u64, u64>) {
+        self.0.dispatch(operation)
+    }
+}
+
+#[test]
+fn dispatchers_inherit_only_by_default() {
+    let dispatcher
+        = Forwarding(tree(lightweight_hybrid_lock()));
+
+    for strategy_override in OVERRIDES {
+        assert_eq!(dispatcher.supports(strategy_override), strategy_override == StrategyOverride::Inherit);
+        assert!(dispatcher.0.supports(strategy_override), "{strategy_override}");
+    }
+
+    assert!(matches!(dispatcher.dispatch_with(CRUDOperation::Insert(1, 1), StrategyOverride::Inherit).1,
+        CRUDOperationResult::Inserted(1)));
+
+    assert!(matches!(dispatcher.dispatch_with(CRUDOperation::Insert(2, 2), StrategyOverride::PessimisticWrite).1,
+        CRUDOperationResult::UnsupportedOverride(StrategyOverride::PessimisticWrite)));
+
+    assert_eq!(get(&dispatcher.0, 2), None);
+}
+
+#[test]
+fn pessimistic_writes_latch_optimistic_trees_exclusively() {
+    for locking_strategy in [OLC(), optiql(), hybrid_lock()] {
+        let tree
+            = tree(locking_strategy.clone());
+
+        (0..10_000).for_each(|key| insert(&tree, key, key));
+
+        let _override
+            = strategy_override::scoped(StrategyOverride::PessimisticWrite);
+
+        let (.., result, profile)
+            = tree.dispatch_profiled(CRUDOperation::Insert(10_000, 10_000));
+
+        assert!(matches!(result, CRUDOperationResult::Inserted(10_000)), "{locking_strategy}");
+        assert!(profile.restarts.is_empty(), "{locking_strategy}: {profile}");
+        assert!(profile.latches.iter().all(|latch| latch.mode == LatchMode::Exclusive), "{locking_strategy}: {profile}");
+    }
+}
+
+#[test]
+fn concurrent_overridden_inserts() {
+    // mono writers admit a single writer only
+    for locking_strategy in strategies().into_iter().filter(|s| !matches!(s, LockingStrategy::MonoWriter)) {
+        for strategy_override in OVERRIDES.into_iter().filter(|o| o.is_supported_by(&locking_strategy)) {
+            let tree
+                = tree(locking_strategy.clone());
+
+            let keys
+                = shuffled(0..40_000, 5);
+
+            thread::scope(|scope| keys
+                .chunks(10_000)
+                .for_each(|keys| { scope.spawn(|| keys.iter().for_each(|key|
+                    assert!(matches!(tree.dispatch_with(CRUDOperation::Insert(*key, *key), strategy_override).1,
+                        CRUDOperationResult::Inserted(..)), "{locking_strategy}: {strategy_override}"))); }));
+
+            assert!(keys.iter().all(|key| get(&tree, *key) == Some(*key)), "{locking_strategy}: {strategy_override}");
+            assert_consistent(&tree, keys.len());
+        }
+    }
+}
+
+#[test]
+fn batch_workers_inherit_override() {
+    let upgrades = |strategy_override| {
+        let tree
+            = tree(orwc());
+
+        let _override
+            = strategy_override::scoped(strategy_override);
+
+        let result = tree
+            .insert_batch(shuffled(0..40_000, 9).into_iter().map(|key| (key, key)), 4);
+
+        assert_eq!(result.inserted, 40_000);
+        tree.metrics().snapshot().upgrades
+    };
+
+    assert!(upgrades(StrategyOverride::Inherit) > 0);
+    assert_eq!(upgrades(StrategyOverride::PessimisticWrite), 0);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+use std::thread;
+use common::{assert_concurrent_workload, tree};
+use CCBPlusTree::locking::locking_strategy::{ticket_orwc, ticket_orwc_attempts};
+use CCBPlusTree::utils::ticket_lock::TicketRwLock;
+
+/// Both halves are written by writers in turn, readers must never see them differ.
+#[test]
+fn writers_exclude_readers_and_writers() {
+    let lock
+        = TicketRwLock::new();
+
+    let halves
+        = [AtomicU64::new(0), AtomicU64::new(0)];
+
+    thread::scope(|scope| {
+        (0..4).for_each(|_| {
+            scope.spawn(|| for _ in 0..10_000 {
+                lock.write();
+
+                let value
+                    = halves[0].load(Relaxed) + 1;
+
+                halves[0].store(value, Relaxed);
+                halves[1].store(value, Relaxed);
+                lock.write_unlock();
+            });
+
+            scope.spawn(|| for _ in 0..10_000 {
+                lock.read();
+                assert_eq!(halves[0].load(Relaxed), halves[1].load(Relaxed));
+                lock.read_unlock();
+            });
+        });
+    });
+
+    assert_eq!(halves[0].load(Relaxed), 40_000);
+    assert!(lock.try_write());
+}
+
+#[test]
+fn try_write_fails_while_latched() {
+    let lock
+        = TicketRwLock::new();
+
+    lock.read();
+    assert!(!lock.try_write());
+    lock.read_unlock();
+
+    assert!(lock.try_write());
+    assert!(!lock.try_write());
+
+    // the downgraded latch is shared with other readers
+    lock.downgrade();
+    lock.read();
+    assert!(!lock.try_write());
+    lock.read_unlock();
+    lock.read_unlock();
+
+    assert!(lock.try_write());
+}
+
+#[test]
+fn concurrent_workload() {
+    for locking_strategy in [ticket_orwc(), ticket_orwc_attempts(0)] {
+        assert_concurrent_workload(&tree(locking_strategy));
+    }
+}
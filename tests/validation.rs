@@ -0,0 +1,161 @@
+mod common;
+
+use common::{tree, Tree};
+use CCBPlusTree::locking::locking_strategy::OLC;
+use CCBPlusTree::page_model::BlockRef;
+use CCBPlusTree::tree::validation::Violation;
+
+/// Two leaves of ten records below a single index page, see `tests/export.rs`.
+fn two_leaves() -> Tree {
+    tree(OLC()).bulk_load((0..20).map(|key| (key * 10, key)))
+}
+
+/// Leaves below a level of index pages below the root.
+fn three_levels() -> Tree {
+    let tree = tree(OLC())
+        .bulk_load((0..1_000).map(|key| (key * 10, key)));
+
+    assert_eq!(tree.height(), 3);
+    assert!(tree.validate().is_valid());
+    tree
+}
+
+/// Cell of the node at `path`, found by the block IDs of the tree's export.
+/// Nodes are changed through it without latching, the tree is used by a single thread only.
+fn block_at(tree: &Tree, path: &[usize]) -> BlockRef<16, 16, u64, u64> {
+    let export
+        = tree.export();
+
+    let node = path
+        .iter()
+        .fold(&export.root, |node, pos| &node.children[*pos]);
+
+    tree.find_block(node.block_id).unwrap()
+}
+
+fn violations(tree: &Tree) -> Vec<Violation<u64>> {
+    tree.validate().violations
+}
+
+#[test]
+fn reports_unsorted_leaf_keys() {
+    let tree
+        = two_leaves();
+
+    let leaf
+        = block_at(&tree, &[1]);
+
+    let records
+        = leaf.unsafe_borrow().records_mut();
+
+    // 130 precedes 140, swapped they stay within the leaf's bounds
+    records.get_unchecked_mut(3).key = 140;
+    records.get_unchecked_mut(4).key = 130;
+    drop(records);
+
+    assert_eq!(violations(&tree), vec![Violation::UnsortedLeafKeys { path: vec![1], position: 4 }]);
+}
+
+#[test]
+fn reports_unsorted_index_keys() {
+    let tree = tree(OLC())
+        .bulk_load((0..45).map(|key| (key * 10, key)));
+
+    let root
+        = block_at(&tree, &[]);
+
+    let keys
+        = root.unsafe_borrow().keys_mut();
+
+    assert_eq!((*keys.get_unchecked(0), *keys.get_unchecked(1)), (150, 300));
+
+    *keys.get_unchecked_mut(0) = 300;
+    *keys.get_unchecked_mut(1) = 150;
+    drop(keys);
+
+    // the middle leaf is routed [300, 150) now, none of its records lies within
+    let expected = [Violation::UnsortedIndexKeys { path: vec![], position: 1 }]
+        .into_iter()
+        .chain((150..300).step_by(10).map(|key| Violation::SeparatorBound {
+            path: vec![1],
+            key,
+            lower: 300,
+            upper: Some(150),
+        }))
+        .collect::<Vec<_>>();
+
+    assert_eq!(violations(&tree), expected);
+}
+
+#[test]
+fn reports_keys_beyond_separators() {
+    let tree
+        = two_leaves();
+
+    let leaf
+        = block_at(&tree, &[0]);
+
+    // still ascending within the leaf, but routed to the right one
+    leaf.unsafe_borrow().records_mut().get_unchecked_mut(9).key = 150;
+
+    assert_eq!(violations(&tree), vec![Violation::SeparatorBound {
+        path: vec![0],
+        key: 150,
+        lower: 0,
+        upper: Some(100),
+    }]);
+}
+
+#[test]
+fn reports_leaves_above_the_leaf_level() {
+    let tree
+        = three_levels();
+
+    let grandchild
+        = block_at(&tree, &[0, 0]);
+
+    // the first leaf replaces its parent, its records are still within the parent's bounds
+    let root
+        = block_at(&tree, &[]);
+
+    *root.unsafe_borrow().children_mut().get_unchecked_mut(0) = grandchild;
+
+    assert_eq!(violations(&tree), vec![Violation::LeafDepth { path: vec![0], depth: 2, height: 3 }]);
+}
+
+#[test]
+fn reports_children_count() {
+    let tree
+        = two_leaves();
+
+    let root
+        = block_at(&tree, &[]);
+
+    // in memory, an index page holds one child more than keys, unless it holds no keys at all
+    let separator
+        = root.unsafe_borrow().keys_mut().pop();
+
+    assert_eq!(violations(&tree), vec![Violation::ChildrenCount { path: vec![], keys: 0, children: 0 }]);
+
+    root.unsafe_borrow().keys_mut().push(separator);
+    assert!(violations(&tree).is_empty());
+}
+
+#[test]
+fn reports_underflow() {
+    let tree
+        = two_leaves();
+
+    let leaf
+        = block_at(&tree, &[1]);
+
+    let records
+        = leaf.unsafe_borrow().records_mut();
+
+    (0..7).for_each(|_| { records.pop(); });
+    drop(records);
+
+    // the minimum fill of one half is taken of the allocation less the record that overflows it,
+    // the root holding a single key is exempt
+    assert_eq!(violations(&tree), vec![Violation::Underflow { path: vec![1], len: 3, allocation: 15 }]);
+}
@@ -0,0 +1,79 @@
+//! Runs in its own process, since the file size limit applies to all threads.
+
+mod common;
+
+use std::{fs, mem};
+use common::{get, insert, temp_dir, Tree};
+use CCBPlusTree::crud_model::crud_api::CRUDDispatcher;
+use CCBPlusTree::crud_model::crud_operation::CRUDOperation;
+use CCBPlusTree::crud_model::crud_operation_result::CRUDOperationResult;
+use CCBPlusTree::locking::locking_strategy::OLC;
+use CCBPlusTree::test::{dec_key, inc_key};
+use CCBPlusTree::tree::wal::{DurabilityConfig, FsyncPolicy, WalError};
+
+fn limit_file_size(bytes: libc::rlim_t) {
+    let limit = libc::rlimit {
+        rlim_cur: bytes,
+        rlim_max: libc::RLIM_INFINITY,
+    };
+
+    // writes beyond the limit fail instead of killing the process
+    unsafe {
+        libc::signal(libc::SIGXFSZ, libc::SIG_IGN);
+        assert_eq!(libc::setrlimit(libc::RLIMIT_FSIZE, &limit), 0);
+    }
+}
+
+#[test]
+fn failed_writes_are_reported_and_retried() {
+    let dir
+        = temp_dir("wal-failure");
+
+    let config = DurabilityConfig::new(dir.join("snapshot"), dir.join("log"))
+        .with_fsync(FsyncPolicy::Always);
+
+    let open = || Tree::open_durable(config.clone(), OLC(), u64::MIN, u64::MAX, inc_key, dec_key)
+        .unwrap();
+
+    let tree
+        = open();
+
+    (0..100).for_each(|key| insert(&tree, key, key));
+
+    let len
+        = fs::metadata(&config.log_path).unwrap().len();
+
+    // a single byte of the next frame fits, the rest is torn off
+    limit_file_size(len + 1);
+
+    for key in 100..102 {
+        match tree.dispatch(CRUDOperation::Insert(key, key)).1 {
+            CRUDOperationResult::NotDurable(result, error) => {
+                assert!(matches!(*result, CRUDOperationResult::Inserted(inserted) if inserted == key));
+                assert!(matches!(*error, WalError::Io(..)), "{error}");
+            }
+            result => panic!("Insert({key}) was durable: {result}")
+        }
+
+        assert_eq!(fs::metadata(&config.log_path).unwrap().len(), len, "torn frame left in the log");
+    }
+
+    assert!(matches!(tree.log().unwrap().flush(), Err(WalError::Io(..))));
+    assert_eq!(get(&tree, 101), Some(101));
+
+    limit_file_size(libc::RLIM_INFINITY);
+
+    // commits the records of the failed writes along with its own
+    insert(&tree, 102, 102);
+    assert!(tree.log().unwrap().flush().is_ok());
+
+    mem::drop(tree);
+
+    let tree
+        = open();
+
+    assert!((0..103).all(|key| get(&tree, key) == Some(key)));
+
+    mem::drop(tree);
+    fs::remove_dir_all(dir).unwrap();
+}
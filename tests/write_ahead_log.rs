@@ -0,0 +1,288 @@
+mod common;
+
+use std::{fs, mem, thread};
+use std::fmt::{Display, Formatter};
+use std::fs::OpenOptions;
+use std::path::Path;
+use serde::{Deserialize, Serialize, Serializer};
+use serde::ser::Error;
+use common::{assert_consistent, delete, get, insert, shuffled, strategies, temp_dir, tree, Tree};
+use CCBPlusTree::crud_model::crud_api::CRUDDispatcher;
+use CCBPlusTree::crud_model::crud_operation::CRUDOperation;
+use CCBPlusTree::crud_model::crud_operation_result::CRUDOperationResult;
+use CCBPlusTree::locking::locking_strategy::{LockingStrategy, OLC};
+use CCBPlusTree::test::{dec_key, inc_key};
+use CCBPlusTree::tree::bplus_tree::BPlusTree;
+use CCBPlusTree::tree::wal::{DurabilityConfig, FsyncPolicy, WalError};
+
+fn open(config: &DurabilityConfig, locking_strategy: LockingStrategy) -> Tree {
+    Tree::open_durable(config.clone(), locking_strategy, u64::MIN, u64::MAX, inc_key, dec_key)
+        .unwrap()
+}
+
+fn config(dir: &Path, fsync: FsyncPolicy) -> DurabilityConfig {
+    DurabilityConfig::new(dir.join("snapshot"), dir.join("log"))
+        .with_fsync(fsync)
+}
+
+fn update(tree: &Tree, key: u64, payload: u64) {
+    match tree.dispatch(CRUDOperation::Update(key, payload)).1 {
+        CRUDOperationResult::Updated(..) => {}
+        _ => panic!("Update({key}) failed")
+    }
+}
+
+/// Inserts, updates every fifth key and deletes every third one.
+fn mutate(tree: &Tree) {
+    shuffled(0..5_000, 1).into_iter().for_each(|key| insert(tree, key, key));
+    (0..5_000).step_by(5).for_each(|key| update(tree, key, key * 2));
+    (0..5_000).step_by(3).for_each(|key| delete(tree, key));
+}
+
+fn expected(key: u64) -> Option<u64> {
+    match (key % 3, key % 5) {
+        (0, _) => None,
+        (_, 0) => Some(key * 2),
+        _ => Some(key)
+    }
+}
+
+#[test]
+fn log_replays_after_reopen() {
+    for (pos, locking_strategy) in strategies().into_iter().enumerate() {
+        let dir
+            = temp_dir(&format!("wal-replay-{pos}"));
+
+        let config
+            = config(&dir, FsyncPolicy::Never);
+
+        mutate(&open(&config, locking_strategy.clone()));
+
+        let tree
+            = open(&config, locking_strategy.clone());
+
+        assert!((0..5_000).all(|key| get(&tree, key) == expected(key)), "{locking_strategy}");
+        assert_consistent(&tree, 3_333);
+
+        mem::drop(tree);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[test]
+fn group_commits_survive_reopen() {
+    let dir
+        = temp_dir("wal-group-commit");
+
+    let config
+        = config(&dir, FsyncPolicy::Always);
+
+    let tree
+        = open(&config, OLC());
+
+    thread::scope(|scope| (0..4).for_each(|thread| {
+        let tree = &tree;
+
+        scope.spawn(move || (thread * 500..(thread + 1) * 500).for_each(|key| insert(tree, key, key)));
+    }));
+
+    mem::drop(tree);
+
+    let tree
+        = open(&config, OLC());
+
+    assert!((0..2_000).all(|key| get(&tree, key) == Some(key)));
+    assert_consistent(&tree, 2_000);
+
+    mem::drop(tree);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+/// A crash while appending leaves a torn frame, which ends the log.
+#[test]
+fn torn_frame_is_truncated() {
+    let dir
+        = temp_dir("wal-torn");
+
+    let config
+        = config(&dir, FsyncPolicy::Always);
+
+    let tree
+        = open(&config, OLC());
+
+    (0..1_000).for_each(|key| insert(&tree, key, key));
+    mem::drop(tree);
+
+    let log = OpenOptions::new()
+        .write(true)
+        .open(&config.log_path)
+        .unwrap();
+
+    log.set_len(log.metadata().unwrap().len() - 1).unwrap();
+    mem::drop(log);
+
+    let tree
+        = open(&config, OLC());
+
+    assert!((0..999).all(|key| get(&tree, key) == Some(key)));
+    assert_eq!(get(&tree, 999), None);
+
+    // appends continue after the last intact frame
+    insert(&tree, 999, 999);
+    mem::drop(tree);
+
+    let tree
+        = open(&config, OLC());
+
+    assert_consistent(&tree, 1_000);
+
+    mem::drop(tree);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn log_replays_after_checkpoint() {
+    for (pos, locking_strategy) in strategies().into_iter().enumerate() {
+        let dir
+            = temp_dir(&format!("wal-checkpoint-{pos}"));
+
+        let config
+            = config(&dir, FsyncPolicy::Never);
+
+        let tree
+            = open(&config, locking_strategy.clone());
+
+        (0..5_000).for_each(|key| insert(&tree, key, key));
+
+        assert_eq!(tree.checkpoint().unwrap(), 5_000, "{locking_strategy}");
+        assert!(config.snapshot_path.exists(), "{locking_strategy}");
+        assert!(!config.previous_log_path().exists(), "{locking_strategy}");
+
+        // covered by the log rotated by the checkpoint
+        (0..5_000).step_by(5).for_each(|key| update(&tree, key, key * 2));
+        (0..5_000).step_by(3).for_each(|key| delete(&tree, key));
+        mem::drop(tree);
+
+        let tree
+            = open(&config, locking_strategy.clone());
+
+        assert!((0..5_000).all(|key| get(&tree, key) == expected(key)), "{locking_strategy}");
+        assert_consistent(&tree, 3_333);
+
+        mem::drop(tree);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[test]
+fn checkpoints_during_writes() {
+    let dir
+        = temp_dir("wal-fuzzy-checkpoint");
+
+    let config
+        = config(&dir, FsyncPolicy::Never);
+
+    let tree
+        = open(&config, OLC());
+
+    thread::scope(|scope| {
+        (0..4).for_each(|thread| {
+            let tree = &tree;
+
+            scope.spawn(move || (thread * 5_000..(thread + 1) * 5_000).for_each(|key| insert(tree, key, key)));
+        });
+
+        scope.spawn(|| (0..5).for_each(|_| {
+            tree.checkpoint().unwrap();
+        }));
+    });
+
+    mem::drop(tree);
+
+    let tree
+        = open(&config, OLC());
+
+    assert!((0..20_000).all(|key| get(&tree, key) == Some(key)));
+    assert_consistent(&tree, 20_000);
+
+    mem::drop(tree);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn checkpoint_requires_log() {
+    assert!(matches!(tree(OLC()).checkpoint(), Err(WalError::NotDurable)));
+}
+
+/// Payload whose log record can't be encoded for `Flaky::UNENCODABLE`.
+#[derive(Deserialize, Default, Clone, Debug, PartialEq)]
+struct Flaky(u64);
+
+impl Flaky {
+    const UNENCODABLE: u64 = 13;
+}
+
+impl Serialize for Flaky {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Flaky::UNENCODABLE => Err(S::Error::custom("unencodable")),
+            value => serializer.serialize_newtype_struct("Flaky", &value)
+        }
+    }
+}
+
+impl Display for Flaky {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[test]
+fn unencodable_records_are_reported() {
+    type FlakyTree = BPlusTree<16, 16, u64, Flaky>;
+
+    let dir
+        = temp_dir("wal-unencodable");
+
+    let config
+        = config(&dir, FsyncPolicy::Always);
+
+    let open = || FlakyTree::open_durable(config.clone(), OLC(), u64::MIN, u64::MAX, inc_key, dec_key)
+        .unwrap();
+
+    let tree
+        = open();
+
+    for key in 0..20 {
+        match tree.dispatch(CRUDOperation::Insert(key, Flaky(key))).1 {
+            CRUDOperationResult::Inserted(..) => assert_ne!(key, Flaky::UNENCODABLE),
+            CRUDOperationResult::NotDurable(result, error) => {
+                assert_eq!(key, Flaky::UNENCODABLE);
+                assert!(matches!(*result, CRUDOperationResult::Inserted(inserted) if inserted == key));
+                assert!(matches!(*error, WalError::Encoding(..)), "{error}");
+            }
+            result => panic!("Insert({key}) failed: {result}")
+        }
+    }
+
+    // applied, but lost with the next reopen
+    assert!(matches!(tree.dispatch(CRUDOperation::Point(Flaky::UNENCODABLE)).1,
+        CRUDOperationResult::MatchedRecord(Some(..))));
+
+    mem::drop(tree);
+
+    let tree
+        = open();
+
+    for key in 0..20 {
+        let found = match tree.dispatch(CRUDOperation::Point(key)).1 {
+            CRUDOperationResult::MatchedRecord(record) => record.map(|record| record.payload),
+            _ => panic!("Point({key}) failed")
+        };
+
+        assert_eq!(found, (key != Flaky::UNENCODABLE).then_some(Flaky(key)));
+    }
+
+    mem::drop(tree);
+    fs::remove_dir_all(dir).unwrap();
+}